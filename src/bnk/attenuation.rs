@@ -0,0 +1,24 @@
+use std::collections::BTreeMap;
+
+use super::{Bnk, hirc::HircEntryType};
+
+impl Bnk {
+    /// Map attenuations to the audio nodes and buses referencing them.
+    ///
+    /// Every Attenuation entry of the bank is included, even if unused.
+    /// Attenuations defined in other banks are included when referenced.
+    /// Nodes inheriting positioning from a referencing parent are not listed.
+    pub fn attenuation_usage(&self) -> BTreeMap<u32, Vec<u32>> {
+        let mut usage: BTreeMap<u32, Vec<u32>> = self
+            .hirc_entries()
+            .filter(|entry| entry.entry_type == HircEntryType::Attenuation)
+            .map(|entry| (entry.id, Vec::new()))
+            .collect();
+        for entry in self.hirc_entries() {
+            if let Some(attenuation_id) = entry.payload.attenuation_id() {
+                usage.entry(attenuation_id).or_default().push(entry.id);
+            }
+        }
+        usage
+    }
+}
@@ -0,0 +1,483 @@
+#[cfg(feature = "json")]
+use std::path::Path;
+use std::{fmt, fs, path::PathBuf};
+
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+    de::{self, MapAccess, SeqAccess, Visitor},
+    ser::{self, SerializeMap, SerializeSeq},
+};
+
+use crate::hash;
+
+/// Encoding of the binary blobs of serialized banks, e.g. media, unmanaged
+/// HIRC entries and unparsed data, see [BlobEncoded].
+///
+/// Blobs are read back from arrays, `{"base64": "..."}` and `{"hex": "..."}`
+/// by any deserializer. `{"file": "<name>"}` references are only resolved by
+/// [from_json_with_blob_dir], plain deserialization rejects them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum BlobEncoding {
+    /// Arrays of numbers, as serde encodes bytes by default.
+    #[default]
+    Array,
+    Base64,
+    Hex,
+    /// Files in a folder, referenced by name as `{"file": "<name>"}`. Files
+    /// are named after a hash of their data, so identical blobs share a file.
+    External(PathBuf),
+}
+
+/// Value serialized with its blobs in a [BlobEncoding], e.g.
+/// `serde_json::to_string(&BlobEncoded::new(&bnk, &BlobEncoding::Base64))`.
+///
+/// Serializing the value directly writes blobs as arrays.
+pub struct BlobEncoded<'a, T: ?Sized> {
+    value: &'a T,
+    encoding: &'a BlobEncoding,
+}
+
+impl<'a, T: ?Sized> BlobEncoded<'a, T> {
+    pub fn new(value: &'a T, encoding: &'a BlobEncoding) -> Self {
+        BlobEncoded { value, encoding }
+    }
+}
+
+impl<T: Serialize + ?Sized> Serialize for BlobEncoded<'_, T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.value.serialize(BlobSerializer {
+            inner: serializer,
+            encoding: self.encoding,
+        })
+    }
+}
+
+/// Blobs are serialized as bytes, which formats like JSON write as arrays.
+pub(crate) fn serialize<S>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_bytes(data)
+}
+
+pub(crate) fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_any(BlobVisitor)
+}
+
+/// Adapters of lists of blobs, e.g. the media of DATA sections.
+pub(crate) mod list {
+    use super::*;
+
+    pub(crate) fn serialize<S>(list: &[Vec<u8>], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut seq = serializer.serialize_seq(Some(list.len()))?;
+        for data in list {
+            seq.serialize_element(&BlobRef(data))?;
+        }
+        seq.end()
+    }
+
+    pub(crate) fn deserialize<'de, D>(deserializer: D) -> Result<Vec<Vec<u8>>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let list = Vec::<Blob>::deserialize(deserializer)?;
+        Ok(list.into_iter().map(|blob| blob.0).collect())
+    }
+}
+
+struct BlobRef<'a>(&'a [u8]);
+
+impl Serialize for BlobRef<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize(self.0, serializer)
+    }
+}
+
+struct Blob(Vec<u8>);
+
+impl<'de> Deserialize<'de> for Blob {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize(deserializer).map(Blob)
+    }
+}
+
+struct BlobVisitor;
+
+impl<'de> Visitor<'de> for BlobVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an array of bytes, an encoded string or a file reference")
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ok(v.to_vec())
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+        Ok(v)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut data = Vec::with_capacity(seq.size_hint().unwrap_or_default().min(4096));
+        while let Some(byte) = seq.next_element()? {
+            data.push(byte);
+        }
+        Ok(data)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        const FIELDS: &[&str] = &["base64", "hex", "file"];
+        let key = map
+            .next_key::<String>()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let data = match key.as_str() {
+            "base64" | "hex" => {
+                let text = map.next_value::<String>()?;
+                let decoded = match key.as_str() {
+                    "hex" => hex_decode(&text),
+                    _ => base64_decode(&text),
+                };
+                decoded
+                    .ok_or_else(|| de::Error::invalid_value(de::Unexpected::Str(&text), &self))?
+            }
+            "file" => {
+                return Err(de::Error::custom(
+                    "file references are only read with a blob folder",
+                ));
+            }
+            _ => return Err(de::Error::unknown_field(&key, FIELDS)),
+        };
+        if map.next_key::<de::IgnoredAny>()?.is_some() {
+            return Err(de::Error::invalid_length(2, &self));
+        }
+        Ok(data)
+    }
+}
+
+/// Serializer writing the bytes of blobs in an encoding and forwarding
+/// everything else to `inner`.
+struct BlobSerializer<'a, S> {
+    inner: S,
+    encoding: &'a BlobEncoding,
+}
+
+impl<S: Serializer> BlobSerializer<'_, S> {
+    fn serialize_ref(self, key: &str, value: &str) -> Result<S::Ok, S::Error> {
+        let mut map = self.inner.serialize_map(Some(1))?;
+        map.serialize_entry(key, value)?;
+        map.end()
+    }
+}
+
+macro_rules! forward {
+    ($($method:ident($($arg:ident: $ty:ty),*);)*) => {
+        $(
+            fn $method(self, $($arg: $ty),*) -> Result<Self::Ok, Self::Error> {
+                self.inner.$method($($arg),*)
+            }
+        )*
+    };
+}
+
+macro_rules! forward_compound {
+    ($($method:ident($($arg:ident: $ty:ty),*) -> $compound:ident;)*) => {
+        $(
+            fn $method(self, $($arg: $ty),*) -> Result<Self::$compound, Self::Error> {
+                Ok(Compound {
+                    inner: self.inner.$method($($arg),*)?,
+                    encoding: self.encoding,
+                })
+            }
+        )*
+    };
+}
+
+impl<'a, S: Serializer> Serializer for BlobSerializer<'a, S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+    type SerializeSeq = Compound<'a, S::SerializeSeq>;
+    type SerializeTuple = Compound<'a, S::SerializeTuple>;
+    type SerializeTupleStruct = Compound<'a, S::SerializeTupleStruct>;
+    type SerializeTupleVariant = Compound<'a, S::SerializeTupleVariant>;
+    type SerializeMap = Compound<'a, S::SerializeMap>;
+    type SerializeStruct = Compound<'a, S::SerializeStruct>;
+    type SerializeStructVariant = Compound<'a, S::SerializeStructVariant>;
+
+    forward! {
+        serialize_bool(v: bool);
+        serialize_i8(v: i8);
+        serialize_i16(v: i16);
+        serialize_i32(v: i32);
+        serialize_i64(v: i64);
+        serialize_i128(v: i128);
+        serialize_u8(v: u8);
+        serialize_u16(v: u16);
+        serialize_u32(v: u32);
+        serialize_u64(v: u64);
+        serialize_u128(v: u128);
+        serialize_f32(v: f32);
+        serialize_f64(v: f64);
+        serialize_char(v: char);
+        serialize_str(v: &str);
+        serialize_none();
+        serialize_unit();
+        serialize_unit_struct(name: &'static str);
+        serialize_unit_variant(name: &'static str, index: u32, variant: &'static str);
+    }
+
+    forward_compound! {
+        serialize_seq(len: Option<usize>) -> SerializeSeq;
+        serialize_tuple(len: usize) -> SerializeTuple;
+        serialize_tuple_struct(name: &'static str, len: usize) -> SerializeTupleStruct;
+        serialize_tuple_variant(
+            name: &'static str,
+            index: u32,
+            variant: &'static str,
+            len: usize
+        ) -> SerializeTupleVariant;
+        serialize_map(len: Option<usize>) -> SerializeMap;
+        serialize_struct(name: &'static str, len: usize) -> SerializeStruct;
+        serialize_struct_variant(
+            name: &'static str,
+            index: u32,
+            variant: &'static str,
+            len: usize
+        ) -> SerializeStructVariant;
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
+        match self.encoding {
+            BlobEncoding::Array => self.inner.serialize_bytes(v),
+            BlobEncoding::Base64 => self.serialize_ref("base64", &base64_encode(v)),
+            BlobEncoding::Hex => self.serialize_ref("hex", &hex_encode(v)),
+            BlobEncoding::External(dir) => {
+                let name = format!("{:016x}.bin", hash::fnv1a_64(v));
+                fs::create_dir_all(dir)
+                    .and_then(|_| fs::write(dir.join(&name), v))
+                    .map_err(ser::Error::custom)?;
+                self.serialize_ref("file", &name)
+            }
+        }
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<S::Ok, S::Error> {
+        self.inner
+            .serialize_some(&BlobEncoded::new(value, self.encoding))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<S::Ok, S::Error> {
+        self.inner
+            .serialize_newtype_struct(name, &BlobEncoded::new(value, self.encoding))
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_newtype_variant(
+            name,
+            index,
+            variant,
+            &BlobEncoded::new(value, self.encoding),
+        )
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+/// Compound of a [BlobSerializer], its values are serialized with the same
+/// encoding.
+struct Compound<'a, C> {
+    inner: C,
+    encoding: &'a BlobEncoding,
+}
+
+macro_rules! impl_compound {
+    ($($trait:ident::$method:ident($($key:ident: $key_ty:ty),*);)*) => {
+        $(
+            impl<C: ser::$trait> ser::$trait for Compound<'_, C> {
+                type Ok = C::Ok;
+                type Error = C::Error;
+
+                fn $method<T: Serialize + ?Sized>(
+                    &mut self,
+                    $($key: $key_ty,)*
+                    value: &T,
+                ) -> Result<(), C::Error> {
+                    self.inner
+                        .$method($($key,)* &BlobEncoded::new(value, self.encoding))
+                }
+
+                fn end(self) -> Result<C::Ok, C::Error> {
+                    self.inner.end()
+                }
+            }
+        )*
+    };
+}
+
+impl_compound! {
+    SerializeSeq::serialize_element();
+    SerializeTuple::serialize_element();
+    SerializeTupleStruct::serialize_field();
+    SerializeTupleVariant::serialize_field();
+    SerializeStruct::serialize_field(key: &'static str);
+    SerializeStructVariant::serialize_field(key: &'static str);
+}
+
+impl<C: SerializeMap> SerializeMap for Compound<'_, C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), C::Error> {
+        self.inner.serialize_key(key)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), C::Error> {
+        self.inner
+            .serialize_value(&BlobEncoded::new(value, self.encoding))
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.inner.end()
+    }
+}
+
+/// Read JSON written with [BlobEncoding::External], resolving
+/// `{"file": "<name>"}` references against `dir`.
+///
+/// Names must stay inside `dir`: absolute paths and `..` are rejected.
+#[cfg(feature = "json")]
+pub fn from_json_with_blob_dir<T: de::DeserializeOwned>(
+    json: &str,
+    dir: impl AsRef<Path>,
+) -> std::io::Result<T> {
+    let invalid = |e: serde_json::Error| std::io::Error::new(std::io::ErrorKind::InvalidData, e);
+    let mut value: serde_json::Value = serde_json::from_str(json).map_err(invalid)?;
+    resolve_files(&mut value, dir.as_ref())?;
+    serde_json::from_value(value).map_err(invalid)
+}
+
+/// Replace the file references in `value` by the hex encoding of the files.
+#[cfg(feature = "json")]
+fn resolve_files(value: &mut serde_json::Value, dir: &Path) -> std::io::Result<()> {
+    use serde_json::Value;
+    use std::{io, path::Component};
+
+    match value {
+        Value::Array(values) => values.iter_mut().try_for_each(|v| resolve_files(v, dir)),
+        Value::Object(map) => {
+            if let (1, Some(Value::String(name))) = (map.len(), map.get("file")) {
+                let path = Path::new(name);
+                let inside = path.components().next().is_some()
+                    && path
+                        .components()
+                        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+                if !inside {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("blob file {name} is outside of the blob folder"),
+                    ));
+                }
+                let path = dir.join(path);
+                let data = fs::read(&path)
+                    .map_err(|e| io::Error::new(e.kind(), format!("{}: {e}", path.display())))?;
+                *value = serde_json::json!({ "hex": hex_encode(&data) });
+                return Ok(());
+            }
+            map.values_mut().try_for_each(|v| resolve_files(v, dir))
+        }
+        _ => Ok(()),
+    }
+}
+
+const BASE64_CHARS: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, &byte)| {
+            bits | (byte as u32) << (16 - 8 * i)
+        });
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64_CHARS[(bits >> (18 - 6 * i)) as usize & 0x3F] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+fn base64_decode(s: &str) -> Option<Vec<u8>> {
+    let s = s.trim_end_matches('=');
+    let mut out = Vec::with_capacity(s.len() * 3 / 4);
+    let (mut bits, mut count) = (0u32, 0);
+    for c in s.bytes() {
+        let value = BASE64_CHARS.iter().position(|&b| b == c)? as u32;
+        bits = bits << 6 | value;
+        count += 6;
+        if count >= 8 {
+            count -= 8;
+            out.push((bits >> count) as u8);
+        }
+    }
+    Some(out)
+}
+
+fn hex_encode(data: &[u8]) -> String {
+    data.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn hex_decode(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blob_codecs() {
+        for data in [&b""[..], b"f", b"fo", b"foo", b"foob", b"\xff\x00\xfe\x01"] {
+            assert_eq!(base64_decode(&base64_encode(data)).unwrap(), data);
+            assert_eq!(hex_decode(&hex_encode(data)).unwrap(), data);
+        }
+        assert_eq!(base64_encode(b"foob"), "Zm9vYg==");
+        assert_eq!(hex_encode(b"\xff\x01"), "ff01");
+        assert!(base64_decode("Zm9v!").is_none());
+        assert!(hex_decode("f").is_none());
+    }
+}
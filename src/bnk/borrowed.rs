@@ -0,0 +1,252 @@
+use std::io;
+
+use byteorder::{LE, ReadBytesExt};
+
+use super::{
+    Bnk, BnkError, BnkParseOptions, DidxEntry, Result, Section, SectionPayload,
+    hirc::{HircEntry, HircEntryPayload, HircEntryType, HircUnmanagedEntry},
+    parse::ParseState,
+};
+
+/// Bank parsed from a buffer, borrowing its media and unmanaged HIRC entries.
+///
+/// Parsing large banks for read-only inspection doesn't copy their media,
+/// use [BnkRef::to_bnk] for an editable bank.
+#[derive(Debug, Clone)]
+pub struct BnkRef<'a> {
+    pub sections: Vec<SectionRef<'a>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SectionRef<'a> {
+    pub magic: [u8; 4],
+    pub section_length: u32,
+    pub payload: SectionPayloadRef<'a>,
+}
+
+#[derive(Debug, Clone)]
+pub enum SectionPayloadRef<'a> {
+    Hirc {
+        entries: Vec<HircEntryRef<'a>>,
+    },
+    Data {
+        data_list: Vec<&'a [u8]>,
+    },
+    /// Other sections, parsed as in [Bnk].
+    Owned(SectionPayload),
+}
+
+#[derive(Debug, Clone)]
+pub struct HircEntryRef<'a> {
+    pub entry_type: HircEntryType,
+    pub length: u32,
+    pub id: u32,
+    /// See [HircEntry::version].
+    pub version: u32,
+    pub payload: HircEntryPayloadRef<'a>,
+}
+
+#[derive(Debug, Clone)]
+pub enum HircEntryPayloadRef<'a> {
+    Parsed(HircEntryPayload),
+    /// Data of an entry type kept raw in [Bnk], see [HircUnmanagedEntry].
+    Raw(&'a [u8]),
+}
+
+impl Bnk {
+    /// Parse a bank borrowing its media and unmanaged HIRC entries from
+    /// `bytes`, see [BnkRef].
+    pub fn from_slice(bytes: &[u8]) -> Result<BnkRef<'_>> {
+        BnkRef::from_bytes(bytes)
+    }
+}
+
+impl<'a> BnkRef<'a> {
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self> {
+        let mut sections: Vec<SectionRef> = Vec::new();
+        let mut version = 0;
+        let mut pos = 0;
+        while pos + 8 <= bytes.len() {
+            let magic: [u8; 4] = bytes[pos..pos + 4].try_into().unwrap();
+            let section_length = u32::from_le_bytes(bytes[pos + 4..pos + 8].try_into().unwrap());
+            let data = bytes
+                .get(pos + 8..pos + 8 + section_length as usize)
+                .ok_or_else(|| BnkError::BadDataSize {
+                    name: String::from_utf8_lossy(&magic).into_owned(),
+                    entry: None,
+                    expected: section_length as u64,
+                    got: (bytes.len() - pos - 8) as u64,
+                    start: pos as u64,
+                })?;
+            let payload = match &magic {
+                b"HIRC" => SectionPayloadRef::Hirc {
+                    entries: read_hirc_entries(data, version)?,
+                },
+                b"DATA" => {
+                    let didx_entries = sections
+                        .iter()
+                        .find_map(|sec| match &sec.payload {
+                            SectionPayloadRef::Owned(SectionPayload::Didx { entries }) => {
+                                Some(entries)
+                            }
+                            _ => None,
+                        })
+                        .ok_or(BnkError::MissingDidx)?;
+                    SectionPayloadRef::Data {
+                        data_list: didx_entries
+                            .iter()
+                            .map(|entry| media_slice(data, entry))
+                            .collect::<Result<_>>()?,
+                    }
+                }
+                _ => {
+                    let mut reader = io::Cursor::new(&bytes[pos + 4..pos + 8 + data.len()]);
+                    let options = BnkParseOptions::default();
+                    let section = Section::from_reader(
+                        &mut reader,
+                        magic,
+                        version,
+                        &mut ParseState::new(&options),
+                    )?;
+                    if let SectionPayload::Bkhd { version: v, .. } = &section.payload {
+                        version = *v;
+                    }
+                    SectionPayloadRef::Owned(section.payload)
+                }
+            };
+            sections.push(SectionRef {
+                magic,
+                section_length,
+                payload,
+            });
+            pos += 8 + data.len();
+        }
+        Ok(BnkRef { sections })
+    }
+
+    pub fn hirc_entries(&self) -> impl Iterator<Item = &HircEntryRef<'a>> {
+        self.sections
+            .iter()
+            .filter_map(|sec| match &sec.payload {
+                SectionPayloadRef::Hirc { entries } => Some(entries),
+                _ => None,
+            })
+            .flatten()
+    }
+
+    /// Get embedded media by id.
+    pub fn media(&self, id: u32) -> Option<&'a [u8]> {
+        let didx_entries = self.sections.iter().find_map(|sec| match &sec.payload {
+            SectionPayloadRef::Owned(SectionPayload::Didx { entries }) => Some(entries),
+            _ => None,
+        })?;
+        let data_list = self.sections.iter().find_map(|sec| match &sec.payload {
+            SectionPayloadRef::Data { data_list } => Some(data_list),
+            _ => None,
+        })?;
+        let index = didx_entries.iter().position(|entry| entry.id == id)?;
+        data_list.get(index).copied()
+    }
+
+    /// Copy the borrowed data into an editable bank.
+    pub fn to_bnk(&self) -> Bnk {
+        let sections = self
+            .sections
+            .iter()
+            .map(|section| Section {
+                magic: section.magic,
+                section_length: section.section_length,
+                payload: match &section.payload {
+                    SectionPayloadRef::Hirc { entries } => SectionPayload::Hirc {
+                        entries: entries.iter().map(HircEntryRef::to_entry).collect(),
+                    },
+                    SectionPayloadRef::Data { data_list } => SectionPayload::Data {
+                        data_list: data_list.iter().map(|data| data.to_vec()).collect(),
+                    },
+                    SectionPayloadRef::Owned(payload) => payload.clone(),
+                },
+            })
+            .collect();
+        Bnk::from_sections(sections)
+    }
+}
+
+impl HircEntryRef<'_> {
+    pub fn to_entry(&self) -> HircEntry {
+        let payload = match &self.payload {
+            HircEntryPayloadRef::Parsed(payload) => payload.clone(),
+            HircEntryPayloadRef::Raw(data) => HircEntryPayload::unmanaged(
+                self.entry_type,
+                HircUnmanagedEntry {
+                    data: data.to_vec(),
+                },
+            ),
+        };
+        HircEntry {
+            entry_type: self.entry_type,
+            length: self.length,
+            id: self.id,
+            version: self.version,
+            payload,
+            trailing: vec![],
+        }
+    }
+}
+
+fn read_hirc_entries(data: &[u8], version: u32) -> Result<Vec<HircEntryRef<'_>>> {
+    let mut reader = io::Cursor::new(data);
+    let options = BnkParseOptions::default();
+    let mut state = ParseState::new(&options);
+    state.section_end = Some(data.len() as u64);
+    let count = reader.read_u32::<LE>()?;
+    // entries take at least 9 bytes
+    let mut entries = Vec::with_capacity((count as usize).min(data.len() / 9));
+    for _ in 0..count {
+        let entry_type = reader.read_u8()?;
+        let entry_type =
+            HircEntryType::from_repr(entry_type).unwrap_or(HircEntryType::Unknown(entry_type));
+        if !entry_type.is_unmanaged() {
+            let entry = HircEntry::read(&mut reader, entry_type, version, &mut state)?;
+            entries.push(HircEntryRef {
+                entry_type,
+                length: entry.length,
+                id: entry.id,
+                version,
+                payload: HircEntryPayloadRef::Parsed(entry.payload),
+            });
+            continue;
+        }
+        let length = reader.read_u32::<LE>()?;
+        let id = reader.read_u32::<LE>()?;
+        let start = reader.position() as usize;
+        let payload = (length as usize)
+            .checked_sub(4)
+            .and_then(|size| data.get(start..start + size))
+            .ok_or(io::Error::from(io::ErrorKind::UnexpectedEof))?;
+        reader.set_position((start + payload.len()) as u64);
+        entries.push(HircEntryRef {
+            entry_type,
+            length,
+            id,
+            version,
+            payload: HircEntryPayloadRef::Raw(payload),
+        });
+    }
+    Ok(entries)
+}
+
+fn media_slice<'a>(data: &'a [u8], entry: &DidxEntry) -> Result<&'a [u8]> {
+    // offsets of zero-length placeholders may lie anywhere
+    if entry.length == 0 {
+        return Ok(&[]);
+    }
+    let start = entry.offset as usize;
+    data.get(start..start + entry.length as usize)
+        .ok_or_else(|| BnkError::BadDataSize {
+            name: format!("media {}", entry.id),
+            entry: None,
+            expected: entry.length as u64,
+            got: data.len().saturating_sub(start) as u64,
+            start: start as u64,
+        })
+}
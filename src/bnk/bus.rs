@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::{Bnk, hirc::HircEntry};
+
+/// A bus and the buses routed into it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BusNode {
+    pub id: u32,
+    /// 0 for master buses.
+    pub parent_id: u32,
+    pub children: Vec<BusNode>,
+}
+
+impl Bnk {
+    /// Build the bus hierarchy of the buses defined in this bank.
+    ///
+    /// Buses whose parent is not in this bank (usually defined in the init
+    /// bank) are returned as roots, with their `parent_id` kept.
+    pub fn bus_tree(&self) -> Vec<BusNode> {
+        let buses: Vec<(u32, u32)> = self
+            .hirc_entries()
+            .filter_map(|entry| Some((entry.id, entry.payload.parent_bus_id()?)))
+            .collect();
+        let mut children: HashMap<u32, Vec<(u32, u32)>> = HashMap::new();
+        for &(id, parent_id) in &buses {
+            children.entry(parent_id).or_default().push((id, parent_id));
+        }
+
+        fn build(id: u32, parent_id: u32, children: &HashMap<u32, Vec<(u32, u32)>>) -> BusNode {
+            BusNode {
+                id,
+                parent_id,
+                children: children
+                    .get(&id)
+                    .into_iter()
+                    .flatten()
+                    .map(|&(child_id, _)| build(child_id, id, children))
+                    .collect(),
+            }
+        }
+
+        buses
+            .iter()
+            .filter(|(_, parent_id)| !buses.iter().any(|(id, _)| id == parent_id))
+            .map(|&(id, parent_id)| build(id, parent_id, &children))
+            .collect()
+    }
+
+    /// Find the bus an object outputs to, following its parents until a node
+    /// overriding the output bus is found.
+    ///
+    /// For buses the parent bus is returned. `None` if the chain leaves this
+    /// bank before a bus is found.
+    pub fn output_bus_of(&self, object_id: u32) -> Option<u32> {
+        let entries: HashMap<u32, &HircEntry> =
+            self.hirc_entries().map(|entry| (entry.id, entry)).collect();
+        let entry = entries.get(&object_id)?;
+        if let Some(parent_bus_id) = entry.payload.parent_bus_id() {
+            return (parent_bus_id != 0).then_some(parent_bus_id);
+        }
+
+        let mut id = object_id;
+        // bounded walk, guards against cycles in broken banks
+        for _ in 0..entries.len() {
+            let routing = entries.get(&id)?.payload.routing()?;
+            if routing.override_bus_id != 0 {
+                return Some(routing.override_bus_id);
+            }
+            id = routing.direct_parent_id;
+        }
+        None
+    }
+}
@@ -0,0 +1,167 @@
+use std::{collections::BTreeMap, fmt};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::{Bnk, Result, SectionPayload, hirc::HircEntryType};
+
+/// Bytes parsed into typed fields and bytes kept as raw blobs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ByteCoverage {
+    pub typed: u64,
+    pub raw: u64,
+}
+
+impl ByteCoverage {
+    pub fn total(&self) -> u64 {
+        self.typed + self.raw
+    }
+
+    /// Share of typed bytes, 1 for empty data.
+    pub fn typed_ratio(&self) -> f64 {
+        match self.total() {
+            0 => 1.0,
+            total => self.typed as f64 / total as f64,
+        }
+    }
+
+    fn add(&mut self, other: ByteCoverage) {
+        self.typed += other.typed;
+        self.raw += other.raw;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SectionCoverage {
+    pub magic: String,
+    /// Including the section header, without media.
+    pub bytes: ByteCoverage,
+    /// Embedded media of DATA sections, opaque to the bank format.
+    pub media: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct HircTypeCoverage {
+    pub entry_type: HircEntryType,
+    pub count: usize,
+    /// Including the entry headers.
+    pub bytes: ByteCoverage,
+}
+
+/// How much of a bank is understood, see [Bnk::parse_coverage].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CoverageReport {
+    pub sections: Vec<SectionCoverage>,
+    /// Sorted by entry type.
+    pub hirc_types: Vec<HircTypeCoverage>,
+}
+
+impl CoverageReport {
+    /// Coverage of the whole bank, without media.
+    pub fn total(&self) -> ByteCoverage {
+        let mut total = ByteCoverage::default();
+        for section in &self.sections {
+            total.add(section.bytes);
+        }
+        total
+    }
+}
+
+impl fmt::Display for CoverageReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let row = |f: &mut fmt::Formatter<'_>, name: &str, bytes: &ByteCoverage| {
+            writeln!(
+                f,
+                "{name:<28} {:>10} {:>10} {:>6.1}%",
+                bytes.typed,
+                bytes.raw,
+                bytes.typed_ratio() * 100.0
+            )
+        };
+        writeln!(f, "{:<28} {:>10} {:>10} {:>7}", "", "typed", "raw", "typed")?;
+        for section in &self.sections {
+            row(f, &section.magic, &section.bytes)?;
+            if section.media > 0 {
+                writeln!(f, "  {:<26} {:>10}", "media", section.media)?;
+            }
+            if section.magic == "HIRC" {
+                for hirc_type in &self.hirc_types {
+                    let name = format!("  {:?} ({})", hirc_type.entry_type, hirc_type.count);
+                    row(f, &name, &hirc_type.bytes)?;
+                }
+            }
+        }
+        row(f, "total", &self.total())
+    }
+}
+
+impl Bnk {
+    /// Count the bytes of each section and HIRC entry type parsed into typed
+    /// structures versus kept as raw blobs, to see how completely the bank is
+    /// understood and where to expect round-trip risk.
+    ///
+    /// Sizes are those of the serialized bank. Raw blobs are unknown
+    /// sections, the unparsed parts of BKHD and STMG, unmanaged HIRC entries,
+    /// the specific parameters of actions, the trailing chunks of buses,
+    /// dialogue events and sounds, the trailing bytes of entries read by
+    /// [Bnk::from_reader_tolerant], and plugin parameters. Media is counted
+    /// apart.
+    pub fn parse_coverage(&self) -> Result<CoverageReport> {
+        let serialized = self.serialize_sections()?;
+
+        let mut hirc_types: BTreeMap<u8, HircTypeCoverage> = BTreeMap::new();
+        let mut sections = Vec::with_capacity(self.sections.len());
+        for (section, bytes) in self.sections.iter().zip(serialized) {
+            let total = bytes.len() as u64;
+            let (raw, media) = match &section.payload {
+                SectionPayload::Bkhd { unknown, .. } => (unknown.len() as u64, 0),
+                SectionPayload::Didx { .. } => (0, 0),
+                SectionPayload::Hirc { entries } => {
+                    let mut raw = 0;
+                    for entry in entries {
+                        let entry_raw = (entry.payload.raw_len() + entry.trailing.len()) as u64;
+                        let entry_total = entry.to_bytes()?.len() as u64;
+                        let coverage =
+                            hirc_types
+                                .entry(entry.entry_type.as_u8())
+                                .or_insert_with(|| HircTypeCoverage {
+                                    entry_type: entry.entry_type,
+                                    count: 0,
+                                    bytes: ByteCoverage::default(),
+                                });
+                        coverage.count += 1;
+                        coverage.bytes.add(ByteCoverage {
+                            typed: entry_total - entry_raw,
+                            raw: entry_raw,
+                        });
+                        raw += entry_raw;
+                    }
+                    (raw, 0)
+                }
+                SectionPayload::Stmg { settings } => (settings.data.len() as u64, 0),
+                SectionPayload::Stid { .. } => (0, 0),
+                SectionPayload::Data { data_list } => {
+                    (0, data_list.iter().map(|data| data.len() as u64).sum())
+                }
+                SectionPayload::Unk { data } => (data.len() as u64, 0),
+            };
+            sections.push(SectionCoverage {
+                magic: String::from_utf8_lossy(&section.magic).into_owned(),
+                bytes: ByteCoverage {
+                    typed: total - raw - media,
+                    raw,
+                },
+                media,
+            });
+        }
+
+        Ok(CoverageReport {
+            sections,
+            hirc_types: hirc_types.into_values().collect(),
+        })
+    }
+}
@@ -0,0 +1,202 @@
+use std::{collections::HashMap, io};
+
+use serde_json::Value;
+
+use super::{
+    Bnk, BnkError, Result,
+    hirc::{
+        AkPropId, HircEntry, HircEntryType, PROP_BUS_VOLUME, PROP_HPF, PROP_LPF, PROP_MAKE_UP_GAIN,
+        PROP_PITCH, PROP_VOLUME,
+    },
+};
+
+/// Changed field of a HIRC entry, values are rendered as JSON.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldChange {
+    /// Path of the field, e.g. `source.media_information.source_id`.
+    pub path: String,
+    /// `None` if the field was added.
+    pub old: Option<String>,
+    /// `None` if the field was removed.
+    pub new: Option<String>,
+}
+
+/// HIRC entry present in both banks with different content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryChange {
+    pub entry_type: HircEntryType,
+    pub id: u32,
+    pub changes: Vec<FieldChange>,
+}
+
+impl HircEntry {
+    /// Compare the fields of two entries.
+    ///
+    /// Managed payloads are compared field by field, properties by their id
+    /// with float values decoded. Raw `data` blobs, including the payload of
+    /// unmanaged entries, are reported as a single change. Lengths and counts
+    /// are skipped as they follow the fields they describe.
+    pub fn field_diff(&self, other: &HircEntry) -> Result<Vec<FieldChange>> {
+        let old = to_value(self)?;
+        let new = to_value(other)?;
+        let mut changes = Vec::new();
+        diff_value("", &old, &new, &mut changes);
+        Ok(changes)
+    }
+}
+
+impl Bnk {
+    /// Field level changes of the HIRC entries found in both banks, by entry
+    /// type and id. Entries added or removed are listed by
+    /// [BnkFingerprint::diff](super::BnkFingerprint::diff).
+    pub fn entry_changes(&self, other: &Bnk) -> Result<Vec<EntryChange>> {
+        let entries: HashMap<(u8, u32), &HircEntry> = self
+            .hirc_entries()
+            .map(|entry| ((entry.entry_type.as_u8(), entry.id), entry))
+            .collect();
+        let mut changes = Vec::new();
+        for entry in other.hirc_entries() {
+            let Some(old) = entries.get(&(entry.entry_type.as_u8(), entry.id)) else {
+                continue;
+            };
+            let field_changes = old.field_diff(entry)?;
+            if !field_changes.is_empty() {
+                changes.push(EntryChange {
+                    entry_type: entry.entry_type,
+                    id: entry.id,
+                    changes: field_changes,
+                });
+            }
+        }
+        Ok(changes)
+    }
+}
+
+fn to_value(entry: &HircEntry) -> Result<Value> {
+    serde_json::to_value(entry).map_err(|e| BnkError::IO(io::Error::other(e)))
+}
+
+fn join(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{path}.{key}")
+    }
+}
+
+fn diff_value(path: &str, old: &Value, new: &Value, changes: &mut Vec<FieldChange>) {
+    if old == new {
+        return;
+    }
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            for (key, old_value) in old {
+                if key == "length" || key.starts_with("num_") {
+                    continue;
+                }
+                match new.get(key) {
+                    Some(new_value) => diff_value(&join(path, key), old_value, new_value, changes),
+                    None => changes.push(FieldChange {
+                        path: join(path, key),
+                        old: Some(old_value.to_string()),
+                        new: None,
+                    }),
+                }
+            }
+            for (key, new_value) in new {
+                if !old.contains_key(key) {
+                    changes.push(FieldChange {
+                        path: join(path, key),
+                        old: None,
+                        new: Some(new_value.to_string()),
+                    });
+                }
+            }
+        }
+        (Value::Array(old), Value::Array(new)) if path == "data" || path.ends_with(".data") => {
+            changes.push(FieldChange {
+                path: path.to_string(),
+                old: Some(format!("<{} bytes>", old.len())),
+                new: Some(format!("<{} bytes>", new.len())),
+            });
+        }
+        (Value::Array(old), Value::Array(new)) if is_prop_list(old) && is_prop_list(new) => {
+            diff_props(path, old, new, changes);
+        }
+        (Value::Array(old), Value::Array(new)) => {
+            for i in 0..old.len().max(new.len()) {
+                let item_path = format!("{path}[{i}]");
+                match (old.get(i), new.get(i)) {
+                    (Some(old), Some(new)) => diff_value(&item_path, old, new, changes),
+                    (old, new) => changes.push(FieldChange {
+                        path: item_path,
+                        old: old.map(Value::to_string),
+                        new: new.map(Value::to_string),
+                    }),
+                }
+            }
+        }
+        _ => changes.push(FieldChange {
+            path: path.to_string(),
+            old: Some(old.to_string()),
+            new: Some(new.to_string()),
+        }),
+    }
+}
+
+/// Elements of an [AkPropBundle](super::hirc::AkPropBundle).
+fn is_prop_list(items: &[Value]) -> bool {
+    items
+        .iter()
+        .all(|item| item.get("p_id").is_some() && item.get("p_value").is_some())
+}
+
+fn diff_props(path: &str, old: &[Value], new: &[Value], changes: &mut Vec<FieldChange>) {
+    let props = |items: &[Value]| -> Vec<(u8, u32)> {
+        items
+            .iter()
+            .filter_map(|item| {
+                Some((
+                    item["p_id"].as_u64()? as u8,
+                    item["p_value"].as_u64()? as u32,
+                ))
+            })
+            .collect()
+    };
+    let (old, new) = (props(old), props(new));
+    let find = |props: &[(u8, u32)], id| props.iter().find(|(p_id, _)| *p_id == id).map(|p| p.1);
+    let mut ids: Vec<u8> = old.iter().chain(&new).map(|(id, _)| *id).collect();
+    ids.sort_unstable();
+    ids.dedup();
+    for id in ids {
+        let (old_value, new_value) = (find(&old, id), find(&new, id));
+        if old_value == new_value {
+            continue;
+        }
+        changes.push(FieldChange {
+            path: format!("{path}[{}]", prop_name(id)),
+            old: old_value.map(|value| prop_value(id, value)),
+            new: new_value.map(|value| prop_value(id, value)),
+        });
+    }
+}
+
+fn prop_name(id: u8) -> String {
+    match id {
+        PROP_VOLUME => "volume".to_string(),
+        PROP_PITCH => "pitch".to_string(),
+        PROP_LPF => "lpf".to_string(),
+        PROP_HPF => "hpf".to_string(),
+        PROP_BUS_VOLUME => "bus_volume".to_string(),
+        PROP_MAKE_UP_GAIN => "make_up_gain".to_string(),
+        id => format!("0x{id:02X}"),
+    }
+}
+
+/// Values of unknown properties are shown raw.
+fn prop_value(id: u8, value: u32) -> String {
+    match AkPropId::from_u8(id) {
+        AkPropId::Unknown(_) => value.to_string(),
+        id => id.value(value).to_string(),
+    }
+}
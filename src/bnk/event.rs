@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+
+use super::{
+    Bnk, BnkError, Result, SectionPayload,
+    hirc::{HircEntryPayload, HircEventActionType},
+};
+use crate::hash;
+
+/// An object given by id or by name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectRef<'a> {
+    Id(u32),
+    Name(&'a str),
+}
+
+impl ObjectRef<'_> {
+    /// Id of the object, names are hashed to their ShortID.
+    pub fn id(&self) -> u32 {
+        match self {
+            ObjectRef::Id(id) => *id,
+            ObjectRef::Name(name) => hash::short_id(name),
+        }
+    }
+}
+
+impl From<u32> for ObjectRef<'_> {
+    fn from(id: u32) -> Self {
+        ObjectRef::Id(id)
+    }
+}
+
+impl<'a> From<&'a str> for ObjectRef<'a> {
+    fn from(name: &'a str) -> Self {
+        ObjectRef::Name(name)
+    }
+}
+
+/// Action of an event reduced to what it does, see [Bnk::event_aliases].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventActionTarget {
+    Action {
+        action_type: HircEventActionType,
+        target_id: u32,
+        /// Group and value of SetState and SetSwitch actions.
+        game_sync_value: Option<(u32, u32)>,
+    },
+    /// Action not found in the bank, compared by id.
+    Missing(u32),
+}
+
+/// Events with the same actions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventAliasGroup {
+    pub actions: Vec<EventActionTarget>,
+    /// Sorted event ids.
+    pub event_ids: Vec<u32>,
+}
+
+/// Action of an event with the media it reaches, see [Bnk::resolve_event].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedAction {
+    pub action_id: u32,
+    pub action_type: HircEventActionType,
+    /// Object the action applies to, 0 for global actions.
+    pub target_id: u32,
+    /// Media sources below the target, sorted. Empty for targets of other
+    /// banks.
+    pub source_ids: Vec<u32>,
+}
+
+impl Bnk {
+    /// Resolve the actions of an event to their targets and the media
+    /// sources reached through the containers of the targets.
+    ///
+    /// Actions are in event order, actions not in the bank are skipped.
+    /// Returns an empty list if the event is not in the bank.
+    pub fn resolve_event(&self, event_id: u32) -> Vec<ResolvedAction> {
+        let Some(HircEntryPayload::Event { action_ids }) =
+            self.hirc_entry(event_id).map(|entry| &entry.payload)
+        else {
+            return Vec::new();
+        };
+        let graph = self.graph();
+        action_ids
+            .iter()
+            .filter_map(|&action_id| match &self.hirc_entry(action_id)?.payload {
+                HircEntryPayload::EventAction(action) => Some(ResolvedAction {
+                    action_id,
+                    action_type: action.action_type,
+                    target_id: action.game_object_id,
+                    source_ids: match action.game_object_id {
+                        0 => Vec::new(),
+                        target_id => graph.source_ids(target_id),
+                    },
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Find distinct events whose actions resolve to the same targets.
+    ///
+    /// Actions are compared in order by type and target, their other
+    /// parameters (delays, fades...) are ignored. Events without actions are
+    /// skipped. Groups are sorted by their first event id.
+    pub fn event_aliases(&self) -> Vec<EventAliasGroup> {
+        let actions: HashMap<u32, EventActionTarget> = self
+            .hirc_entries()
+            .filter_map(|entry| match &entry.payload {
+                HircEntryPayload::EventAction(action) => Some((
+                    entry.id,
+                    EventActionTarget::Action {
+                        action_type: action.action_type,
+                        target_id: action.game_object_id,
+                        game_sync_value: action.game_sync_value(),
+                    },
+                )),
+                _ => None,
+            })
+            .collect();
+
+        let mut groups: HashMap<Vec<EventActionTarget>, Vec<u32>> = HashMap::new();
+        for entry in self.hirc_entries() {
+            let HircEntryPayload::Event { action_ids } = &entry.payload else {
+                continue;
+            };
+            if action_ids.is_empty() {
+                continue;
+            }
+            let targets = action_ids
+                .iter()
+                .map(|id| {
+                    actions
+                        .get(id)
+                        .copied()
+                        .unwrap_or(EventActionTarget::Missing(*id))
+                })
+                .collect();
+            groups.entry(targets).or_default().push(entry.id);
+        }
+
+        let mut aliases: Vec<EventAliasGroup> = groups
+            .into_iter()
+            .map(|(actions, mut event_ids)| {
+                event_ids.sort_unstable();
+                event_ids.dedup();
+                EventAliasGroup { actions, event_ids }
+            })
+            .filter(|group| group.event_ids.len() > 1)
+            .collect();
+        aliases.sort_unstable_by_key(|group| group.event_ids[0]);
+        aliases
+    }
+
+    /// Rename an event, returning its new id.
+    ///
+    /// The Event entry gets the ShortID of `new_name`, event cue clips of
+    /// music tracks posting it and its STID entry are rewritten as well.
+    pub fn rename_event<'a>(
+        &mut self,
+        old_name_or_id: impl Into<ObjectRef<'a>>,
+        new_name: &str,
+    ) -> Result<u32> {
+        let old_id = old_name_or_id.into().id();
+        let new_id = hash::short_id(new_name);
+        let mut found = false;
+        for entry in self.hirc_entries() {
+            if entry.id == old_id && matches!(entry.payload, HircEntryPayload::Event { .. }) {
+                found = true;
+            } else if entry.id == new_id && new_id != old_id {
+                return Err(BnkError::IdCollision(new_id));
+            }
+        }
+        if !found {
+            return Err(BnkError::ObjectNotFound(old_id));
+        }
+
+        for section in &mut self.sections {
+            match &mut section.payload {
+                SectionPayload::Hirc { entries } => {
+                    for entry in entries.iter_mut() {
+                        match &mut entry.payload {
+                            HircEntryPayload::Event { .. } if entry.id == old_id => {
+                                entry.id = new_id;
+                            }
+                            HircEntryPayload::MusicTrack(track) => {
+                                track.replace_event_id(old_id, new_id);
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                SectionPayload::Stid { table } => table.rebase(old_id, new_id, new_name),
+                _ => {}
+            }
+        }
+        self.invalidate_hirc_index();
+
+        Ok(new_id)
+    }
+}
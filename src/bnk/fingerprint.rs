@@ -0,0 +1,119 @@
+use std::collections::BTreeMap;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::{Bnk, Result};
+use crate::hash;
+
+/// Content hashes of a bank, see [Bnk::fingerprint].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BnkFingerprint {
+    pub sections: Vec<SectionFingerprint>,
+    /// By HIRC entry id.
+    pub hirc_entries: BTreeMap<u32, u64>,
+    /// By media id.
+    pub media: BTreeMap<u32, u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SectionFingerprint {
+    pub magic: String,
+    pub hash: u64,
+}
+
+/// Objects that differ between two fingerprints.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FingerprintDiff {
+    pub added_entries: Vec<u32>,
+    pub removed_entries: Vec<u32>,
+    pub changed_entries: Vec<u32>,
+    pub added_media: Vec<u32>,
+    pub removed_media: Vec<u32>,
+    pub changed_media: Vec<u32>,
+}
+
+impl FingerprintDiff {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl Bnk {
+    /// Hash every section, HIRC entry and media of the bank.
+    ///
+    /// Hashes are computed from the serialized data with 64-bit FNV-1a, so
+    /// they are stable across runs and builds and can be stored to compare
+    /// banks of different game versions.
+    pub fn fingerprint(&self) -> Result<BnkFingerprint> {
+        let serialized = self.serialize_sections()?;
+        let sections = self
+            .sections
+            .iter()
+            .zip(serialized)
+            .map(|(section, bytes)| SectionFingerprint {
+                magic: String::from_utf8_lossy(&section.magic).into_owned(),
+                hash: hash::fnv1a_64(&bytes),
+            })
+            .collect();
+
+        let mut hirc_entries = BTreeMap::new();
+        for entry in self.hirc_entries() {
+            hirc_entries.insert(entry.id, hash::fnv1a_64(&entry.to_bytes()?));
+        }
+
+        let didx = self.didx();
+        let data = self.data();
+        let media = didx
+            .into_iter()
+            .flatten()
+            .zip(data.into_iter().flatten())
+            .map(|(entry, data)| (entry.id, hash::fnv1a_64(data)))
+            .collect();
+
+        Ok(BnkFingerprint {
+            sections,
+            hirc_entries,
+            media,
+        })
+    }
+}
+
+impl BnkFingerprint {
+    /// Objects added, removed or changed in `other` compared to `self`.
+    pub fn diff(&self, other: &BnkFingerprint) -> FingerprintDiff {
+        let (added_entries, removed_entries, changed_entries) =
+            diff_maps(&self.hirc_entries, &other.hirc_entries);
+        let (added_media, removed_media, changed_media) = diff_maps(&self.media, &other.media);
+        FingerprintDiff {
+            added_entries,
+            removed_entries,
+            changed_entries,
+            added_media,
+            removed_media,
+            changed_media,
+        }
+    }
+}
+
+fn diff_maps(old: &BTreeMap<u32, u64>, new: &BTreeMap<u32, u64>) -> (Vec<u32>, Vec<u32>, Vec<u32>) {
+    let added = new
+        .keys()
+        .filter(|id| !old.contains_key(id))
+        .copied()
+        .collect();
+    let removed = old
+        .keys()
+        .filter(|id| !new.contains_key(id))
+        .copied()
+        .collect();
+    let changed = old
+        .iter()
+        .filter(|(id, hash)| new.get(id).is_some_and(|h| h != *hash))
+        .map(|(id, _)| *id)
+        .collect();
+    (added, removed, changed)
+}
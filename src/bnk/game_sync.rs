@@ -0,0 +1,240 @@
+use std::collections::BTreeMap;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::names::NameDb;
+
+use super::{
+    Bnk, SectionPayload,
+    hirc::{AkDecisionTree, AkGameSync, HircEntryPayload, HircEventActionType},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GameSyncKind {
+    State,
+    Switch,
+}
+
+/// A state or switch group and the values seen for it.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GameSyncGroup {
+    pub kind: GameSyncKind,
+    pub id: u32,
+    pub name: Option<String>,
+    /// Value id to name.
+    pub values: BTreeMap<u32, Option<String>>,
+}
+
+/// State and switch groups collected from one or more banks.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GameSyncRegistry {
+    groups: BTreeMap<(GameSyncKind, u32), GameSyncGroup>,
+}
+
+impl GameSyncRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a group, and a value of it if given. Value 0 (none / any) is ignored.
+    pub fn insert(&mut self, kind: GameSyncKind, group_id: u32, value_id: Option<u32>) {
+        let group = self
+            .groups
+            .entry((kind, group_id))
+            .or_insert_with(|| GameSyncGroup {
+                kind,
+                id: group_id,
+                name: None,
+                values: BTreeMap::new(),
+            });
+        if let Some(value_id) = value_id.filter(|&id| id != 0) {
+            group.values.entry(value_id).or_insert(None);
+        }
+    }
+
+    pub fn group(&self, kind: GameSyncKind, id: u32) -> Option<&GameSyncGroup> {
+        self.groups.get(&(kind, id))
+    }
+
+    /// All groups, state groups first, ordered by id.
+    pub fn groups(&self) -> impl Iterator<Item = &GameSyncGroup> {
+        self.groups.values()
+    }
+
+    pub fn state_groups(&self) -> impl Iterator<Item = &GameSyncGroup> {
+        self.groups().filter(|g| g.kind == GameSyncKind::State)
+    }
+
+    pub fn switch_groups(&self) -> impl Iterator<Item = &GameSyncGroup> {
+        self.groups().filter(|g| g.kind == GameSyncKind::Switch)
+    }
+
+    pub fn len(&self) -> usize {
+        self.groups.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    /// Merge the groups of another registry, e.g. of another bank.
+    pub fn merge(&mut self, other: GameSyncRegistry) {
+        for (key, group) in other.groups {
+            let entry = self.groups.entry(key).or_insert_with(|| GameSyncGroup {
+                values: BTreeMap::new(),
+                ..group.clone()
+            });
+            if entry.name.is_none() {
+                entry.name = group.name;
+            }
+            for (id, name) in group.values {
+                let value = entry.values.entry(id).or_insert(None);
+                if value.is_none() {
+                    *value = name;
+                }
+            }
+        }
+    }
+
+    /// Record the arguments of a decision tree and the keys of each level.
+    pub fn insert_decision_tree(&mut self, arguments: &[AkGameSync], tree: &AkDecisionTree) {
+        for argument in arguments {
+            if let Some(kind) = registry_kind(argument) {
+                self.insert(kind, argument.group_id, None);
+            }
+        }
+        for (keys, _) in tree.paths() {
+            for (argument, key) in arguments.iter().zip(keys) {
+                if let Some(kind) = registry_kind(argument) {
+                    self.insert(kind, argument.group_id, Some(key));
+                }
+            }
+        }
+    }
+
+    /// Fill in group and value names known to `names`.
+    pub fn resolve_names(&mut self, names: &NameDb) {
+        for group in self.groups.values_mut() {
+            if group.name.is_none() {
+                group.name = names.get(group.id).map(str::to_string);
+            }
+            for (id, name) in group.values.iter_mut() {
+                if name.is_none() {
+                    *name = names.get(*id).map(str::to_string);
+                }
+            }
+        }
+    }
+}
+
+fn registry_kind(argument: &AkGameSync) -> Option<GameSyncKind> {
+    match argument.group_type {
+        0 => Some(GameSyncKind::Switch),
+        1 => Some(GameSyncKind::State),
+        _ => None,
+    }
+}
+
+impl Bnk {
+    /// Collect the state and switch groups referenced by this bank.
+    ///
+    /// Sources are the STMG section, switch music tracks, decision trees,
+    /// state chunks of parsed nodes and SetState / SetSwitch actions. Names
+    /// are not resolved, see [GameSyncRegistry::resolve_names].
+    pub fn game_syncs(&self) -> GameSyncRegistry {
+        let mut registry = GameSyncRegistry::new();
+        for section in &self.sections {
+            match &section.payload {
+                SectionPayload::Stmg { settings } => {
+                    for group in &settings.state_groups {
+                        registry.insert(GameSyncKind::State, group.id, None);
+                        for transition in &group.transitions {
+                            registry.insert(
+                                GameSyncKind::State,
+                                group.id,
+                                Some(transition.state_from),
+                            );
+                            registry.insert(
+                                GameSyncKind::State,
+                                group.id,
+                                Some(transition.state_to),
+                            );
+                        }
+                    }
+                    for group in &settings.switch_groups {
+                        registry.insert(GameSyncKind::Switch, group.id, None);
+                        for point in &group.points {
+                            registry.insert(
+                                GameSyncKind::Switch,
+                                group.id,
+                                Some(point.to.to_bits()),
+                            );
+                        }
+                    }
+                }
+                SectionPayload::Hirc { entries } => {
+                    for entry in entries {
+                        if let Some(params) = entry.payload.node_base_params() {
+                            for group in params.state_chunk().state_groups() {
+                                registry.insert(GameSyncKind::State, group.state_group_id(), None);
+                                for state_id in group.state_ids() {
+                                    registry.insert(
+                                        GameSyncKind::State,
+                                        group.state_group_id(),
+                                        Some(state_id),
+                                    );
+                                }
+                            }
+                        }
+                        match &entry.payload {
+                            HircEntryPayload::MusicTrack(track) => {
+                                let Some(params) = track.switch_params() else {
+                                    continue;
+                                };
+                                let kind = if params.is_state() {
+                                    GameSyncKind::State
+                                } else {
+                                    GameSyncKind::Switch
+                                };
+                                registry.insert(
+                                    kind,
+                                    params.group_id(),
+                                    Some(params.default_switch()),
+                                );
+                                for switch_id in params.switch_ids() {
+                                    registry.insert(kind, params.group_id(), Some(switch_id));
+                                }
+                            }
+                            HircEntryPayload::MusicSwitchContainer(cntr) => {
+                                registry
+                                    .insert_decision_tree(cntr.arguments(), cntr.decision_tree());
+                            }
+                            HircEntryPayload::DialogueEvent(event) => {
+                                registry
+                                    .insert_decision_tree(event.arguments(), event.decision_tree());
+                            }
+                            HircEntryPayload::EventAction(action) => {
+                                let Some((group_id, value_id)) = action.game_sync_value() else {
+                                    continue;
+                                };
+                                let kind = if action.action_type == HircEventActionType::SetState {
+                                    GameSyncKind::State
+                                } else {
+                                    GameSyncKind::Switch
+                                };
+                                registry.insert(kind, group_id, Some(value_id));
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        registry
+    }
+}
@@ -0,0 +1,188 @@
+use std::collections::{HashMap, HashSet};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::{Bnk, hirc::HircEntryPayload};
+
+/// How an object of a [HircGraph] relates to its parent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum EdgeKind {
+    /// Child of a container, actor-mixer or parent bus, or node of a
+    /// dialogue event.
+    Child,
+    /// Action of an event.
+    Action,
+    /// Object an action applies to.
+    Target,
+    /// Media source of a sound or music track, not a HIRC object.
+    Source,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GraphEdge {
+    pub id: u32,
+    pub kind: EdgeKind,
+}
+
+/// Links between the HIRC objects of a bank, from events to their actions,
+/// action targets, containers, nodes and media sources.
+///
+/// Links are gathered from both sides, the parent ids of the nodes and the
+/// children of the containers, so partially parsed hierarchies are linked
+/// as far as they are known. Objects of other banks may appear as ids
+/// without entries.
+#[derive(Debug, Clone, Default)]
+pub struct HircGraph {
+    children: HashMap<u32, Vec<GraphEdge>>,
+    parents: HashMap<u32, Vec<GraphEdge>>,
+}
+
+impl HircGraph {
+    pub fn new(bnk: &Bnk) -> Self {
+        let mut graph = HircGraph::default();
+        for entry in bnk.hirc_entries() {
+            let id = entry.id;
+            let payload = &entry.payload;
+            if let Some(routing) = payload.routing()
+                && routing.direct_parent_id != 0
+            {
+                graph.add_edge(routing.direct_parent_id, id, EdgeKind::Child);
+            }
+            if let HircEntryPayload::AudioBus(bus) | HircEntryPayload::AuxiliaryBus(bus) = payload
+                && bus.parent_bus_id() != 0
+            {
+                graph.add_edge(bus.parent_bus_id(), id, EdgeKind::Child);
+            }
+            for &child in payload.children() {
+                graph.add_edge(id, child, EdgeKind::Child);
+            }
+            for source in payload.sources() {
+                graph.add_edge(id, source.source_id(), EdgeKind::Source);
+            }
+            match payload {
+                HircEntryPayload::Event { action_ids } => {
+                    for &action_id in action_ids {
+                        graph.add_edge(id, action_id, EdgeKind::Action);
+                    }
+                }
+                HircEntryPayload::EventAction(action) if action.game_object_id != 0 => {
+                    graph.add_edge(id, action.game_object_id, EdgeKind::Target);
+                }
+                HircEntryPayload::DialogueEvent(event) => {
+                    for (_, node_id) in event.decision_tree().paths() {
+                        graph.add_edge(id, node_id, EdgeKind::Child);
+                    }
+                }
+                _ => {}
+            }
+        }
+        graph
+    }
+
+    fn add_edge(&mut self, parent: u32, child: u32, kind: EdgeKind) {
+        let children = self.children.entry(parent).or_default();
+        let edge = GraphEdge { id: child, kind };
+        if children.contains(&edge) {
+            return;
+        }
+        children.push(edge);
+        self.parents
+            .entry(child)
+            .or_default()
+            .push(GraphEdge { id: parent, kind });
+    }
+
+    /// Direct children of an object.
+    pub fn children(&self, id: u32) -> &[GraphEdge] {
+        self.children.get(&id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Direct parents of an object, e.g. the containers of a node and the
+    /// actions targeting it.
+    pub fn parents(&self, id: u32) -> &[GraphEdge] {
+        self.parents.get(&id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Objects below an object, depth first, each visited once.
+    pub fn descendants(&self, id: u32) -> Walk<'_> {
+        Walk::new(&self.children, id)
+    }
+
+    /// Media sources below an object through its child nodes, sorted.
+    ///
+    /// Unlike [HircGraph::descendants], actions and their targets are not
+    /// followed.
+    pub fn source_ids(&self, id: u32) -> Vec<u32> {
+        let mut sources = Vec::new();
+        let mut visited = HashSet::from([id]);
+        let mut stack = vec![id];
+        while let Some(id) = stack.pop() {
+            for edge in self.children(id) {
+                match edge.kind {
+                    EdgeKind::Child if visited.insert(edge.id) => stack.push(edge.id),
+                    EdgeKind::Source => sources.push(edge.id),
+                    _ => {}
+                }
+            }
+        }
+        sources.sort_unstable();
+        sources.dedup();
+        sources
+    }
+
+    /// Objects above an object, depth first, each visited once.
+    pub fn ancestors(&self, id: u32) -> Walk<'_> {
+        Walk::new(&self.parents, id)
+    }
+}
+
+/// Depth first walk of a [HircGraph], see [HircGraph::descendants].
+#[derive(Debug, Clone)]
+pub struct Walk<'a> {
+    edges: &'a HashMap<u32, Vec<GraphEdge>>,
+    stack: Vec<GraphEdge>,
+    visited: HashSet<u32>,
+}
+
+impl<'a> Walk<'a> {
+    fn new(edges: &'a HashMap<u32, Vec<GraphEdge>>, id: u32) -> Self {
+        let mut walk = Walk {
+            edges,
+            stack: Vec::new(),
+            visited: HashSet::from([id]),
+        };
+        walk.push_edges(id);
+        walk
+    }
+
+    fn push_edges(&mut self, id: u32) {
+        if let Some(edges) = self.edges.get(&id) {
+            // keep the walk in edge order
+            self.stack.extend(edges.iter().rev());
+        }
+    }
+}
+
+impl Iterator for Walk<'_> {
+    type Item = GraphEdge;
+
+    fn next(&mut self) -> Option<GraphEdge> {
+        while let Some(edge) = self.stack.pop() {
+            if self.visited.insert(edge.id) {
+                self.push_edges(edge.id);
+                return Some(edge);
+            }
+        }
+        None
+    }
+}
+
+impl Bnk {
+    /// Build the graph of the HIRC objects, see [HircGraph].
+    pub fn graph(&self) -> HircGraph {
+        HircGraph::new(self)
+    }
+}
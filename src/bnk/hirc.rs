@@ -1,19 +1,46 @@
+mod actor_mixer;
+mod attenuation;
+mod audio_bus;
+mod blend_cntr;
 mod common;
+mod decision_tree;
+mod dialogue_event;
 mod music_ran_seq_cntr;
 mod music_segment;
+mod music_switch_cntr;
 mod music_track;
+mod ran_seq_cntr;
+mod settings;
 
+pub use actor_mixer::*;
+pub use attenuation::*;
+pub use audio_bus::*;
+pub use blend_cntr::*;
+pub use common::{
+    AdvSettingsParams, AkCurveInterpolation, AkPropBundle, AkPropBundleElem, AkPropId, AkPropValue,
+    AkRTPCGraphPoint, AkStateGroupChunk, AuxParams, FxChunk, InitialRTPC, InitialRTPCCurve,
+    NodeBaseParams, NodeRouting, StateChunk, evaluate_graph,
+};
+pub use decision_tree::*;
+pub use dialogue_event::*;
 pub use music_ran_seq_cntr::*;
 pub use music_segment::*;
+pub use music_switch_cntr::*;
 pub use music_track::*;
+pub use ran_seq_cntr::*;
+pub use settings::*;
 
 use std::io;
 
 use binrw::{BinRead, BinWrite, binrw};
 use byteorder::{LE, ReadBytesExt, WriteBytesExt};
 
-use super::{BnkError, Result};
+use super::{BnkError, ParseWarning, Result, parse::ParseState};
 use crate::rwext::ReadVecExt;
+use common::{NodeBaseParamsPrefix, PROP_ATTENUATION_ID};
+pub(crate) use common::{
+    PROP_BUS_VOLUME, PROP_HPF, PROP_LPF, PROP_MAKE_UP_GAIN, PROP_PITCH, PROP_VOLUME,
+};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -32,33 +59,162 @@ trait EntryPayloadExt: Sized {
     }
 }
 
+/// Bank version the layouts of the typed entries follow.
+///
+/// Entries of other versions failing to parse are kept raw as
+/// [HircEntryPayload::Unknown].
+pub const HIRC_LAYOUT_VERSION: u32 = 145;
+
+/// First bank version storing the action count of events as a u8.
+const EVENT_U8_COUNT_VERSION: u32 = 134;
+
+/// Plugin id of the Vorbis codec, the format of Wwise media.
+const VORBIS_PLUGIN_ID: u32 = 0x0004_0001;
+
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct HircEntry {
     pub entry_type: HircEntryType,
+    /// Recomputed on write.
+    #[cfg_attr(feature = "serde", serde(default))]
     pub length: u32,
     pub id: u32,
+    /// Version of the bank the entry was read from, selects the layout of
+    /// version dependent fields on write.
+    #[cfg_attr(feature = "serde", serde(default = "default_version"))]
+    pub version: u32,
     #[cfg_attr(feature = "serde", serde(flatten))]
     pub payload: HircEntryPayload,
+    /// Bytes past the parsed payload, kept by parses that aren't
+    /// [strict](super::BnkParseOptions::strict) and written back unchanged.
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            default,
+            skip_serializing_if = "Vec::is_empty",
+            with = "crate::bnk::blob"
+        )
+    )]
+    pub trailing: Vec<u8>,
+}
+
+#[cfg(feature = "serde")]
+fn default_version() -> u32 {
+    HIRC_LAYOUT_VERSION
 }
 
 impl HircEntry {
-    pub(super) fn from_reader<R>(reader: &mut R, entry_type: HircEntryType) -> Result<Self>
+    /// Read an entry with the default options, without section bounds.
+    #[cfg(test)]
+    pub(super) fn from_reader<R>(
+        reader: &mut R,
+        entry_type: HircEntryType,
+        version: u32,
+    ) -> Result<Self>
+    where
+        R: io::Read + io::Seek,
+    {
+        let options = super::BnkParseOptions::default();
+        Self::read(reader, entry_type, version, &mut ParseState::new(&options))
+    }
+
+    /// Read an entry as set by the options of `state`, see
+    /// [BnkParseOptions](super::BnkParseOptions).
+    pub(super) fn read<R>(
+        reader: &mut R,
+        entry_type: HircEntryType,
+        version: u32,
+        state: &mut ParseState,
+    ) -> Result<Self>
     where
         R: io::Read + io::Seek,
     {
         let length = reader.read_u32::<LE>()?;
         let id = reader.read_u32::<LE>()?;
+        let start_pos = reader.stream_position()?;
+        let end_pos = (length as u64)
+            .checked_sub(4)
+            .map(|size| start_pos + size)
+            .filter(|end_pos| state.section_end.is_none_or(|limit| *end_pos <= limit));
+        let Some(end_pos) = end_pos else {
+            return Err(BnkError::BadDataSize {
+                name: format!("{entry_type:?} {id}"),
+                entry: Some((entry_type, id)),
+                expected: (length as u64).saturating_sub(4),
+                got: state
+                    .section_end
+                    .map_or(0, |limit| limit.saturating_sub(start_pos)),
+                start: start_pos,
+            });
+        };
+        let payload = Self::read_payload(reader, entry_type, length, version)
+            .and_then(|payload| Ok((payload, reader.stream_position()?)));
+        let strict = state.options.strict;
+        let mut trailing = vec![];
+        let payload = match payload {
+            Ok((payload, pos)) if pos == end_pos => Ok(payload),
+            Ok((payload, pos)) if !strict && pos < end_pos => {
+                trailing = reader.read_vec_u8((end_pos - pos) as usize)?;
+                state.warn(ParseWarning::TrailingBytes {
+                    entry_type,
+                    id,
+                    offset: start_pos,
+                    length: end_pos - pos,
+                });
+                Ok(payload)
+            }
+            Ok((_, pos)) => Err(BnkError::BadDataSize {
+                name: format!("{entry_type:?} {id}"),
+                entry: Some((entry_type, id)),
+                expected: length as u64 - 4,
+                got: pos - start_pos,
+                start: start_pos,
+            }),
+            Err(e) => Err(e),
+        };
+        let payload = match payload {
+            Ok(payload) => payload,
+            Err(e) if strict && version == HIRC_LAYOUT_VERSION => return Err(e),
+            // layouts of other versions are partly known, keep entries not
+            // matching them raw
+            Err(e) => {
+                reader.seek(io::SeekFrom::Start(start_pos))?;
+                state.warn(ParseWarning::RawEntry {
+                    entry_type,
+                    id,
+                    offset: start_pos,
+                    error: e.to_string(),
+                });
+                HircEntryPayload::Unknown(HircUnmanagedEntry::from_reader(reader, length)?)
+            }
+        };
+
+        Ok(HircEntry {
+            entry_type,
+            length,
+            id,
+            version,
+            payload,
+            trailing,
+        })
+    }
+
+    fn read_payload<R>(
+        reader: &mut R,
+        entry_type: HircEntryType,
+        length: u32,
+        version: u32,
+    ) -> Result<HircEntryPayload>
+    where
+        R: io::Read + io::Seek,
+    {
         let payload = match entry_type {
             HircEntryType::Settings => {
-                HircEntryPayload::Settings(HircUnmanagedEntry::from_reader(reader, length)?)
+                HircEntryPayload::Settings(HircSettings::from_reader(reader, length)?)
+            }
+            HircEntryType::Sound => {
+                HircEntryPayload::Sound(Box::new(HircSound::from_reader(reader, length)?))
             }
-            HircEntryType::Sound => HircEntryPayload::Sound(HircSound::read_args(
-                reader,
-                HircSoundBinReadArgs {
-                    data_length: length,
-                },
-            )?),
             HircEntryType::EventAction => {
                 let scope = reader.read_u8()?;
                 let scope = HircEventActionScope::from_repr(scope).ok_or(
@@ -83,9 +239,17 @@ impl HircEntry {
                 let parameters = reader.read_vec_u8(parameter_count as usize)?;
                 let _unk2 = reader.read_u8()?;
 
-                let data = reader.read_vec_u8(
-                    (length as usize) - 13 - (size_of::<u8>() * (parameter_count as usize) * 2),
-                )?;
+                let header_size = 13 + size_of::<u8>() * (parameter_count as usize) * 2;
+                let data_size = (length as usize).checked_sub(header_size).ok_or_else(|| {
+                    BnkError::BadDataSize {
+                        name: "HircEventAction".to_string(),
+                        entry: None,
+                        expected: header_size as u64,
+                        got: length as u64,
+                        start: 0,
+                    }
+                })?;
+                let data = reader.read_vec_u8(data_size)?;
 
                 HircEntryPayload::EventAction(HircEventAction {
                     scope,
@@ -100,29 +264,31 @@ impl HircEntry {
                 })
             }
             HircEntryType::Event => {
-                // if bank_version >= 134 => u8 else u32
-                // assume u8 for now
-                let action_count = reader.read_u8()?;
-                let action_ids = unsafe { reader.read_vec_t_sized(action_count as usize)? };
+                let action_count = if version >= EVENT_U8_COUNT_VERSION {
+                    reader.read_u8()? as usize
+                } else {
+                    reader.read_u32::<LE>()? as usize
+                };
+                let action_ids = reader.read_vec_fn(action_count, |r| r.read_u32::<LE>())?;
                 HircEntryPayload::Event { action_ids }
             }
             HircEntryType::RandomOrSequenceContainer => {
-                HircEntryPayload::RandomOrSequenceContainer(HircUnmanagedEntry::from_reader(
+                HircEntryPayload::RandomOrSequenceContainer(Box::new(HircRanSeqCntr::from_reader(
                     reader, length,
-                )?)
+                )?))
             }
             HircEntryType::SwitchContainer => {
                 HircEntryPayload::SwitchContainer(HircUnmanagedEntry::from_reader(reader, length)?)
             }
             HircEntryType::ActorMixer => {
-                HircEntryPayload::ActorMixer(HircUnmanagedEntry::from_reader(reader, length)?)
+                HircEntryPayload::ActorMixer(Box::new(HircActorMixer::from_reader(reader, length)?))
             }
             HircEntryType::AudioBus => {
-                HircEntryPayload::AudioBus(HircUnmanagedEntry::from_reader(reader, length)?)
-            }
-            HircEntryType::BlendContainer => {
-                HircEntryPayload::BlendContainer(HircUnmanagedEntry::from_reader(reader, length)?)
+                HircEntryPayload::AudioBus(Box::new(HircAudioBus::from_reader(reader, length)?))
             }
+            HircEntryType::BlendContainer => HircEntryPayload::BlendContainer(Box::new(
+                HircBlendCntr::from_reader(reader, length)?,
+            )),
             HircEntryType::MusicSegment => HircEntryPayload::MusicSegment(Box::new(
                 HircMusicSegment::from_reader(reader, length)?,
             )),
@@ -130,17 +296,17 @@ impl HircEntry {
                 HircEntryPayload::MusicTrack(Box::new(HircMusicTrack::from_reader(reader, length)?))
             }
             HircEntryType::MusicSwitchContainer => HircEntryPayload::MusicSwitchContainer(
-                HircUnmanagedEntry::from_reader(reader, length)?,
+                Box::new(HircMusicSwitchCntr::from_reader(reader, length)?),
             ),
             HircEntryType::MusicRanSeqCntr => HircEntryPayload::MusicRanSeqCntr(Box::new(
                 HircMusicRanSeqCntr::from_reader(reader, length)?,
             )),
-            HircEntryType::Attenuation => {
-                HircEntryPayload::Attenuation(HircUnmanagedEntry::from_reader(reader, length)?)
-            }
-            HircEntryType::DialogueEvent => {
-                HircEntryPayload::DialogueEvent(HircUnmanagedEntry::from_reader(reader, length)?)
-            }
+            HircEntryType::Attenuation => HircEntryPayload::Attenuation(Box::new(
+                HircAttenuation::from_reader(reader, length)?,
+            )),
+            HircEntryType::DialogueEvent => HircEntryPayload::DialogueEvent(Box::new(
+                HircDialogueEvent::from_reader(reader, length)?,
+            )),
             HircEntryType::MotionBus => {
                 HircEntryPayload::MotionBus(HircUnmanagedEntry::from_reader(reader, length)?)
             }
@@ -151,22 +317,102 @@ impl HircEntry {
                 HircEntryPayload::Effect(HircUnmanagedEntry::from_reader(reader, length)?)
             }
             HircEntryType::AuxiliaryBus => {
-                HircEntryPayload::AuxiliaryBus(HircUnmanagedEntry::from_reader(reader, length)?)
+                HircEntryPayload::AuxiliaryBus(Box::new(HircAudioBus::from_reader(reader, length)?))
             }
             HircEntryType::Unknown(_) => {
                 HircEntryPayload::Unknown(HircUnmanagedEntry::from_reader(reader, length)?)
             }
         };
+        Ok(payload)
+    }
 
-        Ok(HircEntry {
+    /// Event posting `action_ids` in order.
+    ///
+    /// Entries built by the constructors have the layout of
+    /// [HIRC_LAYOUT_VERSION] banks.
+    pub fn new_event(id: u32, action_ids: Vec<u32>) -> Self {
+        Self::new(
+            HircEntryType::Event,
+            id,
+            HircEntryPayload::Event { action_ids },
+        )
+    }
+
+    /// Play action of an event, playing `target_id` from the bank `bank_id`
+    /// without delay or fade.
+    pub fn new_play_action(id: u32, target_id: u32, bank_id: u32) -> Self {
+        // fade curve (linear), bank id, bank type
+        let mut data = vec![4];
+        data.extend(bank_id.to_le_bytes());
+        data.extend(0u32.to_le_bytes());
+        let action = HircEventAction {
+            scope: HircEventActionScope::GameObject,
+            action_type: HircEventActionType::Play,
+            game_object_id: target_id,
+            _unk1: 0,
+            parameter_count: 0,
+            parameter_types: vec![],
+            parameters: vec![],
+            _unk2: 0,
+            data,
+        };
+        Self::new(
+            HircEntryType::EventAction,
+            id,
+            HircEntryPayload::EventAction(action),
+        )
+    }
+
+    /// Sound of a Vorbis media embedded in the bank, under `parent_id`
+    /// (0 for none), with default parameters.
+    pub fn new_sound(id: u32, source_id: u32, media_size: u32, parent_id: u32) -> Self {
+        let mut node_base_params = NodeBaseParams::default();
+        node_base_params.set_direct_parent_id(parent_id);
+        let sound = HircSound {
+            source: AkBankSourceData {
+                plugin_id: VORBIS_PLUGIN_ID,
+                stream_type: AkBankSourceStreamType::DataBnk as u8,
+                media_information: AkMediaInformation {
+                    source_id,
+                    in_memory_media_size: media_size,
+                    source_bits: 0,
+                },
+                plugin_params: None,
+            },
+            node_base_params,
+            trailing: vec![],
+        };
+        Self::new(
+            HircEntryType::Sound,
+            id,
+            HircEntryPayload::Sound(Box::new(sound)),
+        )
+    }
+
+    fn new(entry_type: HircEntryType, id: u32, payload: HircEntryPayload) -> Self {
+        HircEntry {
             entry_type,
-            length,
+            length: 0,
             id,
+            version: HIRC_LAYOUT_VERSION,
             payload,
-        })
+            trailing: vec![],
+        }
+    }
+
+    /// Serialized entry, including its type and length.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut writer = io::Cursor::new(Vec::new());
+        self.write_to(&mut writer)?;
+        Ok(writer.into_inner())
     }
 
-    pub(super) fn write_to<W>(&mut self, writer: &mut W) -> Result<()>
+    pub(super) fn fix_values(&mut self) -> Result<()> {
+        self.payload.fix_values()
+    }
+
+    /// Write the entry with the counts of a fixed copy of the payload.
+    pub(super) fn write_to<W>(&self, writer: &mut W) -> Result<()>
     where
         W: io::Write + io::Seek,
     {
@@ -176,14 +422,15 @@ impl HircEntry {
         let start_pos = writer.stream_position()?;
         writer.write_u32::<LE>(self.id)?;
 
-        self.payload.fix_values()?;
+        let mut payload = self.payload.clone();
+        payload.fix_values()?;
 
-        match &self.payload {
+        match &payload {
             HircEntryPayload::Settings(entry) => {
                 entry.write_to(writer)?;
             }
             HircEntryPayload::Sound(hirc_sound) => {
-                hirc_sound.write(writer)?;
+                hirc_sound.write_to(writer)?;
             }
             HircEntryPayload::EventAction(hirc_event_action) => {
                 writer.write_u8(hirc_event_action.scope as u8)?;
@@ -200,7 +447,17 @@ impl HircEntry {
                 writer.write_all(&hirc_event_action.data)?;
             }
             HircEntryPayload::Event { action_ids } => {
-                writer.write_u8(action_ids.len() as u8)?;
+                if self.version >= EVENT_U8_COUNT_VERSION {
+                    let count =
+                        u8::try_from(action_ids.len()).map_err(|_| BnkError::TooManyActions {
+                            id: self.id,
+                            count: action_ids.len(),
+                            max: u8::MAX as usize,
+                        })?;
+                    writer.write_u8(count)?;
+                } else {
+                    writer.write_u32::<LE>(action_ids.len() as u32)?;
+                }
                 for action_id in action_ids {
                     writer.write_u32::<LE>(*action_id)?;
                 }
@@ -254,6 +511,7 @@ impl HircEntry {
                 entry.write_to(writer)?;
             }
         }
+        writer.write_all(&self.trailing)?;
 
         let end_pos = writer.stream_position()?;
         // write length
@@ -270,32 +528,181 @@ impl HircEntry {
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "serde", serde(tag = "type"))]
 pub enum HircEntryPayload {
-    Settings(HircUnmanagedEntry),
-    Sound(HircSound),
+    Settings(HircSettings),
+    Sound(Box<HircSound>),
     EventAction(HircEventAction),
     Event { action_ids: Vec<u32> },
-    RandomOrSequenceContainer(HircUnmanagedEntry),
+    RandomOrSequenceContainer(Box<HircRanSeqCntr>),
     SwitchContainer(HircUnmanagedEntry),
-    ActorMixer(HircUnmanagedEntry),
-    AudioBus(HircUnmanagedEntry),
-    BlendContainer(HircUnmanagedEntry),
+    ActorMixer(Box<HircActorMixer>),
+    AudioBus(Box<HircAudioBus>),
+    BlendContainer(Box<HircBlendCntr>),
     MusicSegment(Box<HircMusicSegment>),
     MusicTrack(Box<HircMusicTrack>),
-    MusicSwitchContainer(HircUnmanagedEntry),
+    MusicSwitchContainer(Box<HircMusicSwitchCntr>),
     MusicRanSeqCntr(Box<HircMusicRanSeqCntr>),
-    Attenuation(HircUnmanagedEntry),
-    DialogueEvent(HircUnmanagedEntry),
+    Attenuation(Box<HircAttenuation>),
+    DialogueEvent(Box<HircDialogueEvent>),
     MotionBus(HircUnmanagedEntry),
     MotionFx(HircUnmanagedEntry),
     Effect(HircUnmanagedEntry),
-    AuxiliaryBus(HircUnmanagedEntry),
+    AuxiliaryBus(Box<HircAudioBus>),
     Unknown(HircUnmanagedEntry),
 }
 
 impl HircEntryPayload {
+    /// Payload of an unmanaged entry type, see [HircEntryType::is_unmanaged].
+    pub(super) fn unmanaged(entry_type: HircEntryType, entry: HircUnmanagedEntry) -> Self {
+        match entry_type {
+            HircEntryType::SwitchContainer => HircEntryPayload::SwitchContainer(entry),
+            HircEntryType::MotionBus => HircEntryPayload::MotionBus(entry),
+            HircEntryType::MotionFx => HircEntryPayload::MotionFx(entry),
+            HircEntryType::Effect => HircEntryPayload::Effect(entry),
+            _ => HircEntryPayload::Unknown(entry),
+        }
+    }
+
+    /// Media sources referenced by Sound and MusicTrack entries.
+    pub fn sources(&self) -> &[AkBankSourceData] {
+        match self {
+            HircEntryPayload::Sound(sound) => std::slice::from_ref(&sound.source),
+            HircEntryPayload::MusicTrack(track) => track.sources(),
+            _ => &[],
+        }
+    }
+
+    pub fn sources_mut(&mut self) -> &mut [AkBankSourceData] {
+        match self {
+            HircEntryPayload::Sound(sound) => std::slice::from_mut(&mut sound.source),
+            HircEntryPayload::MusicTrack(track) => track.sources_mut(),
+            _ => &mut [],
+        }
+    }
+
+    /// NodeBaseParams of parsed audio nodes.
+    pub fn node_base_params(&self) -> Option<&NodeBaseParams> {
+        match self {
+            HircEntryPayload::MusicSegment(entry) => Some(entry.node_base_params()),
+            HircEntryPayload::MusicTrack(entry) => Some(entry.node_base_params()),
+            HircEntryPayload::MusicSwitchContainer(entry) => Some(entry.node_base_params()),
+            HircEntryPayload::Sound(entry) => Some(&entry.node_base_params),
+            HircEntryPayload::MusicRanSeqCntr(entry) => Some(entry.node_base_params()),
+            HircEntryPayload::RandomOrSequenceContainer(entry) => Some(&entry.node_base_params),
+            HircEntryPayload::ActorMixer(entry) => Some(&entry.node_base_params),
+            HircEntryPayload::BlendContainer(entry) => Some(&entry.node_base_params),
+            _ => None,
+        }
+    }
+
+    pub fn node_base_params_mut(&mut self) -> Option<&mut NodeBaseParams> {
+        match self {
+            HircEntryPayload::MusicSegment(entry) => Some(entry.node_base_params_mut()),
+            HircEntryPayload::MusicTrack(entry) => Some(entry.node_base_params_mut()),
+            HircEntryPayload::MusicSwitchContainer(entry) => Some(entry.node_base_params_mut()),
+            HircEntryPayload::Sound(entry) => Some(&mut entry.node_base_params),
+            HircEntryPayload::MusicRanSeqCntr(entry) => Some(entry.node_base_params_mut()),
+            HircEntryPayload::RandomOrSequenceContainer(entry) => Some(&mut entry.node_base_params),
+            HircEntryPayload::ActorMixer(entry) => Some(&mut entry.node_base_params),
+            HircEntryPayload::BlendContainer(entry) => Some(&mut entry.node_base_params),
+            _ => None,
+        }
+    }
+
+    /// Child nodes of parsed containers.
+    pub fn children(&self) -> &[u32] {
+        match self {
+            HircEntryPayload::RandomOrSequenceContainer(entry) => entry.children(),
+            HircEntryPayload::ActorMixer(entry) => entry.children(),
+            HircEntryPayload::BlendContainer(entry) => entry.children(),
+            HircEntryPayload::MusicSegment(entry) => entry.children(),
+            HircEntryPayload::MusicSwitchContainer(entry) => entry.children(),
+            HircEntryPayload::MusicRanSeqCntr(entry) => entry.children(),
+            _ => &[],
+        }
+    }
+
+    /// Output bus and parent of audio nodes, including containers which are
+    /// not parsed yet.
+    pub fn routing(&self) -> Option<NodeRouting> {
+        if let Some(params) = self.node_base_params() {
+            return Some(params.routing());
+        }
+        match self {
+            HircEntryPayload::SwitchContainer(entry) => {
+                NodeBaseParamsPrefix::from_bytes(&entry.data).map(|prefix| prefix.routing())
+            }
+            _ => None,
+        }
+    }
+
+    /// Attenuation referenced by audio nodes and buses.
+    pub fn attenuation_id(&self) -> Option<u32> {
+        self.prop(PROP_ATTENUATION_ID)
+    }
+
+    /// Raw property value of audio nodes and buses.
+    pub(crate) fn prop(&self, p_id: u8) -> Option<u32> {
+        if let Some(params) = self.node_base_params() {
+            return params.prop(p_id);
+        }
+        match self {
+            HircEntryPayload::SwitchContainer(entry) => {
+                NodeBaseParamsPrefix::from_bytes(&entry.data)?.prop(p_id)
+            }
+            HircEntryPayload::AudioBus(entry) | HircEntryPayload::AuxiliaryBus(entry) => {
+                entry.prop(p_id)
+            }
+            _ => None,
+        }
+    }
+
+    /// Parent bus of buses, 0 for master buses.
+    pub fn parent_bus_id(&self) -> Option<u32> {
+        match self {
+            HircEntryPayload::AudioBus(entry) | HircEntryPayload::AuxiliaryBus(entry) => {
+                Some(entry.parent_bus_id())
+            }
+            _ => None,
+        }
+    }
+
+    /// Size of the parts of the payload kept as raw bytes instead of typed
+    /// fields, see [Bnk::parse_coverage](super::Bnk::parse_coverage).
+    pub(crate) fn raw_len(&self) -> usize {
+        let plugin_params: usize = self
+            .sources()
+            .iter()
+            .filter_map(|source| source.plugin_params.as_ref())
+            .map(|params| params.data.len())
+            .sum();
+        let raw = match self {
+            HircEntryPayload::SwitchContainer(entry)
+            | HircEntryPayload::MotionBus(entry)
+            | HircEntryPayload::MotionFx(entry)
+            | HircEntryPayload::Effect(entry)
+            | HircEntryPayload::Unknown(entry) => entry.data.len(),
+            // action specific parameters
+            HircEntryPayload::EventAction(action) => action.data.len(),
+            HircEntryPayload::AudioBus(bus) | HircEntryPayload::AuxiliaryBus(bus) => bus.raw_len(),
+            HircEntryPayload::DialogueEvent(event) => event.raw_len(),
+            HircEntryPayload::Sound(sound) => sound.trailing.len(),
+            HircEntryPayload::Settings(_)
+            | HircEntryPayload::Event { .. }
+            | HircEntryPayload::MusicSegment(_)
+            | HircEntryPayload::MusicTrack(_)
+            | HircEntryPayload::MusicSwitchContainer(_)
+            | HircEntryPayload::MusicRanSeqCntr(_)
+            | HircEntryPayload::RandomOrSequenceContainer(_)
+            | HircEntryPayload::ActorMixer(_)
+            | HircEntryPayload::Attenuation(_)
+            | HircEntryPayload::BlendContainer(_) => 0,
+        };
+        plugin_params + raw
+    }
+
     fn fix_values(&mut self) -> Result<()> {
         match self {
-            HircEntryPayload::Settings(v) => v.fix_values(),
+            HircEntryPayload::Settings(_) => Ok(()),
             HircEntryPayload::Sound(_) => Ok(()),
             HircEntryPayload::EventAction(_) => Ok(()),
             HircEntryPayload::Event { .. } => Ok(()),
@@ -322,6 +729,7 @@ impl HircEntryPayload {
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct HircUnmanagedEntry {
+    #[cfg_attr(feature = "serde", serde(with = "crate::bnk::blob"))]
     pub data: Vec<u8>,
 }
 
@@ -346,21 +754,41 @@ impl EntryPayloadExt for HircUnmanagedEntry {
 
 #[binrw]
 #[brw(little)]
-#[br(import{ data_length: u32 })]
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct HircSound {
-    _unk1: u32,
-    _unk2: u8,
-    pub state: u32,
-    pub audio_id: u32,
-    pub source_id: u32,
-    pub sound_type: HircSoundType,
-    _unk3: u32,
-    _unk4: u8,
-    pub game_object_id: u32, // probably
-    #[br(count = data_length - 31)]
-    pub data: Vec<u8>,
+    pub source: AkBankSourceData,
+    pub node_base_params: NodeBaseParams,
+    /// Bytes past the known layout, written back unchanged.
+    #[br(parse_with = binrw::helpers::until_eof)]
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            default,
+            skip_serializing_if = "Vec::is_empty",
+            with = "crate::bnk::blob"
+        )
+    )]
+    pub trailing: Vec<u8>,
+}
+
+impl EntryPayloadExt for HircSound {
+    fn from_reader<R>(reader: &mut R, length: u32) -> Result<Self>
+    where
+        R: io::Read + io::Seek,
+    {
+        // the trailing data is read to the end of the entry
+        let data = reader.read_vec_u8(length as usize - 4)?;
+        Ok(HircSound::read(&mut io::Cursor::new(data))?)
+    }
+
+    fn write_to<W>(&self, writer: &mut W) -> Result<()>
+    where
+        W: io::Write + io::Seek,
+    {
+        self.write(writer)?;
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -377,14 +805,202 @@ pub struct HircEventAction {
     pub data: Vec<u8>,
 }
 
-#[binrw]
-#[brw(repr(u8))]
-#[repr(u8)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+impl HircEventAction {
+    /// Offset of the action type specific parameters in `data`.
+    ///
+    /// Property values are 4 bytes wide while `parameters` holds one byte per
+    /// property, so the remaining property values and the ranged property
+    /// bundle (count, ids, min/max pairs) spill into `data`.
+    fn specific_params_offset(&self) -> Option<usize> {
+        let (ranged_count_pos, ranged_count) = if self.parameter_count == 0 {
+            (None, self._unk2)
+        } else {
+            let pos = 3 * self.parameter_count as usize - 1;
+            (Some(pos), *self.data.get(pos)?)
+        };
+        let offset = ranged_count_pos.map_or(0, |pos| pos + 1) + 9 * ranged_count as usize;
+        (offset <= self.data.len()).then_some(offset)
+    }
+
+    /// Bytes of the bank id loaded by a Play action.
+    pub(crate) fn play_bank_id_mut(&mut self) -> Option<&mut [u8; 4]> {
+        if self.action_type != HircEventActionType::Play {
+            return None;
+        }
+        // fade curve: u8, bank id: u32
+        let offset = self.specific_params_offset()? + 1;
+        self.data.get_mut(offset..offset + 4)?.try_into().ok()
+    }
+
+    /// Group and value ids set by a SetState or SetSwitch action.
+    pub fn game_sync_value(&self) -> Option<(u32, u32)> {
+        match self.params()? {
+            HircEventActionParams::SetState { group_id, state_id } => Some((group_id, state_id)),
+            HircEventActionParams::SetSwitch {
+                group_id,
+                switch_id,
+            } => Some((group_id, switch_id)),
+            _ => None,
+        }
+    }
+
+    /// Decode the parameters specific to the action type, `None` for types
+    /// without decoded parameters.
+    pub fn params(&self) -> Option<HircEventActionParams> {
+        let offset = self.specific_params_offset()?;
+        let mut reader = io::Cursor::new(self.data.get(offset..)?);
+        let params = match self.action_type {
+            HircEventActionType::Play => HircEventActionParams::Play {
+                fade_curve: reader.read_u8().ok()?,
+                bank_id: reader.read_u32::<LE>().ok()?,
+            },
+            HircEventActionType::SetState => HircEventActionParams::SetState {
+                group_id: reader.read_u32::<LE>().ok()?,
+                state_id: reader.read_u32::<LE>().ok()?,
+            },
+            HircEventActionType::SetSwitch => HircEventActionParams::SetSwitch {
+                group_id: reader.read_u32::<LE>().ok()?,
+                switch_id: reader.read_u32::<LE>().ok()?,
+            },
+            HircEventActionType::Seek => HircEventActionParams::Seek {
+                relative_to_duration: reader.read_u8().ok()? != 0,
+                position: reader.read_f32::<LE>().ok()?,
+                position_min: reader.read_f32::<LE>().ok()?,
+                position_max: reader.read_f32::<LE>().ok()?,
+                snap_to_nearest_marker: reader.read_u8().ok()? != 0,
+            },
+            HircEventActionType::SetGameParameter => HircEventActionParams::SetGameParameter {
+                fade_curve: reader.read_u8().ok()?,
+                bypass_transition: reader.read_u8().ok()? != 0,
+                value_meaning: reader.read_u8().ok()?,
+                value: reader.read_f32::<LE>().ok()?,
+                value_min: reader.read_f32::<LE>().ok()?,
+                value_max: reader.read_f32::<LE>().ok()?,
+            },
+            _ => return None,
+        };
+        Some(params)
+    }
+
+    /// Replace the decoded parameters, the bytes following them are kept.
+    ///
+    /// Returns `false` if the parameters don't match the action type or the
+    /// action has no room for them.
+    pub fn set_params(&mut self, params: HircEventActionParams) -> bool {
+        if params.action_type() != self.action_type {
+            return false;
+        }
+        let mut bytes = Vec::new();
+        match params {
+            HircEventActionParams::Play {
+                fade_curve,
+                bank_id,
+            } => {
+                bytes.push(fade_curve);
+                bytes.extend(bank_id.to_le_bytes());
+            }
+            HircEventActionParams::SetState { group_id, state_id } => {
+                bytes.extend(group_id.to_le_bytes());
+                bytes.extend(state_id.to_le_bytes());
+            }
+            HircEventActionParams::SetSwitch {
+                group_id,
+                switch_id,
+            } => {
+                bytes.extend(group_id.to_le_bytes());
+                bytes.extend(switch_id.to_le_bytes());
+            }
+            HircEventActionParams::Seek {
+                relative_to_duration,
+                position,
+                position_min,
+                position_max,
+                snap_to_nearest_marker,
+            } => {
+                bytes.push(relative_to_duration as u8);
+                for value in [position, position_min, position_max] {
+                    bytes.extend(value.to_le_bytes());
+                }
+                bytes.push(snap_to_nearest_marker as u8);
+            }
+            HircEventActionParams::SetGameParameter {
+                fade_curve,
+                bypass_transition,
+                value_meaning,
+                value,
+                value_min,
+                value_max,
+            } => {
+                bytes.extend([fade_curve, bypass_transition as u8, value_meaning]);
+                for value in [value, value_min, value_max] {
+                    bytes.extend(value.to_le_bytes());
+                }
+            }
+        }
+        let Some(offset) = self.specific_params_offset() else {
+            return false;
+        };
+        match self.data.get_mut(offset..offset + bytes.len()) {
+            Some(data) => {
+                data.copy_from_slice(&bytes);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Parameters specific to the type of an action, see
+/// [HircEventAction::params].
+#[derive(Debug, Clone, Copy, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-pub enum HircSoundType {
-    Sfx = 0,
-    Voice = 1,
+pub enum HircEventActionParams {
+    Play {
+        /// See [AkCurveInterpolation].
+        fade_curve: u8,
+        /// Bank the target is loaded from.
+        bank_id: u32,
+    },
+    SetState {
+        group_id: u32,
+        state_id: u32,
+    },
+    SetSwitch {
+        group_id: u32,
+        switch_id: u32,
+    },
+    Seek {
+        /// Position in percent of the duration instead of milliseconds.
+        relative_to_duration: bool,
+        position: f32,
+        /// Randomization range, offsets to `position`.
+        position_min: f32,
+        position_max: f32,
+        snap_to_nearest_marker: bool,
+    },
+    SetGameParameter {
+        /// See [AkCurveInterpolation].
+        fade_curve: u8,
+        bypass_transition: bool,
+        /// 1 to offset the parameter by `value`, 2 to set it.
+        value_meaning: u8,
+        value: f32,
+        /// Randomization range, offsets to `value`.
+        value_min: f32,
+        value_max: f32,
+    },
+}
+
+impl HircEventActionParams {
+    pub fn action_type(&self) -> HircEventActionType {
+        match self {
+            HircEventActionParams::Play { .. } => HircEventActionType::Play,
+            HircEventActionParams::SetState { .. } => HircEventActionType::SetState,
+            HircEventActionParams::SetSwitch { .. } => HircEventActionType::SetSwitch,
+            HircEventActionParams::Seek { .. } => HircEventActionType::Seek,
+            HircEventActionParams::SetGameParameter { .. } => HircEventActionType::SetGameParameter,
+        }
+    }
 }
 
 #[repr(u8)]
@@ -400,14 +1016,14 @@ pub enum HircEventActionScope {
 }
 
 #[repr(u8)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::FromRepr)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, strum::FromRepr)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum HircEventActionType {
     Stop = 1,
     Pause = 2,
     Resume = 3,
     Play = 4,
-    Trigger = 5,
+    PlayAndContinue = 5,
     Mute = 6,
     UnMute = 7,
     SetVoicePitch = 8,
@@ -423,11 +1039,12 @@ pub enum HircEventActionType {
     SetState = 18,
     SetGameParameter = 19,
     ResetGameParameter = 20,
-    SetSwitch = 21,
-    ToggleBypass = 22,
-    ResetBypassEffect = 23,
-    Break = 24,
-    Seek = 25,
+    SetSwitch = 25,
+    ToggleBypass = 26,
+    ResetBypassEffect = 27,
+    Break = 28,
+    Trigger = 29,
+    Seek = 30,
     Unknown(u8),
 }
 
@@ -438,7 +1055,7 @@ impl HircEventActionType {
             HircEventActionType::Pause => 2,
             HircEventActionType::Resume => 3,
             HircEventActionType::Play => 4,
-            HircEventActionType::Trigger => 5,
+            HircEventActionType::PlayAndContinue => 5,
             HircEventActionType::Mute => 6,
             HircEventActionType::UnMute => 7,
             HircEventActionType::SetVoicePitch => 8,
@@ -454,11 +1071,12 @@ impl HircEventActionType {
             HircEventActionType::SetState => 18,
             HircEventActionType::SetGameParameter => 19,
             HircEventActionType::ResetGameParameter => 20,
-            HircEventActionType::SetSwitch => 21,
-            HircEventActionType::ToggleBypass => 22,
-            HircEventActionType::ResetBypassEffect => 23,
-            HircEventActionType::Break => 24,
-            HircEventActionType::Seek => 25,
+            HircEventActionType::SetSwitch => 25,
+            HircEventActionType::ToggleBypass => 26,
+            HircEventActionType::ResetBypassEffect => 27,
+            HircEventActionType::Break => 28,
+            HircEventActionType::Trigger => 29,
+            HircEventActionType::Seek => 30,
             HircEventActionType::Unknown(x) => *x,
         }
     }
@@ -513,7 +1131,7 @@ pub enum HircEntryType {
 }
 
 impl HircEntryType {
-    fn as_u8(&self) -> u8 {
+    pub(crate) fn as_u8(&self) -> u8 {
         match self {
             HircEntryType::Settings => 1,
             HircEntryType::Sound => 2,
@@ -537,11 +1155,42 @@ impl HircEntryType {
             HircEntryType::Unknown(x) => *x,
         }
     }
+
+    /// Whether entries of this type are kept as [HircUnmanagedEntry].
+    pub(super) fn is_unmanaged(&self) -> bool {
+        matches!(
+            self,
+            HircEntryType::SwitchContainer
+                | HircEntryType::MotionBus
+                | HircEntryType::MotionFx
+                | HircEntryType::Effect
+                | HircEntryType::Unknown(_)
+        )
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::bnk::{
+        Bnk,
+        tests::{INPUT_HIRC, INPUT_HIRC_2, reread_bank},
+    };
+
+    /// Read an entry from the bytes following its type, checking that they
+    /// are all read and written back unchanged.
+    pub(super) fn roundtrip(entry_type: HircEntryType, bytes: &[u8]) -> HircEntry {
+        let mut reader = io::Cursor::new(bytes);
+        let entry = HircEntry::from_reader(&mut reader, entry_type, HIRC_LAYOUT_VERSION).unwrap();
+        assert_eq!(reader.position() as usize, bytes.len());
+        assert_eq!(&entry.to_bytes().unwrap()[1..], bytes);
+        entry
+    }
+
+    /// Write an edited entry and read it back.
+    pub(super) fn reread(entry: &HircEntry) -> HircEntry {
+        roundtrip(entry.entry_type, &entry.to_bytes().unwrap()[1..])
+    }
 
     #[test]
     fn test_event_action() {
@@ -551,7 +1200,223 @@ mod tests {
             0x00, 0x00, 0x00, 0x00, 0x00,
         ];
         let mut reader = io::Cursor::new(data);
-        let hirc_entry = HircEntry::from_reader(&mut reader, HircEntryType::EventAction).unwrap();
+        let hirc_entry =
+            HircEntry::from_reader(&mut reader, HircEntryType::EventAction, HIRC_LAYOUT_VERSION)
+                .unwrap();
         eprintln!("{:#?}", hirc_entry);
     }
+
+    #[test]
+    fn test_constructors() {
+        let entries = [
+            HircEntry::new_event(1, vec![2]),
+            HircEntry::new_play_action(2, 3, 4),
+            HircEntry::new_sound(3, 5, 100, 6),
+        ];
+        for entry in &entries {
+            reread(entry);
+        }
+
+        let HircEntryPayload::EventAction(action) = &entries[1].payload else {
+            panic!();
+        };
+        assert_eq!(action.game_object_id, 3);
+        let mut action = action.clone();
+        assert_eq!(*action.play_bank_id_mut().unwrap(), 4u32.to_le_bytes());
+
+        let sound = &entries[2].payload;
+        assert_eq!(sound.sources()[0].source_id(), 5);
+        assert_eq!(sound.routing().unwrap().direct_parent_id, 6);
+        assert!(matches!(sound, HircEntryPayload::Sound(sound) if sound.trailing.is_empty()));
+
+        // the action count of recent versions is a single byte
+        let event = HircEntry::new_event(1, (0..256).collect());
+        assert!(matches!(
+            event.to_bytes(),
+            Err(BnkError::TooManyActions {
+                id: 1,
+                count: 256,
+                max: 255
+            })
+        ));
+        reread(&HircEntry::new_event(1, (0..255).collect()));
+    }
+
+    #[test]
+    fn test_prop_bundle() {
+        let mut entry = HircEntry::new_sound(1, 2, 100, 0);
+        let HircEntryPayload::Sound(sound) = &mut entry.payload else {
+            panic!();
+        };
+        let props = sound.node_base_params.props_mut();
+        props.set(AkPropId::Volume, -3.5);
+        props.set(AkPropId::Loop, 0);
+        props.set(AkPropId::Unknown(0x7F), AkPropValue::Id(5));
+        assert_eq!(sound.node_base_params.volume(), -3.5);
+
+        let entry = reread(&entry);
+        let props = entry.payload.node_base_params().unwrap().props();
+        assert_eq!(props.get(AkPropId::Volume), Some(AkPropValue::Float(-3.5)));
+        assert_eq!(props.get(AkPropId::Loop).unwrap().as_i32(), Some(0));
+        assert_eq!(props.get(AkPropId::Pitch), None);
+        let ids: Vec<_> = props.iter().map(|prop| prop.id()).collect();
+        assert_eq!(
+            ids,
+            [AkPropId::Volume, AkPropId::Loop, AkPropId::Unknown(0x7F)]
+        );
+        assert_eq!(props.iter().last().unwrap().raw_value(), 5);
+
+        let mut props = props.clone();
+        assert_eq!(props.remove(AkPropId::Loop), Some(AkPropValue::Int(0)));
+        assert_eq!(props.iter().count(), 2);
+
+        // 0x48 is the discriminant of Unknown, not a known id
+        assert_eq!(AkPropId::from_u8(0x48), AkPropId::Unknown(0x48));
+        for id in 0..=u8::MAX {
+            assert_eq!(AkPropId::from_u8(id).as_u8(), id);
+        }
+    }
+
+    #[test]
+    fn test_action_params() {
+        let bnk = Bnk::from_file(INPUT_HIRC).unwrap();
+        let bank_id = bnk.bank_id().unwrap();
+        let mut decoded = 0;
+        for entry in bnk.hirc_entries() {
+            let HircEntryPayload::EventAction(action) = &entry.payload else {
+                continue;
+            };
+            match action.params() {
+                Some(HircEventActionParams::Play { bank_id: id, .. }) => assert_eq!(id, bank_id),
+                Some(HircEventActionParams::SetState { group_id, state_id }) => {
+                    assert_eq!(action.game_sync_value(), Some((group_id, state_id)))
+                }
+                Some(params) => assert_eq!(params.action_type(), action.action_type),
+                None => continue,
+            }
+            decoded += 1;
+        }
+        assert!(decoded > 0);
+
+        let mut bnk = Bnk::from_file(INPUT_HIRC_2).unwrap();
+        let (id, params) = bnk
+            .hirc_entries()
+            .find_map(|entry| match &entry.payload {
+                HircEntryPayload::EventAction(action) => match action.params()? {
+                    params @ HircEventActionParams::SetGameParameter { .. } => {
+                        Some((entry.id, params))
+                    }
+                    _ => None,
+                },
+                _ => None,
+            })
+            .unwrap();
+        let HircEventActionParams::SetGameParameter {
+            fade_curve,
+            bypass_transition,
+            value_meaning,
+            ..
+        } = params
+        else {
+            panic!();
+        };
+        let edited = HircEventActionParams::SetGameParameter {
+            fade_curve,
+            bypass_transition,
+            value_meaning,
+            value: 0.5,
+            value_min: -1.0,
+            value_max: 1.0,
+        };
+        let HircEntryPayload::EventAction(action) = &mut bnk.hirc_entry_mut(id).unwrap().payload
+        else {
+            panic!();
+        };
+        let trailing = action.data.len();
+        assert!(!action.set_params(HircEventActionParams::SetState {
+            group_id: 1,
+            state_id: 2
+        }));
+        assert!(action.set_params(edited));
+        assert_eq!(action.data.len(), trailing);
+
+        let bnk = reread_bank(&bnk);
+        let HircEntryPayload::EventAction(action) = &bnk.hirc_entry(id).unwrap().payload else {
+            panic!();
+        };
+        assert_eq!(action.params(), Some(edited));
+    }
+
+    #[test]
+    fn test_sound_edit() {
+        let bnk = Bnk::from_file(INPUT_HIRC).unwrap();
+        let sound = bnk
+            .hirc_entries()
+            .find(|entry| entry.entry_type == HircEntryType::Sound)
+            .unwrap();
+        let HircEntryPayload::Sound(parsed) = &sound.payload else {
+            panic!();
+        };
+        assert!(parsed.trailing.is_empty());
+
+        // bytes past the known layout are kept
+        let mut data = sound.to_bytes().unwrap()[1..].to_vec();
+        data.extend([1, 2, 3]);
+        let length = u32::from_le_bytes(data[..4].try_into().unwrap()) + 3;
+        data[..4].copy_from_slice(&length.to_le_bytes());
+        let mut entry = roundtrip(HircEntryType::Sound, &data);
+
+        let HircEntryPayload::Sound(sound) = &mut entry.payload else {
+            panic!();
+        };
+        assert_eq!(sound.trailing, [1, 2, 3]);
+        sound.node_base_params.set_volume(-6.0);
+        sound.node_base_params.set_pitch(1200.0);
+        sound.node_base_params.set_override_bus_id(42);
+
+        assert!(entry.to_bytes().unwrap().ends_with(&[1, 2, 3]));
+        let entry = reread(&entry);
+        let HircEntryPayload::Sound(sound) = &entry.payload else {
+            panic!();
+        };
+        assert_eq!(sound.node_base_params.volume(), -6.0);
+        assert_eq!(sound.node_base_params.pitch(), 1200.0);
+        assert_eq!(entry.payload.routing().unwrap().override_bus_id, 42);
+        assert_eq!(entry.payload.raw_len(), 3);
+    }
+
+    #[test]
+    fn test_hirc_version() {
+        // events of older banks count their actions with a u32
+        let mut data = 16u32.to_le_bytes().to_vec();
+        data.extend(100u32.to_le_bytes());
+        data.extend(2u32.to_le_bytes());
+        data.extend(1u32.to_le_bytes());
+        data.extend(2u32.to_le_bytes());
+        let entry =
+            HircEntry::from_reader(&mut io::Cursor::new(&data), HircEntryType::Event, 120).unwrap();
+        let HircEntryPayload::Event { action_ids } = &entry.payload else {
+            panic!();
+        };
+        assert_eq!(action_ids, &[1, 2]);
+        assert_eq!(&entry.to_bytes().unwrap()[1..], &data);
+
+        // entries not matching the known layouts are kept raw
+        let mut data = 12u32.to_le_bytes().to_vec();
+        data.extend(100u32.to_le_bytes());
+        data.extend([0xFF; 8]);
+        let entry =
+            HircEntry::from_reader(&mut io::Cursor::new(&data), HircEntryType::Sound, 120).unwrap();
+        assert_eq!(entry.entry_type, HircEntryType::Sound);
+        assert!(matches!(entry.payload, HircEntryPayload::Unknown(_)));
+        assert_eq!(&entry.to_bytes().unwrap()[1..], &data);
+        assert!(
+            HircEntry::from_reader(
+                &mut io::Cursor::new(&data),
+                HircEntryType::Sound,
+                HIRC_LAYOUT_VERSION
+            )
+            .is_err()
+        );
+    }
 }
@@ -0,0 +1,101 @@
+use std::io::{Read, Seek, Write};
+
+use binrw::{BinRead, BinWrite, binrw};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::{Children, EntryPayloadExt, Result, common::NodeBaseParams};
+
+/// Actor-mixer (CAkActorMixer), groups nodes to share their properties.
+#[binrw]
+#[brw(little)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct HircActorMixer {
+    pub node_base_params: NodeBaseParams,
+    children: Children,
+}
+
+impl EntryPayloadExt for HircActorMixer {
+    fn from_reader<R>(reader: &mut R, _length: u32) -> Result<Self>
+    where
+        R: Read + Seek,
+    {
+        let actor_mixer = HircActorMixer::read(reader)?;
+        Ok(actor_mixer)
+    }
+
+    fn write_to<W>(&self, writer: &mut W) -> Result<()>
+    where
+        W: Write + Seek,
+    {
+        self.write(writer)?;
+        Ok(())
+    }
+
+    fn fix_values(&mut self) -> Result<()> {
+        self.children.num_children = self.children.children.len() as u32;
+        Ok(())
+    }
+}
+
+impl HircActorMixer {
+    /// Ids of the child nodes, sorted.
+    pub fn children(&self) -> &[u32] {
+        &self.children.children
+    }
+
+    /// Add a child node, keeping children sorted. Returns `false` if already present.
+    pub fn add_child(&mut self, id: u32) -> bool {
+        let Err(idx) = self.children.children.binary_search(&id) else {
+            return false;
+        };
+        self.children.children.insert(idx, id);
+        true
+    }
+
+    /// Remove a child node, returns `false` if absent.
+    pub fn remove_child(&mut self, id: u32) -> bool {
+        let Ok(idx) = self.children.children.binary_search(&id) else {
+            return false;
+        };
+        self.children.children.remove(idx);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bnk::{
+        Bnk,
+        hirc::{HircEntryPayload, tests::reread},
+        tests::INPUT_HIRC,
+    };
+
+    #[test]
+    fn test_actor_mixer() {
+        let bnk = Bnk::from_file(INPUT_HIRC).unwrap();
+        let mut entry = bnk.hirc_entry(277229044).unwrap().clone();
+        let HircEntryPayload::ActorMixer(mixer) = &mut entry.payload else {
+            panic!();
+        };
+        assert_eq!(mixer.children().len(), 13);
+        assert_eq!(mixer.children()[0], 0x060702F9);
+        assert_eq!(mixer.node_base_params.direct_parent_id(), 0x2CED7974);
+
+        mixer.node_base_params.set_volume(-3.0);
+        mixer.node_base_params.set_override_bus_id(1234);
+        assert!(mixer.add_child(1));
+        assert!(!mixer.add_child(1));
+        assert!(mixer.remove_child(0x060702F9));
+
+        let entry = reread(&entry);
+        let HircEntryPayload::ActorMixer(mixer) = &entry.payload else {
+            panic!();
+        };
+        assert_eq!(mixer.children().len(), 13);
+        assert_eq!(mixer.children()[0], 1);
+        assert_eq!(mixer.node_base_params.volume(), -3.0);
+        assert_eq!(entry.payload.routing().unwrap().override_bus_id, 1234);
+    }
+}
@@ -0,0 +1,222 @@
+use std::io::{Read, Seek, Write};
+
+use binrw::{BinRead, BinWrite, binrw};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::{
+    EntryPayloadExt, Result,
+    common::{AkRTPCGraphPoint, InitialRTPC, evaluate_graph},
+};
+
+/// Attenuation entry (CAkAttenuation), in the layout of bank version 145.
+#[binrw]
+#[brw(little)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct HircAttenuation {
+    pub is_height_spread_enabled: u8,
+    #[br(temp)]
+    #[bw(calc = cone.is_some() as u8)]
+    is_cone_enabled: u8,
+    #[br(if(is_cone_enabled != 0))]
+    pub cone: Option<AttenuationCone>,
+    /// Index in `curves` of the curve of each [AkAttenuationCurveType],
+    /// see [CURVE_NONE] and [CURVE_USE_PROJECT].
+    pub curves_to_use: [u8; AkAttenuationCurveType::COUNT],
+    #[br(temp)]
+    #[bw(calc = curves.len() as u8)]
+    num_curves: u8,
+    #[br(count = num_curves)]
+    pub curves: Vec<AttenuationCurve>,
+    initial_rtpc: InitialRTPC,
+}
+
+impl EntryPayloadExt for HircAttenuation {
+    fn from_reader<R>(reader: &mut R, _length: u32) -> Result<Self>
+    where
+        R: Read + Seek,
+    {
+        let attenuation = HircAttenuation::read(reader)?;
+        Ok(attenuation)
+    }
+
+    fn write_to<W>(&self, writer: &mut W) -> Result<()>
+    where
+        W: Write + Seek,
+    {
+        self.write(writer)?;
+        Ok(())
+    }
+}
+
+/// Value of `curves_to_use` for parameters without a curve.
+pub const CURVE_NONE: u8 = 0xFF;
+/// Value of `curves_to_use` for parameters using the curve of the project.
+pub const CURVE_USE_PROJECT: u8 = 0xFE;
+
+impl HircAttenuation {
+    /// Curve driving a parameter, `None` if unset or from the project.
+    pub fn curve(&self, curve_type: AkAttenuationCurveType) -> Option<&AttenuationCurve> {
+        self.curves
+            .get(self.curves_to_use[curve_type as usize] as usize)
+    }
+
+    /// Curve driving a parameter, may be shared with other parameters.
+    pub fn curve_mut(
+        &mut self,
+        curve_type: AkAttenuationCurveType,
+    ) -> Option<&mut AttenuationCurve> {
+        self.curves
+            .get_mut(self.curves_to_use[curve_type as usize] as usize)
+    }
+
+    /// Maximum distance of the attenuation, the end of the dry volume curve.
+    pub fn max_distance(&self) -> Option<f32> {
+        self.curve(AkAttenuationCurveType::VolumeDry)?
+            .points
+            .last()
+            .map(|point| point.from)
+    }
+
+    pub fn initial_rtpc(&self) -> &InitialRTPC {
+        &self.initial_rtpc
+    }
+}
+
+#[binrw]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AttenuationCone {
+    pub inside_degrees: f32,
+    pub outside_degrees: f32,
+    /// Volume in dB outside of the cone.
+    pub outside_volume: f32,
+    pub low_pass: f32,
+    pub high_pass: f32,
+}
+
+/// Parameters driven by attenuation curves, indices of `curves_to_use`.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, strum::FromRepr)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum AkAttenuationCurveType {
+    VolumeDry = 0,
+    VolumeWetGame = 1,
+    VolumeWetUser = 2,
+    LowPassFilter = 3,
+    HighPassFilter = 4,
+    Spread = 5,
+    Focus = 6,
+    ObstructionVolume = 7,
+    ObstructionLpf = 8,
+    ObstructionHpf = 9,
+    OcclusionVolume = 10,
+    OcclusionLpf = 11,
+    OcclusionHpf = 12,
+    DiffractionVolume = 13,
+    DiffractionLpf = 14,
+    DiffractionHpf = 15,
+    TransmissionVolume = 16,
+    TransmissionLpf = 17,
+    TransmissionHpf = 18,
+}
+
+impl AkAttenuationCurveType {
+    pub const COUNT: usize = 19;
+}
+
+/// An attenuation curve (`AkConversionTable`), mapping distance to a value.
+#[binrw]
+#[brw(little)]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AttenuationCurve {
+    /// Value scaling, see `AkCurveScaling`.
+    pub scaling: u8,
+    #[br(temp)]
+    #[bw(calc = points.len() as u16)]
+    size: u16,
+    #[br(count = size)]
+    pub points: Vec<AkRTPCGraphPoint>,
+}
+
+impl AttenuationCurve {
+    /// Evaluate the curve at `distance`, honoring the interpolation of each segment.
+    pub fn evaluate(&self, distance: f32) -> f32 {
+        evaluate_graph(&self.points, distance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::common::AkCurveInterpolation;
+    use super::*;
+    use crate::bnk::{
+        Bnk,
+        hirc::{HircEntryPayload, tests::reread},
+        tests::INPUT_HIRC,
+    };
+
+    #[test]
+    fn test_attenuation_curve_evaluate() {
+        let point = |from, to, interp: AkCurveInterpolation| AkRTPCGraphPoint {
+            from,
+            to,
+            interp: interp as u32,
+        };
+        let curve = AttenuationCurve {
+            scaling: 2,
+            points: vec![
+                point(0.0, 0.0, AkCurveInterpolation::Linear),
+                point(100.0, -50.0, AkCurveInterpolation::Constant),
+                point(200.0, -200.0, AkCurveInterpolation::Linear),
+            ],
+        };
+        assert_eq!(curve.evaluate(-10.0), 0.0);
+        assert_eq!(curve.evaluate(50.0), -25.0);
+        assert_eq!(curve.evaluate(150.0), -50.0);
+        assert_eq!(curve.evaluate(250.0), -200.0);
+    }
+
+    #[test]
+    fn test_attenuation() {
+        let bnk = Bnk::from_file(INPUT_HIRC).unwrap();
+        let mut entry = bnk.hirc_entry(1002072031).unwrap().clone();
+        let HircEntryPayload::Attenuation(attenuation) = &mut entry.payload else {
+            panic!();
+        };
+        assert!(attenuation.cone.is_none());
+        assert_eq!(attenuation.curves.len(), 3);
+        assert_eq!(attenuation.max_distance(), Some(15.0));
+        assert!(
+            attenuation
+                .curve(AkAttenuationCurveType::LowPassFilter)
+                .is_some()
+        );
+        assert!(attenuation.curve(AkAttenuationCurveType::Spread).is_none());
+        assert_eq!(
+            attenuation.curves_to_use[AkAttenuationCurveType::ObstructionVolume as usize],
+            CURVE_USE_PROJECT
+        );
+
+        let dry = attenuation
+            .curve_mut(AkAttenuationCurveType::VolumeDry)
+            .unwrap();
+        dry.points.last_mut().unwrap().from = 30.0;
+        attenuation.cone = Some(AttenuationCone {
+            inside_degrees: 90.0,
+            outside_degrees: 180.0,
+            outside_volume: -6.0,
+            low_pass: 0.0,
+            high_pass: 0.0,
+        });
+
+        let entry = reread(&entry);
+        let HircEntryPayload::Attenuation(attenuation) = &entry.payload else {
+            panic!();
+        };
+        assert_eq!(attenuation.max_distance(), Some(30.0));
+        assert_eq!(attenuation.cone.as_ref().unwrap().outside_volume, -6.0);
+    }
+}
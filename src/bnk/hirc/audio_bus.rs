@@ -0,0 +1,432 @@
+use std::io::{Cursor, Read, Seek, Write};
+
+use binrw::{BinRead, BinWrite, binrw};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::{
+    EntryPayloadExt, Result,
+    common::{AkPropBundle, AuxParams, FxChunk, PROP_ATTENUATION_ID, PositioningParams},
+};
+
+/// AudioBus and AuxiliaryBus entries.
+///
+/// Bus params, ducking and the FX chain are decoded, the metadata, RTPC and
+/// state chunks following them are kept as raw `data`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct HircAudioBus {
+    bus_initial_values: BusInitialValues,
+}
+
+impl EntryPayloadExt for HircAudioBus {
+    fn from_reader<R>(reader: &mut R, length: u32) -> Result<Self>
+    where
+        R: Read + Seek,
+    {
+        // the trailing data is read to the end of the entry
+        let mut data = vec![0; length as usize - 4];
+        reader.read_exact(&mut data)?;
+        let bus_initial_values = BusInitialValues::read(&mut Cursor::new(data))?;
+        Ok(HircAudioBus { bus_initial_values })
+    }
+
+    fn write_to<W>(&self, writer: &mut W) -> Result<()>
+    where
+        W: Write + Seek,
+    {
+        self.bus_initial_values.write(writer)?;
+        Ok(())
+    }
+}
+
+impl HircAudioBus {
+    /// Parent bus, 0 for master buses.
+    pub fn parent_bus_id(&self) -> u32 {
+        self.bus_initial_values.override_bus_id
+    }
+
+    /// Move the bus under another parent, 0 to make it a master bus.
+    ///
+    /// Buses made master keep no device, set one with
+    /// [set_device_shareset_id](Self::set_device_shareset_id).
+    pub fn set_parent_bus_id(&mut self, parent_bus_id: u32) {
+        let values = &mut self.bus_initial_values;
+        values.override_bus_id = parent_bus_id;
+        values.device_shareset_id = match parent_bus_id {
+            0 => Some(values.device_shareset_id.unwrap_or(0)),
+            _ => None,
+        };
+    }
+
+    /// Audio device shareset of master buses.
+    pub fn device_shareset_id(&self) -> Option<u32> {
+        self.bus_initial_values.device_shareset_id
+    }
+
+    /// Set the audio device of a master bus, ignored for other buses.
+    pub fn set_device_shareset_id(&mut self, device_shareset_id: u32) {
+        if let Some(id) = &mut self.bus_initial_values.device_shareset_id {
+            *id = device_shareset_id;
+        }
+    }
+
+    /// Attenuation of the bus, used when it is 3D positioned.
+    pub fn attenuation_id(&self) -> Option<u32> {
+        self.prop(PROP_ATTENUATION_ID)
+    }
+
+    pub(crate) fn prop(&self, p_id: u8) -> Option<u32> {
+        self.bus_initial_values
+            .bus_initial_params
+            .props
+            .get_raw(p_id)
+    }
+
+    /// Properties set on this bus.
+    pub fn props(&self) -> &AkPropBundle {
+        &self.bus_initial_values.bus_initial_params.props
+    }
+
+    pub fn props_mut(&mut self) -> &mut AkPropBundle {
+        &mut self.bus_initial_values.bus_initial_params.props
+    }
+
+    /// Size of the trailing chunks kept as raw `data`.
+    pub(crate) fn raw_len(&self) -> usize {
+        self.bus_initial_values.data.len()
+    }
+
+    pub fn max_num_instance(&self) -> u16 {
+        self.bus_initial_values.bus_initial_params.max_num_instance
+    }
+
+    pub fn set_max_num_instance(&mut self, max_num_instance: u16) {
+        self.bus_initial_values.bus_initial_params.max_num_instance = max_num_instance;
+    }
+
+    pub fn channel_config(&self) -> AkChannelConfig {
+        self.bus_initial_values.bus_initial_params.channel_config
+    }
+
+    pub fn set_channel_config(&mut self, channel_config: AkChannelConfig) {
+        self.bus_initial_values.bus_initial_params.channel_config = channel_config;
+    }
+
+    pub fn is_hdr_bus(&self) -> bool {
+        self.bus_initial_values.bus_initial_params.bits_hdr & 1 != 0
+    }
+
+    pub fn set_hdr_bus(&mut self, is_hdr_bus: bool) {
+        let bits = &mut self.bus_initial_values.bus_initial_params.bits_hdr;
+        *bits = *bits & !1 | is_hdr_bus as u8;
+    }
+
+    /// HDR release mode, exponential if true, linear otherwise.
+    pub fn is_hdr_release_mode_exponential(&self) -> bool {
+        self.bus_initial_values.bus_initial_params.bits_hdr & (1 << 1) != 0
+    }
+
+    pub fn set_hdr_release_mode_exponential(&mut self, exponential: bool) {
+        let bits = &mut self.bus_initial_values.bus_initial_params.bits_hdr;
+        *bits = *bits & !(1 << 1) | (exponential as u8) << 1;
+    }
+
+    /// Time in ms for ducked buses to recover once the ducking ends.
+    pub fn recovery_time(&self) -> i32 {
+        self.bus_initial_values.recovery_time
+    }
+
+    pub fn set_recovery_time(&mut self, recovery_time: i32) {
+        self.bus_initial_values.recovery_time = recovery_time;
+    }
+
+    /// Maximum volume in dB this bus can be ducked by.
+    pub fn max_duck_volume(&self) -> f32 {
+        self.bus_initial_values.max_duck_volume
+    }
+
+    pub fn set_max_duck_volume(&mut self, max_duck_volume: f32) {
+        self.bus_initial_values.max_duck_volume = max_duck_volume;
+    }
+
+    /// Buses ducked while this bus plays.
+    pub fn ducks(&self) -> &[AkDuckInfo] {
+        &self.bus_initial_values.ducks
+    }
+
+    pub fn ducks_mut(&mut self) -> &mut Vec<AkDuckInfo> {
+        &mut self.bus_initial_values.ducks
+    }
+
+    /// Effects inserted on the bus.
+    pub fn fx_chunks(&self) -> &[FxChunk] {
+        &self.bus_initial_values.fx_params.fx_chunks
+    }
+
+    pub fn fx_chunks_mut(&mut self) -> &mut Vec<FxChunk> {
+        &mut self.bus_initial_values.fx_params.fx_chunks
+    }
+
+    /// Bypass bits of the effects by slot, bit 4 bypasses them all.
+    pub fn fx_bypass(&self) -> u8 {
+        self.bus_initial_values.fx_params.bits_fx_bypass
+    }
+
+    pub fn set_fx_bypass(&mut self, bits_fx_bypass: u8) {
+        self.bus_initial_values.fx_params.bits_fx_bypass = bits_fx_bypass;
+    }
+
+    /// Mixer plugin of the bus, 0 if none.
+    pub fn mixer_id(&self) -> u32 {
+        self.bus_initial_values.fx_params.mixer_id
+    }
+
+    pub fn set_mixer_id(&mut self, mixer_id: u32, is_share_set: bool) {
+        let fx_params = &mut self.bus_initial_values.fx_params;
+        fx_params.mixer_id = mixer_id;
+        fx_params.is_mixer_share_set = is_share_set as u8;
+    }
+}
+
+#[binrw]
+#[brw(little)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BusInitialValues {
+    override_bus_id: u32,
+    #[br(if(override_bus_id == 0))]
+    device_shareset_id: Option<u32>,
+    bus_initial_params: BusInitialParams,
+    recovery_time: i32,
+    max_duck_volume: f32,
+    #[br(temp)]
+    #[bw(calc = ducks.len() as u32)]
+    num_ducks: u32,
+    #[br(count = num_ducks)]
+    ducks: Vec<AkDuckInfo>,
+    fx_params: BusInitialFxParams,
+    /// Metadata, RTPC and state chunks.
+    #[br(parse_with = binrw::helpers::until_eof)]
+    #[cfg_attr(feature = "serde", serde(with = "crate::bnk::blob"))]
+    data: Vec<u8>,
+}
+
+#[binrw]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BusInitialFxParams {
+    #[br(temp)]
+    #[bw(calc = fx_chunks.len() as u8)]
+    num_fx: u8,
+    /// Only stored with effects.
+    #[br(if(num_fx > 0))]
+    #[bw(if(!fx_chunks.is_empty()))]
+    bits_fx_bypass: u8,
+    #[br(count = num_fx)]
+    fx_chunks: Vec<FxChunk>,
+    mixer_id: u32,
+    is_mixer_share_set: u8,
+}
+
+#[binrw]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BusInitialParams {
+    props: AkPropBundle,
+    positioning_params: PositioningParams,
+    aux_params: AuxParams,
+    /// Kill newest, use virtual behavior, override parent max instances,
+    /// background music.
+    by_bit_vector: u8,
+    max_num_instance: u16,
+    #[br(map = AkChannelConfig::from_u32)]
+    #[bw(map = |config| config.to_u32())]
+    channel_config: AkChannelConfig,
+    /// Is HDR bus, HDR release mode exponential.
+    bits_hdr: u8,
+}
+
+#[binrw]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AkDuckInfo {
+    pub bus_id: u32,
+    /// Duck volume in dB.
+    pub duck_volume: f32,
+    pub fade_out_time: i32,
+    pub fade_in_time: i32,
+    /// [`AkCurveInterpolation`](super::AkCurveInterpolation) value.
+    pub fade_curve: u8,
+    pub target_prop: u8,
+}
+
+/// Channel configuration, packed into a u32 in banks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AkChannelConfig {
+    pub num_channels: u8,
+    pub config_type: AkChannelConfigType,
+    /// Speaker mask of standard configurations, 20 bits.
+    pub channel_mask: u32,
+}
+
+impl AkChannelConfig {
+    pub const SPEAKER_FRONT_LEFT: u32 = 0x1;
+    pub const SPEAKER_FRONT_RIGHT: u32 = 0x2;
+    pub const SPEAKER_FRONT_CENTER: u32 = 0x4;
+    pub const SPEAKER_LOW_FREQUENCY: u32 = 0x8;
+    pub const SPEAKER_BACK_LEFT: u32 = 0x10;
+    pub const SPEAKER_BACK_RIGHT: u32 = 0x20;
+    pub const SPEAKER_SIDE_LEFT: u32 = 0x200;
+    pub const SPEAKER_SIDE_RIGHT: u32 = 0x400;
+
+    /// Same as the parent bus or the audio device.
+    pub fn parent() -> Self {
+        Self::from_u32(0)
+    }
+
+    pub fn standard(channel_mask: u32) -> Self {
+        AkChannelConfig {
+            num_channels: (channel_mask & 0xFFFFF).count_ones() as u8,
+            config_type: AkChannelConfigType::Standard,
+            channel_mask: channel_mask & 0xFFFFF,
+        }
+    }
+
+    pub fn mono() -> Self {
+        Self::standard(Self::SPEAKER_FRONT_CENTER)
+    }
+
+    pub fn stereo() -> Self {
+        Self::standard(Self::SPEAKER_FRONT_LEFT | Self::SPEAKER_FRONT_RIGHT)
+    }
+
+    pub fn surround_5_1() -> Self {
+        Self::standard(
+            Self::SPEAKER_FRONT_LEFT
+                | Self::SPEAKER_FRONT_RIGHT
+                | Self::SPEAKER_FRONT_CENTER
+                | Self::SPEAKER_LOW_FREQUENCY
+                | Self::SPEAKER_SIDE_LEFT
+                | Self::SPEAKER_SIDE_RIGHT,
+        )
+    }
+
+    pub fn ambisonic(num_channels: u8) -> Self {
+        AkChannelConfig {
+            num_channels,
+            config_type: AkChannelConfigType::Ambisonic,
+            channel_mask: 0,
+        }
+    }
+
+    pub fn from_u32(value: u32) -> Self {
+        let config_type = ((value >> 8) & 0xF) as u8;
+        AkChannelConfig {
+            num_channels: value as u8,
+            config_type: AkChannelConfigType::from_repr(config_type)
+                .unwrap_or(AkChannelConfigType::Unknown(config_type)),
+            channel_mask: value >> 12,
+        }
+    }
+
+    pub fn to_u32(&self) -> u32 {
+        self.num_channels as u32
+            | ((self.config_type.as_u8() & 0xF) as u32) << 8
+            | (self.channel_mask & 0xFFFFF) << 12
+    }
+
+    /// Whether a speaker is part of a standard configuration.
+    pub fn has_speaker(&self, speaker: u32) -> bool {
+        self.config_type == AkChannelConfigType::Standard && self.channel_mask & speaker != 0
+    }
+}
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::FromRepr)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum AkChannelConfigType {
+    Anonymous = 0x0,
+    Standard = 0x1,
+    Ambisonic = 0x2,
+    Objects = 0x3,
+    UseDeviceMain = 0xE,
+    UseDevicePassthrough = 0xF,
+    Unknown(u8),
+}
+
+impl AkChannelConfigType {
+    fn as_u8(&self) -> u8 {
+        match self {
+            AkChannelConfigType::Anonymous => 0x0,
+            AkChannelConfigType::Standard => 0x1,
+            AkChannelConfigType::Ambisonic => 0x2,
+            AkChannelConfigType::Objects => 0x3,
+            AkChannelConfigType::UseDeviceMain => 0xE,
+            AkChannelConfigType::UseDevicePassthrough => 0xF,
+            AkChannelConfigType::Unknown(x) => *x,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bnk::{
+        hirc::{HircEntryPayload, tests::reread},
+        tests::bus_entry,
+    };
+
+    #[test]
+    fn test_bus_params() {
+        let mut entry = bus_entry(1, 0, AkChannelConfig::surround_5_1());
+        let HircEntryPayload::AudioBus(bus) = &mut entry.payload else {
+            panic!();
+        };
+        assert_eq!(bus.parent_bus_id(), 0);
+        assert_eq!(bus.device_shareset_id(), Some(0x1234));
+        let config = bus.channel_config();
+        assert_eq!(config.num_channels, 6);
+        assert_eq!(config.config_type, AkChannelConfigType::Standard);
+        assert!(config.has_speaker(AkChannelConfig::SPEAKER_LOW_FREQUENCY));
+        assert_eq!(AkChannelConfig::from_u32(config.to_u32()), config);
+        assert!(!bus.is_hdr_bus());
+        assert_eq!(bus.recovery_time(), 500);
+
+        bus.set_channel_config(AkChannelConfig::stereo());
+        bus.set_hdr_bus(true);
+        bus.set_hdr_release_mode_exponential(true);
+        bus.set_max_duck_volume(-12.0);
+        bus.ducks_mut().push(AkDuckInfo {
+            bus_id: 2,
+            duck_volume: -6.0,
+            fade_out_time: 100,
+            fade_in_time: 200,
+            fade_curve: 4,
+            target_prop: 5,
+        });
+        bus.fx_chunks_mut().push(FxChunk {
+            fx_index: 0,
+            fx_id: 3,
+            is_share_set: 1,
+            is_rendered: 0,
+        });
+        bus.set_parent_bus_id(4);
+        assert_eq!(bus.device_shareset_id(), None);
+
+        let entry = reread(&entry);
+        let HircEntryPayload::AudioBus(bus) = &entry.payload else {
+            panic!();
+        };
+        assert_eq!(bus.channel_config(), AkChannelConfig::stereo());
+        assert!(bus.is_hdr_bus());
+        assert!(bus.is_hdr_release_mode_exponential());
+        assert_eq!(bus.max_duck_volume(), -12.0);
+        assert_eq!(bus.ducks()[0].bus_id, 2);
+        assert_eq!(bus.fx_chunks()[0].fx_id, 3);
+        assert_eq!(bus.fx_bypass(), 0);
+        assert_eq!(bus.parent_bus_id(), 4);
+        assert!(entry.to_bytes().unwrap().ends_with(&[0xAA; 6]));
+    }
+}
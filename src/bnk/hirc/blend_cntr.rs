@@ -0,0 +1,206 @@
+use std::io::{Read, Seek, Write};
+
+use binrw::{BinRead, BinWrite, binrw};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::{
+    Children, EntryPayloadExt, Result,
+    common::{AkRTPCGraphPoint, InitialRTPC, NodeBaseParams, evaluate_graph},
+};
+
+/// Blend container (CAkLayerCntr), plays its children at once in layers
+/// cross-faded by an RTPC.
+#[binrw]
+#[brw(little)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct HircBlendCntr {
+    pub node_base_params: NodeBaseParams,
+    children: Children,
+    #[br(temp)]
+    #[bw(calc = layers.len() as u32)]
+    num_layers: u32,
+    #[br(count = num_layers)]
+    pub layers: Vec<AkBlendLayer>,
+    pub is_continuous_validation: u8,
+}
+
+impl EntryPayloadExt for HircBlendCntr {
+    fn from_reader<R>(reader: &mut R, _length: u32) -> Result<Self>
+    where
+        R: Read + Seek,
+    {
+        let container = HircBlendCntr::read(reader)?;
+        Ok(container)
+    }
+
+    fn write_to<W>(&self, writer: &mut W) -> Result<()>
+    where
+        W: Write + Seek,
+    {
+        self.write(writer)?;
+        Ok(())
+    }
+
+    fn fix_values(&mut self) -> Result<()> {
+        self.children.num_children = self.children.children.len() as u32;
+        Ok(())
+    }
+}
+
+impl HircBlendCntr {
+    /// Ids of the child nodes, sorted.
+    pub fn children(&self) -> &[u32] {
+        &self.children.children
+    }
+
+    /// Add a child node, keeping children sorted. Returns `false` if already present.
+    pub fn add_child(&mut self, id: u32) -> bool {
+        let Err(idx) = self.children.children.binary_search(&id) else {
+            return false;
+        };
+        self.children.children.insert(idx, id);
+        true
+    }
+
+    /// Remove a child node and its layer associations, returns `false` if
+    /// absent.
+    pub fn remove_child(&mut self, id: u32) -> bool {
+        let Ok(idx) = self.children.children.binary_search(&id) else {
+            return false;
+        };
+        self.children.children.remove(idx);
+        for layer in &mut self.layers {
+            layer.associations.retain(|assoc| assoc.child_id != id);
+        }
+        true
+    }
+
+    pub fn layer(&self, layer_id: u32) -> Option<&AkBlendLayer> {
+        self.layers.iter().find(|layer| layer.layer_id == layer_id)
+    }
+
+    pub fn layer_mut(&mut self, layer_id: u32) -> Option<&mut AkBlendLayer> {
+        self.layers
+            .iter_mut()
+            .find(|layer| layer.layer_id == layer_id)
+    }
+}
+
+/// Layer of a blend container (CAkLayer).
+#[binrw]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AkBlendLayer {
+    pub layer_id: u32,
+    pub initial_rtpc: InitialRTPC,
+    /// RTPC driving the cross-fades between the children, 0 if none.
+    pub crossfade_rtpc_id: u32,
+    pub crossfade_rtpc_type: u8,
+    #[br(temp)]
+    #[bw(calc = associations.len() as u32)]
+    num_associations: u32,
+    #[br(count = num_associations)]
+    pub associations: Vec<AkLayerAssociation>,
+}
+
+impl AkBlendLayer {
+    pub fn association(&self, child_id: u32) -> Option<&AkLayerAssociation> {
+        self.associations
+            .iter()
+            .find(|assoc| assoc.child_id == child_id)
+    }
+
+    pub fn association_mut(&mut self, child_id: u32) -> Option<&mut AkLayerAssociation> {
+        self.associations
+            .iter_mut()
+            .find(|assoc| assoc.child_id == child_id)
+    }
+}
+
+/// Child of a layer with its cross-fade curve (CAssociatedChildData).
+#[binrw]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AkLayerAssociation {
+    pub child_id: u32,
+    #[br(temp)]
+    #[bw(calc = curve.len() as u32)]
+    curve_size: u32,
+    /// Volume of the child in dB over the cross-fade RTPC, empty for children
+    /// playing over the whole range.
+    #[br(count = curve_size)]
+    pub curve: Vec<AkRTPCGraphPoint>,
+}
+
+impl AkLayerAssociation {
+    /// RTPC range of the cross-fade curve.
+    pub fn range(&self) -> Option<(f32, f32)> {
+        Some((self.curve.first()?.from, self.curve.last()?.from))
+    }
+
+    /// Volume of the child at an RTPC value, 0 without curve.
+    pub fn evaluate(&self, x: f32) -> f32 {
+        evaluate_graph(&self.curve, x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bnk::{
+        Bnk,
+        hirc::{
+            HircEntryPayload, HircEntryType,
+            tests::{reread, roundtrip},
+        },
+        tests::INPUT_HIRC,
+    };
+
+    #[test]
+    fn test_blend_cntr() {
+        let bnk = Bnk::from_file(INPUT_HIRC).unwrap();
+        // node base params and children of an actor-mixer, then one layer
+        let mixer = bnk.hirc_entry(277229044).unwrap();
+        let mut payload = mixer.to_bytes().unwrap()[9..].to_vec();
+        payload.extend(1u32.to_le_bytes());
+        payload.extend(1u32.to_le_bytes());
+        payload.extend(0u16.to_le_bytes());
+        payload.extend(0x77u32.to_le_bytes());
+        payload.push(0);
+        payload.extend(1u32.to_le_bytes());
+        payload.extend(0x060702F9u32.to_le_bytes());
+        payload.extend(2u32.to_le_bytes());
+        for (from, to) in [(0.0f32, -96.0f32), (100.0, 0.0)] {
+            payload.extend(from.to_le_bytes());
+            payload.extend(to.to_le_bytes());
+            payload.extend(4u32.to_le_bytes());
+        }
+        payload.push(0);
+        let mut data = ((payload.len() + 4) as u32).to_le_bytes().to_vec();
+        data.extend(1u32.to_le_bytes());
+        data.extend(&payload);
+
+        let mut entry = roundtrip(HircEntryType::BlendContainer, &data);
+        let HircEntryPayload::BlendContainer(cntr) = &mut entry.payload else {
+            panic!();
+        };
+        assert_eq!(cntr.children().len(), 13);
+        let layer = cntr.layer(1).unwrap();
+        assert_eq!(layer.crossfade_rtpc_id, 0x77);
+        let assoc = layer.association(0x060702F9).unwrap();
+        assert_eq!(assoc.range(), Some((0.0, 100.0)));
+        assert_eq!(assoc.evaluate(50.0), -48.0);
+
+        assert!(cntr.add_child(2));
+        assert!(cntr.remove_child(0x060702F9));
+        assert!(cntr.layer(1).unwrap().associations.is_empty());
+
+        let entry = reread(&entry);
+        let HircEntryPayload::BlendContainer(cntr) = &entry.payload else {
+            panic!();
+        };
+        assert_eq!(cntr.children()[0], 2);
+        assert_eq!(cntr.layers.len(), 1);
+    }
+}
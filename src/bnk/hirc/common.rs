@@ -1,5 +1,8 @@
+use std::fmt;
+
 use crate::rwext::ReadVecExt;
 use binrw::{BinRead, BinWrite, binrw};
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 #[binrw]
@@ -10,6 +13,8 @@ pub struct NodeBaseParams {
     node_initial_fx_params: NodeInitialFxParams,
     is_override_parent_metadata: u8,
     num_fx: u8,
+    #[br(count = num_fx)]
+    fx_metadata: Vec<FxChunkMetadata>,
     override_attachment_params: u8,
     override_bus_id: u32,
     direct_parent_id: u32,
@@ -22,32 +27,370 @@ pub struct NodeBaseParams {
     initial_rtpc: InitialRTPC,
 }
 
+/// NodeBaseParams of a node without effects, properties, positioning,
+/// states or RTPCs, as written by Wwise.
+const EMPTY_NODE_BASE_PARAMS: [u8; 32] = [
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00,
+];
+
+impl Default for NodeBaseParams {
+    fn default() -> Self {
+        NodeBaseParams::read(&mut std::io::Cursor::new(EMPTY_NODE_BASE_PARAMS)).unwrap()
+    }
+}
+
+impl NodeBaseParams {
+    pub fn initial_rtpc(&self) -> &InitialRTPC {
+        &self.initial_rtpc
+    }
+
+    pub fn initial_rtpc_mut(&mut self) -> &mut InitialRTPC {
+        &mut self.initial_rtpc
+    }
+
+    pub fn state_chunk(&self) -> &StateChunk {
+        &self.state_chunk
+    }
+
+    /// Effects inserted on this node.
+    pub fn fx_chunks(&self) -> &[FxChunk] {
+        &self.node_initial_fx_params.fx_chunks
+    }
+
+    pub fn aux_params(&self) -> &AuxParams {
+        &self.aux_params
+    }
+
+    pub fn aux_params_mut(&mut self) -> &mut AuxParams {
+        &mut self.aux_params
+    }
+
+    pub fn adv_settings_params(&self) -> &AdvSettingsParams {
+        &self.adv_settings_params
+    }
+
+    pub fn adv_settings_params_mut(&mut self) -> &mut AdvSettingsParams {
+        &mut self.adv_settings_params
+    }
+
+    /// Output bus set on this node, 0 if inherited from the parent.
+    pub fn override_bus_id(&self) -> u32 {
+        self.override_bus_id
+    }
+
+    /// Set the output bus, 0 to inherit it from the parent.
+    pub fn set_override_bus_id(&mut self, bus_id: u32) {
+        self.override_bus_id = bus_id;
+    }
+
+    pub fn direct_parent_id(&self) -> u32 {
+        self.direct_parent_id
+    }
+
+    pub fn set_direct_parent_id(&mut self, parent_id: u32) {
+        self.direct_parent_id = parent_id;
+    }
+
+    /// Voice volume in dB, relative to the parent.
+    pub fn volume(&self) -> f32 {
+        self.prop(PROP_VOLUME).map_or(0.0, f32::from_bits)
+    }
+
+    pub fn set_volume(&mut self, volume: f32) {
+        self.node_initial_params
+            .ak_prop_bundle
+            .set_raw(PROP_VOLUME, volume.to_bits());
+    }
+
+    /// Pitch in cents, relative to the parent.
+    pub fn pitch(&self) -> f32 {
+        self.prop(PROP_PITCH).map_or(0.0, f32::from_bits)
+    }
+
+    pub fn set_pitch(&mut self, pitch: f32) {
+        self.node_initial_params
+            .ak_prop_bundle
+            .set_raw(PROP_PITCH, pitch.to_bits());
+    }
+
+    pub fn routing(&self) -> NodeRouting {
+        NodeRouting {
+            override_bus_id: self.override_bus_id,
+            direct_parent_id: self.direct_parent_id,
+        }
+    }
+
+    /// Attenuation set on this node, used when it is 3D positioned.
+    pub fn attenuation_id(&self) -> Option<u32> {
+        self.prop(PROP_ATTENUATION_ID)
+    }
+
+    /// Properties set on this node.
+    pub fn props(&self) -> &AkPropBundle {
+        &self.node_initial_params.ak_prop_bundle
+    }
+
+    pub fn props_mut(&mut self) -> &mut AkPropBundle {
+        &mut self.node_initial_params.ak_prop_bundle
+    }
+
+    pub(crate) fn prop(&self, p_id: u8) -> Option<u32> {
+        self.node_initial_params.ak_prop_bundle.get_raw(p_id)
+    }
+}
+
+/// Output bus and parent of an audio node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NodeRouting {
+    /// 0 if inherited from the parent.
+    pub override_bus_id: u32,
+    pub direct_parent_id: u32,
+}
+
+/// Leading fields of NodeBaseParams, readable from the raw data of
+/// entries which are not parsed yet.
+#[binrw]
+#[brw(little)]
+pub(crate) struct NodeBaseParamsPrefix {
+    node_initial_fx_params: NodeInitialFxParams,
+    is_override_parent_metadata: u8,
+    num_fx: u8,
+    #[br(count = num_fx)]
+    fx_metadata: Vec<FxChunkMetadata>,
+    override_attachment_params: u8,
+    override_bus_id: u32,
+    direct_parent_id: u32,
+    by_bit_vector: u8,
+    node_initial_params: NodeInitialParams,
+}
+
+impl NodeBaseParamsPrefix {
+    pub(crate) fn from_bytes(data: &[u8]) -> Option<Self> {
+        Self::read(&mut std::io::Cursor::new(data)).ok()
+    }
+
+    pub(crate) fn routing(&self) -> NodeRouting {
+        NodeRouting {
+            override_bus_id: self.override_bus_id,
+            direct_parent_id: self.direct_parent_id,
+        }
+    }
+
+    pub(crate) fn prop(&self, p_id: u8) -> Option<u32> {
+        self.node_initial_params.ak_prop_bundle.get_raw(p_id)
+    }
+}
+
 #[binrw]
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct NodeInitialFxParams {
     is_override_parent_fx: u8,
     num_fx: u8,
+    #[br(if(num_fx > 0))]
+    #[bw(if(*num_fx > 0))]
+    bits_fx_bypass: u8,
+    #[br(count = num_fx)]
+    fx_chunks: Vec<FxChunk>,
 }
 
 #[binrw]
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-pub struct NodeInitialParams {
-    ak_prop_bundle1: AkPropBundle,
-    ak_prop_bundle2: AkPropBundle,
+pub struct FxChunk {
+    /// Slot of the effect, 0 to 3.
+    pub fx_index: u8,
+    pub fx_id: u32,
+    pub is_share_set: u8,
+    pub is_rendered: u8,
 }
 
 #[binrw]
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FxChunkMetadata {
+    fx_index: u8,
+    fx_id: u32,
+    is_share_set: u8,
+}
+
+#[binrw]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NodeInitialParams {
+    ak_prop_bundle: AkPropBundle,
+    ak_prop_ranged_modifiers: AkPropRangedModifiers,
+}
+
+// `AkPropID`s, float values unless noted
+pub(crate) const PROP_VOLUME: u8 = 0x00;
+pub(crate) const PROP_PITCH: u8 = 0x02;
+pub(crate) const PROP_LPF: u8 = 0x03;
+pub(crate) const PROP_HPF: u8 = 0x04;
+pub(crate) const PROP_BUS_VOLUME: u8 = 0x05;
+pub(crate) const PROP_MAKE_UP_GAIN: u8 = 0x06;
+/// Attenuation of positioned nodes and buses, an object id.
+pub(crate) const PROP_ATTENUATION_ID: u8 = 0x46;
+
+/// Ids of the properties of [AkPropBundle], as numbered by banks of
+/// [HIRC_LAYOUT_VERSION](super::HIRC_LAYOUT_VERSION).
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum AkPropId {
+    /// dB.
+    Volume = 0x00,
+    /// dB.
+    Lfe = 0x01,
+    /// Cents.
+    Pitch = 0x02,
+    /// Low-pass filter, 0 to 100.
+    Lpf = 0x03,
+    /// High-pass filter, 0 to 100.
+    Hpf = 0x04,
+    /// dB.
+    BusVolume = 0x05,
+    /// dB.
+    MakeUpGain = 0x06,
+    /// Center percentage of the speaker panning, 0 to 100.
+    CenterPct = 0x0E,
+    /// dB.
+    GameAuxSendVolume = 0x17,
+    /// Loop count, 0 for infinite looping.
+    Loop = 0x3A,
+    /// Seconds.
+    InitialDelay = 0x3B,
+    /// Object id.
+    AttenuationId = 0x46,
+    /// Blend of 2D and 3D positioning, 0 to 100.
+    PositioningTypeBlend = 0x47,
+    Unknown(u8),
+}
+
+impl AkPropId {
+    pub fn from_u8(p_id: u8) -> Self {
+        match p_id {
+            0x00 => AkPropId::Volume,
+            0x01 => AkPropId::Lfe,
+            0x02 => AkPropId::Pitch,
+            0x03 => AkPropId::Lpf,
+            0x04 => AkPropId::Hpf,
+            0x05 => AkPropId::BusVolume,
+            0x06 => AkPropId::MakeUpGain,
+            0x0E => AkPropId::CenterPct,
+            0x17 => AkPropId::GameAuxSendVolume,
+            0x3A => AkPropId::Loop,
+            0x3B => AkPropId::InitialDelay,
+            0x46 => AkPropId::AttenuationId,
+            0x47 => AkPropId::PositioningTypeBlend,
+            x => AkPropId::Unknown(x),
+        }
+    }
+
+    pub fn as_u8(&self) -> u8 {
+        match self {
+            AkPropId::Volume => 0x00,
+            AkPropId::Lfe => 0x01,
+            AkPropId::Pitch => 0x02,
+            AkPropId::Lpf => 0x03,
+            AkPropId::Hpf => 0x04,
+            AkPropId::BusVolume => 0x05,
+            AkPropId::MakeUpGain => 0x06,
+            AkPropId::CenterPct => 0x0E,
+            AkPropId::GameAuxSendVolume => 0x17,
+            AkPropId::Loop => 0x3A,
+            AkPropId::InitialDelay => 0x3B,
+            AkPropId::AttenuationId => 0x46,
+            AkPropId::PositioningTypeBlend => 0x47,
+            AkPropId::Unknown(x) => *x,
+        }
+    }
+
+    /// Decode the raw value of this property.
+    ///
+    /// Properties of unknown ids are assumed to be floats, the most common
+    /// type.
+    pub fn value(&self, p_value: u32) -> AkPropValue {
+        match self {
+            AkPropId::Loop => AkPropValue::Int(p_value as i32),
+            AkPropId::AttenuationId => AkPropValue::Id(p_value),
+            _ => AkPropValue::Float(f32::from_bits(p_value)),
+        }
+    }
+}
+
+/// Decoded value of a property, see [AkPropId::value].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum AkPropValue {
+    Float(f32),
+    Int(i32),
+    Id(u32),
+}
+
+impl AkPropValue {
+    /// Raw value as stored in the bundle.
+    pub fn to_bits(&self) -> u32 {
+        match self {
+            AkPropValue::Float(value) => value.to_bits(),
+            AkPropValue::Int(value) => *value as u32,
+            AkPropValue::Id(value) => *value,
+        }
+    }
+
+    pub fn as_f32(&self) -> Option<f32> {
+        match self {
+            AkPropValue::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_i32(&self) -> Option<i32> {
+        match self {
+            AkPropValue::Int(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_id(&self) -> Option<u32> {
+        match self {
+            AkPropValue::Id(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for AkPropValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AkPropValue::Float(value) => value.fmt(f),
+            AkPropValue::Int(value) => value.fmt(f),
+            AkPropValue::Id(value) => value.fmt(f),
+        }
+    }
+}
+
+impl From<f32> for AkPropValue {
+    fn from(value: f32) -> Self {
+        AkPropValue::Float(value)
+    }
+}
+
+impl From<i32> for AkPropValue {
+    fn from(value: i32) -> Self {
+        AkPropValue::Int(value)
+    }
+}
+
+/// Property values, stored as the ids followed by the values.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AkPropBundle {
-    num_props: u8,
-    #[br(count = num_props)]
     props: Vec<AkPropBundleElem>,
 }
 
-#[binrw]
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AkPropBundleElem {
@@ -55,12 +398,166 @@ pub struct AkPropBundleElem {
     p_value: u32,
 }
 
+impl AkPropBundleElem {
+    pub fn id(&self) -> AkPropId {
+        AkPropId::from_u8(self.p_id)
+    }
+
+    pub fn value(&self) -> AkPropValue {
+        self.id().value(self.p_value)
+    }
+
+    /// Value as stored, floats are stored as their bits.
+    pub fn raw_value(&self) -> u32 {
+        self.p_value
+    }
+}
+
+impl AkPropBundle {
+    pub fn iter(&self) -> impl Iterator<Item = &AkPropBundleElem> {
+        self.props.iter()
+    }
+
+    pub fn get(&self, id: AkPropId) -> Option<AkPropValue> {
+        self.get_raw(id.as_u8()).map(|p_value| id.value(p_value))
+    }
+
+    /// Set a property, adding it if missing.
+    ///
+    /// Values are stored as given, e.g. setting [AkPropId::Volume] from an
+    /// `i32` stores an integer where the runtime reads a float.
+    pub fn set(&mut self, id: AkPropId, value: impl Into<AkPropValue>) {
+        self.set_raw(id.as_u8(), value.into().to_bits());
+    }
+
+    /// Remove a property, returning its value.
+    pub fn remove(&mut self, id: AkPropId) -> Option<AkPropValue> {
+        let index = self.props.iter().position(|prop| prop.p_id == id.as_u8())?;
+        Some(self.props.remove(index).value())
+    }
+
+    /// Raw value of a property, floats are stored as their bits.
+    pub(crate) fn get_raw(&self, p_id: u8) -> Option<u32> {
+        self.props
+            .iter()
+            .find(|prop| prop.p_id == p_id)
+            .map(|prop| prop.p_value)
+    }
+
+    /// Set the raw value of a property, adding it if missing.
+    pub(crate) fn set_raw(&mut self, p_id: u8, p_value: u32) {
+        match self.props.iter_mut().find(|prop| prop.p_id == p_id) {
+            Some(prop) => prop.p_value = p_value,
+            None => self.props.push(AkPropBundleElem { p_id, p_value }),
+        }
+    }
+}
+
+impl BinRead for AkPropBundle {
+    type Args<'a> = ();
+
+    fn read_options<R: std::io::Read + std::io::Seek>(
+        reader: &mut R,
+        endian: binrw::Endian,
+        _args: Self::Args<'_>,
+    ) -> binrw::BinResult<Self> {
+        let num_props = u8::read_options(reader, endian, ())? as usize;
+        let ids = <Vec<u8>>::read_options(reader, endian, binrw::args! { count: num_props })?;
+        let props = ids
+            .into_iter()
+            .map(|p_id| {
+                let p_value = u32::read_options(reader, endian, ())?;
+                Ok(AkPropBundleElem { p_id, p_value })
+            })
+            .collect::<binrw::BinResult<_>>()?;
+        Ok(AkPropBundle { props })
+    }
+}
+
+impl BinWrite for AkPropBundle {
+    type Args<'a> = ();
+
+    fn write_options<W: std::io::Write + std::io::Seek>(
+        &self,
+        writer: &mut W,
+        endian: binrw::Endian,
+        _args: Self::Args<'_>,
+    ) -> binrw::BinResult<()> {
+        (self.props.len() as u8).write_options(writer, endian, ())?;
+        for prop in &self.props {
+            prop.p_id.write_options(writer, endian, ())?;
+        }
+        for prop in &self.props {
+            prop.p_value.write_options(writer, endian, ())?;
+        }
+        Ok(())
+    }
+}
+
+/// Randomized property ranges, stored as the ids followed by the ranges.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AkPropRangedModifiers {
+    modifiers: Vec<AkPropRangedModifier>,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AkPropRangedModifier {
+    p_id: u8,
+    min: u32,
+    max: u32,
+}
+
+impl BinRead for AkPropRangedModifiers {
+    type Args<'a> = ();
+
+    fn read_options<R: std::io::Read + std::io::Seek>(
+        reader: &mut R,
+        endian: binrw::Endian,
+        _args: Self::Args<'_>,
+    ) -> binrw::BinResult<Self> {
+        let num_modifiers = u8::read_options(reader, endian, ())? as usize;
+        let ids = <Vec<u8>>::read_options(reader, endian, binrw::args! { count: num_modifiers })?;
+        let modifiers = ids
+            .into_iter()
+            .map(|p_id| {
+                let (min, max) = <(u32, u32)>::read_options(reader, endian, ())?;
+                Ok(AkPropRangedModifier { p_id, min, max })
+            })
+            .collect::<binrw::BinResult<_>>()?;
+        Ok(AkPropRangedModifiers { modifiers })
+    }
+}
+
+impl BinWrite for AkPropRangedModifiers {
+    type Args<'a> = ();
+
+    fn write_options<W: std::io::Write + std::io::Seek>(
+        &self,
+        writer: &mut W,
+        endian: binrw::Endian,
+        _args: Self::Args<'_>,
+    ) -> binrw::BinResult<()> {
+        (self.modifiers.len() as u8).write_options(writer, endian, ())?;
+        for modifier in &self.modifiers {
+            modifier.p_id.write_options(writer, endian, ())?;
+        }
+        for modifier in &self.modifiers {
+            (modifier.min, modifier.max).write_options(writer, endian, ())?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PositioningParams {
     bits_positioning: u8,
 
     bits_3d: u8,
+    /// Not stored in v145, kept for serialized banks.
+    #[cfg_attr(not(feature = "serde"), allow(dead_code))]
     is_dynamic: u8,
 
     e_path_mode: AkPathMode,
@@ -212,6 +709,35 @@ pub struct AuxParams {
     reflections_aux_bus: u32,
 }
 
+impl AuxParams {
+    /// User-defined auxiliary sends, `None` if the node has none.
+    pub fn aux_ids(&self) -> Option<[u32; 4]> {
+        (self.by_bit_vector & (1 << 3) != 0).then_some(self.aux_ids)
+    }
+
+    /// Set the user-defined auxiliary sends, `None` to remove them.
+    pub fn set_aux_ids(&mut self, aux_ids: Option<[u32; 4]>) {
+        match aux_ids {
+            Some(aux_ids) => {
+                self.by_bit_vector |= 1 << 3;
+                self.aux_ids = aux_ids;
+            }
+            None => {
+                self.by_bit_vector &= !(1 << 3);
+                self.aux_ids = [0; 4];
+            }
+        }
+    }
+
+    pub fn reflections_aux_bus(&self) -> u32 {
+        self.reflections_aux_bus
+    }
+
+    pub fn set_reflections_aux_bus(&mut self, bus_id: u32) {
+        self.reflections_aux_bus = bus_id;
+    }
+}
+
 #[binrw]
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -223,6 +749,33 @@ pub struct AdvSettingsParams {
     by_bit_vector2: u8,
 }
 
+impl AdvSettingsParams {
+    /// Playback limit, 0 for no limit.
+    pub fn max_num_instance(&self) -> u16 {
+        self.max_num_instance
+    }
+
+    pub fn set_max_num_instance(&mut self, max_num_instance: u16) {
+        self.max_num_instance = max_num_instance;
+    }
+
+    pub fn virtual_queue_behavior(&self) -> u8 {
+        self.virtual_queue_behavior
+    }
+
+    pub fn set_virtual_queue_behavior(&mut self, behavior: u8) {
+        self.virtual_queue_behavior = behavior;
+    }
+
+    pub fn below_threshold_behavior(&self) -> u8 {
+        self.below_threshold_behavior
+    }
+
+    pub fn set_below_threshold_behavior(&mut self, behavior: u8) {
+        self.below_threshold_behavior = behavior;
+    }
+}
+
 #[binrw]
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -251,6 +804,17 @@ pub struct AkStateGroupChunk {
     states: Vec<AkState>,
 }
 
+impl AkStateGroupChunk {
+    pub fn state_group_id(&self) -> u32 {
+        self.state_group_id
+    }
+
+    /// Ids of the states with custom properties in this group.
+    pub fn state_ids(&self) -> impl Iterator<Item = u32> + '_ {
+        self.states.iter().map(|state| state.state_id)
+    }
+}
+
 #[binrw]
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -263,6 +827,12 @@ pub struct StateChunk {
     state_groups: Vec<AkStateGroupChunk>,
 }
 
+impl StateChunk {
+    pub fn state_groups(&self) -> &[AkStateGroupChunk] {
+        &self.state_groups
+    }
+}
+
 #[binrw]
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -287,11 +857,206 @@ pub struct InitialRTPCCurve {
     rtpc_mgr: Vec<AkRTPCGraphPoint>,
 }
 
+impl InitialRTPC {
+    /// Curves can be edited in place, their point counts are kept in sync.
+    pub fn curves_mut(&mut self) -> &mut [InitialRTPCCurve] {
+        &mut self.curves
+    }
+
+    pub fn curves(&self) -> &[InitialRTPCCurve] {
+        &self.curves
+    }
+}
+
+impl InitialRTPCCurve {
+    pub fn rtpc_id(&self) -> u32 {
+        self.rtpc_id
+    }
+
+    /// Type of the game parameter, `AkRtpcType`.
+    pub fn rtpc_type(&self) -> u8 {
+        self.rtpc_type
+    }
+
+    /// Id of the property driven by the curve.
+    pub fn param_id(&self) -> u8 {
+        self.param_id
+    }
+
+    pub fn rtpc_curve_id(&self) -> u32 {
+        self.rtpc_curve_id
+    }
+
+    pub fn points(&self) -> &[AkRTPCGraphPoint] {
+        &self.rtpc_mgr
+    }
+
+    /// Evaluate the curve at game parameter value `x`.
+    pub fn evaluate(&self, x: f32) -> f32 {
+        evaluate_graph(&self.rtpc_mgr, x)
+    }
+
+    /// Insert a point, keeping points sorted by `from`. Returns the index of the new point.
+    pub fn insert_point(&mut self, point: AkRTPCGraphPoint) -> usize {
+        let index = self.rtpc_mgr.partition_point(|p| p.from <= point.from);
+        self.rtpc_mgr.insert(index, point);
+        self.size = self.rtpc_mgr.len() as u16;
+        index
+    }
+
+    pub fn remove_point(&mut self, index: usize) -> Option<AkRTPCGraphPoint> {
+        if index >= self.rtpc_mgr.len() {
+            return None;
+        }
+        let point = self.rtpc_mgr.remove(index);
+        self.size = self.rtpc_mgr.len() as u16;
+        Some(point)
+    }
+}
+
 #[binrw]
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AkRTPCGraphPoint {
-    from: f32,
-    to: f32,
-    interp: u32,
+    pub from: f32,
+    pub to: f32,
+    /// Interpolation towards the next point, see [AkCurveInterpolation].
+    pub interp: u32,
+}
+
+impl AkRTPCGraphPoint {
+    pub fn interpolation(&self) -> Option<AkCurveInterpolation> {
+        AkCurveInterpolation::from_repr(self.interp)
+    }
+}
+
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, strum::FromRepr)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum AkCurveInterpolation {
+    Log3 = 0x0,
+    Sine = 0x1,
+    Log1 = 0x2,
+    InvSCurve = 0x3,
+    Linear = 0x4,
+    SCurve = 0x5,
+    Exp1 = 0x6,
+    SineRecip = 0x7,
+    Exp3 = 0x8,
+    Constant = 0x9,
+}
+
+impl AkCurveInterpolation {
+    /// Map a normalized position `t` in `[0, 1]` to the normalized value of the curve.
+    pub fn apply(self, t: f32) -> f32 {
+        use std::f32::consts::{FRAC_PI_2, PI};
+
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            AkCurveInterpolation::Log3 => 1.0 - (1.0 - t).powi(3),
+            AkCurveInterpolation::Sine => (t * FRAC_PI_2).sin(),
+            AkCurveInterpolation::Log1 => 1.0 - (1.0 - t).powf(1.41),
+            AkCurveInterpolation::InvSCurve => (1.0 - 2.0 * t).acos() / PI,
+            AkCurveInterpolation::Linear => t,
+            AkCurveInterpolation::SCurve => 0.5 - 0.5 * (t * PI).cos(),
+            AkCurveInterpolation::Exp1 => t.powf(1.41),
+            AkCurveInterpolation::SineRecip => 1.0 - (t * FRAC_PI_2).cos(),
+            AkCurveInterpolation::Exp3 => t.powi(3),
+            AkCurveInterpolation::Constant => 0.0,
+        }
+    }
+}
+
+/// Evaluate a graph of points sorted by `from` at `x`.
+///
+/// Each segment uses the interpolation of its starting point, unknown
+/// interpolations fall back to linear. Values outside the graph are clamped
+/// to the first and last points, NaN evaluates to the first point. Unsorted
+/// graphs give unspecified values but don't panic.
+pub fn evaluate_graph(points: &[AkRTPCGraphPoint], x: f32) -> f32 {
+    let (Some(first), Some(last)) = (points.first(), points.last()) else {
+        return 0.0;
+    };
+    if x.is_nan() || x <= first.from {
+        return first.to;
+    }
+    if x >= last.from {
+        return last.to;
+    }
+
+    let i = points.partition_point(|p| p.from <= x).saturating_sub(1);
+    let (start, Some(end)) = (&points[i], points.get(i + 1)) else {
+        return last.to;
+    };
+    let width = end.from - start.from;
+    if width <= 0.0 {
+        return end.to;
+    }
+    let t = (x - start.from) / width;
+    let factor = start
+        .interpolation()
+        .unwrap_or(AkCurveInterpolation::Linear)
+        .apply(t);
+    start.to + (end.to - start.to) * factor
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rtpc_curve_edit() {
+        let point = |from, to| AkRTPCGraphPoint {
+            from,
+            to,
+            interp: AkCurveInterpolation::Linear as u32,
+        };
+        let mut curve = InitialRTPCCurve {
+            rtpc_id: 0,
+            rtpc_type: 0,
+            rtpc_accum: 0,
+            param_id: 0,
+            rtpc_curve_id: 0,
+            e_scaling: 0,
+            size: 0,
+            rtpc_mgr: vec![],
+        };
+        curve.insert_point(point(100.0, 1.0));
+        assert_eq!(curve.insert_point(point(0.0, 0.0)), 0);
+        assert_eq!(curve.insert_point(point(50.0, 0.25)), 1);
+        assert_eq!(curve.size, 3);
+        assert_eq!(curve.evaluate(75.0), 0.625);
+
+        curve.remove_point(1).unwrap();
+        assert_eq!(curve.size, 2);
+        assert_eq!(curve.evaluate(75.0), 0.75);
+        assert!(curve.remove_point(2).is_none());
+    }
+
+    #[test]
+    fn test_evaluate_graph_edges() {
+        let point = |from, to| AkRTPCGraphPoint {
+            from,
+            to,
+            interp: AkCurveInterpolation::Linear as u32,
+        };
+        let points = [point(0.0, 1.0), point(10.0, 2.0), point(20.0, 3.0)];
+        assert_eq!(evaluate_graph(&points, f32::NAN), 1.0);
+        assert_eq!(evaluate_graph(&points, f32::NEG_INFINITY), 1.0);
+        assert_eq!(evaluate_graph(&points, f32::INFINITY), 3.0);
+        assert_eq!(evaluate_graph(&points, 15.0), 2.5);
+
+        // unsorted graphs don't panic
+        let unsorted = [
+            point(0.0, 1.0),
+            point(30.0, 2.0),
+            point(-10.0, 3.0),
+            point(20.0, 4.0),
+        ];
+        let nan_start = [point(f32::NAN, 1.0), point(10.0, 2.0), point(20.0, 3.0)];
+        for x in [-5.0, 1.0, 10.0, 19.0, 25.0] {
+            assert!(evaluate_graph(&unsorted, x).is_finite());
+            evaluate_graph(&nan_start, x);
+        }
+    }
 }
@@ -0,0 +1,329 @@
+use std::io::{Read, Seek, Write};
+
+use binrw::{BinRead, BinResult, BinWrite, Endian};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Switch/state decision tree of switch containers and dialogue events.
+///
+/// Each level of the tree matches one argument (game sync group), leaves
+/// point at the played audio node. Key 0 is the default ("any") path.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AkDecisionTree {
+    pub mode: u8,
+    pub root: AkDecisionTreeNode,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AkDecisionTreeNode {
+    pub key: u32,
+    pub weight: u16,
+    pub probability: u16,
+    pub value: AkDecisionTreeValue,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum AkDecisionTreeValue {
+    /// Leaf, id of the audio node to play.
+    AudioNode(u32),
+    /// Children sorted by key.
+    Children(Vec<AkDecisionTreeNode>),
+}
+
+/// Argument of a decision tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AkGameSync {
+    pub group_id: u32,
+    /// 0: switch, 1: state.
+    pub group_type: u8,
+}
+
+/// Read the arguments of a tree, group ids first, then group types.
+pub(super) fn read_arguments<R: Read + Seek>(
+    reader: &mut R,
+    depth: u32,
+) -> BinResult<Vec<AkGameSync>> {
+    let group_ids = (0..depth)
+        .map(|_| u32::read_le(reader))
+        .collect::<BinResult<Vec<_>>>()?;
+    group_ids
+        .into_iter()
+        .map(|group_id| {
+            Ok(AkGameSync {
+                group_id,
+                group_type: u8::read_le(reader)?,
+            })
+        })
+        .collect()
+}
+
+pub(super) fn write_arguments<W: Write + Seek>(
+    writer: &mut W,
+    arguments: &[AkGameSync],
+) -> BinResult<()> {
+    for argument in arguments {
+        argument.group_id.write_le(writer)?;
+    }
+    for argument in arguments {
+        argument.group_type.write_le(writer)?;
+    }
+    Ok(())
+}
+
+/// Size of a serialized tree node.
+const NODE_SIZE: u32 = 12;
+
+/// Deepest tree read, deeper trees are rejected before recursing.
+const MAX_DEPTH: u32 = 64;
+
+impl AkDecisionTreeNode {
+    /// Node with the weight and probability Wwise gives new paths.
+    pub fn new(key: u32, value: AkDecisionTreeValue) -> Self {
+        AkDecisionTreeNode {
+            key,
+            weight: 50,
+            probability: 100,
+            value,
+        }
+    }
+
+    fn children(&self) -> &[AkDecisionTreeNode] {
+        match &self.value {
+            AkDecisionTreeValue::Children(children) => children,
+            AkDecisionTreeValue::AudioNode(_) => &[],
+        }
+    }
+
+    fn node_count(&self) -> u32 {
+        1 + self.children().iter().map(Self::node_count).sum::<u32>()
+    }
+}
+
+impl AkDecisionTree {
+    /// Empty tree for `depth` arguments.
+    pub fn new(depth: usize) -> Self {
+        let value = if depth == 0 {
+            AkDecisionTreeValue::AudioNode(0)
+        } else {
+            AkDecisionTreeValue::Children(Vec::new())
+        };
+        AkDecisionTree {
+            mode: 0,
+            root: AkDecisionTreeNode::new(0, value),
+        }
+    }
+
+    /// Size of the serialized nodes.
+    pub fn data_size(&self) -> u32 {
+        self.root.node_count() * NODE_SIZE
+    }
+
+    /// Find the audio node played for the given argument values.
+    ///
+    /// Exact keys are preferred over the default key 0 at each level.
+    pub fn resolve(&self, keys: &[u32]) -> Option<u32> {
+        fn resolve_node(node: &AkDecisionTreeNode, keys: &[u32]) -> Option<u32> {
+            match (&node.value, keys.split_first()) {
+                (AkDecisionTreeValue::AudioNode(id), None) => Some(*id),
+                (AkDecisionTreeValue::Children(children), Some((&key, rest))) => [key, 0]
+                    .iter()
+                    .filter_map(|k| children.iter().find(|child| child.key == *k))
+                    .find_map(|child| resolve_node(child, rest)),
+                _ => None,
+            }
+        }
+        resolve_node(&self.root, keys)
+    }
+
+    /// All paths of the tree with their audio node.
+    pub fn paths(&self) -> Vec<(Vec<u32>, u32)> {
+        fn collect(node: &AkDecisionTreeNode, path: &mut Vec<u32>, out: &mut Vec<(Vec<u32>, u32)>) {
+            match &node.value {
+                AkDecisionTreeValue::AudioNode(id) => out.push((path.clone(), *id)),
+                AkDecisionTreeValue::Children(children) => {
+                    for child in children {
+                        path.push(child.key);
+                        collect(child, path, out);
+                        path.pop();
+                    }
+                }
+            }
+        }
+        let mut out = Vec::new();
+        collect(&self.root, &mut Vec::new(), &mut out);
+        out
+    }
+
+    /// Point a path at an audio node, creating the path if needed.
+    ///
+    /// `keys` must have one key per tree level. Returns the previous audio
+    /// node of the path.
+    pub fn insert(&mut self, keys: &[u32], audio_node_id: u32) -> Option<u32> {
+        let mut node = &mut self.root;
+        for &key in keys {
+            let AkDecisionTreeValue::Children(children) = &mut node.value else {
+                return None;
+            };
+            let idx = match children.binary_search_by_key(&key, |child| child.key) {
+                Ok(idx) => idx,
+                Err(idx) => {
+                    let value = AkDecisionTreeValue::Children(Vec::new());
+                    children.insert(idx, AkDecisionTreeNode::new(key, value));
+                    idx
+                }
+            };
+            node = &mut children[idx];
+        }
+        match &mut node.value {
+            AkDecisionTreeValue::AudioNode(id) => Some(std::mem::replace(id, audio_node_id)),
+            // new leaf
+            AkDecisionTreeValue::Children(children) if children.is_empty() => {
+                node.value = AkDecisionTreeValue::AudioNode(audio_node_id);
+                None
+            }
+            AkDecisionTreeValue::Children(_) => None,
+        }
+    }
+
+    /// Remove a path, pruning branches left empty. Returns its audio node.
+    pub fn remove(&mut self, keys: &[u32]) -> Option<u32> {
+        fn remove_node(node: &mut AkDecisionTreeNode, keys: &[u32]) -> Option<u32> {
+            let ((&key, rest), AkDecisionTreeValue::Children(children)) =
+                (keys.split_first()?, &mut node.value)
+            else {
+                return None;
+            };
+            let idx = children
+                .binary_search_by_key(&key, |child| child.key)
+                .ok()?;
+            let child = &mut children[idx];
+            let removed = if rest.is_empty() {
+                match child.value {
+                    AkDecisionTreeValue::AudioNode(id) => id,
+                    AkDecisionTreeValue::Children(_) => return None,
+                }
+            } else {
+                remove_node(child, rest)?
+            };
+            if rest.is_empty() || child.children().is_empty() {
+                children.remove(idx);
+            }
+            Some(removed)
+        }
+        remove_node(&mut self.root, keys)
+    }
+}
+
+impl BinRead for AkDecisionTree {
+    /// Tree depth and serialized node size.
+    type Args<'a> = (u32, u32);
+
+    fn read_options<R: Read + Seek>(
+        reader: &mut R,
+        endian: Endian,
+        (depth, data_size): Self::Args<'_>,
+    ) -> BinResult<Self> {
+        let mode = u8::read_options(reader, endian, ())?;
+        let pos = reader.stream_position()?;
+        // the size isn't trusted, nodes are read until it or the data ends
+        let mut raw = Vec::new();
+        for _ in 0..data_size / NODE_SIZE {
+            raw.push(<(u32, u32, u16, u16)>::read_options(reader, endian, ())?);
+        }
+
+        // each node is built once at most, corrupt trees could share or loop
+        // through their children
+        fn build(
+            raw: &[(u32, u32, u16, u16)],
+            idx: usize,
+            level: u32,
+            depth: u32,
+            pos: u64,
+            budget: &mut usize,
+        ) -> BinResult<AkDecisionTreeNode> {
+            let &(key, data, weight, probability) = raw
+                .get(idx)
+                .filter(|_| *budget > 0 && level <= MAX_DEPTH)
+                .ok_or(binrw::Error::AssertFail {
+                    pos,
+                    message: format!("decision tree node {idx} out of range"),
+                })?;
+            *budget -= 1;
+            let value = if level == depth {
+                AkDecisionTreeValue::AudioNode(data)
+            } else {
+                let (children_idx, children_count) =
+                    ((data & 0xFFFF) as usize, (data >> 16) as usize);
+                let children = (children_idx..children_idx + children_count)
+                    .map(|child_idx| build(raw, child_idx, level + 1, depth, pos, budget))
+                    .collect::<BinResult<Vec<_>>>()?;
+                AkDecisionTreeValue::Children(children)
+            };
+            Ok(AkDecisionTreeNode {
+                key,
+                weight,
+                probability,
+                value,
+            })
+        }
+
+        let root = build(&raw, 0, 0, depth, pos, &mut raw.len())?;
+        Ok(AkDecisionTree { mode, root })
+    }
+}
+
+impl BinWrite for AkDecisionTree {
+    type Args<'a> = ();
+
+    fn write_options<W: Write + Seek>(
+        &self,
+        writer: &mut W,
+        endian: Endian,
+        _args: Self::Args<'_>,
+    ) -> BinResult<()> {
+        self.mode.write_options(writer, endian, ())?;
+        // children of a node are contiguous, each child block is followed by
+        // the subtrees of its nodes in order
+        fn layout(
+            node: &AkDecisionTreeNode,
+            idx: usize,
+            flat: &mut Vec<(u32, u32, u16, u16)>,
+            pos: u64,
+        ) -> BinResult<()> {
+            let data = match &node.value {
+                AkDecisionTreeValue::AudioNode(id) => *id,
+                AkDecisionTreeValue::Children(children) => {
+                    let start = flat.len();
+                    // the first child and the count are stored as u16
+                    let (Ok(first), Ok(count)) =
+                        (u16::try_from(start), u16::try_from(children.len()))
+                    else {
+                        return Err(binrw::Error::AssertFail {
+                            pos,
+                            message: format!(
+                                "decision tree node {idx} with {} children at {start} \
+                                 exceeds the u16 range",
+                                children.len()
+                            ),
+                        });
+                    };
+                    flat.resize(start + children.len(), Default::default());
+                    for (i, child) in children.iter().enumerate() {
+                        layout(child, start + i, flat, pos)?;
+                    }
+                    first as u32 | (count as u32) << 16
+                }
+            };
+            flat[idx] = (node.key, data, node.weight, node.probability);
+            Ok(())
+        }
+        let mut flat = vec![Default::default()];
+        layout(&self.root, 0, &mut flat, writer.stream_position()?)?;
+        flat.write_options(writer, endian, ())?;
+        Ok(())
+    }
+}
@@ -0,0 +1,160 @@
+use std::io::{Cursor, Read, Seek, Write};
+
+use binrw::{BinRead, BinWrite};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::bnk::BnkError;
+
+use super::{
+    AkDecisionTree, AkGameSync, EntryPayloadExt, Result,
+    decision_tree::{read_arguments, write_arguments},
+};
+
+/// Dialogue event (CAkDialogueEvent), plays the audio node its decision
+/// tree resolves for the current arguments.
+///
+/// The property bundles following the tree are kept as raw `data`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct HircDialogueEvent {
+    /// Probability to play, 0 to 100.
+    pub probability: u8,
+    arguments: Vec<AkGameSync>,
+    decision_tree: AkDecisionTree,
+    #[cfg_attr(feature = "serde", serde(with = "crate::bnk::blob"))]
+    data: Vec<u8>,
+}
+
+impl EntryPayloadExt for HircDialogueEvent {
+    fn from_reader<R>(reader: &mut R, length: u32) -> Result<Self>
+    where
+        R: Read + Seek,
+    {
+        // the trailing data is read to the end of the entry
+        let mut data = vec![0; length as usize - 4];
+        reader.read_exact(&mut data)?;
+        let mut reader = Cursor::new(data);
+        let probability = u8::read_le(&mut reader)?;
+        let tree_depth = u32::read_le(&mut reader)?;
+        let arguments = read_arguments(&mut reader, tree_depth)?;
+        let tree_data_size = u32::read_le(&mut reader)?;
+        let decision_tree =
+            AkDecisionTree::read_le_args(&mut reader, (tree_depth, tree_data_size))?;
+        let pos = reader.position() as usize;
+        let data = reader.into_inner().split_off(pos);
+        Ok(HircDialogueEvent {
+            probability,
+            arguments,
+            decision_tree,
+            data,
+        })
+    }
+
+    fn write_to<W>(&self, writer: &mut W) -> Result<()>
+    where
+        W: Write + Seek,
+    {
+        self.probability.write_le(writer)?;
+        (self.arguments.len() as u32).write_le(writer)?;
+        write_arguments(writer, &self.arguments)?;
+        self.decision_tree.data_size().write_le(writer)?;
+        self.decision_tree.write_le(writer)?;
+        writer.write_all(&self.data)?;
+        Ok(())
+    }
+}
+
+impl HircDialogueEvent {
+    /// Game sync groups matched by the levels of the decision tree.
+    pub fn arguments(&self) -> &[AkGameSync] {
+        &self.arguments
+    }
+
+    pub fn decision_tree(&self) -> &AkDecisionTree {
+        &self.decision_tree
+    }
+
+    /// The tree size is recomputed on write.
+    pub fn decision_tree_mut(&mut self) -> &mut AkDecisionTree {
+        &mut self.decision_tree
+    }
+
+    /// Point a path of argument values at an audio node, e.g. the localized
+    /// line of a language.
+    ///
+    /// Key 0 matches any value. Returns the node previously played for the
+    /// path.
+    pub fn set_association(&mut self, keys: &[u32], node_id: u32) -> Result<Option<u32>> {
+        if keys.len() != self.arguments.len() {
+            return Err(BnkError::InvalidDecisionPath {
+                expected: self.arguments.len(),
+                got: keys.len(),
+            });
+        }
+        Ok(self.decision_tree.insert(keys, node_id))
+    }
+
+    /// Remove a path, returning the node it played.
+    pub fn remove_association(&mut self, keys: &[u32]) -> Option<u32> {
+        self.decision_tree.remove(keys)
+    }
+
+    /// Size of the trailing property bundles kept as raw `data`.
+    pub(crate) fn raw_len(&self) -> usize {
+        self.data.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bnk::hirc::{
+        HircEntryPayload, HircEntryType,
+        tests::{reread, roundtrip},
+    };
+
+    #[test]
+    fn test_dialogue_event() {
+        // probability, one state argument, tree of a root and a leaf, no props
+        let mut payload = vec![100];
+        payload.extend(1u32.to_le_bytes());
+        payload.extend(0x10u32.to_le_bytes());
+        payload.push(1);
+        payload.extend(24u32.to_le_bytes());
+        payload.push(0);
+        for (key, data) in [(0u32, 1u32 | 1 << 16), (5, 0x99)] {
+            payload.extend(key.to_le_bytes());
+            payload.extend(data.to_le_bytes());
+            payload.extend(50u16.to_le_bytes());
+            payload.extend(100u16.to_le_bytes());
+        }
+        payload.extend([0, 0]);
+        let mut data = ((payload.len() + 4) as u32).to_le_bytes().to_vec();
+        data.extend(7u32.to_le_bytes());
+        data.extend(&payload);
+
+        let mut entry = roundtrip(HircEntryType::DialogueEvent, &data);
+        let HircEntryPayload::DialogueEvent(event) = &mut entry.payload else {
+            panic!();
+        };
+        assert_eq!(event.probability, 100);
+        assert_eq!(
+            event.arguments(),
+            &[AkGameSync {
+                group_id: 0x10,
+                group_type: 1
+            }]
+        );
+        assert_eq!(event.decision_tree().resolve(&[5]), Some(0x99));
+
+        assert!(event.set_association(&[1, 2], 0x1234).is_err());
+        assert_eq!(event.set_association(&[6], 0x1234).unwrap(), None);
+        let entry = reread(&entry);
+        let HircEntryPayload::DialogueEvent(event) = &entry.payload else {
+            panic!();
+        };
+        assert_eq!(event.decision_tree().resolve(&[6]), Some(0x1234));
+        assert!(entry.to_bytes().unwrap().ends_with(&[0, 0]));
+    }
+}
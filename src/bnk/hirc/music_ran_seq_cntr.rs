@@ -1,11 +1,13 @@
 use std::io::{Read, Seek, Write};
 
 use binrw::{BinRead, BinWrite, binrw};
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-use crate::bnk::BnkError;
-
-use super::{EntryPayloadExt, MusicNodeParams, Result};
+use super::{
+    AkCurveInterpolation, AkMeterInfo, EntryPayloadExt, FadeParams, MusicNodeParams, Result,
+    common::NodeBaseParams,
+};
 
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -14,22 +16,11 @@ pub struct HircMusicRanSeqCntr {
 }
 
 impl EntryPayloadExt for HircMusicRanSeqCntr {
-    fn from_reader<R>(reader: &mut R, length: u32) -> Result<Self>
+    fn from_reader<R>(reader: &mut R, _length: u32) -> Result<Self>
     where
         R: Read + Seek,
     {
-        let pos_start = reader.stream_position()?;
         let music_ran_seq_cntr_initial_values = MusicRanSeqCntrInitialValues::read_le(reader)?;
-        let pos_end = reader.stream_position()?;
-        let read_size = pos_end - pos_start;
-        if read_size != length as u64 - 4 {
-            return Err(BnkError::BadDataSize {
-                name: "MusicRanSeqCntrInitialValues".to_string(),
-                expected: length as u64 - 4,
-                got: read_size,
-                start: pos_start,
-            });
-        }
         Ok(HircMusicRanSeqCntr {
             music_ran_seq_cntr_initial_values,
         })
@@ -57,6 +48,132 @@ impl EntryPayloadExt for HircMusicRanSeqCntr {
     }
 }
 
+impl HircMusicRanSeqCntr {
+    pub fn node_base_params(&self) -> &NodeBaseParams {
+        self.music_ran_seq_cntr_initial_values
+            .music_trans_node_params
+            .music_node_params
+            .node_base_params()
+    }
+
+    pub fn node_base_params_mut(&mut self) -> &mut NodeBaseParams {
+        self.music_ran_seq_cntr_initial_values
+            .music_trans_node_params
+            .music_node_params
+            .node_base_params_mut()
+    }
+
+    /// Ids of the child segments and containers.
+    pub fn children(&self) -> &[u32] {
+        self.music_ran_seq_cntr_initial_values
+            .music_trans_node_params
+            .music_node_params
+            .children()
+    }
+
+    /// Root items of the playlist, usually a single group.
+    pub fn playlist(&self) -> &[AkMusicRanSeqPlaylistItem] {
+        &self.music_ran_seq_cntr_initial_values.play_list_items
+    }
+
+    /// The item count is recomputed on write.
+    pub fn playlist_mut(&mut self) -> &mut [AkMusicRanSeqPlaylistItem] {
+        &mut self.music_ran_seq_cntr_initial_values.play_list_items
+    }
+
+    /// Append an item to the first root group, or as a root item if the
+    /// playlist is empty. The segments of the item are added to the children.
+    pub fn append_playlist_item(&mut self, item: AkMusicRanSeqPlaylistItem) {
+        let values = &mut self.music_ran_seq_cntr_initial_values;
+        let mut segment_ids = Vec::new();
+        collect_segment_ids(&item, &mut segment_ids);
+        for segment_id in segment_ids {
+            values
+                .music_trans_node_params
+                .music_node_params
+                .add_child(segment_id);
+        }
+        match values.play_list_items.first_mut() {
+            Some(root) => root.push_child(item),
+            None => values.play_list_items.push(item),
+        }
+        values.num_play_list_items = values.play_list_items.iter().map(get_num_recursive).sum();
+    }
+
+    /// Remove an item and its children from the playlist by playlist item
+    /// id. Segments stay children of the container.
+    pub fn remove_playlist_item(
+        &mut self,
+        play_list_item_id: i32,
+    ) -> Option<AkMusicRanSeqPlaylistItem> {
+        let values = &mut self.music_ran_seq_cntr_initial_values;
+        let item = match values
+            .play_list_items
+            .iter()
+            .position(|item| item.play_list_item_id == play_list_item_id)
+        {
+            Some(index) => values.play_list_items.remove(index),
+            None => values
+                .play_list_items
+                .iter_mut()
+                .find_map(|item| item.remove_descendant(play_list_item_id))?,
+        };
+        values.num_play_list_items = values.play_list_items.iter().map(get_num_recursive).sum();
+        Some(item)
+    }
+
+    pub fn transition_rules(&self) -> &[AkMusicTransitionRule] {
+        &self
+            .music_ran_seq_cntr_initial_values
+            .music_trans_node_params
+            .rules
+    }
+
+    /// Add a transition rule, rules are matched from last to first.
+    pub fn add_transition_rule(&mut self, rule: AkMusicTransitionRule) {
+        let params = &mut self
+            .music_ran_seq_cntr_initial_values
+            .music_trans_node_params;
+        params.rules.push(rule);
+        params.num_rules = params.rules.len() as u32;
+    }
+
+    pub fn remove_transition_rule(&mut self, index: usize) -> Option<AkMusicTransitionRule> {
+        let params = &mut self
+            .music_ran_seq_cntr_initial_values
+            .music_trans_node_params;
+        if index >= params.rules.len() {
+            return None;
+        }
+        let rule = params.rules.remove(index);
+        params.num_rules = params.rules.len() as u32;
+        Some(rule)
+    }
+
+    pub fn meter_info(&self) -> &AkMeterInfo {
+        self.music_ran_seq_cntr_initial_values
+            .music_trans_node_params
+            .music_node_params
+            .meter_info()
+    }
+
+    pub fn meter_info_mut(&mut self) -> &mut AkMeterInfo {
+        self.music_ran_seq_cntr_initial_values
+            .music_trans_node_params
+            .music_node_params
+            .meter_info_mut()
+    }
+}
+
+fn collect_segment_ids(item: &AkMusicRanSeqPlaylistItem, out: &mut Vec<u32>) {
+    if item.segment_id != 0 {
+        out.push(item.segment_id);
+    }
+    for child in &item.play_list {
+        collect_segment_ids(child, out);
+    }
+}
+
 fn get_num_recursive(play_list_item: &AkMusicRanSeqPlaylistItem) -> u32 {
     let mut num = 1; // Count this item
     for child in &play_list_item.play_list {
@@ -86,9 +203,10 @@ impl BinRead for MusicRanSeqCntrInitialValues {
         let num_play_list_items = u32::read_le(reader)?;
 
         let mut play_list_items = Vec::with_capacity(1);
+        let mut num = 0;
         loop {
-            let play_list_item = AkMusicRanSeqPlaylistItem::read_args(reader, args)?;
-            let num = get_num_recursive(&play_list_item);
+            let play_list_item = AkMusicRanSeqPlaylistItem::read_args(reader, (0,))?;
+            num += get_num_recursive(&play_list_item);
             play_list_items.push(play_list_item);
 
             match num.cmp(&num_play_list_items) {
@@ -128,8 +246,12 @@ impl BinWrite for MusicRanSeqCntrInitialValues {
     }
 }
 
+/// Deepest playlist item read, deeper items are rejected before recursing.
+const MAX_PLAYLIST_DEPTH: u32 = 64;
+
 #[binrw]
 #[brw(little)]
+#[br(import(depth: u32), pre_assert(depth < MAX_PLAYLIST_DEPTH, "playlist deeper than {MAX_PLAYLIST_DEPTH} levels"))]
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AkMusicRanSeqPlaylistItem {
@@ -144,10 +266,114 @@ pub struct AkMusicRanSeqPlaylistItem {
     avoid_repeat_count: u16,
     is_using_weight: u8,
     is_shuffle: u8,
-    #[br(count = num_children)]
+    #[br(count = num_children, args { inner: (depth + 1,) })]
     play_list: Vec<AkMusicRanSeqPlaylistItem>,
 }
 
+/// `rs_type` of segment items.
+const RS_TYPE_SEGMENT: u32 = u32::MAX;
+
+impl AkMusicRanSeqPlaylistItem {
+    /// Item playing a segment once.
+    pub fn new_segment(segment_id: u32, play_list_item_id: i32) -> Self {
+        AkMusicRanSeqPlaylistItem {
+            segment_id,
+            play_list_item_id,
+            num_children: 0,
+            rs_type: RS_TYPE_SEGMENT,
+            r#loop: 1,
+            loop_min: 0,
+            loop_max: 0,
+            weight: 50000,
+            avoid_repeat_count: 0,
+            is_using_weight: 0,
+            is_shuffle: 0,
+            play_list: vec![],
+        }
+    }
+
+    /// Empty group playing its children in sequence once.
+    pub fn new_group(play_list_item_id: i32) -> Self {
+        AkMusicRanSeqPlaylistItem {
+            segment_id: 0,
+            rs_type: 0,
+            avoid_repeat_count: 1,
+            is_using_weight: 1,
+            ..Self::new_segment(0, play_list_item_id)
+        }
+    }
+
+    /// Segment played by the item, 0 for groups.
+    pub fn segment_id(&self) -> u32 {
+        self.segment_id
+    }
+
+    pub fn set_segment_id(&mut self, segment_id: u32) {
+        self.segment_id = segment_id;
+    }
+
+    pub fn play_list_item_id(&self) -> i32 {
+        self.play_list_item_id
+    }
+
+    pub fn is_group(&self) -> bool {
+        self.rs_type != RS_TYPE_SEGMENT
+    }
+
+    /// Number of times the item is played, 0 for infinite looping.
+    pub fn loop_count(&self) -> i16 {
+        self.r#loop
+    }
+
+    pub fn set_loop_count(&mut self, loop_count: i16) {
+        self.r#loop = loop_count;
+    }
+
+    /// Weight of the item in random groups, 50000 by default.
+    pub fn weight(&self) -> u32 {
+        self.weight
+    }
+
+    pub fn set_weight(&mut self, weight: u32) {
+        self.weight = weight;
+    }
+
+    pub fn children(&self) -> &[AkMusicRanSeqPlaylistItem] {
+        &self.play_list
+    }
+
+    pub fn children_mut(&mut self) -> &mut [AkMusicRanSeqPlaylistItem] {
+        &mut self.play_list
+    }
+
+    pub fn push_child(&mut self, item: AkMusicRanSeqPlaylistItem) {
+        self.play_list.push(item);
+        self.num_children = self.play_list.len() as u32;
+    }
+
+    pub fn remove_child(&mut self, index: usize) -> Option<AkMusicRanSeqPlaylistItem> {
+        if index >= self.play_list.len() {
+            return None;
+        }
+        let item = self.play_list.remove(index);
+        self.num_children = self.play_list.len() as u32;
+        Some(item)
+    }
+
+    fn remove_descendant(&mut self, play_list_item_id: i32) -> Option<AkMusicRanSeqPlaylistItem> {
+        if let Some(index) = self
+            .play_list
+            .iter()
+            .position(|item| item.play_list_item_id == play_list_item_id)
+        {
+            return self.remove_child(index);
+        }
+        self.play_list
+            .iter_mut()
+            .find_map(|item| item.remove_descendant(play_list_item_id))
+    }
+}
+
 #[binrw]
 #[brw(little)]
 #[derive(Debug, Clone)]
@@ -159,18 +385,105 @@ pub struct MusicTransNodeParams {
     rules: Vec<AkMusicTransitionRule>,
 }
 
+impl MusicTransNodeParams {
+    pub fn music_node_params(&self) -> &MusicNodeParams {
+        &self.music_node_params
+    }
+
+    pub fn music_node_params_mut(&mut self) -> &mut MusicNodeParams {
+        &mut self.music_node_params
+    }
+}
+
 #[binrw]
 #[brw(little)]
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AkMusicTransitionRule {
     num_src: u32,
-    src_id: u32,
+    #[br(count = num_src)]
+    src_ids: Vec<u32>,
     num_dst: u32,
-    dst_id: u32,
+    #[br(count = num_dst)]
+    dst_ids: Vec<u32>,
     src_rule: AkMusicTransSrcRule,
     dst_rule: AkMusicTransDstRule,
     alloc_trans_object_flag: u8,
+    #[br(if(alloc_trans_object_flag != 0))]
+    #[bw(if(*alloc_trans_object_flag != 0))]
+    trans_object: Option<AkMusicTransitionObject>,
+}
+
+impl AkMusicTransitionRule {
+    /// Rule switching at the exit cue without fades or transition segment,
+    /// the default rule of Wwise. Ids of -1 (`u32::MAX`) match any object.
+    pub fn new(src_ids: Vec<u32>, dst_ids: Vec<u32>) -> Self {
+        AkMusicTransitionRule {
+            num_src: src_ids.len() as u32,
+            src_ids,
+            num_dst: dst_ids.len() as u32,
+            dst_ids,
+            src_rule: AkMusicTransSrcRule {
+                transition_time: 0,
+                fade_curve: AkCurveInterpolation::Linear as u32,
+                fade_offset: 0,
+                sync_type: SYNC_TYPE_EXIT_MARKER,
+                cue_filter_hash: 0,
+                play_post_exit: 1,
+            },
+            dst_rule: AkMusicTransDstRule {
+                transition_time: 0,
+                fade_curve: AkCurveInterpolation::Linear as u32,
+                fade_offset: 0,
+                cue_filter_hash: 0,
+                jump_to_id: 0,
+                jump_to_type: 0,
+                entry_type: 0,
+                play_pre_entry: 1,
+                dest_match_source_cue_name: 0,
+            },
+            alloc_trans_object_flag: 0,
+            trans_object: None,
+        }
+    }
+
+    pub fn src_ids(&self) -> &[u32] {
+        &self.src_ids
+    }
+
+    pub fn set_src_ids(&mut self, src_ids: Vec<u32>) {
+        self.num_src = src_ids.len() as u32;
+        self.src_ids = src_ids;
+    }
+
+    pub fn dst_ids(&self) -> &[u32] {
+        &self.dst_ids
+    }
+
+    pub fn set_dst_ids(&mut self, dst_ids: Vec<u32>) {
+        self.num_dst = dst_ids.len() as u32;
+        self.dst_ids = dst_ids;
+    }
+
+    /// Segment played between the source and the destination.
+    pub fn transition_segment_id(&self) -> Option<u32> {
+        self.trans_object.as_ref().map(|object| object.segment_id)
+    }
+}
+
+/// `sync_type` switching at the exit cue of the source.
+const SYNC_TYPE_EXIT_MARKER: u32 = 7;
+
+#[binrw]
+#[brw(little)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AkMusicTransitionObject {
+    segment_id: u32,
+    fade_in_params: FadeParams,
+    fade_out_params: FadeParams,
+    play_pre_entry: u8,
+    play_post_exit: u8,
 }
 
 #[binrw]
@@ -201,3 +514,79 @@ pub struct AkMusicTransDstRule {
     play_pre_entry: u8,
     dest_match_source_cue_name: u8,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bnk::{
+        Bnk,
+        hirc::HircEntryPayload,
+        tests::{INPUT_HIRC_2, hirc_entries_mut, reread_bank},
+    };
+
+    #[test]
+    fn test_music_playlist_edit() {
+        let mut bnk = Bnk::from_file(INPUT_HIRC_2).unwrap();
+        let (cntr_id, cntr) = hirc_entries_mut(&mut bnk)
+            .iter_mut()
+            .find_map(|entry| match &mut entry.payload {
+                HircEntryPayload::MusicRanSeqCntr(cntr) => Some((entry.id, cntr)),
+                _ => None,
+            })
+            .unwrap();
+        let root_children = cntr.playlist()[0].children().len();
+        let rules = cntr.transition_rules().len();
+
+        let mut group = AkMusicRanSeqPlaylistItem::new_group(-2);
+        group.push_child(AkMusicRanSeqPlaylistItem::new_segment(1234, -3));
+        cntr.append_playlist_item(group);
+        cntr.append_playlist_item(AkMusicRanSeqPlaylistItem::new_segment(1235, -4));
+        assert!(cntr.children().contains(&1234));
+        cntr.add_transition_rule(AkMusicTransitionRule::new(vec![1234], vec![u32::MAX]));
+
+        let mut bnk = reread_bank(&bnk);
+        let HircEntryPayload::MusicRanSeqCntr(cntr) =
+            &mut bnk.hirc_entry_mut(cntr_id).unwrap().payload
+        else {
+            panic!();
+        };
+        let root = &cntr.playlist()[0];
+        assert_eq!(root.children().len(), root_children + 2);
+        let group = &root.children()[root_children];
+        assert!(group.is_group());
+        assert_eq!(group.children()[0].segment_id(), 1234);
+        assert_eq!(cntr.transition_rules().len(), rules + 1);
+        assert_eq!(cntr.transition_rules()[rules].src_ids(), [1234]);
+
+        let removed = cntr.remove_playlist_item(-3).unwrap();
+        assert_eq!(removed.segment_id(), 1234);
+        assert!(cntr.remove_playlist_item(-3).is_none());
+        assert!(cntr.remove_playlist_item(-2).unwrap().children().is_empty());
+        assert!(cntr.remove_transition_rule(rules).is_some());
+        cntr.remove_playlist_item(-4).unwrap();
+        assert_eq!(cntr.playlist()[0].children().len(), root_children);
+    }
+
+    #[test]
+    fn test_meter_info() {
+        let mut bnk = Bnk::from_file(INPUT_HIRC_2).unwrap();
+        let mut checked = 0;
+        for entry in hirc_entries_mut(&mut bnk) {
+            let HircEntryPayload::MusicRanSeqCntr(cntr) = &mut entry.payload else {
+                continue;
+            };
+            let meter = cntr.meter_info_mut();
+            let bars = meter.grid_period() / meter.bar_duration();
+            if (bars - bars.round()).abs() > 1e-6 {
+                continue;
+            }
+            checked += 1;
+            meter.set_meter(meter.tempo() * 2.0, 3, 8);
+            let new_bars = meter.grid_period() / meter.bar_duration();
+            assert!((new_bars - bars).abs() < 1e-6);
+            assert_eq!(meter.grid_offset(), 0.0);
+        }
+        // tempo maps of this bank use bar aligned grids
+        assert!(checked > 0);
+    }
+}
@@ -1,9 +1,10 @@
 use std::io::{Read, Seek, Write};
 
 use binrw::{BinRead, BinWrite, binrw};
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-use crate::{bnk::BnkError, rwext::BinrwNullString};
+use crate::rwext::BinrwNullString;
 
 use super::{EntryPayloadExt, Result, common::NodeBaseParams};
 
@@ -14,22 +15,11 @@ pub struct HircMusicSegment {
 }
 
 impl EntryPayloadExt for HircMusicSegment {
-    fn from_reader<R>(reader: &mut R, length: u32) -> Result<Self>
+    fn from_reader<R>(reader: &mut R, _length: u32) -> Result<Self>
     where
         R: Read + Seek,
     {
-        let start_pos = reader.stream_position()?;
         let music_segment_initial_values = MusicSegmentInitialValues::read(reader)?;
-        let end_pos = reader.stream_position()?;
-        let read_size = end_pos - start_pos;
-        if read_size != length as u64 - 4 {
-            return Err(BnkError::BadDataSize {
-                name: "MusicSegment".to_string(),
-                expected: length as u64 - 4,
-                got: read_size,
-                start: start_pos,
-            });
-        }
         Ok(HircMusicSegment {
             music_segment_initial_values,
         })
@@ -44,6 +34,106 @@ impl EntryPayloadExt for HircMusicSegment {
     }
 }
 
+impl HircMusicSegment {
+    pub fn node_base_params(&self) -> &NodeBaseParams {
+        self.music_segment_initial_values
+            .music_node_params
+            .node_base_params()
+    }
+
+    pub fn node_base_params_mut(&mut self) -> &mut NodeBaseParams {
+        self.music_segment_initial_values
+            .music_node_params
+            .node_base_params_mut()
+    }
+
+    pub fn music_node_params(&self) -> &MusicNodeParams {
+        &self.music_segment_initial_values.music_node_params
+    }
+
+    pub fn music_node_params_mut(&mut self) -> &mut MusicNodeParams {
+        &mut self.music_segment_initial_values.music_node_params
+    }
+
+    /// Ids of the child tracks.
+    pub fn children(&self) -> &[u32] {
+        self.music_segment_initial_values
+            .music_node_params
+            .children()
+    }
+
+    /// Duration in milliseconds, the position of the exit cue.
+    pub fn duration(&self) -> f64 {
+        self.music_segment_initial_values.duration
+    }
+
+    /// Set the duration, moving the markers at the old end (the exit cue)
+    /// along and clamping the markers past the new end.
+    pub fn set_duration(&mut self, duration: f64) {
+        let values = &mut self.music_segment_initial_values;
+        for marker in &mut values.markers {
+            if marker.position == values.duration || marker.position > duration {
+                marker.position = duration;
+            }
+        }
+        values.duration = duration;
+    }
+
+    /// Cues of the segment, the entry and exit cues and custom cues.
+    pub fn markers(&self) -> &[AkMusicMarkerWwise] {
+        &self.music_segment_initial_values.markers
+    }
+
+    pub fn markers_mut(&mut self) -> &mut [AkMusicMarkerWwise] {
+        &mut self.music_segment_initial_values.markers
+    }
+
+    /// Add a custom cue, keeping the markers sorted by position. Returns the
+    /// id of the new marker.
+    pub fn add_marker(&mut self, position: f64, name: &str) -> u32 {
+        let values = &mut self.music_segment_initial_values;
+        let id = values
+            .markers
+            .iter()
+            .map(|marker| marker.id)
+            .max()
+            .map_or(1, |id| id.wrapping_add(1));
+        let index = values
+            .markers
+            .partition_point(|marker| marker.position <= position);
+        values.markers.insert(
+            index,
+            AkMusicMarkerWwise {
+                id,
+                position,
+                marker_name: BinrwNullString(name.into()),
+            },
+        );
+        values.num_markers = values.markers.len() as u32;
+        id
+    }
+
+    pub fn remove_marker(&mut self, id: u32) -> Option<AkMusicMarkerWwise> {
+        let values = &mut self.music_segment_initial_values;
+        let index = values.markers.iter().position(|marker| marker.id == id)?;
+        let marker = values.markers.remove(index);
+        values.num_markers = values.markers.len() as u32;
+        Some(marker)
+    }
+
+    pub fn meter_info(&self) -> &AkMeterInfo {
+        self.music_segment_initial_values
+            .music_node_params
+            .meter_info()
+    }
+
+    pub fn meter_info_mut(&mut self) -> &mut AkMeterInfo {
+        self.music_segment_initial_values
+            .music_node_params
+            .meter_info_mut()
+    }
+}
+
 #[binrw]
 #[brw(little)]
 #[derive(Debug, Clone)]
@@ -70,13 +160,51 @@ pub struct MusicNodeParams {
     stingers: Vec<CAkStinger>,
 }
 
+impl MusicNodeParams {
+    pub fn node_base_params(&self) -> &NodeBaseParams {
+        &self.node_base_params
+    }
+
+    pub fn node_base_params_mut(&mut self) -> &mut NodeBaseParams {
+        &mut self.node_base_params
+    }
+
+    /// Stingers played over the node on triggers.
+    pub fn stingers(&self) -> &[CAkStinger] {
+        &self.stingers
+    }
+
+    /// Ids of the child nodes.
+    pub fn children(&self) -> &[u32] {
+        &self.children.children
+    }
+
+    /// Add a child node, keeping children sorted. Returns `false` if already present.
+    pub fn add_child(&mut self, id: u32) -> bool {
+        let Err(idx) = self.children.children.binary_search(&id) else {
+            return false;
+        };
+        self.children.children.insert(idx, id);
+        self.children.num_children = self.children.children.len() as u32;
+        true
+    }
+
+    pub fn meter_info(&self) -> &AkMeterInfo {
+        &self.ak_meter_info
+    }
+
+    pub fn meter_info_mut(&mut self) -> &mut AkMeterInfo {
+        &mut self.ak_meter_info
+    }
+}
+
 #[binrw]
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Children {
-    num_children: u32,
+    pub(super) num_children: u32,
     #[br(count = num_children)]
-    children: Vec<u32>,
+    pub(super) children: Vec<u32>,
 }
 
 #[binrw]
@@ -90,6 +218,70 @@ pub struct AkMeterInfo {
     time_sig_beat_value: u8,
 }
 
+impl AkMeterInfo {
+    /// Tempo in quarter notes per minute.
+    pub fn tempo(&self) -> f32 {
+        self.tempo
+    }
+
+    /// Beats per bar and beat value.
+    pub fn time_signature(&self) -> (u8, u8) {
+        (self.time_sig_num_beats_bar, self.time_sig_beat_value)
+    }
+
+    /// Grid period in milliseconds.
+    pub fn grid_period(&self) -> f64 {
+        self.grid_period
+    }
+
+    /// Grid offset in milliseconds.
+    pub fn grid_offset(&self) -> f64 {
+        self.grid_offset
+    }
+
+    /// Duration of a beat in milliseconds, the tempo counts quarter notes
+    /// regardless of the beat value.
+    pub fn beat_duration(&self) -> f64 {
+        60_000.0 / self.tempo as f64 * 4.0 / self.time_sig_beat_value as f64
+    }
+
+    /// Duration of a bar in milliseconds.
+    pub fn bar_duration(&self) -> f64 {
+        self.beat_duration() * self.time_sig_num_beats_bar as f64
+    }
+
+    /// Set the tempo, rescaling the grid so it keeps its musical length.
+    pub fn set_tempo(&mut self, tempo: f32) {
+        let ratio = self.tempo as f64 / tempo as f64;
+        self.tempo = tempo;
+        self.grid_period *= ratio;
+        self.grid_offset *= ratio;
+    }
+
+    /// Set the time signature.
+    ///
+    /// A grid period or offset spanning whole bars keeps its number of bars,
+    /// otherwise it is left unchanged.
+    pub fn set_time_signature(&mut self, num_beats_bar: u8, beat_value: u8) {
+        let old_bar = self.bar_duration();
+        self.time_sig_num_beats_bar = num_beats_bar;
+        self.time_sig_beat_value = beat_value;
+        let new_bar = self.bar_duration();
+        for value in [&mut self.grid_period, &mut self.grid_offset] {
+            let bars = *value / old_bar;
+            if bars != 0.0 && (bars - bars.round()).abs() < 1e-6 {
+                *value = bars.round() * new_bar;
+            }
+        }
+    }
+
+    /// Set tempo and time signature at once.
+    pub fn set_meter(&mut self, tempo: f32, num_beats_bar: u8, beat_value: u8) {
+        self.set_time_signature(num_beats_bar, beat_value);
+        self.set_tempo(tempo);
+    }
+}
+
 #[binrw]
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -102,6 +294,21 @@ pub struct CAkStinger {
     num_segment_look_ahead: u32,
 }
 
+impl CAkStinger {
+    pub fn trigger_id(&self) -> u32 {
+        self.trigger_id
+    }
+
+    /// Segment played by the stinger.
+    pub fn segment_id(&self) -> u32 {
+        self.segment_id
+    }
+
+    pub fn set_segment_id(&mut self, segment_id: u32) {
+        self.segment_id = segment_id;
+    }
+}
+
 #[binrw]
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -110,3 +317,75 @@ pub struct AkMusicMarkerWwise {
     position: f64,
     marker_name: BinrwNullString,
 }
+
+impl AkMusicMarkerWwise {
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Position in milliseconds.
+    pub fn position(&self) -> f64 {
+        self.position
+    }
+
+    pub fn set_position(&mut self, position: f64) {
+        self.position = position;
+    }
+
+    /// Empty for the entry and exit cues.
+    pub fn name(&self) -> String {
+        self.marker_name.0.to_string()
+    }
+
+    pub fn set_name(&mut self, name: &str) {
+        self.marker_name = BinrwNullString(name.into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bnk::{
+        Bnk,
+        hirc::HircEntryPayload,
+        tests::{INPUT_HIRC_2, hirc_entries_mut, reread_bank},
+    };
+
+    #[test]
+    fn test_segment_markers() {
+        let mut bnk = Bnk::from_file(INPUT_HIRC_2).unwrap();
+        let (segment_id, segment) = hirc_entries_mut(&mut bnk)
+            .iter_mut()
+            .find_map(|entry| match &mut entry.payload {
+                HircEntryPayload::MusicSegment(segment) => Some((entry.id, segment)),
+                _ => None,
+            })
+            .unwrap();
+        let count = segment.markers().len();
+        let position = segment.duration() / 2.0;
+        let id = segment.add_marker(position, "Custom");
+        assert!(segment.markers().iter().filter(|m| m.id() == id).count() == 1);
+        assert!(
+            segment
+                .markers()
+                .windows(2)
+                .all(|pair| pair[0].position() <= pair[1].position())
+        );
+        segment.set_duration(position / 2.0);
+        assert_eq!(segment.duration(), position / 2.0);
+
+        let mut bnk = reread_bank(&bnk);
+        let HircEntryPayload::MusicSegment(segment) =
+            &mut bnk.hirc_entry_mut(segment_id).unwrap().payload
+        else {
+            panic!();
+        };
+        assert_eq!(segment.markers().len(), count + 1);
+        let marker = segment.markers().iter().find(|m| m.id() == id).unwrap();
+        assert_eq!(marker.name(), "Custom");
+        assert_eq!(marker.position(), position / 2.0);
+        segment.markers_mut()[0].set_name("Entry");
+        assert_eq!(segment.markers()[0].name(), "Entry");
+        assert!(segment.remove_marker(id).is_some());
+        assert_eq!(segment.markers().len(), count);
+    }
+}
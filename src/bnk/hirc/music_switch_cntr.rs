@@ -0,0 +1,242 @@
+use std::io::{Read, Seek, Write};
+
+use binrw::{BinRead, BinWrite};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::bnk::BnkError;
+
+use super::{
+    AkDecisionTree, AkGameSync, EntryPayloadExt, MusicTransNodeParams, Result,
+    common::NodeBaseParams,
+    decision_tree::{read_arguments, write_arguments},
+};
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct HircMusicSwitchCntr {
+    music_switch_cntr_initial_values: MusicSwitchCntrInitialValues,
+}
+
+impl EntryPayloadExt for HircMusicSwitchCntr {
+    fn from_reader<R>(reader: &mut R, _length: u32) -> Result<Self>
+    where
+        R: Read + Seek,
+    {
+        let music_switch_cntr_initial_values = MusicSwitchCntrInitialValues::read_le(reader)?;
+        Ok(HircMusicSwitchCntr {
+            music_switch_cntr_initial_values,
+        })
+    }
+
+    fn write_to<W>(&self, writer: &mut W) -> Result<()>
+    where
+        W: Write + Seek,
+    {
+        self.music_switch_cntr_initial_values.write_le(writer)?;
+        Ok(())
+    }
+}
+
+impl HircMusicSwitchCntr {
+    pub fn node_base_params(&self) -> &NodeBaseParams {
+        self.music_switch_cntr_initial_values
+            .music_trans_node_params
+            .music_node_params()
+            .node_base_params()
+    }
+
+    pub fn node_base_params_mut(&mut self) -> &mut NodeBaseParams {
+        self.music_switch_cntr_initial_values
+            .music_trans_node_params
+            .music_node_params_mut()
+            .node_base_params_mut()
+    }
+
+    /// Ids of the child segments and containers.
+    pub fn children(&self) -> &[u32] {
+        self.music_switch_cntr_initial_values
+            .music_trans_node_params
+            .music_node_params()
+            .children()
+    }
+
+    /// Game sync groups matched by the levels of the decision tree.
+    pub fn arguments(&self) -> &[AkGameSync] {
+        &self.music_switch_cntr_initial_values.arguments
+    }
+
+    pub fn decision_tree(&self) -> &AkDecisionTree {
+        &self.music_switch_cntr_initial_values.decision_tree
+    }
+
+    /// The tree size is recomputed on write. Unlike
+    /// [set_association](Self::set_association), audio nodes are not added to
+    /// the children.
+    pub fn decision_tree_mut(&mut self) -> &mut AkDecisionTree {
+        &mut self.music_switch_cntr_initial_values.decision_tree
+    }
+
+    /// Point a path of argument values at a child, adding the child if needed.
+    ///
+    /// Key 0 matches any value, a path of zeros is the default path.
+    /// Returns the node previously played for the path.
+    pub fn set_association(&mut self, keys: &[u32], node_id: u32) -> Result<Option<u32>> {
+        let values = &mut self.music_switch_cntr_initial_values;
+        if keys.len() != values.arguments.len() {
+            return Err(BnkError::InvalidDecisionPath {
+                expected: values.arguments.len(),
+                got: keys.len(),
+            });
+        }
+        values
+            .music_trans_node_params
+            .music_node_params_mut()
+            .add_child(node_id);
+        Ok(values.decision_tree.insert(keys, node_id))
+    }
+
+    /// Set the node played when no other path matches.
+    pub fn set_default(&mut self, node_id: u32) -> Result<Option<u32>> {
+        let keys = vec![0; self.arguments().len()];
+        self.set_association(&keys, node_id)
+    }
+
+    /// Remove a path, returning the node it played.
+    ///
+    /// The node stays a child of the container.
+    pub fn remove_association(&mut self, keys: &[u32]) -> Option<u32> {
+        self.music_switch_cntr_initial_values
+            .decision_tree
+            .remove(keys)
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MusicSwitchCntrInitialValues {
+    music_trans_node_params: MusicTransNodeParams,
+    is_continue_playback: u8,
+    arguments: Vec<AkGameSync>,
+    decision_tree: AkDecisionTree,
+}
+
+impl BinRead for MusicSwitchCntrInitialValues {
+    type Args<'a> = ();
+
+    fn read_options<R: Read + Seek>(
+        reader: &mut R,
+        _endian: binrw::Endian,
+        args: Self::Args<'_>,
+    ) -> binrw::BinResult<Self> {
+        let music_trans_node_params = MusicTransNodeParams::read_args(reader, args)?;
+        let is_continue_playback = u8::read_le(reader)?;
+        let tree_depth = u32::read_le(reader)?;
+        let arguments = read_arguments(reader, tree_depth)?;
+        let tree_data_size = u32::read_le(reader)?;
+        let decision_tree = AkDecisionTree::read_le_args(reader, (tree_depth, tree_data_size))?;
+
+        Ok(MusicSwitchCntrInitialValues {
+            music_trans_node_params,
+            is_continue_playback,
+            arguments,
+            decision_tree,
+        })
+    }
+}
+
+impl BinWrite for MusicSwitchCntrInitialValues {
+    type Args<'a> = ();
+
+    fn write_options<W: Write + Seek>(
+        &self,
+        writer: &mut W,
+        _endian: binrw::Endian,
+        args: Self::Args<'_>,
+    ) -> binrw::BinResult<()> {
+        self.music_trans_node_params.write_args(writer, args)?;
+        self.is_continue_playback.write_le(writer)?;
+        (self.arguments.len() as u32).write_le(writer)?;
+        write_arguments(writer, &self.arguments)?;
+        self.decision_tree.data_size().write_le(writer)?;
+        self.decision_tree.write_le(writer)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bnk::{
+        Bnk,
+        hirc::{
+            AkDecisionTreeNode, AkDecisionTreeValue, HircEntry, HircEntryPayload, tests::reread,
+        },
+        tests::{INPUT_HIRC_2, hirc_entries_mut, reread_bank},
+    };
+
+    #[test]
+    fn test_music_switch_association() {
+        let mut bnk = Bnk::from_file(INPUT_HIRC_2).unwrap();
+        let (cntr_id, cntr) = hirc_entries_mut(&mut bnk)
+            .iter_mut()
+            .find_map(|entry| match &mut entry.payload {
+                HircEntryPayload::MusicSwitchContainer(cntr) if cntr.arguments().len() == 1 => {
+                    Some((entry.id, cntr))
+                }
+                _ => None,
+            })
+            .unwrap();
+        let paths = cntr.decision_tree().paths();
+        for (keys, node_id) in &paths {
+            assert_eq!(cntr.decision_tree().resolve(keys), Some(*node_id));
+        }
+
+        assert!(cntr.set_association(&[1, 2], 0x1234).is_err());
+        assert_eq!(cntr.set_association(&[0xABCD], 0x1234).unwrap(), None);
+        cntr.set_default(0x5678).unwrap();
+        assert!(cntr.children().contains(&0x1234));
+
+        let mut bnk = reread_bank(&bnk);
+        let entry = bnk.hirc_entry_mut(cntr_id).unwrap();
+        let HircEntryPayload::MusicSwitchContainer(cntr) = &mut entry.payload else {
+            panic!();
+        };
+        let tree = cntr.decision_tree();
+        assert_eq!(tree.resolve(&[0xABCD]), Some(0x1234));
+        assert_eq!(tree.resolve(&[0xDEAD]), Some(0x5678));
+        assert_eq!(cntr.remove_association(&[0xABCD]), Some(0x1234));
+        assert_eq!(cntr.decision_tree().resolve(&[0xABCD]), Some(0x5678));
+
+        let size = cntr.decision_tree().data_size();
+        cntr.decision_tree_mut().insert(&[0xBEEF], 0x9ABC);
+        let entry = reread(entry);
+        let HircEntryPayload::MusicSwitchContainer(cntr) = &entry.payload else {
+            panic!();
+        };
+        assert_eq!(cntr.decision_tree().resolve(&[0xBEEF]), Some(0x9ABC));
+        assert_eq!(cntr.decision_tree().data_size(), size + 12);
+
+        // first children and counts are stored as u16
+        fn root_children(entry: &mut HircEntry) -> &mut Vec<AkDecisionTreeNode> {
+            let HircEntryPayload::MusicSwitchContainer(cntr) = &mut entry.payload else {
+                panic!();
+            };
+            let AkDecisionTreeValue::Children(children) = &mut cntr.decision_tree_mut().root.value
+            else {
+                panic!();
+            };
+            children
+        }
+        let mut entry = entry;
+        let leaf = root_children(&mut entry)[0].clone();
+        root_children(&mut entry).resize(0xFFFF, leaf.clone());
+        assert!(entry.to_bytes().is_ok());
+        root_children(&mut entry).last_mut().unwrap().value =
+            AkDecisionTreeValue::Children(vec![leaf.clone()]);
+        assert!(entry.to_bytes().is_err());
+        let children = root_children(&mut entry);
+        children.pop();
+        children.extend([leaf.clone(), leaf]);
+        assert!(entry.to_bytes().is_err());
+    }
+}
@@ -1,13 +1,14 @@
 use std::io::{Read, Seek, Write};
 
 use binrw::{BinRead, BinWrite, binrw};
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 use crate::bnk::BnkError;
 
 use super::{
     EntryPayloadExt, Result,
-    common::{AkRTPCGraphPoint, NodeBaseParams},
+    common::{AkCurveInterpolation, AkRTPCGraphPoint, NodeBaseParams},
 };
 
 #[derive(Debug, Clone)]
@@ -17,22 +18,11 @@ pub struct HircMusicTrack {
 }
 
 impl EntryPayloadExt for HircMusicTrack {
-    fn from_reader<R>(reader: &mut R, length: u32) -> Result<Self>
+    fn from_reader<R>(reader: &mut R, _length: u32) -> Result<Self>
     where
         R: Read + Seek,
     {
-        let pos_start = reader.stream_position()?;
         let music_track_initial_values = MusicTrackInitialValues::read(reader)?;
-        let pos_end = reader.stream_position()?;
-        let read_size = pos_end - pos_start;
-        if read_size != length as u64 - 4 {
-            return Err(BnkError::BadDataSize {
-                name: "MusicTrackInitialValues".to_string(),
-                expected: length as u64 - 4,
-                got: read_size,
-                start: pos_start,
-            });
-        }
         Ok(HircMusicTrack {
             music_track_initial_values,
         })
@@ -47,6 +37,368 @@ impl EntryPayloadExt for HircMusicTrack {
     }
 }
 
+impl HircMusicTrack {
+    pub fn sources(&self) -> &[AkBankSourceData] {
+        &self.music_track_initial_values.sources
+    }
+
+    pub fn sources_mut(&mut self) -> &mut [AkBankSourceData] {
+        &mut self.music_track_initial_values.sources
+    }
+
+    pub fn node_base_params(&self) -> &NodeBaseParams {
+        &self.music_track_initial_values.node_base_params
+    }
+
+    pub fn node_base_params_mut(&mut self) -> &mut NodeBaseParams {
+        &mut self.music_track_initial_values.node_base_params
+    }
+
+    /// Number of sub tracks, 0 for tracks without clips.
+    pub fn sub_track_count(&self) -> u32 {
+        self.music_track_initial_values.num_sub_track
+    }
+
+    /// Volume, filter and fade automations of the clips.
+    pub fn clip_automations(&self) -> &[AkClipAutomation] {
+        &self.music_track_initial_values.clip_automations
+    }
+
+    /// Time in milliseconds the track is prepared ahead of playback, for
+    /// streamed sources.
+    pub fn look_ahead_time(&self) -> i32 {
+        self.music_track_initial_values.look_ahead_time
+    }
+
+    pub fn set_look_ahead_time(&mut self, look_ahead_time: i32) {
+        self.music_track_initial_values.look_ahead_time = look_ahead_time;
+    }
+
+    /// Switch parameters of a switch track.
+    pub fn switch_params(&self) -> Option<&SwitchParams> {
+        self.music_track_initial_values.switch_params.as_ref()
+    }
+
+    /// Clips of all sub tracks.
+    pub fn playlist(&self) -> &[AkTrackSrcInfo] {
+        &self.music_track_initial_values.playlist
+    }
+
+    /// Edit a clip, keeping the change only if the clip stays valid.
+    ///
+    /// `media_duration` is the duration of the clip source in milliseconds if
+    /// the media is available, see [crate::bnk::Bnk::media_duration].
+    pub fn update_clip<F>(&mut self, index: usize, media_duration: Option<f64>, f: F) -> Result<()>
+    where
+        F: FnOnce(&mut AkTrackSrcInfo),
+    {
+        let playlist = &mut self.music_track_initial_values.playlist;
+        let invalid = |reason: &str| BnkError::InvalidClip {
+            index,
+            reason: reason.to_string(),
+        };
+        let mut clip = playlist
+            .get(index)
+            .ok_or_else(|| invalid("out of range"))?
+            .clone();
+        f(&mut clip);
+
+        clip.validate(media_duration).map_err(invalid)?;
+        // banks may already contain overlapping clips, only reject new overlaps
+        let old = &playlist[index];
+        let overlaps = playlist
+            .iter()
+            .enumerate()
+            .any(|(i, other)| i != index && clip.overlaps(other) && !old.overlaps(other));
+        if overlaps {
+            return Err(invalid("overlaps another clip of the sub track"));
+        }
+
+        playlist[index] = clip;
+        Ok(())
+    }
+
+    pub fn set_play_at(&mut self, index: usize, play_at: f64) -> Result<()> {
+        self.update_clip(index, None, |clip| clip.play_at = play_at)
+    }
+
+    pub fn set_trim(
+        &mut self,
+        index: usize,
+        begin_trim_offset: f64,
+        end_trim_offset: f64,
+    ) -> Result<()> {
+        self.update_clip(index, None, |clip| {
+            clip.begin_trim_offset = begin_trim_offset;
+            clip.end_trim_offset = end_trim_offset;
+        })
+    }
+
+    pub fn set_src_duration(
+        &mut self,
+        index: usize,
+        src_duration: f64,
+        media_duration: Option<f64>,
+    ) -> Result<()> {
+        self.update_clip(index, media_duration, |clip| {
+            clip.src_duration = src_duration
+        })
+    }
+
+    /// Set the position, trims and source duration of a clip at once, see
+    /// [HircMusicTrack::update_clip].
+    pub fn set_clip_timing(
+        &mut self,
+        index: usize,
+        play_at: f64,
+        begin_trim_offset: f64,
+        end_trim_offset: f64,
+        src_duration: f64,
+    ) -> Result<()> {
+        self.update_clip(index, None, |clip| {
+            clip.play_at = play_at;
+            clip.begin_trim_offset = begin_trim_offset;
+            clip.end_trim_offset = end_trim_offset;
+            clip.src_duration = src_duration;
+        })
+    }
+
+    /// Add a source, returns `false` if the track already has a source with
+    /// the same id.
+    pub fn add_source(&mut self, source: AkBankSourceData) -> bool {
+        let values = &mut self.music_track_initial_values;
+        if values
+            .sources
+            .iter()
+            .any(|s| s.source_id() == source.source_id())
+        {
+            return false;
+        }
+        values.sources.push(source);
+        values.num_sources = values.sources.len() as u32;
+        true
+    }
+
+    /// Remove a source with its clips and their automations.
+    pub fn remove_source(&mut self, source_id: u32) -> Option<AkBankSourceData> {
+        let values = &mut self.music_track_initial_values;
+        let index = values
+            .sources
+            .iter()
+            .position(|s| s.source_id() == source_id)?;
+        let source = values.sources.remove(index);
+
+        // old clip index -> new clip index
+        let mut clip_map = Vec::with_capacity(values.playlist.len());
+        let mut kept = 0;
+        values.playlist.retain(|clip| {
+            if clip.source_id == source_id {
+                clip_map.push(None);
+                false
+            } else {
+                clip_map.push(Some(kept));
+                kept += 1;
+                true
+            }
+        });
+        values.clip_automations.retain_mut(|automation| {
+            match clip_map
+                .get(automation.clip_index as usize)
+                .copied()
+                .flatten()
+            {
+                Some(clip_index) => {
+                    automation.clip_index = clip_index;
+                    true
+                }
+                None => false,
+            }
+        });
+
+        values.num_sources = values.sources.len() as u32;
+        values.num_playlist_items = values.playlist.len() as u32;
+        values.num_clip_automations = values.clip_automations.len() as u32;
+        Some(source)
+    }
+
+    /// Point the source `old_id` and its clips at the media `new_id`.
+    ///
+    /// Returns the number of clips changed, `None` if the track has no
+    /// source `old_id` or already one `new_id`.
+    pub fn retarget_source(&mut self, old_id: u32, new_id: u32) -> Option<usize> {
+        let values = &mut self.music_track_initial_values;
+        if old_id != new_id && values.sources.iter().any(|s| s.source_id() == new_id) {
+            return None;
+        }
+        let source = values
+            .sources
+            .iter_mut()
+            .find(|s| s.source_id() == old_id)?;
+        source.media_information.source_id = new_id;
+
+        let mut count = 0;
+        for clip in values.playlist.iter_mut().filter(|c| c.source_id == old_id) {
+            clip.source_id = new_id;
+            count += 1;
+        }
+        Some(count)
+    }
+
+    /// Add a clip, returns its index.
+    ///
+    /// The clip must be valid and not overlap other clips of its sub track,
+    /// sub tracks are added up to the one of the clip.
+    pub fn add_clip(&mut self, clip: AkTrackSrcInfo) -> Result<usize> {
+        let values = &mut self.music_track_initial_values;
+        let index = values.playlist.len();
+        let invalid = |reason: &str| BnkError::InvalidClip {
+            index,
+            reason: reason.to_string(),
+        };
+        clip.validate(None).map_err(invalid)?;
+        if clip.source_id != 0
+            && !values
+                .sources
+                .iter()
+                .any(|s| s.source_id() == clip.source_id)
+        {
+            return Err(invalid("source not in the track"));
+        }
+        if values.playlist.iter().any(|other| clip.overlaps(other)) {
+            return Err(invalid("overlaps another clip of the sub track"));
+        }
+
+        values.num_sub_track = values.num_sub_track.max(clip.track_id + 1);
+        if let Some(switch_params) = &mut values.switch_params {
+            let sub_tracks = values.num_sub_track as usize;
+            if switch_params.switch_assoc.len() < sub_tracks {
+                switch_params
+                    .switch_assoc
+                    .resize(sub_tracks, TrackSwitchAssoc { switch_assoc: 0 });
+                switch_params.num_switch_assoc = sub_tracks as u32;
+            }
+        }
+        values.playlist.push(clip);
+        values.num_playlist_items = values.playlist.len() as u32;
+        Ok(index)
+    }
+
+    /// Point event cue clips posting `old_event_id` at another event.
+    ///
+    /// Returns the number of clips changed.
+    pub fn replace_event_id(&mut self, old_event_id: u32, new_event_id: u32) -> usize {
+        let mut count = 0;
+        for clip in &mut self.music_track_initial_values.playlist {
+            if clip.event_id == old_event_id {
+                clip.event_id = new_event_id;
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Set the source duration of the clips playing `source_id`, e.g. after
+    /// replacing its media. Clips left without length are reset to play the
+    /// whole source from where they started.
+    ///
+    /// Returns the number of clips changed.
+    pub fn set_source_duration(&mut self, source_id: u32, duration: f64) -> usize {
+        let mut count = 0;
+        for clip in &mut self.music_track_initial_values.playlist {
+            if source_id == 0 || clip.source_id != source_id {
+                continue;
+            }
+            let clip_start = clip.clip_start();
+            clip.src_duration = duration;
+            if clip.clip_end() <= clip_start {
+                clip.play_at = clip_start;
+                clip.begin_trim_offset = 0.0;
+                clip.end_trim_offset = 0.0;
+            }
+            count += 1;
+        }
+        count
+    }
+
+    /// Track position where the last audio clip stops playing, 0 without
+    /// audio clips.
+    pub fn clips_end(&self) -> f64 {
+        self.music_track_initial_values
+            .playlist
+            .iter()
+            .filter(|clip| clip.source_id != 0)
+            .map(AkTrackSrcInfo::clip_end)
+            .fold(0.0, f64::max)
+    }
+
+    pub fn track_type(&self) -> AkMusicTrackType {
+        self.music_track_initial_values.track_type
+    }
+
+    /// Change the track type, restructuring the dependent fields.
+    ///
+    /// - Normal tracks have a single sub track, only the first sub track and
+    ///   the clip automations of its clips are kept.
+    /// - Random and Sequence tracks keep all sub tracks.
+    /// - Switch tracks get default switch and transition parameters with one
+    ///   (unassigned) switch association per sub track.
+    pub fn convert_to(&mut self, track_type: AkMusicTrackType) {
+        let values = &mut self.music_track_initial_values;
+        if values.track_type == track_type {
+            return;
+        }
+
+        if track_type == AkMusicTrackType::Normal {
+            // old clip index -> new clip index
+            let mut clip_map = Vec::with_capacity(values.playlist.len());
+            let mut playlist = Vec::with_capacity(values.playlist.len());
+            for item in values.playlist.drain(..) {
+                if item.track_id == 0 {
+                    clip_map.push(Some(playlist.len() as u32));
+                    playlist.push(item);
+                } else {
+                    clip_map.push(None);
+                }
+            }
+            values.playlist = playlist;
+            values.clip_automations.retain_mut(|automation| {
+                match clip_map
+                    .get(automation.clip_index as usize)
+                    .copied()
+                    .flatten()
+                {
+                    Some(clip_index) => {
+                        automation.clip_index = clip_index;
+                        true
+                    }
+                    None => false,
+                }
+            });
+            values.num_sub_track = 1;
+        }
+        values.num_sub_track = values.num_sub_track.max(1);
+
+        if track_type == AkMusicTrackType::Switch {
+            let sub_tracks = values.num_sub_track;
+            values.switch_params = Some(SwitchParams {
+                group_type: 0,
+                group_id: 0,
+                default_switch: 0,
+                num_switch_assoc: sub_tracks,
+                switch_assoc: vec![TrackSwitchAssoc { switch_assoc: 0 }; sub_tracks as usize],
+            });
+            values.trans_params = Some(TransParams::default());
+        } else {
+            values.switch_params = None;
+            values.trans_params = None;
+        }
+
+        values.track_type = track_type;
+        values.num_playlist_items = values.playlist.len() as u32;
+        values.num_clip_automations = values.clip_automations.len() as u32;
+    }
+}
+
 #[binrw]
 #[brw(little)]
 #[derive(Debug, Clone)]
@@ -81,33 +433,131 @@ pub struct MusicTrackInitialValues {
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AkBankSourceData {
-    plugin_id: u32,
-    stream_type: u8,
-    media_information: AkMediaInformation,
+    pub plugin_id: u32,
+    /// See [AkBankSourceData::stream_type].
+    pub stream_type: u8,
+    pub media_information: AkMediaInformation,
+    /// Parameters of source plugins (tone generator, silence...).
+    #[br(if(plugin_id & 0xF == 2))]
+    #[bw(if(*plugin_id & 0xF == 2))]
+    pub plugin_params: Option<AkPluginParams>,
 }
 
 #[binrw]
 #[brw(little)]
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-pub struct AkMediaInformation {
-    source_id: u32,
-    in_memory_media_size: u32,
-    source_bits: u8,
+pub struct AkPluginParams {
+    #[br(temp)]
+    #[bw(calc = data.len() as u32)]
+    size: u32,
+    #[br(count = size)]
+    #[cfg_attr(feature = "serde", serde(with = "crate::bnk::blob"))]
+    pub data: Vec<u8>,
+}
+
+impl AkBankSourceData {
+    /// Where the media of this source is stored, `None` for unknown stream types.
+    pub fn stream_type(&self) -> Option<AkBankSourceStreamType> {
+        AkBankSourceStreamType::from_repr(self.stream_type)
+    }
+
+    pub fn source_id(&self) -> u32 {
+        self.media_information.source_id
+    }
+}
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, strum::FromRepr)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum AkBankSourceStreamType {
+    /// Embedded in a bank (DIDX/DATA).
+    DataBnk = 0x0,
+    /// Prefetch data embedded in a bank, the rest streamed.
+    PrefetchStreaming = 0x1,
+    /// Fully streamed from a package or loose file.
+    Streaming = 0x2,
 }
 
 #[binrw]
 #[brw(little)]
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AkMediaInformation {
+    pub source_id: u32,
+    pub in_memory_media_size: u32,
+    pub source_bits: u8,
+}
+
+#[binrw]
+#[brw(little)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AkTrackSrcInfo {
-    track_id: u32,
-    source_id: u32,
-    event_id: u32,
-    play_at: f64,
-    begin_trim_offset: f64,
-    end_trim_offset: f64,
-    src_duration: f64,
+    /// Sub track index.
+    pub track_id: u32,
+    /// 0 for event cue clips.
+    pub source_id: u32,
+    pub event_id: u32,
+    /// Position of the start of the source on the track, in milliseconds.
+    pub play_at: f64,
+    /// Offset of the clip start from the source start, beyond the source for
+    /// looped clips.
+    pub begin_trim_offset: f64,
+    /// Offset of the clip end from the source end, positive for looped clips.
+    pub end_trim_offset: f64,
+    pub src_duration: f64,
+}
+
+impl AkTrackSrcInfo {
+    /// Track position where the clip starts playing.
+    pub fn clip_start(&self) -> f64 {
+        self.play_at + self.begin_trim_offset
+    }
+
+    /// Track position where the clip stops playing.
+    pub fn clip_end(&self) -> f64 {
+        self.play_at + self.src_duration + self.end_trim_offset
+    }
+
+    /// Whether two audio clips of the same sub track play at the same time.
+    pub fn overlaps(&self, other: &AkTrackSrcInfo) -> bool {
+        self.source_id != 0
+            && other.source_id != 0
+            && self.track_id == other.track_id
+            && self.clip_start() < other.clip_end()
+            && other.clip_start() < self.clip_end()
+    }
+
+    /// Check the clip length, and the source duration against the media
+    /// duration if given. Event cue clips have no length.
+    pub fn validate(&self, media_duration: Option<f64>) -> std::result::Result<(), &'static str> {
+        // durations are stored rounded to milliseconds or finer
+        const TOLERANCE: f64 = 1.0;
+
+        if !(self.play_at.is_finite()
+            && self.begin_trim_offset.is_finite()
+            && self.end_trim_offset.is_finite()
+            && self.src_duration.is_finite())
+        {
+            return Err("non-finite value");
+        }
+        if self.source_id == 0 {
+            return Ok(());
+        }
+        if self.src_duration <= 0.0 {
+            return Err("source duration is not positive");
+        }
+        if self.clip_end() <= self.clip_start() {
+            return Err("clip length is not positive");
+        }
+        if let Some(media_duration) = media_duration
+            && (self.src_duration - media_duration).abs() > TOLERANCE
+        {
+            return Err("source duration does not match the media");
+        }
+        Ok(())
+    }
 }
 
 #[binrw]
@@ -122,6 +572,22 @@ pub struct AkClipAutomation {
     graph_points: Vec<AkRTPCGraphPoint>,
 }
 
+impl AkClipAutomation {
+    /// Index of the clip in the track playlist.
+    pub fn clip_index(&self) -> u32 {
+        self.clip_index
+    }
+
+    /// Volume, LPF, HPF, fade-in or fade-out.
+    pub fn auto_type(&self) -> u32 {
+        self.auto_type
+    }
+
+    pub fn points(&self) -> &[AkRTPCGraphPoint] {
+        &self.graph_points
+    }
+}
+
 #[repr(u8)]
 #[binrw]
 #[brw(repr(u8))]
@@ -147,6 +613,26 @@ pub struct SwitchParams {
     switch_assoc: Vec<TrackSwitchAssoc>,
 }
 
+impl SwitchParams {
+    /// `true` if the track switches on a state group instead of a switch group.
+    pub fn is_state(&self) -> bool {
+        self.group_type == 1
+    }
+
+    pub fn group_id(&self) -> u32 {
+        self.group_id
+    }
+
+    pub fn default_switch(&self) -> u32 {
+        self.default_switch
+    }
+
+    /// Switch or state ids associated to the sub tracks, in sub track order.
+    pub fn switch_ids(&self) -> impl Iterator<Item = u32> + '_ {
+        self.switch_assoc.iter().map(|assoc| assoc.switch_assoc)
+    }
+}
+
 #[binrw]
 #[brw(little)]
 #[derive(Debug, Clone)]
@@ -157,7 +643,7 @@ pub struct TrackSwitchAssoc {
 
 #[binrw]
 #[brw(little)]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TransParams {
     src_fade_params: FadeParams,
@@ -175,3 +661,169 @@ pub struct FadeParams {
     fade_curve: u32,
     fade_offset: i32,
 }
+
+impl Default for FadeParams {
+    fn default() -> Self {
+        FadeParams {
+            transition_time: 0,
+            fade_curve: AkCurveInterpolation::Linear as u32,
+            fade_offset: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::bnk::{
+        Bnk,
+        hirc::HircEntryPayload,
+        tests::{INPUT_DIDX_DATA, INPUT_HIRC_2, hirc_entries_mut, reread_bank},
+    };
+
+    #[test]
+    fn test_track_source_edit() {
+        let mut bnk = Bnk::from_file(INPUT_HIRC_2).unwrap();
+        let (track_id, track) = hirc_entries_mut(&mut bnk)
+            .iter_mut()
+            .find_map(|entry| match &mut entry.payload {
+                HircEntryPayload::MusicTrack(track)
+                    if track.sources().len() == 1 && track.playlist().len() == 1 =>
+                {
+                    Some((entry.id, track))
+                }
+                _ => None,
+            })
+            .unwrap();
+        let source = track.sources()[0].clone();
+        let old_id = source.source_id();
+        let clip = track.playlist()[0].clone();
+
+        assert_eq!(track.retarget_source(old_id, 1), Some(1));
+        assert_eq!(track.playlist()[0].source_id, 1);
+        assert_eq!(track.retarget_source(old_id, 2), None);
+
+        let mut new_source = source.clone();
+        new_source.media_information.source_id = 2;
+        assert!(track.add_source(new_source.clone()));
+        assert!(!track.add_source(new_source));
+        let new_clip = AkTrackSrcInfo {
+            source_id: 2,
+            play_at: clip.clip_end() - clip.begin_trim_offset + 1000.0,
+            ..clip.clone()
+        };
+        let index = track.add_clip(new_clip.clone()).unwrap();
+        assert!(track.add_clip(new_clip).is_err());
+        track
+            .set_clip_timing(
+                index,
+                clip.play_at + clip.src_duration * 2.0,
+                0.0,
+                0.0,
+                500.0,
+            )
+            .unwrap();
+        assert_eq!(track.playlist()[index].src_duration, 500.0);
+
+        let mut bnk = reread_bank(&bnk);
+        let HircEntryPayload::MusicTrack(track) =
+            &mut bnk.hirc_entry_mut(track_id).unwrap().payload
+        else {
+            panic!();
+        };
+        assert_eq!(track.playlist().len(), 2);
+        assert!(track.remove_source(2).is_some());
+        assert_eq!(track.playlist().len(), 1);
+        assert_eq!(track.retarget_source(1, old_id), Some(1));
+        assert_eq!(track.sources().len(), 1);
+        assert_eq!(track.sources()[0].source_id(), old_id);
+    }
+
+    #[test]
+    fn test_convert_music_track() {
+        let input = fs::read(INPUT_HIRC_2).unwrap();
+        let mut bnk = Bnk::from_file(INPUT_HIRC_2).unwrap();
+        let track_id = hirc_entries_mut(&mut bnk)
+            .iter_mut()
+            .find_map(|entry| match &mut entry.payload {
+                HircEntryPayload::MusicTrack(track)
+                    if track.track_type() == AkMusicTrackType::Normal =>
+                {
+                    track.convert_to(AkMusicTrackType::Switch);
+                    Some(entry.id)
+                }
+                _ => None,
+            })
+            .unwrap();
+
+        let mut bnk = reread_bank(&bnk);
+        let HircEntryPayload::MusicTrack(track) =
+            &mut bnk.hirc_entry_mut(track_id).unwrap().payload
+        else {
+            panic!();
+        };
+        assert_eq!(track.track_type(), AkMusicTrackType::Switch);
+        assert_eq!(track.switch_params().unwrap().switch_ids().count(), 1);
+
+        track.convert_to(AkMusicTrackType::Normal);
+        let mut output = Vec::new();
+        bnk.write_to(&mut std::io::Cursor::new(&mut output))
+            .unwrap();
+        assert_eq!(input, output);
+    }
+
+    #[test]
+    fn test_clip_edit() {
+        let mut bnk = Bnk::from_file(INPUT_HIRC_2).unwrap();
+        let mut tracks: Vec<_> = hirc_entries_mut(&mut bnk)
+            .iter_mut()
+            .filter_map(|entry| match &mut entry.payload {
+                HircEntryPayload::MusicTrack(track) => Some(track),
+                _ => None,
+            })
+            .collect();
+        for track in &tracks {
+            for clip in track.playlist() {
+                assert_eq!(clip.validate(None), Ok(()));
+            }
+        }
+
+        let track = tracks
+            .iter_mut()
+            .find(|track| {
+                let playlist = track.playlist();
+                playlist.len() == 2
+                    && playlist.iter().all(|clip| clip.source_id != 0)
+                    && playlist[0].clip_end() <= playlist[1].clip_start()
+            })
+            .unwrap();
+        let clip = track.playlist()[0].clone();
+        let other = track.playlist()[1].clone();
+        let length = clip.clip_end() - clip.clip_start();
+        assert!(
+            track
+                .set_trim(0, clip.begin_trim_offset + length, clip.end_trim_offset)
+                .is_err()
+        );
+        assert!(track.set_src_duration(0, 0.0, None).is_err());
+        assert!(
+            track
+                .set_src_duration(0, clip.src_duration, Some(clip.src_duration + 10.0))
+                .is_err()
+        );
+        let play_at = other.clip_start() - clip.begin_trim_offset;
+        assert!(track.set_play_at(0, play_at).is_err());
+        assert_eq!(track.playlist()[0], clip);
+
+        track
+            .set_trim(0, clip.begin_trim_offset + 1.0, clip.end_trim_offset)
+            .unwrap();
+        assert_eq!(track.playlist()[0].clip_start(), clip.clip_start() + 1.0);
+
+        let bnk = Bnk::from_file(INPUT_DIDX_DATA).unwrap();
+        let duration = bnk.media_duration(0x598dc).unwrap();
+        assert!((duration - 38811.0 / 48.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,218 @@
+use std::io::{Read, Seek, Write};
+
+use binrw::{BinRead, BinWrite, binrw};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::{Children, EntryPayloadExt, Result, common::NodeBaseParams};
+
+/// Random or sequence container (CAkRanSeqCntr).
+#[binrw]
+#[brw(little)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct HircRanSeqCntr {
+    pub node_base_params: NodeBaseParams,
+    /// Number of plays of the playlist, 0 loops infinitely.
+    pub loop_count: u16,
+    pub loop_mod_min: u16,
+    pub loop_mod_max: u16,
+    /// Transition duration in milliseconds, for continuous containers.
+    pub transition_time: f32,
+    pub transition_time_mod_min: f32,
+    pub transition_time_mod_max: f32,
+    /// Number of played items not repeated by random containers.
+    pub avoid_repeat_count: u16,
+    /// See [AkTransitionMode].
+    pub transition_mode: u8,
+    /// See [AkRandomMode].
+    pub random_mode: u8,
+    /// See [AkContainerMode].
+    pub mode: u8,
+    /// See the `RAN_SEQ_*` flags.
+    pub flags: u8,
+    children: Children,
+    num_playlist_items: u16,
+    #[br(count = num_playlist_items)]
+    playlist: Vec<AkPlaylistItem>,
+}
+
+impl EntryPayloadExt for HircRanSeqCntr {
+    fn from_reader<R>(reader: &mut R, _length: u32) -> Result<Self>
+    where
+        R: Read + Seek,
+    {
+        let container = HircRanSeqCntr::read(reader)?;
+        Ok(container)
+    }
+
+    fn write_to<W>(&self, writer: &mut W) -> Result<()>
+    where
+        W: Write + Seek,
+    {
+        self.write(writer)?;
+        Ok(())
+    }
+
+    fn fix_values(&mut self) -> Result<()> {
+        self.children.num_children = self.children.children.len() as u32;
+        self.num_playlist_items = self.playlist.len() as u16;
+        Ok(())
+    }
+}
+
+/// Weighted random selection.
+pub const RAN_SEQ_USING_WEIGHT: u8 = 1 << 0;
+/// Restart the playlist at each play instead of resuming it.
+pub const RAN_SEQ_RESET_PLAYLIST_AT_EACH_PLAY: u8 = 1 << 1;
+/// Sequences play backward once finished instead of restarting.
+pub const RAN_SEQ_RESTART_BACKWARD: u8 = 1 << 2;
+/// Play the whole playlist at each play instead of a single item.
+pub const RAN_SEQ_CONTINUOUS: u8 = 1 << 3;
+/// Avoid repeats across all game objects.
+pub const RAN_SEQ_GLOBAL: u8 = 1 << 4;
+
+impl HircRanSeqCntr {
+    /// Ids of the child nodes.
+    pub fn children(&self) -> &[u32] {
+        &self.children.children
+    }
+
+    /// Played children in order, a subset of the children.
+    pub fn playlist(&self) -> &[AkPlaylistItem] {
+        &self.playlist
+    }
+
+    pub fn playlist_mut(&mut self) -> &mut [AkPlaylistItem] {
+        &mut self.playlist
+    }
+
+    pub fn mode(&self) -> Option<AkContainerMode> {
+        AkContainerMode::from_repr(self.mode)
+    }
+
+    pub fn random_mode(&self) -> Option<AkRandomMode> {
+        AkRandomMode::from_repr(self.random_mode)
+    }
+
+    pub fn transition_mode(&self) -> Option<AkTransitionMode> {
+        AkTransitionMode::from_repr(self.transition_mode)
+    }
+
+    pub fn has_flag(&self, flag: u8) -> bool {
+        self.flags & flag != 0
+    }
+
+    pub fn set_flag(&mut self, flag: u8, value: bool) {
+        if value {
+            self.flags |= flag;
+        } else {
+            self.flags &= !flag;
+        }
+    }
+
+    /// Add a child played at the end of the playlist, adding it to the
+    /// children if needed. Weights are in thousandths, 50000 being the
+    /// default weight of Wwise.
+    pub fn push_item(&mut self, play_id: u32, weight: i32) {
+        let children = &mut self.children.children;
+        if let Err(idx) = children.binary_search(&play_id) {
+            children.insert(idx, play_id);
+        }
+        self.playlist.push(AkPlaylistItem { play_id, weight });
+    }
+
+    /// Remove a child and its playlist items, returns `false` if absent.
+    pub fn remove_child(&mut self, id: u32) -> bool {
+        let Ok(idx) = self.children.children.binary_search(&id) else {
+            return false;
+        };
+        self.children.children.remove(idx);
+        self.playlist.retain(|item| item.play_id != id);
+        true
+    }
+}
+
+#[binrw]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AkPlaylistItem {
+    pub play_id: u32,
+    /// Random weight in thousandths.
+    pub weight: i32,
+}
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, strum::FromRepr)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum AkContainerMode {
+    Sequence = 0,
+    Random = 1,
+}
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, strum::FromRepr)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum AkRandomMode {
+    Normal = 0,
+    Shuffle = 1,
+}
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, strum::FromRepr)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum AkTransitionMode {
+    Disabled = 0,
+    CrossFadeAmp = 1,
+    CrossFadePower = 2,
+    Delay = 3,
+    SampleAccurate = 4,
+    TriggerRate = 5,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bnk::{
+        Bnk,
+        hirc::{HircEntryPayload, tests::reread},
+        tests::INPUT_HIRC,
+    };
+
+    #[test]
+    fn test_ran_seq_cntr() {
+        let bnk = Bnk::from_file(INPUT_HIRC).unwrap();
+        let mut entry = bnk.hirc_entry(157518257).unwrap().clone();
+        let HircEntryPayload::RandomOrSequenceContainer(container) = &mut entry.payload else {
+            panic!();
+        };
+        assert_eq!(container.children(), &[0x031B8EF7]);
+        assert_eq!(
+            container.playlist(),
+            &[AkPlaylistItem {
+                play_id: 0x031B8EF7,
+                weight: 50000
+            }]
+        );
+        assert_eq!(container.loop_count, 1);
+        assert_eq!(container.transition_time, 1000.0);
+        assert_eq!(container.mode(), Some(AkContainerMode::Sequence));
+        assert!(container.has_flag(RAN_SEQ_GLOBAL));
+        assert!(!container.has_flag(RAN_SEQ_CONTINUOUS));
+
+        container.mode = AkContainerMode::Random as u8;
+        container.set_flag(RAN_SEQ_USING_WEIGHT, true);
+        container.push_item(1, 25000);
+        assert!(container.remove_child(0x031B8EF7));
+        assert!(!container.remove_child(0x031B8EF7));
+
+        let entry = reread(&entry);
+        let HircEntryPayload::RandomOrSequenceContainer(container) = &entry.payload else {
+            panic!();
+        };
+        assert_eq!(container.children(), &[1]);
+        assert_eq!(container.playlist()[0].weight, 25000);
+        assert_eq!(container.mode(), Some(AkContainerMode::Random));
+        assert!(container.has_flag(RAN_SEQ_USING_WEIGHT));
+    }
+}
@@ -0,0 +1,162 @@
+use std::io::{Read, Seek, Write};
+
+use binrw::{BinRead, BinWrite};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::{AkPropId, EntryPayloadExt, Result};
+
+/// Settings entry (CAkState), the property values applied by a state.
+///
+/// Stored as the setting types followed by the values.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct HircSettings {
+    pub settings: Vec<HircSetting>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct HircSetting {
+    /// Property id, see [AkPropId].
+    pub setting_type: u16,
+    pub value: f32,
+}
+
+impl EntryPayloadExt for HircSettings {
+    fn from_reader<R>(reader: &mut R, _length: u32) -> Result<Self>
+    where
+        R: Read + Seek,
+    {
+        let settings = HircSettings::read_le(reader)?;
+        Ok(settings)
+    }
+
+    fn write_to<W>(&self, writer: &mut W) -> Result<()>
+    where
+        W: Write + Seek,
+    {
+        self.write_le(writer)?;
+        Ok(())
+    }
+}
+
+impl HircSettings {
+    pub fn get(&self, id: AkPropId) -> Option<f32> {
+        self.settings
+            .iter()
+            .find(|setting| setting.setting_type == id.as_u8() as u16)
+            .map(|setting| setting.value)
+    }
+
+    /// Set a setting, adding it if missing.
+    pub fn set(&mut self, id: AkPropId, value: f32) {
+        let setting_type = id.as_u8() as u16;
+        match self
+            .settings
+            .iter_mut()
+            .find(|setting| setting.setting_type == setting_type)
+        {
+            Some(setting) => setting.value = value,
+            None => self.settings.push(HircSetting {
+                setting_type,
+                value,
+            }),
+        }
+    }
+
+    /// Remove a setting, returning its value.
+    pub fn remove(&mut self, id: AkPropId) -> Option<f32> {
+        let index = self
+            .settings
+            .iter()
+            .position(|setting| setting.setting_type == id.as_u8() as u16)?;
+        Some(self.settings.remove(index).value)
+    }
+}
+
+impl BinRead for HircSettings {
+    type Args<'a> = ();
+
+    fn read_options<R: std::io::Read + std::io::Seek>(
+        reader: &mut R,
+        endian: binrw::Endian,
+        _args: Self::Args<'_>,
+    ) -> binrw::BinResult<Self> {
+        let num_settings = u16::read_options(reader, endian, ())? as usize;
+        let types = <Vec<u16>>::read_options(reader, endian, binrw::args! { count: num_settings })?;
+        let settings = types
+            .into_iter()
+            .map(|setting_type| {
+                let value = f32::read_options(reader, endian, ())?;
+                Ok(HircSetting {
+                    setting_type,
+                    value,
+                })
+            })
+            .collect::<binrw::BinResult<_>>()?;
+        Ok(HircSettings { settings })
+    }
+}
+
+impl BinWrite for HircSettings {
+    type Args<'a> = ();
+
+    fn write_options<W: std::io::Write + std::io::Seek>(
+        &self,
+        writer: &mut W,
+        endian: binrw::Endian,
+        _args: Self::Args<'_>,
+    ) -> binrw::BinResult<()> {
+        (self.settings.len() as u16).write_options(writer, endian, ())?;
+        for setting in &self.settings {
+            setting.setting_type.write_options(writer, endian, ())?;
+        }
+        for setting in &self.settings {
+            setting.value.write_options(writer, endian, ())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::bnk::{
+        Bnk,
+        hirc::{HircEntryPayload, HircEntryType},
+        tests::{INPUT_HIRC_2, reread_bank},
+    };
+
+    #[test]
+    fn test_settings_entry() {
+        let input = fs::read(INPUT_HIRC_2).unwrap();
+        let mut bnk = Bnk::from_file(INPUT_HIRC_2).unwrap();
+        let id = bnk
+            .hirc_entries()
+            .find(|entry| entry.entry_type == HircEntryType::Settings)
+            .unwrap()
+            .id;
+        let HircEntryPayload::Settings(settings) = &mut bnk.hirc_entry_mut(id).unwrap().payload
+        else {
+            panic!();
+        };
+        assert!(settings.get(AkPropId::Volume).is_some());
+        settings.set(AkPropId::Volume, -6.0);
+        settings.set(AkPropId::Lpf, 30.0);
+        assert_eq!(settings.remove(AkPropId::Hpf), None);
+
+        let mut output = Vec::new();
+        bnk.write_to(&mut std::io::Cursor::new(&mut output))
+            .unwrap();
+        assert_eq!(output.len(), input.len() + 6);
+        let bnk = reread_bank(&bnk);
+        let HircEntryPayload::Settings(settings) = &bnk.hirc_entry(id).unwrap().payload else {
+            panic!();
+        };
+        assert_eq!(settings.get(AkPropId::Volume), Some(-6.0));
+        assert_eq!(settings.get(AkPropId::Lpf), Some(30.0));
+    }
+}
@@ -0,0 +1,110 @@
+use std::{
+    collections::HashMap,
+    sync::{PoisonError, RwLock},
+};
+
+use super::{Bnk, SectionPayload, hirc::HircEntry};
+
+/// Positions of the HIRC entries by id, built on the first lookup.
+#[derive(Debug, Default)]
+pub(super) struct HircIndex(RwLock<Option<IndexData>>);
+
+#[derive(Debug)]
+struct IndexData {
+    /// Number of entries when the index was built.
+    count: usize,
+    /// Section and entry index of the first entry of each id.
+    positions: HashMap<u32, (usize, usize)>,
+}
+
+impl Clone for HircIndex {
+    fn clone(&self) -> Self {
+        HircIndex::default()
+    }
+}
+
+impl Bnk {
+    /// Get a HIRC entry by id.
+    ///
+    /// Lookups use an index rebuilt when the number of entries changes, an
+    /// indexed entry moved or the id is missing from it.
+    pub fn hirc_entry(&self, id: u32) -> Option<&HircEntry> {
+        let (section, index) = self.hirc_position(id)?;
+        self.entry_at(section, index)
+    }
+
+    /// Get a HIRC entry by id, see [Bnk::hirc_entry].
+    pub fn hirc_entry_mut(&mut self, id: u32) -> Option<&mut HircEntry> {
+        let (section, index) = self.hirc_position(id)?;
+        match &mut self.sections[section].payload {
+            SectionPayload::Hirc { entries } => entries.get_mut(index),
+            _ => None,
+        }
+    }
+
+    /// Drop the index of the HIRC entries, rebuilt on the next lookup.
+    pub fn invalidate_hirc_index(&mut self) {
+        *self
+            .hirc_index
+            .0
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner) = None;
+    }
+
+    fn hirc_position(&self, id: u32) -> Option<(usize, usize)> {
+        let count = self
+            .sections
+            .iter()
+            .map(|sec| match &sec.payload {
+                SectionPayload::Hirc { entries } => entries.len(),
+                _ => 0,
+            })
+            .sum();
+        // the second pass uses a fresh index
+        for _ in 0..2 {
+            {
+                let index = self
+                    .hirc_index
+                    .0
+                    .read()
+                    .unwrap_or_else(PoisonError::into_inner);
+                let position = index
+                    .as_ref()
+                    .filter(|data| data.count == count)
+                    .and_then(|data| data.positions.get(&id));
+                if let Some(&(section, index)) = position
+                    && self
+                        .entry_at(section, index)
+                        .is_some_and(|entry| entry.id == id)
+                {
+                    return Some((section, index));
+                }
+            }
+            *self
+                .hirc_index
+                .0
+                .write()
+                .unwrap_or_else(PoisonError::into_inner) = Some(self.build_hirc_index(count));
+        }
+        None
+    }
+
+    fn entry_at(&self, section: usize, index: usize) -> Option<&HircEntry> {
+        match &self.sections.get(section)?.payload {
+            SectionPayload::Hirc { entries } => entries.get(index),
+            _ => None,
+        }
+    }
+
+    fn build_hirc_index(&self, count: usize) -> IndexData {
+        let mut positions = HashMap::with_capacity(count);
+        for (section_index, section) in self.sections.iter().enumerate() {
+            if let SectionPayload::Hirc { entries } = &section.payload {
+                for (index, entry) in entries.iter().enumerate() {
+                    positions.entry(entry.id).or_insert((section_index, index));
+                }
+            }
+        }
+        IndexData { count, positions }
+    }
+}
@@ -0,0 +1,246 @@
+//! JSON Patch (RFC 6902) of the serialized form of banks.
+
+use std::io;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{Bnk, BnkError, Result};
+
+/// Operation of a [JsonPatch], paths are JSON pointers (RFC 6901).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOperation {
+    Add { path: String, value: Value },
+    Remove { path: String },
+    Replace { path: String, value: Value },
+    Move { from: String, path: String },
+    Copy { from: String, path: String },
+    Test { path: String, value: Value },
+}
+
+/// A JSON Patch document, serialized as the array of its operations.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct JsonPatch(pub Vec<PatchOperation>);
+
+impl JsonPatch {
+    /// Compute the operations turning `old` into `new`.
+    ///
+    /// Objects are compared by key and arrays by index, removed array items
+    /// are removed from the end so the indices of the patch stay valid.
+    pub fn diff(old: &Value, new: &Value) -> Self {
+        let mut operations = Vec::new();
+        diff_value(&mut String::new(), old, new, &mut operations);
+        JsonPatch(operations)
+    }
+
+    /// Apply the operations in order. On error `value` is left unchanged.
+    pub fn apply(&self, value: &mut Value) -> Result<()> {
+        let mut patched = value.clone();
+        for operation in &self.0 {
+            apply_operation(&mut patched, operation)?;
+        }
+        *value = patched;
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl Bnk {
+    /// JSON Patch from the serialized form of this bank to the one of `other`.
+    pub fn json_patch(&self, other: &Bnk) -> Result<JsonPatch> {
+        Ok(JsonPatch::diff(&to_value(self)?, &to_value(other)?))
+    }
+
+    /// Apply a JSON Patch to the serialized form of the bank. On error the
+    /// bank is left unchanged.
+    pub fn apply_json_patch(&mut self, patch: &JsonPatch) -> Result<()> {
+        let mut value = to_value(self)?;
+        patch.apply(&mut value)?;
+        let patched: Bnk = serde_json::from_value(value)
+            .map_err(|e| BnkError::Patch(format!("patched bank doesn't deserialize: {e}")))?;
+        self.sections = patched.sections;
+        self.invalidate_hirc_index();
+        Ok(())
+    }
+}
+
+fn to_value(bnk: &Bnk) -> Result<Value> {
+    serde_json::to_value(bnk).map_err(|e| BnkError::IO(io::Error::other(e)))
+}
+
+/// Escape a key as a JSON pointer token.
+fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+fn diff_value(path: &mut String, old: &Value, new: &Value, operations: &mut Vec<PatchOperation>) {
+    if old == new {
+        return;
+    }
+    let len = path.len();
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            for (key, old_value) in old {
+                path.push('/');
+                path.push_str(&escape(key));
+                match new.get(key) {
+                    Some(new_value) => diff_value(path, old_value, new_value, operations),
+                    None => operations.push(PatchOperation::Remove { path: path.clone() }),
+                }
+                path.truncate(len);
+            }
+            for (key, new_value) in new {
+                if !old.contains_key(key) {
+                    operations.push(PatchOperation::Add {
+                        path: format!("{path}/{}", escape(key)),
+                        value: new_value.clone(),
+                    });
+                }
+            }
+        }
+        (Value::Array(old), Value::Array(new)) => {
+            let common = old.len().min(new.len());
+            for i in 0..common {
+                path.push_str(&format!("/{i}"));
+                diff_value(path, &old[i], &new[i], operations);
+                path.truncate(len);
+            }
+            for i in (common..old.len()).rev() {
+                operations.push(PatchOperation::Remove {
+                    path: format!("{path}/{i}"),
+                });
+            }
+            for value in &new[common..] {
+                operations.push(PatchOperation::Add {
+                    path: format!("{path}/-"),
+                    value: value.clone(),
+                });
+            }
+        }
+        _ => operations.push(PatchOperation::Replace {
+            path: path.clone(),
+            value: new.clone(),
+        }),
+    }
+}
+
+/// Split a JSON pointer into its unescaped tokens.
+fn parse_pointer(path: &str) -> Result<Vec<String>> {
+    if path.is_empty() {
+        return Ok(Vec::new());
+    }
+    let Some(tokens) = path.strip_prefix('/') else {
+        return Err(BnkError::Patch(format!("invalid JSON pointer {path:?}")));
+    };
+    Ok(tokens
+        .split('/')
+        .map(|token| token.replace("~1", "/").replace("~0", "~"))
+        .collect())
+}
+
+fn array_index(token: &str, len: usize, path: &str) -> Result<usize> {
+    token
+        .parse::<usize>()
+        .ok()
+        .filter(|index| *index < len && (token == "0" || !token.starts_with('0')))
+        .ok_or_else(|| BnkError::Patch(format!("invalid array index in {path:?}")))
+}
+
+fn get<'a>(value: &'a Value, path: &str) -> Result<&'a Value> {
+    value
+        .pointer(path)
+        .ok_or_else(|| BnkError::Patch(format!("path {path:?} not found")))
+}
+
+fn add(value: &mut Value, path: &str, item: Value) -> Result<()> {
+    let mut tokens = parse_pointer(path)?;
+    let Some(last) = tokens.pop() else {
+        *value = item;
+        return Ok(());
+    };
+    let parent = value
+        .pointer_mut(&parent_pointer(path))
+        .ok_or_else(|| BnkError::Patch(format!("parent of {path:?} not found")))?;
+    match parent {
+        Value::Object(map) => {
+            map.insert(last, item);
+        }
+        Value::Array(items) if last == "-" => items.push(item),
+        Value::Array(items) => {
+            let index = array_index(&last, items.len() + 1, path)?;
+            items.insert(index, item);
+        }
+        _ => {
+            return Err(BnkError::Patch(format!(
+                "parent of {path:?} is not a container"
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn remove(value: &mut Value, path: &str) -> Result<Value> {
+    let mut tokens = parse_pointer(path)?;
+    let last = tokens
+        .pop()
+        .ok_or_else(|| BnkError::Patch("can't remove the root".to_string()))?;
+    let parent = value
+        .pointer_mut(&parent_pointer(path))
+        .ok_or_else(|| BnkError::Patch(format!("parent of {path:?} not found")))?;
+    match parent {
+        Value::Object(map) => map
+            .remove(&last)
+            .ok_or_else(|| BnkError::Patch(format!("path {path:?} not found"))),
+        Value::Array(items) => {
+            let index = array_index(&last, items.len(), path)?;
+            Ok(items.remove(index))
+        }
+        _ => Err(BnkError::Patch(format!("path {path:?} not found"))),
+    }
+}
+
+/// Pointer of the parent of a pointer with at least one token.
+fn parent_pointer(path: &str) -> String {
+    path[..path.rfind('/').unwrap_or(0)].to_string()
+}
+
+fn apply_operation(value: &mut Value, operation: &PatchOperation) -> Result<()> {
+    match operation {
+        PatchOperation::Add { path, value: item } => add(value, path, item.clone()),
+        PatchOperation::Remove { path } => remove(value, path).map(|_| ()),
+        PatchOperation::Replace { path, value: item } => {
+            let target = value
+                .pointer_mut(path)
+                .ok_or_else(|| BnkError::Patch(format!("path {path:?} not found")))?;
+            *target = item.clone();
+            Ok(())
+        }
+        PatchOperation::Move { from, path } => {
+            if path.starts_with(&format!("{from}/")) {
+                return Err(BnkError::Patch(format!(
+                    "can't move {from:?} into its child {path:?}"
+                )));
+            }
+            let item = remove(value, from)?;
+            add(value, path, item)
+        }
+        PatchOperation::Copy { from, path } => {
+            let item = get(value, from)?.clone();
+            add(value, path, item)
+        }
+        PatchOperation::Test {
+            path,
+            value: expected,
+        } => {
+            if get(value, path)? != expected {
+                return Err(BnkError::Patch(format!("test of {path:?} failed")));
+            }
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use std::{
+    fs::File,
+    io::{self, Read},
+    path::Path,
+};
+
+use super::{
+    Bnk, BnkError, BnkParseOptions, DidxEntry, MediaProvider, Result, Section, parse::ParseState,
+};
+
+/// Position of a DATA section skipped while reading.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct DataLocation {
+    /// Index of the section in the bank.
+    pub index: usize,
+    /// Start of the section data in the reader.
+    pub offset: u64,
+    pub length: u32,
+}
+
+/// Bank whose embedded media is read on demand.
+///
+/// Every section but DATA is parsed, media is read from the reader when
+/// requested, so large banks can be inspected without loading their media.
+pub struct LazyBnk<R> {
+    reader: R,
+    bnk: Bnk,
+    data: Option<DataLocation>,
+}
+
+impl LazyBnk<io::BufReader<File>> {
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let file = File::open(path)?;
+        Self::from_reader(io::BufReader::new(file))
+    }
+}
+
+impl<R> LazyBnk<R>
+where
+    R: io::Read + io::Seek,
+{
+    pub fn from_reader(mut reader: R) -> Result<Self> {
+        let mut data = None;
+        let options = BnkParseOptions::default();
+        let bnk = Bnk::read_sections(
+            &mut reader,
+            false,
+            &mut ParseState::new(&options),
+            Some(&mut data),
+        )?;
+        Ok(LazyBnk { reader, bnk, data })
+    }
+
+    /// Sections of the bank, without the DATA section.
+    pub fn bnk(&self) -> &Bnk {
+        &self.bnk
+    }
+
+    /// Entries of the DIDX section, the embedded media.
+    pub fn media_entries(&self) -> &[DidxEntry] {
+        self.bnk.didx().unwrap_or_default()
+    }
+
+    /// Read embedded media by id, `None` if it is not in the bank.
+    pub fn read_media(&mut self, id: u32) -> Result<Option<Vec<u8>>> {
+        let mut data = Vec::new();
+        Ok(self.copy_media(id, &mut data)?.map(|_| data))
+    }
+
+    /// Stream embedded media by id into `sink`, returns the number of bytes
+    /// copied or `None` if it is not in the bank.
+    pub fn copy_media<W>(&mut self, id: u32, sink: &mut W) -> Result<Option<u64>>
+    where
+        W: io::Write + ?Sized,
+    {
+        let Some(entry) = self.media_entries().iter().find(|entry| entry.id == id) else {
+            return Ok(None);
+        };
+        let (offset, length) = (entry.offset as u64, entry.length as u64);
+        // placeholders don't need the DATA section
+        if length == 0 {
+            return Ok(Some(0));
+        }
+        let Some(data) = self.data else {
+            return Ok(None);
+        };
+        // nothing is written for media past the end of the DATA section
+        if offset + length > data.length as u64 {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+        self.reader
+            .seek(io::SeekFrom::Start(data.offset + offset))?;
+        let copied = io::copy(&mut (&mut self.reader).take(length), sink)?;
+        if copied != length {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+        Ok(Some(copied))
+    }
+
+    /// Read the media, returns the whole bank.
+    pub fn into_bnk(mut self) -> Result<Bnk> {
+        let Some(data) = self.data else {
+            return Ok(self.bnk);
+        };
+        self.reader.seek(io::SeekFrom::Start(data.offset - 4))?;
+        let didx_entries = self.media_entries().to_vec();
+        let section = Section::read_data(&mut self.reader, &didx_entries)?;
+        self.bnk.sections.insert(data.index, section);
+        self.bnk.invalidate_hirc_index();
+        Ok(self.bnk)
+    }
+}
+
+impl<R> MediaProvider for LazyBnk<R>
+where
+    R: io::Read + io::Seek,
+{
+    fn media(&mut self, id: u32) -> io::Result<Option<Vec<u8>>> {
+        self.read_media(id).map_err(|e| match e {
+            BnkError::IO(e) => e,
+            e => io::Error::other(e),
+        })
+    }
+}
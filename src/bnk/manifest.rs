@@ -0,0 +1,141 @@
+use std::path::{Path, PathBuf};
+
+use byteorder::{LE, WriteBytesExt};
+use serde::Deserialize;
+
+use super::{
+    AkBankSourceStreamType, Bnk, BnkError, MediaFolder, Result, Section, SectionPayload,
+    hirc::HircEntry,
+};
+use crate::hash;
+
+/// Declarative description of a bank, see [Bnk::from_manifest].
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BnkManifest {
+    pub bank: BkhdSettings,
+    /// Folder of `<id>.wem` files embedded for the in-memory sources of the
+    /// objects, relative to the manifest.
+    pub media: Option<PathBuf>,
+    /// HIRC objects, in the serialized form of [HircEntry].
+    #[serde(default)]
+    pub objects: Vec<HircEntry>,
+}
+
+/// Settings of the BKHD section.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BkhdSettings {
+    /// Bank name, the bank id is its ShortID unless `id` is set.
+    pub name: Option<String>,
+    pub id: Option<u32>,
+    #[serde(default = "BkhdSettings::default_version")]
+    pub version: u32,
+    /// Language name, hashed like Wwise does.
+    #[serde(default = "BkhdSettings::default_language")]
+    pub language: String,
+    /// Alignment of the embedded media.
+    #[serde(default = "BkhdSettings::default_alignment")]
+    pub alignment: u16,
+    #[serde(default)]
+    pub project_id: u32,
+}
+
+impl BkhdSettings {
+    fn default_version() -> u32 {
+        145
+    }
+
+    fn default_language() -> String {
+        "SFX".to_string()
+    }
+
+    fn default_alignment() -> u16 {
+        16
+    }
+
+    fn to_section(&self) -> Result<Section> {
+        let id = match (self.id, &self.name) {
+            (Some(id), _) => id,
+            (None, Some(name)) => hash::short_id(name),
+            (None, None) => {
+                return Err(BnkError::Manifest(
+                    "the bank needs a name or an id".to_string(),
+                ));
+            }
+        };
+        // language, alignment, device allocated, project id, bank type and
+        // bank hash
+        let mut unknown = Vec::with_capacity(32);
+        unknown.write_u32::<LE>(hash::short_id(&self.language))?;
+        unknown.write_u16::<LE>(self.alignment)?;
+        unknown.write_u16::<LE>(0)?;
+        unknown.write_u32::<LE>(self.project_id)?;
+        unknown.write_u32::<LE>(0)?;
+        unknown.extend([0; 16]);
+        Ok(Section {
+            magic: *b"BKHD",
+            section_length: 0,
+            payload: SectionPayload::Bkhd {
+                version: self.version,
+                id,
+                unknown,
+            },
+        })
+    }
+}
+
+impl BnkManifest {
+    /// Assemble the bank, media paths are relative to `base_dir`.
+    pub fn build(&self, base_dir: &Path) -> Result<Bnk> {
+        let mut bnk = Bnk::from_sections(vec![
+            self.bank.to_section()?,
+            Section {
+                magic: *b"HIRC",
+                section_length: 0,
+                payload: SectionPayload::Hirc {
+                    entries: self.objects.clone(),
+                },
+            },
+        ]);
+
+        if let Some(media) = &self.media {
+            bnk.materialize_media(&mut MediaFolder::new(base_dir.join(media)))?;
+        }
+        let missing: Vec<u32> = bnk
+            .media_usage()
+            .into_iter()
+            .filter(|(id, usage)| {
+                usage.stream_type == AkBankSourceStreamType::DataBnk && bnk.media(*id).is_none()
+            })
+            .map(|(id, _)| id)
+            .collect();
+        if !missing.is_empty() {
+            return Err(BnkError::Manifest(format!(
+                "media not found for in-memory sources: {missing:?}"
+            )));
+        }
+        Ok(bnk)
+    }
+}
+
+impl Bnk {
+    /// Build a bank from a manifest file, JSON or TOML with the `.toml`
+    /// extension:
+    ///
+    /// ```json
+    /// {
+    ///     "bank": { "name": "Wp00_Custom", "language": "SFX" },
+    ///     "media": "wem",
+    ///     "objects": [{ "entry_type": "Sound", "id": 1001, "type": "Sound", ... }]
+    /// }
+    /// ```
+    ///
+    /// Media of in-memory sources is embedded from the `media` folder, a
+    /// source without media fails the build.
+    pub fn from_manifest(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let manifest: BnkManifest = crate::utils::read_manifest(path)?;
+        manifest.build(path.parent().unwrap_or(Path::new("")))
+    }
+}
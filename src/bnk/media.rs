@@ -0,0 +1,373 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::wem::{self, PrefetchLength, WemInfo};
+
+use super::{AkBankSourceStreamType, Bnk, BnkError, DidxEntry, Result, Section, SectionPayload};
+
+/// How embedded media is removed by [Bnk::strip_media].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StripMediaMode {
+    /// Remove the DATA section, keep DIDX entries as they are.
+    KeepDidx,
+    /// Keep DIDX and DATA, but empty every media entry.
+    Placeholder,
+    /// Remove both DIDX and DATA sections.
+    RemoveAll,
+}
+
+/// How a media source referenced by HIRC is stored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MediaUsage {
+    pub stream_type: AkBankSourceStreamType,
+    /// Size of the in-memory part, the whole file for embedded media or the
+    /// prefetch chunk for prefetched media.
+    pub in_memory_size: u32,
+    /// Ids of the HIRC objects referencing the source.
+    pub objects: Vec<u32>,
+}
+
+/// Source of media data by id, see [Bnk::materialize_media].
+pub trait MediaProvider {
+    /// Data of a media file, `None` if the provider doesn't have it.
+    fn media(&mut self, id: u32) -> io::Result<Option<Vec<u8>>>;
+}
+
+impl MediaProvider for HashMap<u32, Vec<u8>> {
+    fn media(&mut self, id: u32) -> io::Result<Option<Vec<u8>>> {
+        Ok(self.get(&id).cloned())
+    }
+}
+
+/// Loose media files named `<id>.wem` in a directory.
+#[derive(Debug, Clone)]
+pub struct MediaFolder {
+    pub path: PathBuf,
+}
+
+impl MediaFolder {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        MediaFolder { path: path.into() }
+    }
+}
+
+impl MediaProvider for MediaFolder {
+    fn media(&mut self, id: u32) -> io::Result<Option<Vec<u8>>> {
+        match fs::read(self.path.join(format!("{id}.wem"))) {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl Bnk {
+    /// Get embedded media by id.
+    pub fn media(&self, id: u32) -> Option<&[u8]> {
+        self.wems()
+            .find(|(media_id, _)| *media_id == id)
+            .map(|(_, data)| data)
+    }
+
+    /// Iterate over the embedded media in DIDX order.
+    ///
+    /// Zero-length entries, placeholders for media loaded from elsewhere, are
+    /// included as empty slices, even in banks without a DATA section.
+    pub fn wems(&self) -> impl Iterator<Item = (u32, &[u8])> {
+        let entries = self.didx();
+        let data_list = self.data();
+        entries
+            .into_iter()
+            .flatten()
+            .enumerate()
+            .filter_map(move |(i, entry)| {
+                let data = data_list.and_then(|list| list.get(i)).map(Vec::as_slice);
+                match data {
+                    Some(data) => Some((entry.id, data)),
+                    None if entry.length == 0 => Some((entry.id, &[][..])),
+                    None => None,
+                }
+            })
+    }
+
+    /// Ids of the embedded media in DIDX order, placeholders included.
+    pub fn wem_ids(&self) -> impl Iterator<Item = u32> {
+        self.wems().map(|(id, _)| id)
+    }
+
+    /// Same as [Bnk::media], named after [Bnk::wems].
+    pub fn wem_by_id(&self, id: u32) -> Option<&[u8]> {
+        self.media(id)
+    }
+
+    /// Write the embedded media to `<dir>/<id>.wem`, the layout read by
+    /// [MediaFolder], returns the number of files written.
+    ///
+    /// Zero-length placeholders are skipped.
+    pub fn extract_all(&self, dir: impl AsRef<Path>) -> Result<usize> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+        let mut count = 0;
+        for (id, data) in self.wems().filter(|(_, data)| !data.is_empty()) {
+            fs::write(dir.join(format!("{id}.wem")), data)?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Duration in milliseconds of embedded media, `None` if the media is not
+    /// embedded or its header can not be read.
+    pub fn media_duration(&self, id: u32) -> Option<f64> {
+        WemInfo::from_bytes(self.media(id)?).ok()?.duration_ms()
+    }
+
+    /// List every media source referenced by HIRC and where it is stored.
+    ///
+    /// Sources with an unknown stream type are skipped.
+    pub fn media_usage(&self) -> BTreeMap<u32, MediaUsage> {
+        let mut usage: BTreeMap<u32, MediaUsage> = BTreeMap::new();
+        let entries = self.sections.iter().filter_map(|sec| match &sec.payload {
+            SectionPayload::Hirc { entries } => Some(entries),
+            _ => None,
+        });
+        for entry in entries.flatten() {
+            for source in entry.payload.sources() {
+                let Some(stream_type) = source.stream_type() else {
+                    continue;
+                };
+                usage
+                    .entry(source.source_id())
+                    .or_insert_with(|| MediaUsage {
+                        stream_type,
+                        in_memory_size: source.media_information.in_memory_media_size,
+                        objects: Vec::new(),
+                    })
+                    .objects
+                    .push(entry.id);
+            }
+        }
+        usage
+    }
+
+    /// Embed media, replacing the existing media of the same id.
+    ///
+    /// DIDX and DATA sections are created after BKHD if missing, entries are
+    /// kept sorted by id. Media missing from DATA, e.g. after
+    /// [Bnk::strip_media] kept the DIDX, is filled with empty data. Returns
+    /// the replaced data.
+    pub fn insert_media(&mut self, id: u32, data: Vec<u8>) -> Option<Vec<u8>> {
+        let mut insert_at = self
+            .sections
+            .iter()
+            .position(|s| &s.magic == b"BKHD")
+            .map_or(0, |i| i + 1);
+        for (magic, payload) in [
+            (b"DIDX", SectionPayload::Didx { entries: vec![] }),
+            (b"DATA", SectionPayload::Data { data_list: vec![] }),
+        ] {
+            match self.sections.iter().position(|s| &s.magic == magic) {
+                Some(i) => insert_at = i + 1,
+                None => {
+                    self.sections.insert(
+                        insert_at,
+                        Section {
+                            magic: *magic,
+                            section_length: 0,
+                            payload,
+                        },
+                    );
+                    insert_at += 1;
+                }
+            }
+        }
+
+        let entries = self.didx_mut()?;
+        let count = entries.len();
+        let i = entries.partition_point(|entry| entry.id < id);
+        let exists = entries.get(i).is_some_and(|entry| entry.id == id);
+        if !exists {
+            entries.insert(
+                i,
+                DidxEntry {
+                    id,
+                    offset: 0,
+                    length: data.len() as u32,
+                },
+            );
+        }
+
+        let data_list = self.data_mut()?;
+        data_list.resize(count, Vec::new());
+        if exists {
+            return Some(std::mem::replace(&mut data_list[i], data));
+        }
+        data_list.insert(i, data);
+        None
+    }
+
+    /// Replace existing embedded media, returns the previous data.
+    pub fn replace_wem(&mut self, id: u32, data: Vec<u8>) -> Result<Vec<u8>> {
+        if self.media(id).is_none() {
+            return Err(BnkError::ObjectNotFound(id));
+        }
+        Ok(self.insert_media(id, data).unwrap_or_default())
+    }
+
+    /// Embed new media, see [Bnk::insert_media]. Fails if the id is already
+    /// embedded.
+    pub fn add_wem(&mut self, id: u32, data: Vec<u8>) -> Result<()> {
+        if self.wem_ids().any(|media_id| media_id == id) {
+            return Err(BnkError::IdCollision(id));
+        }
+        self.insert_media(id, data);
+        Ok(())
+    }
+
+    /// Remove embedded media and its DIDX entry, returns the removed data,
+    /// empty for placeholders, or `None` if the id is not embedded.
+    pub fn remove_wem(&mut self, id: u32) -> Option<Vec<u8>> {
+        let mut index = None;
+        for section in &mut self.sections {
+            match &mut section.payload {
+                SectionPayload::Didx { entries } => {
+                    let i = entries.iter().position(|entry| entry.id == id)?;
+                    entries.remove(i);
+                    index = Some(i);
+                }
+                SectionPayload::Data { data_list } => {
+                    if let Some(i) = index.filter(|&i| i < data_list.len()) {
+                        return Some(data_list.remove(i));
+                    }
+                }
+                _ => {}
+            }
+        }
+        index.map(|_| vec![])
+    }
+
+    /// Embed the media of in-memory sources lacking a DIDX entry, taking the
+    /// data from `provider`.
+    ///
+    /// The in-memory size of the sources is updated to the media size.
+    /// Returns the ids of the added media, media the provider doesn't have is
+    /// left missing, see [Bnk::validate].
+    pub fn materialize_media<P>(&mut self, provider: &mut P) -> Result<Vec<u32>>
+    where
+        P: MediaProvider + ?Sized,
+    {
+        let missing: Vec<u32> = self
+            .media_usage()
+            .into_iter()
+            .filter(|(id, usage)| {
+                usage.stream_type == AkBankSourceStreamType::DataBnk && self.media(*id).is_none()
+            })
+            .map(|(id, _)| id)
+            .collect();
+
+        let mut added = Vec::new();
+        for id in missing {
+            let Some(data) = provider.media(id)? else {
+                continue;
+            };
+            let size = data.len() as u32;
+            self.insert_media(id, data);
+            for section in &mut self.sections {
+                let SectionPayload::Hirc { entries } = &mut section.payload else {
+                    continue;
+                };
+                for source in entries
+                    .iter_mut()
+                    .flat_map(|entry| entry.payload.sources_mut())
+                    .filter(|source| source.source_id() == id)
+                {
+                    source.media_information.in_memory_media_size = size;
+                }
+            }
+            added.push(id);
+        }
+        Ok(added)
+    }
+
+    /// Embed the start of a streamed wem as prefetch data, for sources to
+    /// start playing while the rest is streamed from the package.
+    ///
+    /// Sources using the media are switched to prefetch streaming. Returns the
+    /// size of the prefetch data.
+    pub fn install_prefetch(
+        &mut self,
+        source_id: u32,
+        wem: &[u8],
+        length: PrefetchLength,
+    ) -> Result<u32> {
+        if !self.media_usage().contains_key(&source_id) {
+            return Err(BnkError::ObjectNotFound(source_id));
+        }
+        let chunk = wem::prefetch_chunk(wem, length)?;
+        let size = chunk.len() as u32;
+        self.insert_media(source_id, chunk);
+        for section in &mut self.sections {
+            let SectionPayload::Hirc { entries } = &mut section.payload else {
+                continue;
+            };
+            for source in entries
+                .iter_mut()
+                .flat_map(|entry| entry.payload.sources_mut())
+                .filter(|source| source.source_id() == source_id)
+            {
+                source.stream_type = AkBankSourceStreamType::PrefetchStreaming as u8;
+                source.media_information.in_memory_media_size = size;
+            }
+        }
+        Ok(size)
+    }
+
+    /// Remove embedded media to produce a header-only bank.
+    ///
+    /// Returns the removed media as `(id, data)` pairs in DIDX order, so it can be
+    /// moved into a companion package.
+    pub fn strip_media(&mut self, mode: StripMediaMode) -> Vec<(u32, Vec<u8>)> {
+        let ids: Vec<u32> = self
+            .didx()
+            .unwrap_or_default()
+            .iter()
+            .map(|e| e.id)
+            .collect();
+
+        let mut removed = Vec::new();
+        for section in &mut self.sections {
+            match &mut section.payload {
+                SectionPayload::Didx { entries } if mode == StripMediaMode::Placeholder => {
+                    for entry in entries.iter_mut() {
+                        entry.offset = 0;
+                        entry.length = 0;
+                    }
+                }
+                SectionPayload::Data { data_list } => {
+                    removed.extend(
+                        data_list
+                            .iter_mut()
+                            .map(std::mem::take)
+                            .enumerate()
+                            .map(|(i, data)| (ids.get(i).copied().unwrap_or_default(), data)),
+                    );
+                }
+                _ => {}
+            }
+        }
+
+        self.sections.retain(|sec| match &sec.payload {
+            SectionPayload::Data { .. } => mode == StripMediaMode::Placeholder,
+            SectionPayload::Didx { .. } => mode != StripMediaMode::RemoveAll,
+            _ => true,
+        });
+
+        removed
+    }
+}
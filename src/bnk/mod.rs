@@ -1,64 +1,315 @@
+mod attenuation;
+#[cfg(feature = "serde")]
+mod blob;
+mod borrowed;
+mod bus;
+mod coverage;
+#[cfg(feature = "json")]
+mod diff;
+mod event;
+mod fingerprint;
+mod game_sync;
+mod graph;
 pub mod hirc;
+mod index;
+#[cfg(feature = "json")]
+mod json_patch;
+mod lazy;
+#[cfg(feature = "manifest")]
+mod manifest;
+mod media;
+mod parse;
+mod preserve;
+#[cfg(feature = "json")]
+mod project;
+mod props;
+mod rtpc;
+mod stid;
+mod stmg;
+mod transaction;
+mod validate;
+mod view;
+
+#[cfg(feature = "json")]
+pub use blob::from_json_with_blob_dir;
+#[cfg(feature = "serde")]
+pub use blob::{BlobEncoded, BlobEncoding};
+pub use borrowed::*;
+pub use bus::*;
+pub use coverage::*;
+#[cfg(feature = "json")]
+pub use diff::*;
+pub use event::*;
+pub use fingerprint::*;
+pub use game_sync::*;
+pub use graph::*;
+use index::HircIndex;
+#[cfg(feature = "json")]
+pub use json_patch::*;
+pub use lazy::*;
+#[cfg(feature = "manifest")]
+pub use manifest::*;
+pub use media::*;
+use parse::ParseState;
+pub use parse::*;
+pub(crate) use preserve::OriginalBytes;
+pub use props::*;
+pub use rtpc::*;
+pub use stid::*;
+pub use stmg::*;
+pub use transaction::*;
+pub use validate::*;
+pub use view::*;
 
 use std::io;
 
-use byteorder::{ReadBytesExt, WriteBytesExt, LE};
+use binrw::{BinRead, BinWrite};
+use byteorder::{LE, ReadBytesExt, WriteBytesExt};
 
 use hirc::*;
 
+use crate::{hash, rwext::ReadVecExt};
+
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 type Result<T> = std::result::Result<T, BnkError>;
 
+/// Default alignment of the media in DATA sections, as written by Wwise.
+pub const DATA_ALIGNMENT: u32 = 16;
+
 #[derive(Debug, thiserror::Error)]
 pub enum BnkError {
     #[error("IO error: {0}")]
     IO(#[from] std::io::Error),
     #[error("Binrw error: {0}")]
     Binrw(#[from] binrw::Error),
+    #[error("Wem error: {0}")]
+    Wem(#[from] crate::wem::WemError),
 
     #[error("Accessing DATA section before DIDX section.")]
     MissingDidx,
+    #[error("Missing BKHD section.")]
+    MissingBkhd,
+    #[error("Object not found: {0}")]
+    ObjectNotFound(u32),
+    #[error("Object id already in use: {0}")]
+    IdCollision(u32),
+    #[error("Validation failed: {0:?}")]
+    Validation(Vec<ValidationIssue>),
+    #[error("Invalid music clip {index}: {reason}")]
+    InvalidClip { index: usize, reason: String },
+    #[error("Invalid manifest: {0}")]
+    Manifest(String),
+    #[error("JSON Patch failed: {0}")]
+    Patch(String),
+    #[error("Can't replace music: {0}")]
+    ReplaceMusic(String),
+    #[error("Invalid decision tree path: expected {expected} keys, got {got}")]
+    InvalidDecisionPath { expected: usize, got: usize },
     #[error("Unknown HIRC entry type at offset {0}: {0}")]
     UnknownHircEntryType(u64, u8),
     #[error("Unknown SoundType at offset {0}: {0}")]
     UnknownSoundType(u64, u8),
     #[error("Unknown EventActionScope at offset {0}: {0}")]
     UnknownEventActionScope(u64, u8),
+    /// Section longer than [BnkParseOptions::max_section_size] or the rest
+    /// of the stream.
+    #[error("Section {magic} of {length} bytes exceeds the limit of {max} bytes")]
+    SectionTooLarge {
+        magic: String,
+        length: u32,
+        max: u64,
+    },
+    #[error("HIRC section of {count} entries exceeds the limit of {max} entries")]
+    TooManyEntries { count: u32, max: u32 },
+    #[error("Event {id} has {count} actions, at most {max} can be written")]
+    TooManyActions { id: u32, count: usize, max: usize },
     #[error(
         "Incorrect data size for {name}: expected {expected}, got {got}. Section start: {start}"
     )]
     BadDataSize {
         name: String,
+        /// Type and id of the malformed HIRC entry.
+        entry: Option<(HircEntryType, u32)>,
         expected: u64,
         got: u64,
         start: u64,
     },
 }
 
+impl BnkError {
+    /// Type and id of the HIRC entry the error occurred in, if known.
+    pub fn hirc_entry(&self) -> Option<(HircEntryType, u32)> {
+        match self {
+            BnkError::BadDataSize { entry, .. } => *entry,
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Bnk {
     pub sections: Vec<Section>,
+    /// Bytes the bank was read from, see [Bnk::from_reader_preserving].
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) original: Option<Box<OriginalBytes>>,
+    /// See [Bnk::set_data_alignment].
+    #[cfg_attr(feature = "serde", serde(skip, default = "default_data_alignment"))]
+    pub(crate) data_alignment: u32,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    hirc_index: HircIndex,
+}
+
+#[cfg(feature = "serde")]
+fn default_data_alignment() -> u32 {
+    DATA_ALIGNMENT
 }
 
 impl Bnk {
+    /// Bank made of `sections`, written without preserving original bytes.
+    pub fn from_sections(sections: Vec<Section>) -> Self {
+        Bnk {
+            sections,
+            original: None,
+            data_alignment: DATA_ALIGNMENT,
+            hirc_index: HircIndex::default(),
+        }
+    }
+
+    /// Empty bank with BKHD, DIDX, DATA and HIRC sections.
+    ///
+    /// The remaining BKHD fields get the values Wwise writes for `version`:
+    /// the SFX language, the default media alignment, and a zero project id,
+    /// bank type and hash.
+    pub fn new(version: u32, bank_id: u32) -> Self {
+        let section = |magic: &[u8; 4], payload| Section {
+            magic: *magic,
+            section_length: 0,
+            payload,
+        };
+        Bnk::from_sections(vec![
+            section(
+                b"BKHD",
+                SectionPayload::Bkhd {
+                    version,
+                    id: bank_id,
+                    unknown: default_bkhd_fields(version),
+                },
+            ),
+            section(b"DIDX", SectionPayload::Didx { entries: vec![] }),
+            section(b"DATA", SectionPayload::Data { data_list: vec![] }),
+            section(b"HIRC", SectionPayload::Hirc { entries: vec![] }),
+        ])
+    }
+
     pub fn from_reader<R>(reader: &mut R) -> Result<Self>
+    where
+        R: io::Read + io::Seek,
+    {
+        Ok(Self::from_reader_with(reader, &BnkParseOptions::default())?.0)
+    }
+
+    pub fn from_file(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        let mut reader = io::BufReader::new(std::fs::File::open(path)?);
+        Self::from_reader(&mut reader)
+    }
+
+    /// Write the bank to a file, replacing it only once fully written.
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> Result<()> {
+        crate::utils::write_file_atomic(path.as_ref(), |writer| self.write_to(writer))
+    }
+
+    /// Read a single bank starting at `offset`, stopping at the end of the bank
+    /// instead of the end of the stream.
+    pub fn from_reader_at<R>(reader: &mut R, offset: u64) -> Result<Self>
+    where
+        R: io::Read + io::Seek,
+    {
+        reader.seek(io::SeekFrom::Start(offset))?;
+        let options = BnkParseOptions::default();
+        Self::read_sections(reader, true, &mut ParseState::new(&options), None)
+    }
+
+    /// Read the next bank from a stream of concatenated banks.
+    ///
+    /// The reader is left at the start of the following bank, `None` is returned
+    /// at the end of the stream.
+    pub fn read_next<R>(reader: &mut R) -> Result<Option<Self>>
+    where
+        R: io::Read + io::Seek,
+    {
+        let options = BnkParseOptions::default();
+        let bnk = Self::read_sections(reader, true, &mut ParseState::new(&options), None)?;
+        Ok((!bnk.sections.is_empty()).then_some(bnk))
+    }
+
+    /// Read the sections of a bank, skipping the DATA section if
+    /// `data_location` is given and storing where it is instead.
+    fn read_sections<R>(
+        reader: &mut R,
+        single_bank: bool,
+        state: &mut ParseState,
+        mut data_location: Option<&mut Option<DataLocation>>,
+    ) -> Result<Self>
     where
         R: io::Read + io::Seek,
     {
         let mut sections = Vec::new();
+        // bank version, some sections are laid out differently across versions
+        let mut version = 0;
+        // lengths are checked against the stream before allocating, corrupt
+        // ones would request huge buffers
+        let start = reader.stream_position()?;
+        let stream_length = reader.seek(io::SeekFrom::End(0))?;
+        reader.seek(io::SeekFrom::Start(start))?;
         loop {
             let mut magic = [0u8; 4];
-            if let Err(e) = reader.read_exact(&mut magic) {
-                if e.kind() == io::ErrorKind::UnexpectedEof {
-                    break;
-                }
-            };
+            match reader.read_exact(&mut magic) {
+                Ok(()) => {}
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+                Err(e) => return Err(e.into()),
+            }
+            // a new BKHD starts the next bank
+            if single_bank && &magic == b"BKHD" && !sections.is_empty() {
+                reader.seek(io::SeekFrom::Current(-4))?;
+                break;
+            }
+            let length = reader.read_u32::<LE>()?;
+            let data_start = reader.stream_position()?;
+            reader.seek(io::SeekFrom::Current(-4))?;
+            state.diagnose(Diagnostic {
+                magic,
+                entry: None,
+                offset: data_start - 8,
+                length,
+            });
+            let max = state
+                .options
+                .max_section_size
+                .map_or(u64::MAX, u64::from)
+                .min(stream_length.saturating_sub(data_start));
+            if length as u64 > max {
+                return Err(BnkError::SectionTooLarge {
+                    magic: String::from_utf8_lossy(&magic).into_owned(),
+                    length,
+                    max,
+                });
+            }
             // handle data section separately
             let section = if &magic == b"DATA" {
-                let total_length = reader.read_u32::<LE>()?;
+                if let Some(location) = data_location.as_deref_mut() {
+                    let length = reader.read_u32::<LE>()?;
+                    let offset = reader.stream_position()?;
+                    *location = Some(DataLocation {
+                        index: sections.len(),
+                        offset,
+                        length,
+                    });
+                    reader.seek(io::SeekFrom::Start(offset + length as u64))?;
+                    continue;
+                }
                 let didx_entries = sections
                     .iter()
                     .find_map(|sec: &Section| {
@@ -69,100 +320,295 @@ impl Bnk {
                         }
                     })
                     .ok_or(BnkError::MissingDidx)?;
-                let data_start_pos = reader.stream_position()?;
-                let mut data_list = Vec::with_capacity(didx_entries.len());
-                for entry in didx_entries {
-                    let mut data = vec![0; entry.length as usize];
-                    reader.seek(io::SeekFrom::Start(data_start_pos + entry.offset as u64))?;
-                    reader.read_exact(&mut data)?;
-                    data_list.push(data);
-                }
-                reader.seek(io::SeekFrom::Start(data_start_pos + total_length as u64))?;
-                Section {
-                    magic,
-                    section_length: total_length,
-                    payload: SectionPayload::Data { data_list },
-                }
+                Section::read_data(reader, didx_entries)?
             } else {
-                Section::from_reader(reader, magic)?
+                Section::from_reader(reader, magic, version, state)?
             };
+            // a length not matching the parsed section would leave the reader
+            // inside this bank or the next one
+            let pos = reader.stream_position()?;
+            if pos != data_start + length as u64 {
+                return Err(BnkError::BadDataSize {
+                    name: String::from_utf8_lossy(&magic).into_owned(),
+                    entry: None,
+                    expected: length as u64,
+                    got: pos - data_start,
+                    start: data_start,
+                });
+            }
+            if let SectionPayload::Bkhd { version: v, .. } = &section.payload {
+                version = *v;
+            }
             sections.push(section);
         }
-        Ok(Bnk { sections })
+        Ok(Bnk::from_sections(sections))
     }
 
-    pub fn write_to<W>(&mut self, writer: &mut W) -> Result<()>
+    /// Write the bank, counts, lengths and DIDX offsets are computed from
+    /// the data without modifying the bank, see [Bnk::fix_values].
+    pub fn write_to<W>(&self, writer: &mut W) -> Result<()>
     where
         W: io::Write + io::Seek,
     {
-        // fix values
-        self.fix_values()?;
+        if let Some(original) = &self.original {
+            return self.write_with_original(writer, original);
+        }
 
-        let mut didx_entries: Option<&[DidxEntry]> = None;
+        let didx_entries = self.fixed_didx_entries()?;
+        for section in &self.sections {
+            section.write_to(writer, didx_entries.as_deref())?;
+        }
+        Ok(())
+    }
 
-        for section in &mut self.sections {
-            writer.write_all(&section.magic)?;
-            // fix section length
-            writer.write_u32::<LE>(0)?;
-            let start_pos = writer.stream_position()?;
+    /// Iterate over the entries of all HIRC sections.
+    pub fn hirc_entries(&self) -> impl Iterator<Item = &HircEntry> {
+        self.sections
+            .iter()
+            .filter_map(|sec| match &sec.payload {
+                SectionPayload::Hirc { entries } => Some(entries),
+                _ => None,
+            })
+            .flatten()
+    }
 
-            match &mut section.payload {
+    /// First section with the given magic, e.g. `b"HIRC"`.
+    pub fn section_by_magic(&self, magic: &[u8; 4]) -> Option<&Section> {
+        self.sections.iter().find(|sec| &sec.magic == magic)
+    }
+
+    /// Fields of the BKHD section.
+    pub fn bkhd(&self) -> Option<Bkhd<'_>> {
+        self.sections.iter().find_map(|sec| match &sec.payload {
+            SectionPayload::Bkhd {
+                version,
+                id,
+                unknown,
+            } => Some(Bkhd {
+                version: *version,
+                id: *id,
+                unknown,
+            }),
+            _ => None,
+        })
+    }
+
+    pub fn bkhd_mut(&mut self) -> Option<BkhdMut<'_>> {
+        self.sections
+            .iter_mut()
+            .find_map(|sec| match &mut sec.payload {
                 SectionPayload::Bkhd {
                     version,
                     id,
                     unknown,
-                } => {
-                    writer.write_u32::<LE>(*version)?;
-                    writer.write_u32::<LE>(*id)?;
-                    writer.write_all(unknown)?;
-                }
-                SectionPayload::Didx { entries } => {
-                    didx_entries.replace(entries);
-                    for entry in entries.iter() {
-                        let entry_bytes: [u8; 12] = unsafe { std::mem::transmute(entry.clone()) };
-                        writer.write_all(&entry_bytes)?;
-                    }
-                }
+                } => Some(BkhdMut {
+                    version,
+                    id,
+                    unknown,
+                }),
+                _ => None,
+            })
+    }
+
+    /// Entries of the DIDX section.
+    pub fn didx(&self) -> Option<&[DidxEntry]> {
+        self.sections.iter().find_map(|sec| match &sec.payload {
+            SectionPayload::Didx { entries } => Some(entries.as_slice()),
+            _ => None,
+        })
+    }
+
+    pub fn didx_mut(&mut self) -> Option<&mut Vec<DidxEntry>> {
+        self.sections
+            .iter_mut()
+            .find_map(|sec| match &mut sec.payload {
+                SectionPayload::Didx { entries } => Some(entries),
+                _ => None,
+            })
+    }
+
+    /// Entries of the first HIRC section, see [Bnk::hirc_entries] for all
+    /// of them.
+    pub fn hirc(&self) -> Option<&[HircEntry]> {
+        self.sections.iter().find_map(|sec| match &sec.payload {
+            SectionPayload::Hirc { entries } => Some(entries.as_slice()),
+            _ => None,
+        })
+    }
+
+    /// Entries of the first HIRC section, the index of [Bnk::hirc_entry] is
+    /// invalidated.
+    pub fn hirc_mut(&mut self) -> Option<&mut Vec<HircEntry>> {
+        self.invalidate_hirc_index();
+        self.sections
+            .iter_mut()
+            .find_map(|sec| match &mut sec.payload {
+                SectionPayload::Hirc { entries } => Some(entries),
+                _ => None,
+            })
+    }
+
+    /// Media of the DATA section, in DIDX order.
+    pub fn data(&self) -> Option<&[Vec<u8>]> {
+        self.sections.iter().find_map(|sec| match &sec.payload {
+            SectionPayload::Data { data_list } => Some(data_list.as_slice()),
+            _ => None,
+        })
+    }
+
+    pub fn data_mut(&mut self) -> Option<&mut Vec<Vec<u8>>> {
+        self.sections
+            .iter_mut()
+            .find_map(|sec| match &mut sec.payload {
+                SectionPayload::Data { data_list } => Some(data_list),
+                _ => None,
+            })
+    }
+
+    /// Get the bank id from the BKHD section.
+    pub fn bank_id(&self) -> Option<u32> {
+        self.bkhd().map(|bkhd| bkhd.id)
+    }
+
+    /// Language of the bank from the BKHD section, the ShortID of the
+    /// language name (`SFX` for banks without localized content).
+    pub fn language_id(&self) -> Option<u32> {
+        let unknown = self.bkhd()?.unknown;
+        Some(u32::from_le_bytes(unknown.get(..4)?.try_into().unwrap()))
+    }
+
+    pub fn set_language_id(&mut self, language_id: u32) -> Result<()> {
+        for section in &mut self.sections {
+            if let SectionPayload::Bkhd { unknown, .. } = &mut section.payload
+                && let Some(bytes) = unknown.get_mut(..4)
+            {
+                bytes.copy_from_slice(&language_id.to_le_bytes());
+                return Ok(());
+            }
+        }
+        Err(BnkError::MissingBkhd)
+    }
+
+    /// Copy of the bank with its media for another language, e.g. `French(France)`.
+    pub fn clone_for_language(&self, language: &str) -> Result<Bnk> {
+        let mut bnk = self.clone();
+        bnk.set_language_id(hash::short_id(language))?;
+        Ok(bnk)
+    }
+
+    /// Get the id to name pairs stored in STID sections.
+    pub fn stid_names(&self) -> Result<Vec<(u32, String)>> {
+        Ok(self
+            .string_tables()
+            .flat_map(|table| &table.entries)
+            .map(|entry| (entry.id, entry.name.clone()))
+            .collect())
+    }
+
+    /// Name of a bank in the STID sections.
+    pub fn stid_name(&self, id: u32) -> Option<&str> {
+        self.string_tables().find_map(|table| table.name(id))
+    }
+
+    fn string_tables(&self) -> impl Iterator<Item = &StringTable> {
+        self.sections
+            .iter()
+            .filter_map(|section| match &section.payload {
+                SectionPayload::Stid { table } => Some(table),
+                _ => None,
+            })
+    }
+
+    /// Alignment of the media offsets in the DATA section.
+    pub fn data_alignment(&self) -> u32 {
+        self.data_alignment
+    }
+
+    /// Set the alignment of the media offsets in the DATA section, applied
+    /// when the offsets are recomputed on write. Defaults to
+    /// [DATA_ALIGNMENT], 1 packs the media.
+    pub fn set_data_alignment(&mut self, alignment: u32) {
+        self.data_alignment = alignment.max(1);
+    }
+
+    /// Check whether the BKHD id matches the ShortID of a bank name.
+    ///
+    /// `name` is the bank name without extensions, e.g. `Wp00_Cmn`.
+    pub fn check_id(&self, name: &str) -> bool {
+        self.bank_id() == Some(hash::short_id(name))
+    }
+
+    /// Set the BKHD id to the ShortID of a new bank name, returning the new id.
+    ///
+    /// If `update_references` is set, Play actions loading this bank and the
+    /// STID entry of this bank are rewritten as well.
+    pub fn rebase(&mut self, name: &str, update_references: bool) -> Result<u32> {
+        let new_id = hash::short_id(name);
+        let mut old_id = None;
+        for section in &mut self.sections {
+            if let SectionPayload::Bkhd { id, .. } = &mut section.payload {
+                old_id = Some(*id);
+                *id = new_id;
+                break;
+            }
+        }
+        let old_id = old_id.ok_or(BnkError::MissingBkhd)?;
+        if !update_references || old_id == new_id {
+            return Ok(new_id);
+        }
+
+        for section in &mut self.sections {
+            match &mut section.payload {
                 SectionPayload::Hirc { entries } => {
-                    writer.write_u32::<LE>(entries.len() as u32)?;
                     for entry in entries.iter_mut() {
-                        entry.write_to(writer)?;
+                        let HircEntryPayload::EventAction(action) = &mut entry.payload else {
+                            continue;
+                        };
+                        if let Some(bank_id) = action.play_bank_id_mut()
+                            && u32::from_le_bytes(*bank_id) == old_id
+                        {
+                            *bank_id = new_id.to_le_bytes();
+                        }
                     }
                 }
-                SectionPayload::Data { data_list } => {
-                    let Some(didx_entries) = didx_entries else {
-                        return Err(BnkError::MissingDidx);
-                    };
-                    let data_start_pos = writer.stream_position()?;
-                    for (i, data) in data_list.iter().enumerate() {
-                        let entry = &didx_entries[i];
-                        writer.seek(io::SeekFrom::Start(data_start_pos + entry.offset as u64))?;
-                        writer.write_all(data)?;
-                        // Unimplemented feature: 16字节对齐 padding
-                    }
+                SectionPayload::Stid { table } => table.rebase(old_id, new_id, name),
+                _ => {}
+            }
+        }
+
+        Ok(new_id)
+    }
+
+    /// Bring the stored counts, lengths and DIDX offsets in line with the
+    /// data, as done on write.
+    pub fn fix_values(&mut self) -> Result<()> {
+        if let Some(fixed) = self.fixed_didx_entries()? {
+            for section in &mut self.sections {
+                if let SectionPayload::Didx { entries } = &mut section.payload {
+                    *entries = fixed;
+                    break;
                 }
-                SectionPayload::Unk { data } => {
-                    writer.write_all(data)?;
+            }
+        }
+        for section in &mut self.sections {
+            if let SectionPayload::Hirc { entries } = &mut section.payload {
+                for entry in entries.iter_mut() {
+                    entry.fix_values()?;
                 }
             }
-
-            let end_pos = writer.stream_position()?;
-            // write section length
-            let length = (end_pos - start_pos) as u32;
-            writer.seek(io::SeekFrom::Start(start_pos - 4))?;
-            writer.write_u32::<LE>(length)?;
-            writer.seek(io::SeekFrom::Start(end_pos))?;
         }
         Ok(())
     }
 
-    fn fix_values(&mut self) -> Result<()> {
+    /// DIDX entries with the offsets and lengths of the DATA layout, `None`
+    /// without DIDX or DATA section.
+    fn fixed_didx_entries(&self) -> Result<Option<Vec<DidxEntry>>> {
         // 查找 DIDX 和 DATA 部分
         let mut didx_section = None;
         let mut data_section = None;
 
-        for section in &mut self.sections {
-            match &mut section.payload {
+        for section in &self.sections {
+            match &section.payload {
                 SectionPayload::Didx { entries } => {
                     didx_section = Some(entries);
                 }
@@ -176,13 +622,14 @@ impl Bnk {
         // 确保找到了 DIDX 和 DATA 部分
         let (didx_entries, data_list) = match (didx_section, data_section) {
             (Some(didx), Some(data)) => (didx, data),
-            _ => return Ok(()), // 如果没有 DIDX 或 DATA 部分，直接返回
+            _ => return Ok(None), // 如果没有 DIDX 或 DATA 部分，直接返回
         };
 
         // 检查 DIDX 条目数量是否与 DATA 列表数量匹配
         if didx_entries.len() != data_list.len() {
             return Err(BnkError::BadDataSize {
                 name: "DIDX entries".to_string(),
+                entry: None,
                 expected: data_list.len() as u64,
                 got: didx_entries.len() as u64,
                 start: 0,
@@ -190,17 +637,24 @@ impl Bnk {
         }
 
         // 修复偏移和长度值
+        let mut didx_entries = didx_entries.clone();
         let mut current_offset = 0u32;
         for (didx_entry, data) in didx_entries.iter_mut().zip(data_list.iter()) {
             // 更新长度
             didx_entry.length = data.len() as u32;
+            // zero-length entries keep their offset inside the data written
+            // so far, e.g. 0 for placeholders
+            if data.is_empty() && didx_entry.offset <= current_offset {
+                continue;
+            }
             // 更新偏移
+            current_offset = current_offset.next_multiple_of(self.data_alignment);
             didx_entry.offset = current_offset;
             // 计算下一个偏移（当前偏移 + 当前长度）
             current_offset += didx_entry.length;
         }
 
-        Ok(())
+        Ok(Some(didx_entries))
     }
 }
 
@@ -214,43 +668,129 @@ pub struct Section {
 }
 
 impl Section {
-    fn from_reader<R>(reader: &mut R, magic: [u8; 4]) -> Result<Self>
+    /// Read a DATA section after its magic.
+    fn read_data<R>(reader: &mut R, didx_entries: &[DidxEntry]) -> Result<Self>
+    where
+        R: io::Read + io::Seek,
+    {
+        let total_length = reader.read_u32::<LE>()?;
+        let data_start_pos = reader.stream_position()?;
+        let mut data_list = Vec::with_capacity(didx_entries.len());
+        for entry in didx_entries {
+            // offsets of zero-length placeholders may lie anywhere
+            if entry.length != 0 && entry.offset as u64 + entry.length as u64 > total_length as u64
+            {
+                return Err(BnkError::BadDataSize {
+                    name: format!("media {}", entry.id),
+                    entry: None,
+                    expected: entry.length as u64,
+                    got: (total_length as u64).saturating_sub(entry.offset as u64),
+                    start: data_start_pos + entry.offset as u64,
+                });
+            }
+            let mut data = vec![0; entry.length as usize];
+            if entry.length != 0 {
+                reader.seek(io::SeekFrom::Start(data_start_pos + entry.offset as u64))?;
+                reader.read_exact(&mut data)?;
+            }
+            data_list.push(data);
+        }
+        reader.seek(io::SeekFrom::Start(data_start_pos + total_length as u64))?;
+        Ok(Section {
+            magic: *b"DATA",
+            section_length: total_length,
+            payload: SectionPayload::Data { data_list },
+        })
+    }
+
+    fn from_reader<R>(
+        reader: &mut R,
+        magic: [u8; 4],
+        version: u32,
+        state: &mut ParseState,
+    ) -> Result<Self>
     where
         R: io::Read + io::Seek,
     {
         let section_length = reader.read_u32::<LE>()?;
         let payload = match &magic {
-            b"BKHD" => SectionPayload::Bkhd {
-                version: reader.read_u32::<LE>()?,
-                id: reader.read_u32::<LE>()?,
-                unknown: {
-                    let mut unknown = vec![0; section_length as usize - 8];
-                    reader.read_exact(&mut unknown)?;
-                    unknown
-                },
-            },
-            b"DIDX" => {
-                let entry_count = (section_length as usize) / size_of::<DidxEntry>();
-                let mut entries = Vec::with_capacity(entry_count);
-                for _ in 0..entry_count {
-                    let mut buf = [0; size_of::<DidxEntry>()];
-                    reader.read_exact(&mut buf)?;
-                    entries.push(unsafe { std::mem::transmute::<[u8; 12], DidxEntry>(buf) });
+            b"BKHD" => {
+                let Some(size) = (section_length as usize).checked_sub(8) else {
+                    return Err(BnkError::BadDataSize {
+                        name: "BKHD".to_string(),
+                        entry: None,
+                        expected: 8,
+                        got: section_length as u64,
+                        start: reader.stream_position()?,
+                    });
+                };
+                SectionPayload::Bkhd {
+                    version: reader.read_u32::<LE>()?,
+                    id: reader.read_u32::<LE>()?,
+                    unknown: reader.read_vec_u8(size)?,
                 }
+            }
+            b"DIDX" => {
+                let entry_count = (section_length as usize) / DidxEntry::SIZE;
+                let entries = reader.read_vec_fn(entry_count, DidxEntry::from_reader)?;
                 SectionPayload::Didx { entries }
             }
             b"HIRC" => {
+                state.section_end = Some(reader.stream_position()? + section_length as u64);
                 let count = reader.read_u32::<LE>()?;
-                let mut entries = Vec::with_capacity(count as usize);
+                if let Some(max) = state.options.max_hirc_entries
+                    && count > max
+                {
+                    return Err(BnkError::TooManyEntries { count, max });
+                }
+                // entries take at least 9 bytes
+                let mut entries =
+                    Vec::with_capacity((count as usize).min(section_length as usize / 9));
                 for _ in 0..count {
                     let entry_type = reader.read_u8()?;
                     let entry_type = HircEntryType::from_repr(entry_type)
                         .unwrap_or(HircEntryType::Unknown(entry_type));
-                    let hirc_entry = HircEntry::from_reader(reader, entry_type)?;
+                    if state.has_diagnostics() {
+                        let length = reader.read_u32::<LE>()?;
+                        let id = reader.read_u32::<LE>()?;
+                        reader.seek(io::SeekFrom::Current(-8))?;
+                        state.diagnose(Diagnostic {
+                            magic,
+                            entry: Some((entry_type, id)),
+                            offset: reader.stream_position()? - 1,
+                            length,
+                        });
+                    }
+                    let hirc_entry = HircEntry::read(reader, entry_type, version, state)?;
                     entries.push(hirc_entry);
                 }
+                state.section_end = None;
                 SectionPayload::Hirc { entries }
             }
+            b"STMG" => {
+                let mut data = vec![0; section_length as usize];
+                reader.read_exact(&mut data)?;
+                // fall back to raw data for layouts not handled yet
+                match GlobalSettings::read_args(
+                    &mut io::Cursor::new(&data),
+                    binrw::args! { version },
+                ) {
+                    Ok(settings) => SectionPayload::Stmg { settings },
+                    Err(_) => SectionPayload::Unk { data },
+                }
+            }
+            b"STID" => {
+                let mut data = vec![0; section_length as usize];
+                reader.read_exact(&mut data)?;
+                // names that are not UTF-8 are kept raw
+                let mut cursor = io::Cursor::new(&data);
+                match StringTable::read(&mut cursor) {
+                    Ok(table) if cursor.position() == data.len() as u64 => {
+                        SectionPayload::Stid { table }
+                    }
+                    _ => SectionPayload::Unk { data },
+                }
+            }
             b"DATA" => {
                 unreachable!("DATA section should be handled separately.");
             }
@@ -267,6 +807,104 @@ impl Section {
             payload,
         })
     }
+
+    /// Write the section, `didx_entries` are the fixed entries of the DIDX
+    /// section, written in place of the stored ones and locating the media
+    /// of DATA sections.
+    fn write_to<W>(&self, writer: &mut W, didx_entries: Option<&[DidxEntry]>) -> Result<()>
+    where
+        W: io::Write + io::Seek,
+    {
+        writer.write_all(&self.magic)?;
+        // fix section length
+        writer.write_u32::<LE>(0)?;
+        let start_pos = writer.stream_position()?;
+
+        match &self.payload {
+            SectionPayload::Bkhd {
+                version,
+                id,
+                unknown,
+            } => {
+                writer.write_u32::<LE>(*version)?;
+                writer.write_u32::<LE>(*id)?;
+                writer.write_all(unknown)?;
+            }
+            SectionPayload::Didx { entries } => {
+                for entry in didx_entries.unwrap_or(entries) {
+                    entry.write_to(writer)?;
+                }
+            }
+            SectionPayload::Hirc { entries } => {
+                writer.write_u32::<LE>(entries.len() as u32)?;
+                for entry in entries {
+                    entry.write_to(writer)?;
+                }
+            }
+            SectionPayload::Stmg { settings } => {
+                settings.write(writer)?;
+            }
+            SectionPayload::Stid { table } => {
+                table.write(writer)?;
+            }
+            SectionPayload::Data { data_list } => {
+                let Some(didx_entries) = didx_entries else {
+                    return Err(BnkError::MissingDidx);
+                };
+                let data_start_pos = writer.stream_position()?;
+                let mut data_end = 0;
+                for (i, data) in data_list.iter().enumerate() {
+                    let offset = didx_entries[i].offset as u64;
+                    if offset > data_end {
+                        // alignment padding
+                        writer.seek(io::SeekFrom::Start(data_start_pos + data_end))?;
+                        writer.write_all(&vec![0; (offset - data_end) as usize])?;
+                    } else {
+                        writer.seek(io::SeekFrom::Start(data_start_pos + offset))?;
+                    }
+                    writer.write_all(data)?;
+                    data_end = data_end.max(offset + data.len() as u64);
+                }
+                // a zero-length entry may come last with an earlier offset
+                writer.seek(io::SeekFrom::Start(data_start_pos + data_end))?;
+            }
+            SectionPayload::Unk { data } => {
+                writer.write_all(data)?;
+            }
+        }
+
+        let end_pos = writer.stream_position()?;
+        // write section length
+        let length = (end_pos - start_pos) as u32;
+        writer.seek(io::SeekFrom::Start(start_pos - 4))?;
+        writer.write_u32::<LE>(length)?;
+        writer.seek(io::SeekFrom::Start(end_pos))?;
+        Ok(())
+    }
+}
+
+/// BKHD fields after the bank id, see [Bnk::new].
+fn default_bkhd_fields(version: u32) -> Vec<u8> {
+    let mut fields = Vec::new();
+    // languages were indexes before they were hashed, SFX being 0
+    let language_id = if version <= 122 {
+        0
+    } else {
+        hash::short_id("SFX")
+    };
+    fields.extend(language_id.to_le_bytes());
+    // feedback flag, replaced by the alignment and device allocation flag
+    let alt_values = if version <= 126 { 0 } else { DATA_ALIGNMENT };
+    fields.extend(alt_values.to_le_bytes());
+    if version > 76 {
+        // project id
+        fields.extend(0u32.to_le_bytes());
+    }
+    if version > 140 {
+        // bank type and hash
+        fields.extend([0; 20]);
+    }
+    fields
 }
 
 #[derive(Debug, Clone)]
@@ -276,6 +914,7 @@ pub enum SectionPayload {
     Bkhd {
         version: u32,
         id: u32,
+        #[cfg_attr(feature = "serde", serde(with = "blob"))]
         unknown: Vec<u8>,
     },
     Didx {
@@ -284,15 +923,38 @@ pub enum SectionPayload {
     Hirc {
         entries: Vec<HircEntry>,
     },
+    Stmg {
+        settings: GlobalSettings,
+    },
+    Stid {
+        table: StringTable,
+    },
     Data {
+        #[cfg_attr(feature = "serde", serde(with = "blob::list"))]
         data_list: Vec<Vec<u8>>,
     },
     Unk {
+        #[cfg_attr(feature = "serde", serde(with = "blob"))]
         data: Vec<u8>,
     },
 }
 
-#[repr(C)]
+/// Fields of a BKHD section, see [Bnk::bkhd].
+#[derive(Debug, Clone, Copy)]
+pub struct Bkhd<'a> {
+    pub version: u32,
+    pub id: u32,
+    pub unknown: &'a [u8],
+}
+
+/// Fields of a BKHD section, see [Bnk::bkhd_mut].
+#[derive(Debug)]
+pub struct BkhdMut<'a> {
+    pub version: &'a mut u32,
+    pub id: &'a mut u32,
+    pub unknown: &'a mut Vec<u8>,
+}
+
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct DidxEntry {
@@ -301,26 +963,70 @@ pub struct DidxEntry {
     pub length: u32,
 }
 
+impl DidxEntry {
+    /// Size of an entry in the DIDX section.
+    pub const SIZE: usize = 12;
+
+    fn from_reader<R>(reader: &mut R) -> io::Result<Self>
+    where
+        R: io::Read,
+    {
+        Ok(DidxEntry {
+            id: reader.read_u32::<LE>()?,
+            offset: reader.read_u32::<LE>()?,
+            length: reader.read_u32::<LE>()?,
+        })
+    }
+
+    fn write_to<W>(&self, writer: &mut W) -> io::Result<()>
+    where
+        W: io::Write,
+    {
+        writer.write_u32::<LE>(self.id)?;
+        writer.write_u32::<LE>(self.offset)?;
+        writer.write_u32::<LE>(self.length)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::{
+        borrow::Cow,
+        collections::HashMap,
         fs::{self, File},
         io::{self, Seek},
     };
 
-    use crate::rwext::ReadVecExt;
-
     use super::*;
+    use crate::{
+        rwext::ReadVecExt,
+        wem::{self, MediaProblem, PrefetchLength, WemInfo},
+    };
+
+    pub(super) const INPUT_HIRC: &str = "test_files/Wp00_Cmn.sbnk.1.X64";
+    pub(super) const INPUT_HIRC_2: &str = "test_files/bgm_resident_ev.sbnk.1.X64";
+    pub(super) const INPUT_DIDX_DATA: &str = "test_files/Wp00_Cmn_m.sbnk.1.X64";
+
+    /// Write the bank and read it back.
+    pub(super) fn reread_bank(bnk: &Bnk) -> Bnk {
+        let mut output = Vec::new();
+        bnk.write_to(&mut io::Cursor::new(&mut output)).unwrap();
+        Bnk::from_reader(&mut io::Cursor::new(&output)).unwrap()
+    }
 
-    const INPUT_HIRC: &str = "test_files/Wp00_Cmn.sbnk.1.X64";
-    const INPUT_HIRC_2: &str = "test_files/bgm_resident_ev.sbnk.1.X64";
-    const INPUT_DIDX_DATA: &str = "test_files/Wp00_Cmn_m.sbnk.1.X64";
+    /// Entries of the HIRC section of the test banks.
+    pub(super) fn hirc_entries_mut(bnk: &mut Bnk) -> &mut Vec<HircEntry> {
+        let SectionPayload::Hirc { entries } = &mut bnk.sections[1].payload else {
+            panic!();
+        };
+        entries
+    }
 
     #[test]
     fn test_hirc() {
         let input = fs::read(INPUT_HIRC).unwrap();
         let mut reader = io::Cursor::new(&input);
-        let mut bnk = Bnk::from_reader(&mut reader).unwrap();
+        let bnk = Bnk::from_reader(&mut reader).unwrap();
         assert_eq!(&bnk.sections[0].magic, b"BKHD");
 
         let mut output = Vec::new();
@@ -333,7 +1039,7 @@ mod tests {
     fn test_hirc_2() {
         let input = fs::read(INPUT_HIRC_2).unwrap();
         let mut reader = io::Cursor::new(&input);
-        let mut bnk = Bnk::from_reader(&mut reader).unwrap();
+        let bnk = Bnk::from_reader(&mut reader).unwrap();
 
         let mut output = Vec::new();
         let mut writer = io::Cursor::new(&mut output);
@@ -345,7 +1051,7 @@ mod tests {
     fn test_didx_data() {
         let input = fs::read(INPUT_DIDX_DATA).unwrap();
         let mut reader = io::Cursor::new(&input);
-        let mut bnk = Bnk::from_reader(&mut reader).unwrap();
+        let bnk = Bnk::from_reader(&mut reader).unwrap();
 
         let mut output = Vec::new();
         let mut writer = io::Cursor::new(&mut output);
@@ -353,6 +1059,1821 @@ mod tests {
         assert!(input == output);
     }
 
+    #[test]
+    fn test_data_alignment() {
+        let input = fs::read(INPUT_DIDX_DATA).unwrap();
+        let mut bnk = Bnk::from_reader(&mut io::Cursor::new(&input)).unwrap();
+        let didx = |bnk: &Bnk| -> Vec<DidxEntry> {
+            bnk.sections
+                .iter()
+                .find_map(|sec| match &sec.payload {
+                    SectionPayload::Didx { entries } => Some(entries.clone()),
+                    _ => None,
+                })
+                .unwrap()
+        };
+
+        // a larger media moves the following ones to the next aligned offsets
+        let id = didx(&bnk)[0].id;
+        let media = vec![0xAB; bnk.media(id).unwrap().len() + 100];
+        bnk.insert_media(id, media.clone());
+        let mut output = Vec::new();
+        bnk.write_to(&mut io::Cursor::new(&mut output)).unwrap();
+        let bnk = Bnk::from_reader(&mut io::Cursor::new(&output)).unwrap();
+        assert_eq!(bnk.media(id).unwrap(), &media);
+        let entries = didx(&bnk);
+        assert!(
+            entries
+                .iter()
+                .all(|entry| entry.offset % DATA_ALIGNMENT == 0)
+        );
+        assert!(entries[1].offset >= media.len() as u32);
+
+        let mut bnk = bnk;
+        bnk.set_data_alignment(1);
+        let mut packed = Vec::new();
+        bnk.write_to(&mut io::Cursor::new(&mut packed)).unwrap();
+        let packed = Bnk::from_reader(&mut io::Cursor::new(&packed)).unwrap();
+        let entries = didx(&packed);
+        assert_eq!(entries[1].offset, entries[0].length);
+        assert_eq!(packed.media(id).unwrap(), &media);
+    }
+
+    #[test]
+    fn test_lazy_bnk() {
+        let input = fs::read(INPUT_DIDX_DATA).unwrap();
+        let bnk = Bnk::from_reader(&mut io::Cursor::new(&input)).unwrap();
+        let mut lazy = LazyBnk::from_reader(io::Cursor::new(&input)).unwrap();
+        assert_eq!(lazy.bnk().sections.len(), bnk.sections.len() - 1);
+        assert!(lazy.bnk().wems().next().is_none());
+        assert_eq!(lazy.media_entries().len(), bnk.wems().count());
+        for (id, data) in bnk.wems() {
+            assert_eq!(lazy.read_media(id).unwrap().unwrap(), data);
+        }
+        assert!(lazy.read_media(0).unwrap().is_none());
+
+        let mut output = Vec::new();
+        lazy.into_bnk()
+            .unwrap()
+            .write_to(&mut io::Cursor::new(&mut output))
+            .unwrap();
+        assert!(input == output);
+
+        // media past the end of DATA is not copied
+        let mut input = input;
+        let didx = input.windows(4).position(|magic| magic == b"DIDX").unwrap();
+        let didx_length = u32::from_le_bytes(input[didx + 4..didx + 8].try_into().unwrap());
+        let length = didx + 8 + didx_length as usize - 4;
+        let last_length = u32::from_le_bytes(input[length..length + 4].try_into().unwrap());
+        input[length..length + 4].copy_from_slice(&(last_length + 16).to_le_bytes());
+        let mut lazy = LazyBnk::from_reader(io::Cursor::new(&input)).unwrap();
+        let id = lazy.media_entries().last().unwrap().id;
+        let mut sink = Vec::new();
+        assert!(lazy.copy_media(id, &mut sink).is_err());
+        assert!(sink.is_empty());
+    }
+
+    #[test]
+    fn test_from_slice() {
+        for path in [INPUT_HIRC, INPUT_DIDX_DATA] {
+            let input = fs::read(path).unwrap();
+            let bnk = Bnk::from_reader(&mut io::Cursor::new(&input)).unwrap();
+            let bnk_ref = Bnk::from_slice(&input).unwrap();
+            assert_eq!(bnk_ref.sections.len(), bnk.sections.len());
+            assert_eq!(bnk_ref.hirc_entries().count(), bnk.hirc_entries().count());
+            for (id, data) in bnk.wems() {
+                let media = bnk_ref.media(id).unwrap();
+                assert_eq!(media, data);
+                // borrowed from the input
+                assert!(input.as_ptr_range().contains(&media.as_ptr()));
+            }
+
+            let mut output = Vec::new();
+            bnk_ref
+                .to_bnk()
+                .write_to(&mut io::Cursor::new(&mut output))
+                .unwrap();
+            assert!(input == output);
+        }
+    }
+
+    #[test]
+    fn test_write_shared() {
+        let input = fs::read(INPUT_DIDX_DATA).unwrap();
+        let mut bnk = Bnk::from_reader(&mut io::Cursor::new(&input)).unwrap();
+        let outputs: Vec<Vec<u8>> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..2)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut output = Vec::new();
+                        bnk.write_to(&mut io::Cursor::new(&mut output)).unwrap();
+                        output
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        assert!(outputs.iter().all(|output| *output == input));
+
+        // writing doesn't fix the stored values
+        let didx_len = |bnk: &Bnk| {
+            bnk.sections
+                .iter()
+                .find_map(|sec| match &sec.payload {
+                    SectionPayload::Didx { entries } => Some(entries[0].length),
+                    _ => None,
+                })
+                .unwrap()
+        };
+        let id = bnk.wems().next().unwrap().0;
+        bnk.insert_media(id, vec![0; 4]);
+        let mut output = Vec::new();
+        bnk.write_to(&mut io::Cursor::new(&mut output)).unwrap();
+        assert_ne!(didx_len(&bnk), 4);
+        bnk.fix_values().unwrap();
+        assert_eq!(didx_len(&bnk), 4);
+        let written = Bnk::from_reader(&mut io::Cursor::new(&output)).unwrap();
+        assert_eq!(written.media(id).unwrap(), &[0; 4]);
+    }
+
+    #[test]
+    fn test_save() {
+        let dir = std::env::temp_dir().join(format!("re-sound-bnk-save-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("Wp00_Cmn_m.bnk");
+        let bnk = Bnk::from_file(INPUT_DIDX_DATA).unwrap();
+        bnk.save(&path).unwrap();
+        // replaces the existing file
+        bnk.save(&path).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), fs::read(INPUT_DIDX_DATA).unwrap());
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        assert!(Bnk::from_file(&path).is_ok());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_extract_all() {
+        let bnk = Bnk::from_file(INPUT_DIDX_DATA).unwrap();
+        let ids: Vec<u32> = bnk.wem_ids().collect();
+        assert_eq!(ids.len(), 77);
+        assert_eq!(bnk.wem_by_id(ids[0]), bnk.media(ids[0]));
+        assert_eq!(bnk.wem_by_id(0), None);
+
+        let dir = std::env::temp_dir().join(format!("re-sound-bnk-extract-{}", std::process::id()));
+        assert_eq!(bnk.extract_all(&dir).unwrap(), ids.len());
+        let mut folder = MediaFolder::new(&dir);
+        for id in ids {
+            assert_eq!(folder.media(id).unwrap().as_deref(), bnk.wem_by_id(id));
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_wem_edit() {
+        let mut bnk = Bnk::from_file(INPUT_DIDX_DATA).unwrap();
+        let ids: Vec<u32> = bnk.wem_ids().collect();
+        let original = bnk.media(ids[1]).unwrap().to_vec();
+
+        assert_eq!(bnk.replace_wem(ids[1], vec![1; 100]).unwrap(), original);
+        assert!(matches!(
+            bnk.replace_wem(1, vec![]),
+            Err(BnkError::ObjectNotFound(1))
+        ));
+        assert!(matches!(
+            bnk.add_wem(ids[0], vec![]),
+            Err(BnkError::IdCollision(_))
+        ));
+        bnk.add_wem(1, vec![2; 10]).unwrap();
+        assert_eq!(bnk.remove_wem(ids[2]).unwrap().len(), 9258);
+        assert_eq!(bnk.remove_wem(ids[2]), None);
+
+        let mut output = Vec::new();
+        bnk.write_to(&mut io::Cursor::new(&mut output)).unwrap();
+        let bnk = Bnk::from_reader(&mut io::Cursor::new(&output)).unwrap();
+        assert_eq!(bnk.wem_ids().count(), ids.len());
+        assert_eq!(bnk.wem_ids().next(), Some(1));
+        assert_eq!(bnk.media(1).unwrap(), &[2; 10]);
+        assert_eq!(bnk.media(ids[1]).unwrap(), &[1; 100]);
+        assert_eq!(bnk.media(ids[2]), None);
+        assert_eq!(bnk.media(ids[3]).unwrap().len(), 16489);
+
+        // sections are created for the first media
+        let mut bnk = Bnk::from_file(INPUT_HIRC).unwrap();
+        bnk.add_wem(1, vec![3; 10]).unwrap();
+        let magics: Vec<_> = bnk.sections.iter().map(|sec| &sec.magic).collect();
+        assert_eq!(magics[1..3], [b"DIDX", b"DATA"]);
+        assert_eq!(bnk.remove_wem(1).unwrap(), vec![3; 10]);
+        assert_eq!(bnk.wem_ids().count(), 0);
+    }
+
+    #[test]
+    fn test_hirc_entry() {
+        let mut bnk = Bnk::from_file(INPUT_HIRC_2).unwrap();
+        for entry in bnk.hirc_entries().step_by(50) {
+            assert_eq!(bnk.hirc_entry(entry.id).unwrap().id, entry.id);
+        }
+        assert!(bnk.hirc_entry(0).is_none());
+
+        // removed and moved entries
+        let first = bnk.hirc_entries().next().unwrap().clone();
+        let second_id = bnk.hirc_entries().nth(1).unwrap().id;
+        let SectionPayload::Hirc { entries } = &mut bnk.sections[1].payload else {
+            panic!("not a HIRC section");
+        };
+        entries.remove(0);
+        assert!(bnk.hirc_entry(first.id).is_none());
+        assert_eq!(bnk.hirc_entry(second_id).unwrap().id, second_id);
+
+        let SectionPayload::Hirc { entries } = &mut bnk.sections[1].payload else {
+            panic!("not a HIRC section");
+        };
+        entries.push(first.clone());
+        // ids changed in place are found without invalidating the index
+        bnk.hirc_entry_mut(first.id).unwrap().id = 1;
+        assert!(bnk.hirc_entry(1).is_some());
+        assert!(bnk.hirc_entry(first.id).is_none());
+    }
+
+    #[test]
+    fn test_graph() {
+        let bnk = Bnk::from_file(INPUT_HIRC_2).unwrap();
+        let graph = bnk.graph();
+        // an event playing music of the bank
+        let (event, source) = bnk
+            .hirc_entries()
+            .filter(|entry| matches!(entry.payload, HircEntryPayload::Event { .. }))
+            .find_map(|entry| {
+                let source = graph
+                    .descendants(entry.id)
+                    .find(|edge| edge.kind == EdgeKind::Source)?;
+                Some((entry, source))
+            })
+            .unwrap();
+        let HircEntryPayload::Event { action_ids } = &event.payload else {
+            unreachable!();
+        };
+        assert_eq!(graph.children(event.id).len(), action_ids.len());
+        assert!(graph.descendants(event.id).all(|edge| edge.id != event.id));
+        assert!(graph.children(source.id).is_empty());
+        assert!(graph.ancestors(source.id).any(|edge| edge.id == event.id));
+
+        for entry in bnk.hirc_entries() {
+            if let Some(routing) = entry.payload.routing()
+                && routing.direct_parent_id != 0
+            {
+                assert!(
+                    graph
+                        .parents(entry.id)
+                        .iter()
+                        .any(|edge| edge.id == routing.direct_parent_id)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_resolve_event() {
+        let bnk = Bnk::from_file(INPUT_HIRC_2).unwrap();
+        let graph = bnk.graph();
+        let mut resolved_sources = 0;
+        for entry in bnk.hirc_entries() {
+            let HircEntryPayload::Event { action_ids } = &entry.payload else {
+                continue;
+            };
+            let actions = bnk.resolve_event(entry.id);
+            assert_eq!(actions.len(), action_ids.len());
+            for action in &actions {
+                for source_id in &action.source_ids {
+                    assert!(graph.ancestors(*source_id).any(|edge| edge.id == entry.id));
+                }
+                resolved_sources += action.source_ids.len();
+            }
+        }
+        assert!(resolved_sources > 0);
+        assert!(bnk.resolve_event(0).is_empty());
+    }
+
+    #[test]
+    fn test_section_accessors() {
+        let mut bnk = Bnk::from_file(INPUT_DIDX_DATA).unwrap();
+        assert_eq!(bnk.bkhd().unwrap().id, bnk.bank_id().unwrap());
+        assert_eq!(bnk.didx().unwrap().len(), 77);
+        assert_eq!(bnk.data().unwrap().len(), 77);
+        assert_eq!(bnk.section_by_magic(b"DIDX").unwrap().magic, *b"DIDX");
+        assert!(bnk.section_by_magic(b"XXXX").is_none());
+
+        *bnk.bkhd_mut().unwrap().id = 1;
+        assert_eq!(bnk.bank_id(), Some(1));
+        bnk.data_mut().unwrap()[0] = vec![1, 2, 3];
+        let first_id = bnk.didx_mut().unwrap()[0].id;
+        assert_eq!(bnk.wem_by_id(first_id).unwrap(), [1, 2, 3]);
+
+        let mut bnk = Bnk::from_file(INPUT_HIRC_2).unwrap();
+        let first_id = bnk.hirc().unwrap()[0].id;
+        assert!(bnk.hirc_entry(first_id).is_some());
+        bnk.hirc_mut().unwrap()[0].id = 1;
+        assert!(bnk.hirc_entry(1).is_some());
+    }
+
+    #[test]
+    fn test_new_bnk() {
+        let mut bnk = Bnk::new(HIRC_LAYOUT_VERSION, hash::short_id("Wp00_New"));
+        let bkhd_len = |bnk: &Bnk| bnk.bkhd().unwrap().unknown.len();
+        let template = Bnk::from_file(INPUT_HIRC).unwrap();
+        assert_eq!(bkhd_len(&bnk), bkhd_len(&template));
+        assert_eq!(bnk.language_id(), template.language_id());
+
+        bnk.add_wem(1, vec![1; 10]).unwrap();
+        bnk.add_wem(2, vec![2; 10]).unwrap();
+        let mut writer = io::Cursor::new(vec![]);
+        bnk.write_to(&mut writer).unwrap();
+        let bytes = writer.into_inner();
+        let reread = Bnk::from_reader(&mut io::Cursor::new(&bytes)).unwrap();
+        assert_eq!(reread.bank_id(), Some(hash::short_id("Wp00_New")));
+        assert_eq!(reread.hirc().unwrap().len(), 0);
+        assert_eq!(reread.wem_by_id(2).unwrap(), [2; 10]);
+        assert_eq!(reread.didx().unwrap()[1].offset, 16);
+
+        let empty = Bnk::new(HIRC_LAYOUT_VERSION, 1);
+        let mut writer = io::Cursor::new(vec![]);
+        empty.write_to(&mut writer).unwrap();
+        let reread = Bnk::from_reader(&mut io::Cursor::new(writer.into_inner())).unwrap();
+        assert_eq!(reread.sections.len(), 4);
+        assert!(reread.didx().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_node_accessors() {
+        let mut bnk = Bnk::from_file(INPUT_HIRC_2).unwrap();
+        let SectionPayload::Hirc { entries } = &mut bnk.sections[1].payload else {
+            panic!("not a HIRC section");
+        };
+        let mut edited = 0;
+        for entry in entries.iter_mut() {
+            if let HircEntryPayload::MusicTrack(track) = &mut entry.payload {
+                track.set_look_ahead_time(123);
+            }
+            let Some(params) = entry.payload.node_base_params_mut() else {
+                continue;
+            };
+            params.adv_settings_params_mut().set_max_num_instance(7);
+            params.aux_params_mut().set_aux_ids(Some([1, 2, 3, 4]));
+            edited += 1;
+        }
+        assert!(edited > 0);
+
+        let mut writer = io::Cursor::new(vec![]);
+        bnk.write_to(&mut writer).unwrap();
+        let reread = Bnk::from_reader(&mut io::Cursor::new(writer.into_inner())).unwrap();
+        let mut checked = 0;
+        for entry in reread.hirc_entries() {
+            if let HircEntryPayload::MusicTrack(track) = &entry.payload {
+                assert_eq!(track.look_ahead_time(), 123);
+            }
+            let Some(params) = entry.payload.node_base_params() else {
+                continue;
+            };
+            assert_eq!(params.adv_settings_params().max_num_instance(), 7);
+            assert_eq!(params.aux_params().aux_ids(), Some([1, 2, 3, 4]));
+            checked += 1;
+        }
+        assert_eq!(checked, edited);
+    }
+
+    #[test]
+    fn test_tolerant_read() {
+        let mut bnk = Bnk::from_file(INPUT_HIRC).unwrap();
+        // an actor-mixer with bytes of a newer layout
+        bnk.hirc_entry_mut(277229044).unwrap().trailing = vec![1, 2, 3];
+        let mut buf = io::Cursor::new(Vec::new());
+        bnk.write_to(&mut buf).unwrap();
+        let data = buf.into_inner();
+
+        let err = Bnk::from_reader(&mut io::Cursor::new(&data)).unwrap_err();
+        assert!(matches!(err, BnkError::BadDataSize { .. }));
+        assert_eq!(
+            err.hirc_entry(),
+            Some((HircEntryType::ActorMixer, 277229044))
+        );
+        let bnk = Bnk::from_reader_tolerant(&mut io::Cursor::new(&data)).unwrap();
+        let entry = bnk.hirc_entry(277229044).unwrap();
+        assert!(matches!(entry.payload, HircEntryPayload::ActorMixer(_)));
+        assert_eq!(entry.trailing, [1, 2, 3]);
+        assert!(
+            bnk.hirc_entries()
+                .filter(|entry| entry.id != 277229044)
+                .all(|entry| entry.trailing.is_empty())
+        );
+
+        let mut buf = io::Cursor::new(Vec::new());
+        bnk.write_to(&mut buf).unwrap();
+        assert_eq!(buf.into_inner(), data);
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let mut bnk = Bnk::from_file(INPUT_HIRC).unwrap();
+        bnk.hirc_entry_mut(277229044).unwrap().trailing = vec![1, 2, 3];
+        // a sound in an unknown layout
+        let SectionPayload::Hirc { entries } = &mut bnk.sections[1].payload else {
+            panic!("HIRC section expected");
+        };
+        entries.push(HircEntry {
+            entry_type: HircEntryType::Sound,
+            length: 0,
+            id: 1,
+            version: HIRC_LAYOUT_VERSION,
+            payload: HircEntryPayload::Unknown(HircUnmanagedEntry {
+                data: vec![0xFF; 8],
+            }),
+            trailing: vec![],
+        });
+        let mut buf = io::Cursor::new(Vec::new());
+        bnk.write_to(&mut buf).unwrap();
+        let data = buf.into_inner();
+
+        let strict = BnkParseOptions {
+            collect_warnings: true,
+            ..Default::default()
+        };
+        assert!(Bnk::from_reader_with(&mut io::Cursor::new(&data), &strict).is_err());
+
+        let options = BnkParseOptions {
+            strict: false,
+            ..strict
+        };
+        let (bnk, warnings) = Bnk::from_reader_with(&mut io::Cursor::new(&data), &options).unwrap();
+        assert_eq!(warnings.len(), 2);
+        assert!(matches!(
+            warnings[0],
+            ParseWarning::TrailingBytes {
+                id: 277229044,
+                length: 3,
+                ..
+            }
+        ));
+        assert!(matches!(
+            warnings[1],
+            ParseWarning::RawEntry {
+                entry_type: HircEntryType::Sound,
+                id: 1,
+                ..
+            }
+        ));
+        assert!(matches!(
+            bnk.hirc_entry(1).unwrap().payload,
+            HircEntryPayload::Unknown(_)
+        ));
+        let mut buf = io::Cursor::new(Vec::new());
+        bnk.write_to(&mut buf).unwrap();
+        assert_eq!(buf.into_inner(), data);
+
+        let options = BnkParseOptions {
+            max_section_size: Some(64),
+            ..Default::default()
+        };
+        assert!(matches!(
+            Bnk::from_reader_with(&mut io::Cursor::new(&data), &options),
+            Err(BnkError::SectionTooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_diagnostics() {
+        let input = fs::read(INPUT_HIRC).unwrap();
+        let mut diagnostics = Vec::new();
+        let (bnk, _) = Bnk::from_reader_with_diagnostics(
+            &mut io::Cursor::new(&input),
+            &BnkParseOptions::default(),
+            |diagnostic| diagnostics.push(diagnostic.clone()),
+        )
+        .unwrap();
+        let (sections, entries): (Vec<_>, Vec<_>) =
+            diagnostics.iter().partition(|diag| diag.entry.is_none());
+        assert_eq!(sections.len(), bnk.sections.len());
+        assert_eq!(entries.len(), bnk.hirc_entries().count());
+        assert_eq!(sections[0].magic, *b"BKHD");
+        assert_eq!(sections[0].offset, 0);
+        for (diag, entry) in entries.iter().zip(bnk.hirc_entries()) {
+            assert_eq!(diag.magic, *b"HIRC");
+            assert_eq!(diag.entry, Some((entry.entry_type, entry.id)));
+            assert_eq!(input[diag.offset as usize], entry.entry_type.as_u8());
+            assert_eq!(diag.length, entry.length);
+        }
+
+        // the last object is the one failing
+        let mut bnk = bnk;
+        bnk.hirc_entry_mut(277229044).unwrap().trailing = vec![1, 2, 3];
+        let mut buf = io::Cursor::new(Vec::new());
+        bnk.write_to(&mut buf).unwrap();
+        buf.set_position(0);
+        let mut last = None;
+        let result =
+            Bnk::from_reader_with_diagnostics(&mut buf, &BnkParseOptions::default(), |diag| {
+                last = Some(diag.clone())
+            });
+        assert!(result.is_err());
+        let last = last.unwrap();
+        assert_eq!(last.entry, Some((HircEntryType::ActorMixer, 277229044)));
+        assert!(
+            last.to_string()
+                .starts_with("HIRC ActorMixer 277229044 at 0x")
+        );
+    }
+
+    #[test]
+    fn test_corrupt_lengths() {
+        let input = fs::read(INPUT_HIRC).unwrap();
+        let hirc = input.windows(4).position(|magic| magic == b"HIRC").unwrap();
+
+        let mut data = input.clone();
+        data[hirc + 4..hirc + 8].copy_from_slice(&0xFFFF_FFF0u32.to_le_bytes());
+        let err = Bnk::from_reader(&mut io::Cursor::new(&data)).unwrap_err();
+        assert!(matches!(
+            err,
+            BnkError::SectionTooLarge { length: 0xFFFF_FFF0, max, .. }
+                if max == (input.len() - hirc - 8) as u64
+        ));
+
+        // first entry: type, length, id
+        let mut data = input.clone();
+        let entry = hirc + 12;
+        data[entry + 1..entry + 5].copy_from_slice(&0xFFFF_FFF0u32.to_le_bytes());
+        let id = u32::from_le_bytes(data[entry + 5..entry + 9].try_into().unwrap());
+        let err = Bnk::from_reader(&mut io::Cursor::new(&data)).unwrap_err();
+        assert!(matches!(err, BnkError::BadDataSize { .. }));
+        assert_eq!(err.hirc_entry().map(|(_, entry_id)| entry_id), Some(id));
+        assert!(Bnk::from_slice(&data).is_err());
+
+        data[entry + 1..entry + 5].copy_from_slice(&2u32.to_le_bytes());
+        assert!(Bnk::from_reader(&mut io::Cursor::new(&data)).is_err());
+    }
+
+    #[test]
+    fn test_parse_untrusted() {
+        let input = fs::read(INPUT_HIRC).unwrap();
+        let limits = ParseLimits::default();
+        let bnk = Bnk::parse_untrusted(&input, &limits).unwrap();
+        let mut output = vec![];
+        bnk.write_to(&mut io::Cursor::new(&mut output)).unwrap();
+        assert_eq!(input, output);
+
+        let count = bnk.hirc_entries().count() as u32;
+        let limits = ParseLimits {
+            max_hirc_entries: count - 1,
+            ..Default::default()
+        };
+        let err = Bnk::parse_untrusted(&input, &limits).unwrap_err();
+        assert!(matches!(err, BnkError::TooManyEntries { count: c, .. } if c == count));
+        let limits = ParseLimits {
+            max_section_size: 1024,
+            ..Default::default()
+        };
+        let err = Bnk::parse_untrusted(&input, &limits).unwrap_err();
+        assert!(matches!(err, BnkError::SectionTooLarge { max: 1024, .. }));
+
+        // truncated and corrupted inputs don't panic
+        for end in (0..input.len()).step_by(97) {
+            let _ = Bnk::parse_untrusted(&input[..end], &ParseLimits::default());
+        }
+        let mut seed = 0x9e37_79b9_7f4a_7c15u64;
+        for _ in 0..200 {
+            let mut data = input.clone();
+            for _ in 0..4 {
+                seed ^= seed << 13;
+                seed ^= seed >> 7;
+                seed ^= seed << 17;
+                data[seed as usize % input.len()] = (seed >> 32) as u8;
+            }
+            let _ = Bnk::parse_untrusted(&data, &ParseLimits::default());
+        }
+    }
+
+    #[test]
+    fn test_rebase() {
+        let input = fs::read(INPUT_HIRC).unwrap();
+        let mut bnk = Bnk::from_reader(&mut io::Cursor::new(&input)).unwrap();
+        assert!(bnk.check_id("Wp00_Cmn"));
+
+        let new_id = bnk.rebase("Wp00_Cmn_Clone", true).unwrap();
+        assert!(bnk.check_id("Wp00_Cmn_Clone"));
+        assert!(!bnk.check_id("Wp00_Cmn"));
+
+        let play_bank_ids = bnk
+            .sections
+            .iter_mut()
+            .filter_map(|sec| match &mut sec.payload {
+                SectionPayload::Hirc { entries } => Some(entries),
+                _ => None,
+            })
+            .flatten()
+            .filter_map(|entry| match &mut entry.payload {
+                HircEntryPayload::EventAction(action) => {
+                    action.play_bank_id_mut().map(|id| u32::from_le_bytes(*id))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert!(!play_bank_ids.is_empty());
+        assert!(play_bank_ids.iter().all(|&id| id == new_id));
+    }
+
+    fn stid_section(name: &[u8]) -> Vec<u8> {
+        let mut data = b"STID".to_vec();
+        data.write_u32::<LE>(13 + name.len() as u32).unwrap();
+        data.write_u32::<LE>(1).unwrap();
+        data.write_u32::<LE>(1).unwrap();
+        data.write_u32::<LE>(hash::short_id("Wp00_Cmn")).unwrap();
+        data.write_u8(name.len() as u8).unwrap();
+        data.extend(name);
+        data
+    }
+
+    #[test]
+    fn test_stid() {
+        let mut input = fs::read(INPUT_HIRC).unwrap();
+        input.extend(stid_section(b"Wp00_Cmn"));
+        let mut bnk = Bnk::from_reader(&mut io::Cursor::new(&input)).unwrap();
+        let id = hash::short_id("Wp00_Cmn");
+        assert!(matches!(
+            bnk.sections.last().unwrap().payload,
+            SectionPayload::Stid { .. }
+        ));
+        assert_eq!(bnk.stid_name(id), Some("Wp00_Cmn"));
+        assert_eq!(bnk.stid_name(0), None);
+
+        let mut output = Vec::new();
+        bnk.write_to(&mut io::Cursor::new(&mut output)).unwrap();
+        assert!(input == output);
+
+        let new_id = bnk.rebase("Wp00_Cmn_Clone", true).unwrap();
+        assert_eq!(bnk.stid_name(id), None);
+        assert_eq!(bnk.stid_name(new_id), Some("Wp00_Cmn_Clone"));
+        assert_eq!(
+            bnk.stid_names().unwrap(),
+            vec![(new_id, "Wp00_Cmn_Clone".to_string())]
+        );
+
+        // names that are not UTF-8 are kept raw
+        let mut input = fs::read(INPUT_HIRC).unwrap();
+        input.extend(stid_section(&[0xFF, 0xFE]));
+        let bnk = Bnk::from_reader(&mut io::Cursor::new(&input)).unwrap();
+        assert!(matches!(
+            bnk.sections.last().unwrap().payload,
+            SectionPayload::Unk { .. }
+        ));
+        assert_eq!(bnk.stid_name(id), None);
+    }
+
+    #[test]
+    fn test_media_usage() {
+        let input = fs::read(INPUT_HIRC).unwrap();
+        let bnk = Bnk::from_reader(&mut io::Cursor::new(&input)).unwrap();
+        let usage = bnk.media_usage();
+        // embedded in the companion media bank
+        let usage_0 = &usage[&0x13009529];
+        assert_eq!(usage_0.stream_type, AkBankSourceStreamType::DataBnk);
+        assert_eq!(usage_0.in_memory_size, 7843);
+        assert_eq!(usage_0.objects, vec![52137719]);
+
+        let input = fs::read(INPUT_HIRC_2).unwrap();
+        let bnk = Bnk::from_reader(&mut io::Cursor::new(&input)).unwrap();
+        assert!(!bnk.media_usage().is_empty());
+    }
+
+    #[test]
+    fn test_rtpc_usage() {
+        let input = fs::read(INPUT_HIRC_2).unwrap();
+        let bnk = Bnk::from_reader(&mut io::Cursor::new(&input)).unwrap();
+        let usage = bnk.rtpc_usage();
+        assert!(!usage.is_empty());
+        for references in usage.values() {
+            for reference in references {
+                assert!(reference.input_range.0 <= reference.input_range.1);
+                assert!(reference.output_range.0 <= reference.output_range.1);
+            }
+        }
+    }
+
+    /// Init bank with a BKHD and a STMG section.
+    fn init_bank() -> Vec<u8> {
+        let mut stmg = Vec::new();
+        stmg.write_u16::<LE>(0).unwrap(); // filter behavior
+        stmg.write_f32::<LE>(-80.0).unwrap();
+        stmg.write_u16::<LE>(64).unwrap();
+        stmg.write_u16::<LE>(128).unwrap();
+        // state group with a single transition
+        for v in [1, 0x100, 0, 1, 0x101, 0x102, 500] {
+            stmg.write_u32::<LE>(v).unwrap();
+        }
+        // switch group with a single point
+        for v in [1, 0x200, 0x300] {
+            stmg.write_u32::<LE>(v).unwrap();
+        }
+        stmg.write_u8(0).unwrap();
+        stmg.write_u32::<LE>(1).unwrap();
+        stmg.write_f32::<LE>(0.0).unwrap();
+        // switch id stored as the bits of the float
+        stmg.write_u32::<LE>(hash::short_id("Combat")).unwrap();
+        stmg.write_u32::<LE>(9).unwrap();
+        stmg.extend([0; 8]); // rtpc ramping, acoustic textures
+
+        let mut input = Vec::new();
+        input.extend(b"BKHD");
+        input.write_u32::<LE>(8).unwrap();
+        input.write_u32::<LE>(0x91).unwrap();
+        input.write_u32::<LE>(hash::short_id("Init")).unwrap();
+        input.extend(b"STMG");
+        input.write_u32::<LE>(stmg.len() as u32).unwrap();
+        input.extend(&stmg);
+
+        input
+    }
+
+    #[test]
+    fn test_stmg_game_syncs() {
+        let input = init_bank();
+        let bnk = Bnk::from_reader(&mut io::Cursor::new(&input)).unwrap();
+        let SectionPayload::Stmg { settings } = &bnk.sections[1].payload else {
+            panic!("STMG not parsed");
+        };
+        assert_eq!(settings.state_groups[0].transitions.len(), 1);
+        assert_eq!(settings.data.len(), 8);
+
+        let syncs = bnk.game_syncs();
+        let states = syncs.group(GameSyncKind::State, 0x100).unwrap();
+        assert_eq!(
+            states.values.keys().copied().collect::<Vec<_>>(),
+            [0x101, 0x102]
+        );
+        let switches = syncs.group(GameSyncKind::Switch, 0x200).unwrap();
+        assert_eq!(
+            switches.values.keys().copied().collect::<Vec<_>>(),
+            [hash::short_id("Combat")]
+        );
+
+        let mut output = Vec::new();
+        bnk.write_to(&mut io::Cursor::new(&mut output)).unwrap();
+        assert_eq!(input, output);
+
+        // music tracks and actions of a regular bank
+        let input = fs::read(INPUT_HIRC_2).unwrap();
+        let bnk = Bnk::from_reader(&mut io::Cursor::new(&input)).unwrap();
+        let mut syncs = bnk.game_syncs();
+        syncs.resolve_names(&crate::names::NameDb::from_banks([&bnk]).unwrap());
+        for group in syncs.groups() {
+            assert_ne!(group.id, 0);
+        }
+    }
+
+    #[test]
+    fn test_stmg_transitions() {
+        let mut bnk = Bnk::from_reader(&mut io::Cursor::new(init_bank())).unwrap();
+        let SectionPayload::Stmg { settings } = &mut bnk.sections[1].payload else {
+            panic!("STMG not parsed");
+        };
+        let group = settings.state_group_mut(0x100).unwrap();
+        assert_eq!(group.transition_time(0x101, 0x102), 500);
+        assert_eq!(group.transition_time(0x102, 0x101), 0);
+        group.set_default_transition_time(100);
+        group.set_transition(0, 0x101, 250);
+        group.set_transition(0x101, 0x102, 750);
+        assert_eq!(group.transition_time(0x102, 0x101), 250);
+        assert_eq!(group.remove_transition(0x101, 0x102), Some(750));
+
+        let mut output = Vec::new();
+        bnk.write_to(&mut io::Cursor::new(&mut output)).unwrap();
+        let bnk = Bnk::from_reader(&mut io::Cursor::new(&output)).unwrap();
+        let SectionPayload::Stmg { settings } = &bnk.sections[1].payload else {
+            panic!("STMG not parsed");
+        };
+        let group = settings.state_group(0x100).unwrap();
+        assert_eq!(group.default_transition_time, 100);
+        assert_eq!(group.transition_time(0x102, 0x101), 250);
+        assert_eq!(group.transition_time(0x101, 0x102), 100);
+        assert_eq!(group.transitions.len(), 1);
+    }
+
+    #[test]
+    fn test_bus_routing() {
+        let input = fs::read(INPUT_HIRC).unwrap();
+        let mut bnk = Bnk::from_reader(&mut io::Cursor::new(&input)).unwrap();
+        let sound_ids: Vec<u32> = bnk
+            .hirc_entries()
+            .filter(|entry| matches!(entry.payload, HircEntryPayload::Sound(_)))
+            .map(|entry| entry.id)
+            .collect();
+        assert!(!sound_ids.is_empty());
+        for id in &sound_ids {
+            assert!(bnk.output_bus_of(*id).is_some_and(|bus| bus != 0));
+        }
+
+        // master -> music -> sfx, music outputs to master
+        let bus = |id: u32, parent_id: u32| bus_entry(id, parent_id, AkChannelConfig::parent());
+        let SectionPayload::Hirc { entries } = &mut bnk.sections[1].payload else {
+            panic!("HIRC not found");
+        };
+        entries.extend([bus(1, 0), bus(2, 1), bus(3, 2), bus(4, 0x999)]);
+        let tree = bnk.bus_tree();
+        assert_eq!(tree.len(), 2);
+        assert_eq!(tree[0].children[0].children[0].id, 3);
+        assert_eq!(tree[1].parent_id, 0x999);
+        assert_eq!(bnk.output_bus_of(2), Some(1));
+        assert_eq!(bnk.output_bus_of(1), None);
+    }
+
+    #[test]
+    fn test_attenuation_usage() {
+        let input = fs::read(INPUT_HIRC).unwrap();
+        let bnk = Bnk::from_reader(&mut io::Cursor::new(&input)).unwrap();
+        let usage = bnk.attenuation_usage();
+        assert_eq!(usage.len(), 3);
+        assert_eq!(usage[&879868680].len(), 2);
+        assert_eq!(usage[&1002072031].len(), 14);
+        // defined in another bank
+        assert_eq!(usage[&401510780].len(), 8);
+        let user = bnk
+            .hirc_entries()
+            .find(|entry| entry.id == usage[&1002072031][0])
+            .unwrap();
+        assert_eq!(user.payload.attenuation_id(), Some(1002072031));
+    }
+
+    #[test]
+    fn test_effective_props() {
+        let input = fs::read(INPUT_HIRC).unwrap();
+        let bnk = Bnk::from_reader(&mut io::Cursor::new(&input)).unwrap();
+        let props = bnk.effective_props(16711684).unwrap();
+        assert_eq!(
+            props.hierarchy,
+            [
+                16711684, 155478820, 214221022, 277229044, 753760628, 443143958
+            ]
+        );
+        let volumes: Vec<f32> = props
+            .hierarchy
+            .iter()
+            .map(|id| {
+                let entry = bnk.hirc_entries().find(|entry| entry.id == *id).unwrap();
+                entry.payload.prop(0).map(f32::from_bits).unwrap_or(0.0)
+            })
+            .collect();
+        // inherited from more than one level
+        assert!(volumes.iter().filter(|volume| **volume != 0.0).count() > 1);
+        assert_eq!(props.volume, -14.0);
+        assert_eq!(props.output_bus_id, Some(4232434793));
+        assert!(bnk.effective_props(4232434793).is_none());
+    }
+
+    #[test]
+    fn test_rename_event() {
+        let input = fs::read(INPUT_HIRC_2).unwrap();
+        let mut bnk = Bnk::from_reader(&mut io::Cursor::new(&input)).unwrap();
+        let cue_clips = |bnk: &Bnk, event_id: u32| {
+            bnk.hirc_entries()
+                .filter_map(|entry| match &entry.payload {
+                    HircEntryPayload::MusicTrack(track) => Some(track.playlist()),
+                    _ => None,
+                })
+                .flatten()
+                .filter(|clip| clip.event_id == event_id)
+                .count()
+        };
+        let old_cues = cue_clips(&bnk, 3442882590);
+        assert!(old_cues > 0);
+
+        let new_id = bnk.rename_event(3442882590, "Play_Renamed_Cue").unwrap();
+        assert_eq!(new_id, hash::short_id("Play_Renamed_Cue"));
+        assert_eq!(cue_clips(&bnk, 3442882590), 0);
+        assert_eq!(cue_clips(&bnk, new_id), old_cues);
+        assert!(bnk.hirc_entries().any(|entry| entry.id == new_id));
+
+        assert!(matches!(
+            bnk.rename_event(3442882590, "Play_Other"),
+            Err(BnkError::ObjectNotFound(3442882590))
+        ));
+        assert!(matches!(
+            bnk.rename_event(4039231595, "play_renamed_cue"),
+            Err(BnkError::IdCollision(_))
+        ));
+        assert_eq!(
+            bnk.rename_event("Play_Renamed_Cue", "Play_Renamed_Cue")
+                .unwrap(),
+            new_id
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_entry_changes() {
+        let input = fs::read(INPUT_HIRC).unwrap();
+        let old = Bnk::from_reader(&mut io::Cursor::new(&input)).unwrap();
+        let mut new = old.clone();
+        assert!(old.entry_changes(&new).unwrap().is_empty());
+
+        let SectionPayload::Hirc { entries } = &mut new.sections[1].payload else {
+            panic!("HIRC section expected");
+        };
+        let sound = entries
+            .iter_mut()
+            .find(|e| matches!(e.payload, HircEntryPayload::Sound(_)))
+            .unwrap();
+        let sound_id = sound.id;
+        let source_id = sound.payload.sources()[0].source_id();
+        sound.payload.sources_mut()[0].media_information.source_id = 1234;
+        let settings = entries
+            .iter_mut()
+            .find(|e| e.entry_type == HircEntryType::Settings);
+        let settings_id = settings.as_ref().map(|e| e.id);
+        if let Some(HircEntry {
+            payload: HircEntryPayload::Settings(settings),
+            ..
+        }) = settings
+        {
+            settings.settings[0].value = 1.0;
+        }
+
+        let changes = old.entry_changes(&new).unwrap();
+        let sound_change = changes.iter().find(|c| c.id == sound_id).unwrap();
+        assert_eq!(
+            sound_change.changes,
+            vec![FieldChange {
+                path: "source.media_information.source_id".to_string(),
+                old: Some(source_id.to_string()),
+                new: Some("1234".to_string()),
+            }]
+        );
+        if let Some(settings_id) = settings_id {
+            let change = changes.iter().find(|c| c.id == settings_id).unwrap();
+            assert_eq!(change.changes.len(), 1);
+            assert_eq!(change.changes[0].path, "settings.0.value");
+        }
+
+        // properties are compared by id with decoded values
+        let sound = old.hirc_entries().find(|e| e.id == sound_id).unwrap();
+        let mut value = serde_json::to_value(sound).unwrap();
+        value["node_base_params"]["node_initial_params"]["ak_prop_bundle"]["props"] =
+            serde_json::json!([{ "p_id": 0, "p_value": (-3.0f32).to_bits() }]);
+        let louder: HircEntry = serde_json::from_value(value).unwrap();
+        assert_eq!(
+            sound.field_diff(&louder).unwrap(),
+            vec![FieldChange {
+                path: "node_base_params.node_initial_params.ak_prop_bundle.props[volume]"
+                    .to_string(),
+                old: None,
+                new: Some("-3".to_string()),
+            }]
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_blob_encoding() {
+        let dir = std::env::temp_dir().join(format!("re-sound-bnk-blobs-{}", std::process::id()));
+        let input = fs::read(INPUT_DIDX_DATA).unwrap();
+        let bnk = Bnk::from_reader(&mut io::Cursor::new(&input)).unwrap();
+        let array = serde_json::to_string(&bnk).unwrap();
+
+        for encoding in [
+            BlobEncoding::Base64,
+            BlobEncoding::Hex,
+            BlobEncoding::External(dir.clone()),
+        ] {
+            let json = serde_json::to_string(&BlobEncoded::new(&bnk, &encoding)).unwrap();
+            assert!(json.len() < array.len(), "{encoding:?}");
+            let read: Bnk = match &encoding {
+                BlobEncoding::External(dir) => {
+                    // plain deserialization does no file I/O
+                    assert!(serde_json::from_str::<Bnk>(&json).is_err());
+                    from_json_with_blob_dir(&json, dir).unwrap()
+                }
+                // documents describe their encoding, e.g. when read on another thread
+                _ => std::thread::spawn(move || serde_json::from_str(&json).unwrap())
+                    .join()
+                    .unwrap(),
+            };
+            let mut output = io::Cursor::new(Vec::new());
+            read.write_to(&mut output).unwrap();
+            assert_eq!(output.into_inner(), input, "{encoding:?}");
+        }
+        // media are stored once per distinct content
+        let files = fs::read_dir(&dir).unwrap().count();
+        assert!(files > 0 && files <= bnk.didx().unwrap().len() + 1);
+        // references may not leave the blob folder
+        let name = fs::read_dir(&dir)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .file_name();
+        let name = name.to_str().unwrap();
+        let blob = |name: &str| format!(r#"{{"type": "Unk", "data": {{"file": "{name}"}}}}"#);
+        let unknown: SectionPayload = from_json_with_blob_dir(&blob(name), &dir).unwrap();
+        assert!(matches!(unknown, SectionPayload::Unk { data } if !data.is_empty()));
+        let absolute = dir.join(name);
+        let traversal = format!("../{}/{name}", dir.file_name().unwrap().to_str().unwrap());
+        for name in [absolute.to_str().unwrap(), &traversal, "/etc/passwd", ""] {
+            let result = from_json_with_blob_dir::<SectionPayload>(&blob(name), &dir);
+            assert!(result.is_err(), "{name}");
+        }
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            serde_json::to_string(&BlobEncoded::new(&bnk, &BlobEncoding::Array)).unwrap(),
+            array
+        );
+        let value = serde_json::to_value(BlobEncoded::new(&bnk, &BlobEncoding::Hex)).unwrap();
+        assert_eq!(
+            value["sections"][0]["unknown"]["hex"]
+                .as_str()
+                .unwrap()
+                .len()
+                % 2,
+            0
+        );
+        // bare strings could be read as both hex and base64
+        let unknown: serde_json::Result<SectionPayload> =
+            serde_json::from_str(r#"{"type": "Unk", "data": "00ff"}"#);
+        assert!(unknown.is_err());
+        let unknown: SectionPayload =
+            serde_json::from_str(r#"{"type": "Unk", "data": {"hex": "00ff"}}"#).unwrap();
+        assert!(matches!(unknown, SectionPayload::Unk { data } if data == [0, 0xff]));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json_project() {
+        let dir = std::env::temp_dir().join(format!("re-sound-bnk-project-{}", std::process::id()));
+        for path in [INPUT_HIRC, INPUT_DIDX_DATA] {
+            let input = fs::read(path).unwrap();
+            let bnk = Bnk::from_reader(&mut io::Cursor::new(&input)).unwrap();
+            bnk.to_json_project(&dir).unwrap();
+            let read = Bnk::from_json_project(&dir).unwrap();
+            let mut output = io::Cursor::new(Vec::new());
+            read.write_to(&mut output).unwrap();
+            assert_eq!(output.into_inner(), input, "{path}");
+        }
+
+        // the structured part is a plain serialized bank
+        let json = fs::read_to_string(dir.join("bank.json")).unwrap();
+        let mut plain: Bnk = serde_json::from_str(&json).unwrap();
+        let original = Bnk::from_file(INPUT_DIDX_DATA).unwrap();
+        *plain.data_mut().unwrap() = original.data().unwrap().to_vec();
+        let mut output = io::Cursor::new(Vec::new());
+        plain.write_to(&mut output).unwrap();
+        assert_eq!(output.into_inner(), fs::read(INPUT_DIDX_DATA).unwrap());
+
+        // media is edited as files
+        let (id, data) = Bnk::from_file(INPUT_DIDX_DATA)
+            .unwrap()
+            .wems()
+            .map(|(id, data)| (id, data.to_vec()))
+            .next()
+            .unwrap();
+        let json = fs::read_to_string(dir.join("bank.json")).unwrap();
+        assert!(json.len() < data.len() * 2);
+        fs::write(dir.join(format!("wem/{id}.wem")), [1, 2, 3]).unwrap();
+        let read = Bnk::from_json_project(&dir).unwrap();
+        assert_eq!(read.media(id).unwrap(), &[1, 2, 3]);
+
+        fs::remove_file(dir.join(format!("wem/{id}.wem"))).unwrap();
+        assert!(matches!(
+            Bnk::from_json_project(&dir),
+            Err(BnkError::IO(e)) if e.kind() == io::ErrorKind::NotFound
+        ));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json_patch() {
+        let input = fs::read(INPUT_HIRC).unwrap();
+        let old = Bnk::from_reader(&mut io::Cursor::new(&input)).unwrap();
+        assert!(old.json_patch(&old).unwrap().is_empty());
+
+        let mut new = old.clone();
+        let SectionPayload::Hirc { entries } = &mut new.sections[1].payload else {
+            panic!("HIRC section expected");
+        };
+        let sound = entries
+            .iter_mut()
+            .find(|e| matches!(e.payload, HircEntryPayload::Sound(_)))
+            .unwrap();
+        sound.payload.sources_mut()[0].media_information.source_id = 1234;
+        entries.pop();
+        let patch = old.json_patch(&new).unwrap();
+        assert_eq!(patch.0.len(), 2);
+
+        // round trip through the patch document
+        let document = serde_json::to_string(&patch).unwrap();
+        assert!(document.contains(r#""op":"replace""#));
+        let patch: JsonPatch = serde_json::from_str(&document).unwrap();
+        let mut patched = old.clone();
+        patched.apply_json_patch(&patch).unwrap();
+        assert!(patched.json_patch(&new).unwrap().is_empty());
+
+        // lookups see the patched entries
+        let id = old.hirc_entries().nth(3).unwrap().id;
+        let mut renamed = old.clone();
+        renamed.hirc_entry_mut(id).unwrap().id = 1;
+        let patch = old.json_patch(&renamed).unwrap();
+        let mut patched = old.clone();
+        assert!(patched.hirc_entry(id).is_some());
+        patched.apply_json_patch(&patch).unwrap();
+        assert!(patched.hirc_entry(id).is_none());
+        assert_eq!(patched.hirc_entry(1).unwrap().id, 1);
+
+        // failed patches leave the bank unchanged
+        let failing = JsonPatch(vec![
+            PatchOperation::Remove {
+                path: "/sections/0".to_string(),
+            },
+            PatchOperation::Test {
+                path: "/sections/0/magic".to_string(),
+                value: serde_json::json!([0, 0, 0, 0]),
+            },
+        ]);
+        let mut unchanged = old.clone();
+        assert!(matches!(
+            unchanged.apply_json_patch(&failing),
+            Err(BnkError::Patch(_))
+        ));
+        assert_eq!(unchanged.sections.len(), old.sections.len());
+
+        let mut value = serde_json::json!({ "a/b": [1, 2], "c": { "d": 1 } });
+        JsonPatch(vec![
+            PatchOperation::Move {
+                from: "/a~1b/0".to_string(),
+                path: "/c/e".to_string(),
+            },
+            PatchOperation::Copy {
+                from: "/c".to_string(),
+                path: "/a~1b/0".to_string(),
+            },
+            PatchOperation::Add {
+                path: "/a~1b/-".to_string(),
+                value: serde_json::json!(3),
+            },
+        ])
+        .apply(&mut value)
+        .unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({ "a/b": [{ "d": 1, "e": 1 }, 2, 3], "c": { "d": 1, "e": 1 } })
+        );
+    }
+
+    #[test]
+    fn test_event_aliases() {
+        let input = fs::read(INPUT_HIRC).unwrap();
+        let mut bnk = Bnk::from_reader(&mut io::Cursor::new(&input)).unwrap();
+        assert!(bnk.event_aliases().is_empty());
+
+        // a copy of an event with copies of its actions, and a copy with a
+        // different target
+        let (event_id, action_ids) = bnk
+            .hirc_entries()
+            .find_map(|entry| match &entry.payload {
+                HircEntryPayload::Event { action_ids } if !action_ids.is_empty() => {
+                    Some((entry.id, action_ids.clone()))
+                }
+                _ => None,
+            })
+            .unwrap();
+        let SectionPayload::Hirc { entries } = &mut bnk.sections[1].payload else {
+            panic!("HIRC section expected");
+        };
+        let mut copies = Vec::new();
+        for (i, action_id) in action_ids.iter().enumerate() {
+            let mut action = entries.iter().find(|e| e.id == *action_id).unwrap().clone();
+            action.id = 0x7000_0000 + i as u32;
+            let mut other = action.clone();
+            other.id = 0x7100_0000 + i as u32;
+            if let HircEntryPayload::EventAction(action) = &mut other.payload {
+                action.game_object_id ^= 1;
+            }
+            copies.extend([action, other]);
+        }
+        let mut event = entries.iter().find(|e| e.id == event_id).unwrap().clone();
+        for (id, base) in [(1, 0x7000_0000), (2, 0x7100_0000)] {
+            event.id = id;
+            event.payload = HircEntryPayload::Event {
+                action_ids: (0..action_ids.len() as u32).map(|i| base + i).collect(),
+            };
+            copies.push(event.clone());
+        }
+        entries.extend(copies);
+
+        let aliases = bnk.event_aliases();
+        assert_eq!(aliases.len(), 1);
+        assert_eq!(aliases[0].event_ids, vec![1, event_id]);
+        assert_eq!(aliases[0].actions.len(), action_ids.len());
+        assert!(matches!(
+            aliases[0].actions[0],
+            EventActionTarget::Action { .. }
+        ));
+    }
+
+    #[test]
+    fn test_validate_media() {
+        // media of Wp00_Cmn is stored in Wp00_Cmn_m
+        let input = fs::read(INPUT_HIRC).unwrap();
+        let bnk = Bnk::from_reader(&mut io::Cursor::new(&input)).unwrap();
+        let issues = bnk.validate();
+        assert_eq!(issues.len(), bnk.media_usage().len());
+        assert!(issues.contains(&ValidationIssue::MissingMedia {
+            source_id: 366812,
+            objects: vec![570721134]
+        }));
+
+        let input = fs::read(INPUT_DIDX_DATA).unwrap();
+        let bnk = Bnk::from_reader(&mut io::Cursor::new(&input)).unwrap();
+        let issues = bnk.validate();
+        assert!(!issues.is_empty());
+        assert!(issues.contains(&ValidationIssue::UnreferencedMedia { media_id: 366812 }));
+        assert!(
+            issues
+                .iter()
+                .all(|issue| matches!(issue, ValidationIssue::UnreferencedMedia { .. }))
+        );
+
+        // wrong file copied into a media slot
+        let mut bnk = bnk;
+        bnk.insert_media(366812, fs::read(INPUT_HIRC).unwrap());
+        let mut wem = bnk
+            .sections
+            .iter()
+            .find_map(|sec| match &sec.payload {
+                SectionPayload::Data { data_list } => Some(data_list[1].clone()),
+                _ => None,
+            })
+            .unwrap();
+        wem.truncate(wem.len() - 4);
+        let media_id = bnk
+            .sections
+            .iter()
+            .find_map(|sec| match &sec.payload {
+                SectionPayload::Didx { entries } => Some(entries[1].id),
+                _ => None,
+            })
+            .unwrap();
+        bnk.insert_media(media_id, wem.clone());
+        let issues = bnk.validate();
+        assert!(issues.contains(&ValidationIssue::InvalidMedia {
+            media_id: 366812,
+            problem: MediaProblem::NotRiff
+        }));
+        assert!(issues.contains(&ValidationIssue::InvalidMedia {
+            media_id,
+            problem: MediaProblem::SizeMismatch {
+                riff_size: wem.len() as u32 - 4,
+                length: wem.len() as u32
+            }
+        }));
+        assert_eq!(wem::check_media(&wem, true), None);
+    }
+
+    #[test]
+    fn test_materialize_media() {
+        let input = fs::read(INPUT_DIDX_DATA).unwrap();
+        let mut media_bnk = Bnk::from_reader(&mut io::Cursor::new(&input)).unwrap();
+        let mut provider: HashMap<u32, Vec<u8>> = media_bnk
+            .strip_media(StripMediaMode::RemoveAll)
+            .into_iter()
+            .collect();
+
+        let input = fs::read(INPUT_HIRC).unwrap();
+        let mut bnk = Bnk::from_reader(&mut io::Cursor::new(&input)).unwrap();
+        let missing = bnk.validate().len();
+        let expected: Vec<u32> = bnk
+            .media_usage()
+            .into_keys()
+            .filter(|id| provider.contains_key(id))
+            .collect();
+        let added = bnk.materialize_media(&mut provider).unwrap();
+        assert!(!added.is_empty());
+        assert_eq!(added, expected);
+        assert_eq!(bnk.validate().len(), missing - added.len());
+        assert_eq!(&bnk.sections[1].magic, b"DIDX");
+        assert_eq!(&bnk.sections[2].magic, b"DATA");
+
+        let mut output = io::Cursor::new(vec![]);
+        bnk.write_to(&mut output).unwrap();
+        let bnk = Bnk::from_reader(&mut io::Cursor::new(output.into_inner())).unwrap();
+        let usage = bnk.media_usage();
+        for id in added {
+            assert_eq!(bnk.media(id), Some(provider[&id].as_slice()));
+            assert_eq!(usage[&id].in_memory_size as usize, provider[&id].len());
+        }
+    }
+
+    #[test]
+    fn test_install_prefetch() {
+        let input = fs::read(INPUT_DIDX_DATA).unwrap();
+        let media_bnk = Bnk::from_reader(&mut io::Cursor::new(&input)).unwrap();
+        let wem = media_bnk.media(0x598dc).unwrap();
+        let info = WemInfo::from_bytes(wem).unwrap();
+
+        let chunk = wem::prefetch_chunk(wem, PrefetchLength::Millis(100.0)).unwrap();
+        assert!(chunk.len() < wem.len());
+        assert!(wem.starts_with(&chunk));
+        // header is unchanged, the data is cut after a whole packet
+        assert_eq!(WemInfo::from_bytes(&chunk).unwrap(), info);
+        let mut pos = (info.data_offset + info.first_audio_packet_offset.unwrap()) as usize;
+        while pos < chunk.len() {
+            pos += 2 + u16::from_le_bytes([wem[pos], wem[pos + 1]]) as usize;
+        }
+        assert_eq!(pos, chunk.len());
+        let smaller = wem::prefetch_chunk(wem, PrefetchLength::Bytes(chunk.len() - 1)).unwrap();
+        assert!(smaller.len() < chunk.len());
+
+        let input = fs::read(INPUT_HIRC).unwrap();
+        let mut bnk = Bnk::from_reader(&mut io::Cursor::new(&input)).unwrap();
+        let size = bnk
+            .install_prefetch(0x598dc, wem, PrefetchLength::Millis(100.0))
+            .unwrap();
+        assert_eq!(size as usize, chunk.len());
+        assert_eq!(bnk.media(0x598dc), Some(chunk.as_slice()));
+        let usage = &bnk.media_usage()[&0x598dc];
+        assert_eq!(usage.stream_type, AkBankSourceStreamType::PrefetchStreaming);
+        assert_eq!(usage.in_memory_size, size);
+        assert!(matches!(
+            bnk.install_prefetch(1, wem, PrefetchLength::Millis(100.0)),
+            Err(BnkError::ObjectNotFound(1))
+        ));
+    }
+
+    #[test]
+    fn test_clone_for_language() {
+        let input = fs::read(INPUT_HIRC).unwrap();
+        let bnk = Bnk::from_reader(&mut io::Cursor::new(&input)).unwrap();
+        assert_eq!(bnk.language_id(), Some(hash::short_id("SFX")));
+
+        let cloned = bnk.clone_for_language("English(US)").unwrap();
+        assert_eq!(cloned.language_id(), Some(hash::short_id("English(US)")));
+        let mut output = io::Cursor::new(vec![]);
+        cloned.write_to(&mut output).unwrap();
+        let output = output.into_inner();
+        assert_eq!(output.len(), input.len());
+        let reread = Bnk::from_reader(&mut io::Cursor::new(&output)).unwrap();
+        assert_eq!(reread.language_id(), cloned.language_id());
+        assert_eq!(reread.bank_id(), bnk.bank_id());
+    }
+
+    /// AudioBus entry with default params and an empty trailing chunk.
+    pub(super) fn bus_entry(id: u32, parent_id: u32, channel_config: AkChannelConfig) -> HircEntry {
+        let mut payload = parent_id.to_le_bytes().to_vec();
+        if parent_id == 0 {
+            payload.extend(0x1234u32.to_le_bytes());
+        }
+        // props, positioning, aux params, bit vector, max instances
+        payload.extend([0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        payload.extend(channel_config.to_u32().to_le_bytes());
+        payload.push(0);
+        payload.extend(500i32.to_le_bytes());
+        payload.extend((-96.0f32).to_le_bytes());
+        payload.extend(0u32.to_le_bytes());
+        // no effect, mixer id and share set
+        payload.extend([0, 0, 0, 0, 0, 0]);
+        payload.extend([0xAA; 6]);
+
+        let mut data = ((payload.len() + 4) as u32).to_le_bytes().to_vec();
+        data.extend(id.to_le_bytes());
+        data.extend(payload);
+        HircEntry::from_reader(
+            &mut io::Cursor::new(data),
+            HircEntryType::AudioBus,
+            HIRC_LAYOUT_VERSION,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_preserving_write() {
+        // DATA padding isn't serialized yet, the original bytes are kept
+        let input = fs::read(INPUT_DIDX_DATA).unwrap();
+        let mut bnk = Bnk::from_reader_preserving(&mut io::Cursor::new(&input)).unwrap();
+        assert!(bnk.preserves_original_bytes());
+        let mut output = io::Cursor::new(vec![]);
+        bnk.write_to(&mut output).unwrap();
+        assert_eq!(output.into_inner(), input);
+
+        // modified media is serialized
+        let mut original_byte = 0;
+        for section in &mut bnk.sections {
+            if let SectionPayload::Data { data_list } = &mut section.payload {
+                original_byte = data_list[0][100];
+                data_list[0][100] ^= 0xFF;
+            }
+        }
+        let mut output = io::Cursor::new(vec![]);
+        bnk.write_to(&mut output).unwrap();
+        let output = output.into_inner();
+        assert_ne!(output, input);
+        let reread = Bnk::from_reader(&mut io::Cursor::new(&output)).unwrap();
+        let data = reread.sections.iter().find_map(|sec| match &sec.payload {
+            SectionPayload::Data { data_list } => Some(data_list[0][100]),
+            _ => None,
+        });
+        assert_eq!(data, Some(original_byte ^ 0xFF));
+
+        // only the modified HIRC entry is serialized
+        let input = fs::read(INPUT_HIRC).unwrap();
+        let mut bnk = Bnk::from_reader_preserving(&mut io::Cursor::new(&input)).unwrap();
+        let event = bnk
+            .sections
+            .iter_mut()
+            .find_map(|sec| match &mut sec.payload {
+                SectionPayload::Hirc { entries } => Some(entries),
+                _ => None,
+            })
+            .unwrap()
+            .iter_mut()
+            .find(|entry| matches!(entry.payload, HircEntryPayload::Event { .. }))
+            .unwrap();
+        let event_id = event.id;
+        if let HircEntryPayload::Event { action_ids } = &mut event.payload {
+            action_ids[0] = 0x12345678;
+        }
+        let mut output = io::Cursor::new(vec![]);
+        bnk.write_to(&mut output).unwrap();
+        let output = output.into_inner();
+        assert_eq!(output.len(), input.len());
+        let changed: Vec<usize> = (0..input.len())
+            .filter(|&i| input[i] != output[i])
+            .collect();
+        assert!(!changed.is_empty());
+        assert!(changed.iter().all(|&i| i < changed[0] + 4));
+        let reread = Bnk::from_reader(&mut io::Cursor::new(&output)).unwrap();
+        let event = reread.hirc_entries().find(|e| e.id == event_id).unwrap();
+        assert!(matches!(
+            &event.payload,
+            HircEntryPayload::Event { action_ids } if action_ids[0] == 0x12345678
+        ));
+
+        bnk.discard_original_bytes();
+        assert!(!bnk.preserves_original_bytes());
+    }
+
+    #[test]
+    fn test_parse_coverage() {
+        let input = fs::read(INPUT_HIRC).unwrap();
+        let bnk = Bnk::from_reader(&mut io::Cursor::new(&input)).unwrap();
+        let report = bnk.parse_coverage().unwrap();
+        assert_eq!(report.total().total(), input.len() as u64);
+        let hirc = report.sections.iter().find(|s| s.magic == "HIRC").unwrap();
+        let hirc_types = ByteCoverage {
+            typed: report.hirc_types.iter().map(|t| t.bytes.typed).sum(),
+            raw: report.hirc_types.iter().map(|t| t.bytes.raw).sum(),
+        };
+        // section header and entry count
+        assert_eq!(hirc_types.total() + 12, hirc.bytes.total());
+        assert_eq!(hirc_types.raw, hirc.bytes.raw);
+        let count: usize = report.hirc_types.iter().map(|t| t.count).sum();
+        assert_eq!(count, bnk.hirc_entries().count());
+        let sounds = report
+            .hirc_types
+            .iter()
+            .find(|t| t.entry_type == HircEntryType::Sound)
+            .unwrap();
+        assert_eq!(sounds.bytes.raw, 0);
+        assert!(report.total().typed_ratio() < 1.0);
+        assert!(report.to_string().contains("  Sound ("));
+
+        let input = fs::read(INPUT_DIDX_DATA).unwrap();
+        let bnk = Bnk::from_reader(&mut io::Cursor::new(&input)).unwrap();
+        let report = bnk.parse_coverage().unwrap();
+        let media: u64 = bnk.wems().map(|(_, data)| data.len() as u64).sum();
+        let data = report.sections.iter().find(|s| s.magic == "DATA").unwrap();
+        assert_eq!(data.media, media);
+        // section header and alignment padding
+        let section = bnk.sections.iter().find(|s| &s.magic == b"DATA").unwrap();
+        assert_eq!(
+            data.bytes,
+            ByteCoverage {
+                typed: 8 + section.section_length as u64 - media,
+                raw: 0
+            }
+        );
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let input = fs::read(INPUT_DIDX_DATA).unwrap();
+        let bnk = Bnk::from_reader(&mut io::Cursor::new(&input)).unwrap();
+        let fingerprint = bnk.fingerprint().unwrap();
+        assert_eq!(fingerprint, bnk.fingerprint().unwrap());
+        assert_eq!(fingerprint.sections.len(), bnk.sections.len());
+        assert_eq!(fingerprint.media.len(), 77);
+        assert!(fingerprint.hirc_entries.is_empty());
+
+        let mut patched = bnk.clone();
+        let media_id = *fingerprint.media.keys().next().unwrap();
+        patched.insert_media(media_id, vec![1, 2, 3]);
+        patched.insert_media(1, vec![4]);
+        let diff = fingerprint.diff(&patched.fingerprint().unwrap());
+        assert_eq!(diff.changed_media, vec![media_id]);
+        assert_eq!(diff.added_media, vec![1]);
+        assert!(diff.removed_media.is_empty() && diff.changed_entries.is_empty());
+
+        let input = fs::read(INPUT_HIRC).unwrap();
+        let bnk = Bnk::from_reader(&mut io::Cursor::new(&input)).unwrap();
+        let fingerprint = bnk.fingerprint().unwrap();
+        assert_eq!(fingerprint.hirc_entries.len(), bnk.hirc_entries().count());
+        let mut renamed = bnk.clone();
+        let event_id = renamed.rename_event(
+            bnk.hirc_entries()
+                .find(|e| matches!(e.payload, HircEntryPayload::Event { .. }))
+                .unwrap()
+                .id,
+            "Play_Renamed",
+        );
+        let diff = fingerprint.diff(&renamed.fingerprint().unwrap());
+        assert_eq!(diff.added_entries, vec![event_id.unwrap()]);
+        assert_eq!(diff.removed_entries.len(), 1);
+        assert!(fingerprint.diff(&fingerprint).is_empty());
+    }
+
+    #[test]
+    fn test_bnk_view() {
+        let input = fs::read(INPUT_DIDX_DATA).unwrap();
+        let bnk = Bnk::from_reader(&mut io::Cursor::new(&input)).unwrap();
+        let view = BnkView::from_bytes(&input).unwrap();
+        assert_eq!(view.sections.len(), bnk.sections.len());
+        assert_eq!(view.bank_id(), bnk.bank_id());
+
+        let media: Vec<MediaView> = view.media().collect();
+        assert_eq!(media.len(), 77);
+        assert!(matches!(media[0].data, Cow::Borrowed(_)));
+        for (view_media, bnk_media) in media.iter().zip(bnk.media_views()) {
+            assert_eq!(view_media.id, bnk_media.id);
+            assert_eq!(view_media.data, bnk_media.data);
+        }
+        let id = media[5].id;
+        assert_eq!(view.media_by_id(id).unwrap().data, media[5].data);
+
+        let owned = view.clone().into_owned();
+        assert!(matches!(owned.sections[0].data, Cow::Owned(_)));
+        assert_eq!(owned.media().count(), 77);
+        let parsed = owned.to_bnk().unwrap();
+        assert_eq!(parsed.media_views().count(), 77);
+
+        assert!(BnkView::from_bytes(&input[..input.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_transaction() {
+        let input = fs::read(INPUT_DIDX_DATA).unwrap();
+        let mut bnk = Bnk::from_reader(&mut io::Cursor::new(&input)).unwrap();
+        let media_count = bnk.media_views().count();
+        let wem = bnk.media_views().next().unwrap().data.into_owned();
+
+        // dropped without commit
+        {
+            let mut transaction = bnk.transaction();
+            transaction.insert_media(1, wem.clone());
+            assert_eq!(transaction.media_views().count(), media_count + 1);
+        }
+        assert_eq!(bnk.media_views().count(), media_count);
+
+        let mut transaction = bnk.transaction();
+        transaction.insert_media(1, wem.clone());
+        transaction.rollback();
+        assert_eq!(bnk.media_views().count(), media_count);
+
+        // invalid media fails validation
+        let mut transaction = bnk.transaction();
+        transaction.insert_media(1, wem.clone());
+        transaction.insert_media(2, vec![0; 16]);
+        let Err(BnkError::Validation(issues)) = transaction.commit() else {
+            panic!("expected validation error");
+        };
+        assert_eq!(
+            issues,
+            vec![
+                ValidationIssue::InvalidMedia {
+                    media_id: 2,
+                    problem: MediaProblem::NotRiff
+                },
+                ValidationIssue::UnreferencedMedia { media_id: 1 },
+                ValidationIssue::UnreferencedMedia { media_id: 2 },
+            ]
+        );
+        assert_eq!(bnk.media_views().count(), media_count);
+
+        // unreferenced media is already reported for this media bank
+        let media_id = bnk.media_views().next().unwrap().id;
+        let mut transaction = bnk.transaction();
+        transaction.insert_media(media_id, wem[..wem.len() / 2].to_vec());
+        assert!(matches!(
+            transaction.commit(),
+            Err(BnkError::Validation(issues)) if issues.len() == 1
+        ));
+        let mut transaction = bnk.transaction();
+        transaction.insert_media(media_id, wem.clone());
+        transaction.commit().unwrap();
+    }
+
+    #[cfg(feature = "manifest")]
+    #[test]
+    fn test_from_manifest() {
+        let objects = Bnk::from_reader(&mut File::open(INPUT_HIRC).unwrap()).unwrap();
+        let media = Bnk::from_reader(&mut File::open(INPUT_DIDX_DATA).unwrap()).unwrap();
+        let sound = objects
+            .hirc_entries()
+            .find(|entry| {
+                entry.payload.sources().iter().any(|source| {
+                    source.stream_type() == Some(AkBankSourceStreamType::DataBnk)
+                        && media.media(source.source_id()).is_some()
+                })
+            })
+            .unwrap();
+        let media_id = sound.payload.sources()[0].source_id();
+
+        let dir =
+            std::env::temp_dir().join(format!("re-sound-bnk-manifest-{}", std::process::id()));
+        fs::create_dir_all(dir.join("wem")).unwrap();
+        fs::write(
+            dir.join(format!("wem/{media_id}.wem")),
+            media.media(media_id).unwrap(),
+        )
+        .unwrap();
+        let mut object = serde_json::to_value(sound).unwrap();
+        object.as_object_mut().unwrap().remove("length");
+        let manifest = serde_json::json!({
+            "bank": { "name": "Wp00_Custom" },
+            "media": "wem",
+            "objects": [object],
+        });
+        fs::write(dir.join("bank.json"), manifest.to_string()).unwrap();
+
+        let bnk = Bnk::from_manifest(dir.join("bank.json")).unwrap();
+        assert_eq!(bnk.bank_id(), Some(hash::short_id("Wp00_Custom")));
+        assert_eq!(bnk.language_id(), Some(hash::short_id("SFX")));
+        assert_eq!(bnk.media(media_id), media.media(media_id));
+        let mut output = io::Cursor::new(Vec::new());
+        bnk.write_to(&mut output).unwrap();
+        output.set_position(0);
+        let bnk = Bnk::from_reader(&mut output).unwrap();
+        let entry = bnk.hirc_entries().next().unwrap();
+        assert_eq!(entry.to_bytes().unwrap(), sound.to_bytes().unwrap());
+
+        // media missing from the folder
+        fs::remove_file(dir.join(format!("wem/{media_id}.wem"))).unwrap();
+        assert!(matches!(
+            Bnk::from_manifest(dir.join("bank.json")),
+            Err(BnkError::Manifest(_))
+        ));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_read_next() {
+        let mut input = fs::read(INPUT_HIRC).unwrap();
+        let first_len = input.len() as u64;
+        input.extend(fs::read(INPUT_DIDX_DATA).unwrap());
+        let mut reader = io::Cursor::new(&input);
+
+        let first = Bnk::read_next(&mut reader).unwrap().unwrap();
+        assert_eq!(reader.position(), first_len);
+        let second = Bnk::read_next(&mut reader).unwrap().unwrap();
+        assert!(Bnk::read_next(&mut reader).unwrap().is_none());
+        assert!(first.check_id("Wp00_Cmn"));
+        assert!(second.check_id("Wp00_Cmn_m"));
+
+        let second = Bnk::from_reader_at(&mut reader, first_len).unwrap();
+        assert!(second.check_id("Wp00_Cmn_m"));
+
+        // section lengths must match the parsed sections, not run into the
+        // following bank or stop inside this one
+        let hirc = input.windows(4).position(|magic| magic == b"HIRC").unwrap();
+        let length = u32::from_le_bytes(input[hirc + 4..hirc + 8].try_into().unwrap());
+        for length in [length + 16, length - 16] {
+            let mut data = input.clone();
+            data[hirc + 4..hirc + 8].copy_from_slice(&length.to_le_bytes());
+            let mut reader = io::Cursor::new(&data);
+            assert!(matches!(
+                Bnk::read_next(&mut reader),
+                Err(BnkError::BadDataSize { .. })
+            ));
+            assert!(matches!(
+                Bnk::from_reader_at(&mut reader, 0),
+                Err(BnkError::BadDataSize { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_strip_media() {
+        let input = fs::read(INPUT_DIDX_DATA).unwrap();
+        let mut bnk = Bnk::from_reader(&mut io::Cursor::new(&input)).unwrap();
+        let removed = bnk.strip_media(StripMediaMode::Placeholder);
+        assert!(!removed.is_empty());
+        assert!(
+            removed
+                .iter()
+                .all(|(id, data)| *id != 0 && !data.is_empty())
+        );
+
+        let mut output = Vec::new();
+        bnk.write_to(&mut io::Cursor::new(&mut output)).unwrap();
+        let stripped = Bnk::from_reader(&mut io::Cursor::new(&output)).unwrap();
+        assert!(stripped.sections.iter().any(|sec| matches!(
+            &sec.payload,
+            SectionPayload::Data { data_list } if data_list.iter().all(Vec::is_empty)
+        )));
+
+        let mut bnk = Bnk::from_reader(&mut io::Cursor::new(&input)).unwrap();
+        bnk.strip_media(StripMediaMode::RemoveAll);
+        assert_eq!(bnk.sections.len(), 1);
+    }
+
+    #[test]
+    fn test_zero_length_media() {
+        let input = fs::read(INPUT_DIDX_DATA).unwrap();
+        let mut bnk = Bnk::from_reader(&mut io::Cursor::new(&input)).unwrap();
+        let ids: Vec<u32> = bnk.wems().map(|(id, _)| id).collect();
+        assert!(ids.len() > 2);
+        // empty the first and the last media
+        for section in &mut bnk.sections {
+            if let SectionPayload::Data { data_list } = &mut section.payload {
+                data_list[0].clear();
+                data_list.last_mut().unwrap().clear();
+            }
+        }
+
+        let mut output = Vec::new();
+        bnk.write_to(&mut io::Cursor::new(&mut output)).unwrap();
+        let bnk = Bnk::from_reader(&mut io::Cursor::new(&output)).unwrap();
+        let wems: Vec<(u32, &[u8])> = bnk.wems().collect();
+        assert_eq!(wems.iter().map(|(id, _)| *id).collect::<Vec<_>>(), ids);
+        assert!(wems[0].1.is_empty() && wems.last().unwrap().1.is_empty());
+        assert!(!wems[1].1.is_empty());
+        assert_eq!(bnk.media(ids[0]), Some(&[][..]));
+        assert!(
+            !bnk.validate()
+                .iter()
+                .any(|issue| matches!(issue, ValidationIssue::InvalidMedia { .. }))
+        );
+        let view = BnkView::from_bytes(&output).unwrap();
+        assert_eq!(view.media().count(), ids.len());
+
+        // placeholders without a DATA section
+        let mut bnk = Bnk::from_reader(&mut io::Cursor::new(&input)).unwrap();
+        bnk.strip_media(StripMediaMode::Placeholder);
+        bnk.sections.retain(|sec| &sec.magic != b"DATA");
+        let mut output = Vec::new();
+        bnk.write_to(&mut io::Cursor::new(&mut output)).unwrap();
+        let bnk = Bnk::from_reader(&mut io::Cursor::new(&output)).unwrap();
+        assert_eq!(bnk.wems().count(), ids.len());
+        assert!(bnk.wems().all(|(_, data)| data.is_empty()));
+    }
+
+    #[test]
+    fn test_insert_media_without_data() {
+        let input = fs::read(INPUT_DIDX_DATA).unwrap();
+        let bnk = Bnk::from_reader(&mut io::Cursor::new(&input)).unwrap();
+        let ids: Vec<u32> = bnk.wem_ids().collect();
+        let new_id = ids.last().unwrap() + 1;
+        let check = |bnk: &Bnk| {
+            assert_eq!(bnk.data().unwrap().len(), bnk.didx().unwrap().len());
+            let bnk = reread_bank(bnk);
+            assert_eq!(bnk.media(ids[1]), Some(&[1; 4][..]));
+            assert_eq!(bnk.media(new_id), Some(&[2; 4][..]));
+        };
+
+        // DIDX kept without DATA, and placeholders without DATA
+        let mut stripped = bnk.clone();
+        stripped.strip_media(StripMediaMode::KeepDidx);
+        let mut placeholders = bnk.clone();
+        placeholders.strip_media(StripMediaMode::Placeholder);
+        placeholders.sections.retain(|sec| &sec.magic != b"DATA");
+        let placeholders = reread_bank(&placeholders);
+        for mut bnk in [stripped, placeholders] {
+            assert_eq!(bnk.insert_media(ids[1], vec![1; 4]), Some(vec![]));
+            assert_eq!(bnk.insert_media(new_id, vec![2; 4]), None);
+            check(&bnk);
+        }
+
+        // DATA before DIDX
+        let mut bnk = bnk.clone();
+        let didx = bnk
+            .sections
+            .iter()
+            .position(|s| &s.magic == b"DIDX")
+            .unwrap();
+        let data = bnk
+            .sections
+            .iter()
+            .position(|s| &s.magic == b"DATA")
+            .unwrap();
+        bnk.sections.swap(didx, data);
+        assert!(bnk.insert_media(ids[1], vec![1; 4]).is_some());
+        assert_eq!(bnk.insert_media(new_id, vec![2; 4]), None);
+        bnk.sections.swap(didx, data);
+        check(&bnk);
+    }
+
     #[test]
     #[ignore]
     fn test_on_all_files() {
@@ -0,0 +1,203 @@
+use std::{fmt, io};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::{Bnk, Result, hirc::HircEntryType};
+
+/// Options of [Bnk::from_reader_with].
+#[derive(Debug, Clone)]
+pub struct BnkParseOptions {
+    /// Fail on HIRC entries not matching the known layouts.
+    ///
+    /// Without it, entries parsing fewer bytes than they hold keep the
+    /// remaining bytes in [HircEntry::trailing](super::hirc::HircEntry::trailing)
+    /// and entries failing to parse are kept raw as
+    /// [HircEntryPayload::Unknown](super::hirc::HircEntryPayload::Unknown).
+    pub strict: bool,
+    /// Largest section accepted, in bytes, larger sections fail the parse
+    /// in both modes instead of being allocated.
+    pub max_section_size: Option<u32>,
+    /// Most entries accepted in a HIRC section.
+    pub max_hirc_entries: Option<u32>,
+    /// Record a [ParseWarning] for each entry not parsed as is.
+    pub collect_warnings: bool,
+}
+
+impl Default for BnkParseOptions {
+    /// Strict parsing, as [Bnk::from_reader].
+    fn default() -> Self {
+        BnkParseOptions {
+            strict: true,
+            max_section_size: None,
+            max_hirc_entries: None,
+            collect_warnings: false,
+        }
+    }
+}
+
+/// Limits of [Bnk::parse_untrusted].
+#[derive(Debug, Clone)]
+pub struct ParseLimits {
+    /// See [BnkParseOptions::max_section_size].
+    pub max_section_size: u32,
+    /// See [BnkParseOptions::max_hirc_entries].
+    pub max_hirc_entries: u32,
+}
+
+impl Default for ParseLimits {
+    /// 256 MiB sections of at most 1M entries.
+    fn default() -> Self {
+        ParseLimits {
+            max_section_size: 256 << 20,
+            max_hirc_entries: 1 << 20,
+        }
+    }
+}
+
+/// A HIRC entry not parsed as is, see [BnkParseOptions::collect_warnings].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ParseWarning {
+    /// Entry kept raw, its layout is unknown.
+    RawEntry {
+        entry_type: HircEntryType,
+        id: u32,
+        /// Start of the entry payload in the reader.
+        offset: u64,
+        error: String,
+    },
+    /// Entry whose payload parsed fewer bytes than it holds.
+    TrailingBytes {
+        entry_type: HircEntryType,
+        id: u32,
+        offset: u64,
+        length: u64,
+    },
+}
+
+/// A section or HIRC entry about to be parsed, see
+/// [Bnk::from_reader_with_diagnostics].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Diagnostic {
+    /// Magic of the section, of the HIRC section for entries.
+    pub magic: [u8; 4],
+    /// Type and id of HIRC entries, `None` for sections.
+    pub entry: Option<(HircEntryType, u32)>,
+    /// Absolute offset of the object in the reader.
+    pub offset: u64,
+    /// Length of the object as stored, without its header.
+    pub length: u32,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", String::from_utf8_lossy(&self.magic))?;
+        if let Some((entry_type, id)) = self.entry {
+            write!(f, " {entry_type:?} {id}")?;
+        }
+        write!(f, " at {:#x} ({} bytes)", self.offset, self.length)
+    }
+}
+
+/// Options, warnings and diagnostics of a parse in progress.
+pub(super) struct ParseState<'a> {
+    pub options: &'a BnkParseOptions,
+    pub warnings: Vec<ParseWarning>,
+    /// End of the section being read, entries can't extend past it.
+    pub section_end: Option<u64>,
+    diagnostics: Option<&'a mut dyn FnMut(&Diagnostic)>,
+}
+
+impl<'a> ParseState<'a> {
+    pub fn new(options: &'a BnkParseOptions) -> Self {
+        ParseState {
+            options,
+            warnings: Vec::new(),
+            section_end: None,
+            diagnostics: None,
+        }
+    }
+
+    pub fn warn(&mut self, warning: ParseWarning) {
+        if self.options.collect_warnings {
+            self.warnings.push(warning);
+        }
+    }
+
+    pub fn has_diagnostics(&self) -> bool {
+        self.diagnostics.is_some()
+    }
+
+    pub fn diagnose(&mut self, diagnostic: Diagnostic) {
+        if let Some(on_object) = &mut self.diagnostics {
+            on_object(&diagnostic);
+        }
+    }
+}
+
+impl Bnk {
+    /// Read a bank with `options`, returns the bank and the warnings
+    /// collected.
+    pub fn from_reader_with<R>(
+        reader: &mut R,
+        options: &BnkParseOptions,
+    ) -> Result<(Self, Vec<ParseWarning>)>
+    where
+        R: io::Read + io::Seek,
+    {
+        let mut state = ParseState::new(options);
+        let bnk = Self::read_sections(reader, false, &mut state, None)?;
+        Ok((bnk, state.warnings))
+    }
+
+    /// Read a bank with `options`, calling `on_object` before parsing each
+    /// section and HIRC entry.
+    ///
+    /// When the parse fails, the last object passed to `on_object` is the
+    /// one that broke.
+    pub fn from_reader_with_diagnostics<R, F>(
+        reader: &mut R,
+        options: &BnkParseOptions,
+        mut on_object: F,
+    ) -> Result<(Self, Vec<ParseWarning>)>
+    where
+        R: io::Read + io::Seek,
+        F: FnMut(&Diagnostic),
+    {
+        let mut state = ParseState::new(options);
+        state.diagnostics = Some(&mut on_object);
+        let bnk = Self::read_sections(reader, false, &mut state, None)?;
+        Ok((bnk, state.warnings))
+    }
+
+    /// Parse a bank from untrusted bytes, e.g. user uploads.
+    ///
+    /// Lengths and counts are checked against `limits` and the input before
+    /// allocating, malformed input fails with an error.
+    pub fn parse_untrusted(bytes: &[u8], limits: &ParseLimits) -> Result<Self> {
+        let options = BnkParseOptions {
+            max_section_size: Some(limits.max_section_size),
+            max_hirc_entries: Some(limits.max_hirc_entries),
+            ..Default::default()
+        };
+        Ok(Self::from_reader_with(&mut io::Cursor::new(bytes), &options)?.0)
+    }
+
+    /// Read a bank of a version whose layouts differ from the known ones,
+    /// see [BnkParseOptions::strict].
+    ///
+    /// The bank still round-trips, but the typed fields of entries with
+    /// trailing bytes may be misread, edit them with care.
+    pub fn from_reader_tolerant<R>(reader: &mut R) -> Result<Self>
+    where
+        R: io::Read + io::Seek,
+    {
+        let options = BnkParseOptions {
+            strict: false,
+            ..Default::default()
+        };
+        Ok(Self::from_reader_with(reader, &options)?.0)
+    }
+}
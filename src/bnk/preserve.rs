@@ -0,0 +1,195 @@
+use std::{
+    collections::HashMap,
+    hash::{DefaultHasher, Hasher},
+    io::{self, Read, Seek, Write},
+};
+
+use byteorder::{LE, WriteBytesExt};
+
+use super::{Bnk, Result, SectionPayload};
+
+/// Original bytes of the sections and HIRC entries of a bank.
+#[derive(Debug, Clone)]
+pub(crate) struct OriginalBytes {
+    /// By section index.
+    sections: Vec<Original>,
+    /// By entry type and id.
+    hirc_entries: HashMap<(u8, u32), Original>,
+}
+
+#[derive(Debug, Clone)]
+struct Original {
+    bytes: Vec<u8>,
+    /// Hash of the serialization at load time, a different serialization
+    /// means the object was modified.
+    serialized_hash: u64,
+}
+
+fn hash_bytes(bytes: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    hasher.write(bytes);
+    hasher.finish()
+}
+
+impl Bnk {
+    /// Read a bank keeping the bytes of every section and HIRC entry.
+    ///
+    /// [Bnk::write_to] then writes the original bytes of sections and entries
+    /// that weren't modified, so the output is byte-exact even where
+    /// serialization of a structure isn't. Modifications are detected by
+    /// comparing the serialization with the one at load time.
+    pub fn from_reader_preserving<R>(reader: &mut R) -> Result<Self>
+    where
+        R: Read + Seek,
+    {
+        let start = reader.stream_position()?;
+        let mut bnk = Self::from_reader(reader)?;
+        let end = reader.stream_position()?;
+        let mut bytes = vec![0; (end - start) as usize];
+        reader.seek(io::SeekFrom::Start(start))?;
+        reader.read_exact(&mut bytes)?;
+        reader.seek(io::SeekFrom::Start(end))?;
+
+        bnk.original = Some(Box::new(OriginalBytes::new(&bnk, &bytes)?));
+        Ok(bnk)
+    }
+
+    /// Whether the bank keeps its original bytes, see
+    /// [Bnk::from_reader_preserving].
+    pub fn preserves_original_bytes(&self) -> bool {
+        self.original.is_some()
+    }
+
+    /// Drop the original bytes, later writes serialize every section.
+    pub fn discard_original_bytes(&mut self) {
+        self.original = None;
+    }
+
+    /// Serialize every section as written by [Bnk::write_to].
+    pub(super) fn serialize_sections(&self) -> Result<Vec<Vec<u8>>> {
+        let didx_entries = self.fixed_didx_entries()?;
+        let mut serialized = Vec::with_capacity(self.sections.len());
+        for section in &self.sections {
+            let mut writer = io::Cursor::new(Vec::new());
+            section.write_to(&mut writer, didx_entries.as_deref())?;
+            serialized.push(writer.into_inner());
+        }
+        Ok(serialized)
+    }
+
+    /// Write unmodified sections and HIRC entries from their original bytes.
+    pub(super) fn write_with_original<W>(
+        &self,
+        writer: &mut W,
+        original: &OriginalBytes,
+    ) -> Result<()>
+    where
+        W: Write + Seek,
+    {
+        let serialized = self.serialize_sections()?;
+        let mut unchanged: Vec<bool> = serialized
+            .iter()
+            .enumerate()
+            .map(|(i, bytes)| {
+                original
+                    .sections
+                    .get(i)
+                    .is_some_and(|o| o.serialized_hash == hash_bytes(bytes))
+            })
+            .collect();
+        // DIDX offsets follow the DATA layout, both are kept or rewritten
+        let media_sections: Vec<usize> = self
+            .sections
+            .iter()
+            .enumerate()
+            .filter(|(_, sec)| {
+                matches!(
+                    sec.payload,
+                    SectionPayload::Didx { .. } | SectionPayload::Data { .. }
+                )
+            })
+            .map(|(i, _)| i)
+            .collect();
+        if media_sections.iter().any(|&i| !unchanged[i]) {
+            for i in media_sections {
+                unchanged[i] = false;
+            }
+        }
+
+        for (i, section) in self.sections.iter().enumerate() {
+            if unchanged[i] {
+                writer.write_all(&original.sections[i].bytes)?;
+                continue;
+            }
+            let SectionPayload::Hirc { entries } = &section.payload else {
+                writer.write_all(&serialized[i])?;
+                continue;
+            };
+            // keep the unmodified entries of a modified HIRC section
+            let mut data = Vec::new();
+            data.write_u32::<LE>(entries.len() as u32)?;
+            for entry in entries {
+                let bytes = entry.to_bytes()?;
+                let key = (entry.entry_type.as_u8(), entry.id);
+                match original.hirc_entries.get(&key) {
+                    Some(o) if o.serialized_hash == hash_bytes(&bytes) => {
+                        data.extend_from_slice(&o.bytes)
+                    }
+                    _ => data.extend(bytes),
+                }
+            }
+            writer.write_all(&section.magic)?;
+            writer.write_u32::<LE>(data.len() as u32)?;
+            writer.write_all(&data)?;
+        }
+        Ok(())
+    }
+}
+
+impl OriginalBytes {
+    /// Split the bytes of `bnk` into its sections and HIRC entries.
+    fn new(bnk: &Bnk, bytes: &[u8]) -> Result<Self> {
+        let u32_at = |pos: usize| -> Result<u32> {
+            let value = bytes.get(pos..pos + 4).ok_or_else(|| {
+                io::Error::new(io::ErrorKind::UnexpectedEof, "section out of range")
+            })?;
+            Ok(u32::from_le_bytes(value.try_into().unwrap()))
+        };
+
+        // serialization the writes are compared with
+        let serialized = bnk.serialize_sections()?;
+
+        let mut sections = Vec::with_capacity(bnk.sections.len());
+        let mut hirc_entries = HashMap::new();
+        let mut pos = 0;
+        for (section, serialized) in bnk.sections.iter().zip(&serialized) {
+            let end = pos + 8 + u32_at(pos + 4)? as usize;
+            let section_bytes = &bytes[pos..end];
+            sections.push(Original {
+                bytes: section_bytes.to_vec(),
+                serialized_hash: hash_bytes(serialized),
+            });
+
+            if let SectionPayload::Hirc { entries } = &section.payload {
+                // count, then type, length and data of each entry
+                let mut entry_pos = pos + 12;
+                for entry in entries {
+                    let entry_end = entry_pos + 5 + u32_at(entry_pos + 1)? as usize;
+                    hirc_entries.insert(
+                        (entry.entry_type.as_u8(), entry.id),
+                        Original {
+                            bytes: bytes[entry_pos..entry_end].to_vec(),
+                            serialized_hash: hash_bytes(&entry.to_bytes()?),
+                        },
+                    );
+                    entry_pos = entry_end;
+                }
+            }
+            pos = end;
+        }
+        Ok(OriginalBytes {
+            sections,
+            hirc_entries,
+        })
+    }
+}
@@ -0,0 +1,105 @@
+use std::{borrow::Cow, fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    Bnk, DATA_ALIGNMENT, Result, Section, SectionPayload,
+    blob::{self, BlobEncoded, BlobEncoding},
+};
+
+/// Structured part of a project, next to the media folder.
+const PROJECT_FILE: &str = "bank.json";
+/// Folder of the `<id>.wem` files of a project.
+const MEDIA_DIR: &str = "wem";
+
+#[derive(Serialize)]
+struct JsonProjectRef<'a> {
+    data_alignment: u32,
+    sections: Vec<Cow<'a, Section>>,
+}
+
+#[derive(Deserialize)]
+struct JsonProject {
+    #[serde(default = "default_alignment")]
+    data_alignment: u32,
+    sections: Vec<Section>,
+}
+
+fn default_alignment() -> u32 {
+    DATA_ALIGNMENT
+}
+
+impl Bnk {
+    /// Write the bank as a project folder to edit by hand:
+    ///
+    /// - `bank.json`, the sections and HIRC entries in their serialized form,
+    ///   with the DATA section left empty and small blobs as `{"hex": ...}`.
+    ///   Blobs describe their encoding, so the file can be read as a [Bnk]
+    ///   by any serde deserializer.
+    /// - `wem/<id>.wem`, the embedded media, as written by [Bnk::extract_all].
+    ///
+    /// [Bnk::from_json_project] reads the folder back into the same bank,
+    /// resolving `{"file": "<name>"}` blobs against the project folder.
+    pub fn to_json_project(&self, dir: impl AsRef<Path>) -> Result<()> {
+        let dir = dir.as_ref();
+        self.extract_all(dir.join(MEDIA_DIR))?;
+        let project = JsonProjectRef {
+            data_alignment: self.data_alignment,
+            sections: self
+                .sections
+                .iter()
+                .map(|section| match &section.payload {
+                    SectionPayload::Data { .. } => Cow::Owned(Section {
+                        magic: section.magic,
+                        section_length: section.section_length,
+                        payload: SectionPayload::Data { data_list: vec![] },
+                    }),
+                    _ => Cow::Borrowed(section),
+                })
+                .collect(),
+        };
+        crate::utils::write_file_atomic(&dir.join(PROJECT_FILE), |writer| {
+            serde_json::to_writer_pretty(
+                &mut *writer,
+                &BlobEncoded::new(&project, &BlobEncoding::Hex),
+            )
+            .map_err(io::Error::other)?;
+            Ok(())
+        })
+    }
+
+    /// Read a project folder written by [Bnk::to_json_project].
+    ///
+    /// Media is read from `wem/<id>.wem` for every DIDX entry, only
+    /// zero-length placeholders may be missing. Unchanged projects write the
+    /// bank they were exported from byte for byte.
+    pub fn from_json_project(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref();
+        let path = dir.join(PROJECT_FILE);
+        let text = fs::read_to_string(&path)?;
+        let project: JsonProject = blob::from_json_with_blob_dir(&text, dir).map_err(|e| {
+            io::Error::new(e.kind(), format!("invalid project {}: {e}", path.display()))
+        })?;
+
+        let mut bnk = Bnk::from_sections(project.sections);
+        bnk.set_data_alignment(project.data_alignment);
+        let didx_entries = bnk.didx().unwrap_or_default().to_vec();
+        if let Some(data_list) = bnk.data_mut() {
+            data_list.clear();
+            for entry in &didx_entries {
+                let path = dir.join(MEDIA_DIR).join(format!("{}.wem", entry.id));
+                let data = match fs::read(&path) {
+                    Ok(data) => data,
+                    Err(e) if e.kind() == io::ErrorKind::NotFound && entry.length == 0 => vec![],
+                    Err(e) => {
+                        return Err(
+                            io::Error::new(e.kind(), format!("{}: {e}", path.display())).into()
+                        );
+                    }
+                };
+                data_list.push(data);
+            }
+        }
+        Ok(bnk)
+    }
+}
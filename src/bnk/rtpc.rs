@@ -0,0 +1,64 @@
+use std::collections::BTreeMap;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::{Bnk, SectionPayload};
+
+/// An RTPC curve attached to a HIRC object.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RtpcReference {
+    pub object_id: u32,
+    pub curve_id: u32,
+    /// Type of the game parameter, `AkRtpcType`.
+    pub rtpc_type: u8,
+    /// Id of the property driven by the curve.
+    pub param_id: u8,
+    /// Range of game parameter values covered by the curve.
+    pub input_range: (f32, f32),
+    /// Range of property values produced by the curve.
+    pub output_range: (f32, f32),
+}
+
+impl Bnk {
+    /// List every RTPC used by parsed HIRC objects, keyed by RTPC id.
+    pub fn rtpc_usage(&self) -> BTreeMap<u32, Vec<RtpcReference>> {
+        let mut usage: BTreeMap<u32, Vec<RtpcReference>> = BTreeMap::new();
+        let entries = self.sections.iter().filter_map(|sec| match &sec.payload {
+            SectionPayload::Hirc { entries } => Some(entries),
+            _ => None,
+        });
+        for entry in entries.flatten() {
+            let Some(node_base_params) = entry.payload.node_base_params() else {
+                continue;
+            };
+            for curve in node_base_params.initial_rtpc().curves() {
+                let points = curve.points();
+                let input_range = range(points.iter().map(|p| p.from));
+                let output_range = range(points.iter().map(|p| p.to));
+                usage
+                    .entry(curve.rtpc_id())
+                    .or_default()
+                    .push(RtpcReference {
+                        object_id: entry.id,
+                        curve_id: curve.rtpc_curve_id(),
+                        rtpc_type: curve.rtpc_type(),
+                        param_id: curve.param_id(),
+                        input_range,
+                        output_range,
+                    });
+            }
+        }
+        usage
+    }
+}
+
+fn range(values: impl Iterator<Item = f32>) -> (f32, f32) {
+    values
+        .fold(None, |range: Option<(f32, f32)>, v| match range {
+            Some((min, max)) => Some((min.min(v), max.max(v))),
+            None => Some((v, v)),
+        })
+        .unwrap_or_default()
+}
@@ -0,0 +1,61 @@
+use binrw::binrw;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Bank names (STID section), mapping bank ids to the names of the banks.
+#[binrw]
+#[brw(little)]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StringTable {
+    /// 1 for bank names.
+    pub string_type: u32,
+    #[br(temp)]
+    #[bw(calc = entries.len() as u32)]
+    num_entries: u32,
+    #[br(count = num_entries)]
+    pub entries: Vec<StidEntry>,
+}
+
+impl StringTable {
+    pub fn name(&self, id: u32) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|entry| entry.id == id)
+            .map(|entry| entry.name.as_str())
+    }
+
+    /// Set the name of an id, adding an entry if needed.
+    pub fn set_name(&mut self, id: u32, name: &str) {
+        match self.entries.iter_mut().find(|entry| entry.id == id) {
+            Some(entry) => entry.name = name.to_string(),
+            None => self.entries.push(StidEntry {
+                id,
+                name: name.to_string(),
+            }),
+        }
+    }
+
+    /// Replace the id and name of an entry.
+    pub(super) fn rebase(&mut self, old_id: u32, new_id: u32, name: &str) {
+        for entry in self.entries.iter_mut().filter(|entry| entry.id == old_id) {
+            entry.id = new_id;
+            entry.name = name.to_string();
+        }
+    }
+}
+
+#[binrw]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StidEntry {
+    pub id: u32,
+    #[br(temp)]
+    #[bw(try_calc = u8::try_from(name.len()))]
+    name_len: u8,
+    /// At most 255 bytes.
+    #[br(count = name_len, try_map = String::from_utf8)]
+    #[bw(map = |name: &String| name.as_bytes().to_vec())]
+    pub name: String,
+}
@@ -0,0 +1,144 @@
+use binrw::binrw;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::hirc::AkRTPCGraphPoint;
+
+/// Global settings (STMG section), only present in the init bank.
+#[binrw]
+#[brw(little)]
+#[br(import { version: u32 })]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GlobalSettings {
+    #[br(if(version > 140))]
+    pub filter_behavior: Option<u16>,
+    pub volume_threshold: f32,
+    pub max_num_voices_limit_internal: u16,
+    pub max_num_dangerous_virt_voices_limit_internal: u16,
+    #[br(temp)]
+    #[bw(calc = state_groups.len() as u32)]
+    num_state_groups: u32,
+    #[br(count = num_state_groups)]
+    pub state_groups: Vec<StmgStateGroup>,
+    #[br(temp)]
+    #[bw(calc = switch_groups.len() as u32)]
+    num_switch_groups: u32,
+    #[br(count = num_switch_groups)]
+    pub switch_groups: Vec<StmgSwitchGroup>,
+    /// RTPC ramping and acoustic textures, not parsed yet.
+    #[br(parse_with = binrw::helpers::until_eof)]
+    #[cfg_attr(feature = "serde", serde(with = "super::blob"))]
+    pub data: Vec<u8>,
+}
+
+impl GlobalSettings {
+    pub fn state_group(&self, id: u32) -> Option<&StmgStateGroup> {
+        self.state_groups.iter().find(|group| group.id == id)
+    }
+
+    pub fn state_group_mut(&mut self, id: u32) -> Option<&mut StmgStateGroup> {
+        self.state_groups.iter_mut().find(|group| group.id == id)
+    }
+
+    pub fn switch_group(&self, id: u32) -> Option<&StmgSwitchGroup> {
+        self.switch_groups.iter().find(|group| group.id == id)
+    }
+}
+
+#[binrw]
+#[brw(little)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StmgStateGroup {
+    pub id: u32,
+    pub default_transition_time: i32,
+    #[br(temp)]
+    #[bw(calc = transitions.len() as u32)]
+    num_transitions: u32,
+    #[br(count = num_transitions)]
+    pub transitions: Vec<AkStateTransition>,
+}
+
+impl StmgStateGroup {
+    /// Set the transition time in milliseconds used when no custom transition matches.
+    pub fn set_default_transition_time(&mut self, time: i32) {
+        self.default_transition_time = time;
+    }
+
+    /// Transition time in milliseconds from one state to another.
+    ///
+    /// Custom transitions are matched exactly first, then with "any state" (0)
+    /// on either side, falling back to the default transition time.
+    pub fn transition_time(&self, state_from: u32, state_to: u32) -> i32 {
+        [
+            (state_from, state_to),
+            (state_from, 0),
+            (0, state_to),
+            (0, 0),
+        ]
+        .iter()
+        .find_map(|&(from, to)| {
+            self.transitions
+                .iter()
+                .find(|t| t.state_from == from && t.state_to == to)
+        })
+        .map_or(self.default_transition_time, |t| t.transition_time)
+    }
+
+    /// Set the time of a custom transition, adding it if it does not exist.
+    pub fn set_transition(&mut self, state_from: u32, state_to: u32, time: i32) {
+        match self
+            .transitions
+            .iter_mut()
+            .find(|t| t.state_from == state_from && t.state_to == state_to)
+        {
+            Some(transition) => transition.transition_time = time,
+            None => self.transitions.push(AkStateTransition {
+                state_from,
+                state_to,
+                transition_time: time,
+            }),
+        }
+    }
+
+    /// Remove a custom transition, returning its time.
+    pub fn remove_transition(&mut self, state_from: u32, state_to: u32) -> Option<i32> {
+        let idx = self
+            .transitions
+            .iter()
+            .position(|t| t.state_from == state_from && t.state_to == state_to)?;
+        Some(self.transitions.remove(idx).transition_time)
+    }
+}
+
+#[binrw]
+#[brw(little)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AkStateTransition {
+    /// 0 for any state.
+    pub state_from: u32,
+    /// 0 for any state.
+    pub state_to: u32,
+    pub transition_time: i32,
+}
+
+/// Switch group driven by a game parameter.
+#[binrw]
+#[brw(little)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StmgSwitchGroup {
+    pub id: u32,
+    pub rtpc_id: u32,
+    pub rtpc_type: u8,
+    #[br(temp)]
+    #[bw(calc = points.len() as u32)]
+    size: u32,
+    /// Game parameter value to switch id, `to` holds the bits of the switch
+    /// id, see [f32::to_bits].
+    #[br(count = size)]
+    pub points: Vec<AkRTPCGraphPoint>,
+}
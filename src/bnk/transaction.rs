@@ -0,0 +1,78 @@
+use std::ops::{Deref, DerefMut};
+
+use super::{Bnk, BnkError, Result};
+
+/// Edits of a bank applied all at once, see [Bnk::transaction].
+///
+/// Dereferences to the bank being edited. Dropping the transaction without
+/// committing rolls the edits back.
+pub struct BnkTransaction<'a> {
+    bnk: &'a mut Bnk,
+    snapshot: Option<Bnk>,
+}
+
+impl Bnk {
+    /// Start a multi-step edit that can be rolled back as a whole.
+    pub fn transaction(&mut self) -> BnkTransaction<'_> {
+        let snapshot = self.clone();
+        BnkTransaction {
+            bnk: self,
+            snapshot: Some(snapshot),
+        }
+    }
+}
+
+impl BnkTransaction<'_> {
+    /// Keep the edits after fixing the DIDX values and validating the bank.
+    ///
+    /// The edits are rolled back if they fail or introduce validation
+    /// issues, issues the bank had before the transaction are ignored.
+    pub fn commit(mut self) -> Result<()> {
+        let snapshot = self.snapshot.take().expect("transaction not finished");
+        if let Err(e) = self.bnk.fix_values() {
+            *self.bnk = snapshot;
+            return Err(e);
+        }
+        let previous_issues = snapshot.validate();
+        let new_issues: Vec<_> = self
+            .bnk
+            .validate()
+            .into_iter()
+            .filter(|issue| !previous_issues.contains(issue))
+            .collect();
+        if !new_issues.is_empty() {
+            *self.bnk = snapshot;
+            return Err(BnkError::Validation(new_issues));
+        }
+        Ok(())
+    }
+
+    /// Restore the bank as it was before the transaction.
+    pub fn rollback(mut self) {
+        if let Some(snapshot) = self.snapshot.take() {
+            *self.bnk = snapshot;
+        }
+    }
+}
+
+impl Drop for BnkTransaction<'_> {
+    fn drop(&mut self) {
+        if let Some(snapshot) = self.snapshot.take() {
+            *self.bnk = snapshot;
+        }
+    }
+}
+
+impl Deref for BnkTransaction<'_> {
+    type Target = Bnk;
+
+    fn deref(&self) -> &Bnk {
+        self.bnk
+    }
+}
+
+impl DerefMut for BnkTransaction<'_> {
+    fn deref_mut(&mut self) -> &mut Bnk {
+        self.bnk
+    }
+}
@@ -0,0 +1,83 @@
+use std::collections::BTreeSet;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::{AkBankSourceStreamType, Bnk, SectionPayload};
+use crate::wem::{self, MediaProblem};
+
+/// A consistency problem found by [Bnk::validate].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ValidationIssue {
+    /// DIDX entry not used by any source of the bank.
+    UnreferencedMedia { media_id: u32 },
+    /// In-memory or prefetched source without a DIDX entry.
+    MissingMedia { source_id: u32, objects: Vec<u32> },
+    /// Media data that isn't a valid wem.
+    InvalidMedia {
+        media_id: u32,
+        problem: MediaProblem,
+    },
+}
+
+impl Bnk {
+    /// Check the bank for consistency problems.
+    ///
+    /// Banks are checked on their own, media loaded from another bank is
+    /// reported as missing.
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        self.validate_media(&mut issues);
+        issues
+    }
+
+    fn validate_media(&self, issues: &mut Vec<ValidationIssue>) {
+        let didx_ids: BTreeSet<u32> = self
+            .sections
+            .iter()
+            .filter_map(|sec| match &sec.payload {
+                SectionPayload::Didx { entries } => Some(entries),
+                _ => None,
+            })
+            .flatten()
+            .map(|entry| entry.id)
+            .collect();
+        let usage = self.media_usage();
+
+        let didx = self.didx();
+        let data = self.data();
+        if let (Some(didx), Some(data)) = (didx, data) {
+            // zero-length entries are placeholders
+            for (entry, media) in didx.iter().zip(data).filter(|(_, media)| !media.is_empty()) {
+                let prefetch = usage.get(&entry.id).is_some_and(|usage| {
+                    usage.stream_type == AkBankSourceStreamType::PrefetchStreaming
+                });
+                if let Some(problem) = wem::check_media(media, prefetch) {
+                    issues.push(ValidationIssue::InvalidMedia {
+                        media_id: entry.id,
+                        problem,
+                    });
+                }
+            }
+        }
+
+        issues.extend(
+            didx_ids
+                .iter()
+                .filter(|id| !usage.contains_key(id))
+                .map(|&media_id| ValidationIssue::UnreferencedMedia { media_id }),
+        );
+        issues.extend(
+            usage
+                .into_iter()
+                .filter(|(id, usage)| {
+                    usage.stream_type != AkBankSourceStreamType::Streaming && !didx_ids.contains(id)
+                })
+                .map(|(source_id, usage)| ValidationIssue::MissingMedia {
+                    source_id,
+                    objects: usage.objects,
+                }),
+        );
+    }
+}
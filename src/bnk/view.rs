@@ -0,0 +1,125 @@
+use std::{borrow::Cow, io};
+
+use super::{Bnk, BnkError, DidxEntry, Result};
+
+/// Read-only view of a bank, borrowing the section data from a buffer.
+///
+/// Only the section headers are read, so inspecting many large banks doesn't
+/// copy their media. Use [BnkView::to_bnk] to parse the bank.
+#[derive(Debug, Clone)]
+pub struct BnkView<'a> {
+    pub sections: Vec<SectionView<'a>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SectionView<'a> {
+    pub magic: [u8; 4],
+    pub data: Cow<'a, [u8]>,
+}
+
+/// Media data of a bank.
+#[derive(Debug, Clone)]
+pub struct MediaView<'a> {
+    pub id: u32,
+    pub data: Cow<'a, [u8]>,
+}
+
+impl<'a> BnkView<'a> {
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self> {
+        let mut sections = Vec::new();
+        let mut pos = 0;
+        while pos + 8 <= bytes.len() {
+            let magic: [u8; 4] = bytes[pos..pos + 4].try_into().unwrap();
+            let length = u32::from_le_bytes(bytes[pos + 4..pos + 8].try_into().unwrap()) as usize;
+            let data =
+                bytes
+                    .get(pos + 8..pos + 8 + length)
+                    .ok_or_else(|| BnkError::BadDataSize {
+                        name: String::from_utf8_lossy(&magic).into_owned(),
+                        entry: None,
+                        expected: length as u64,
+                        got: (bytes.len() - pos - 8) as u64,
+                        start: pos as u64,
+                    })?;
+            sections.push(SectionView {
+                magic,
+                data: Cow::Borrowed(data),
+            });
+            pos += 8 + length;
+        }
+        Ok(BnkView { sections })
+    }
+
+    /// View owning its data, independent of the buffer.
+    pub fn into_owned(self) -> BnkView<'static> {
+        BnkView {
+            sections: self
+                .sections
+                .into_iter()
+                .map(|section| SectionView {
+                    magic: section.magic,
+                    data: Cow::Owned(section.data.into_owned()),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn section(&self, magic: &[u8; 4]) -> Option<&SectionView<'a>> {
+        self.sections.iter().find(|section| &section.magic == magic)
+    }
+
+    pub fn bank_id(&self) -> Option<u32> {
+        let data = &self.section(b"BKHD")?.data;
+        Some(u32::from_le_bytes(data.get(4..8)?.try_into().unwrap()))
+    }
+
+    /// Iterate over the media of the DIDX and DATA sections.
+    pub fn media(&self) -> impl Iterator<Item = MediaView<'_>> {
+        let entries = self.section(b"DIDX").map(|didx| {
+            didx.data.chunks_exact(DidxEntry::SIZE).map(|entry| {
+                let word = |i: usize| u32::from_le_bytes(entry[i..i + 4].try_into().unwrap());
+                (word(0), word(4) as usize, word(8) as usize)
+            })
+        });
+        let data = self.section(b"DATA").map(|data| &*data.data);
+        entries
+            .into_iter()
+            .flatten()
+            .filter_map(move |(id, offset, length)| {
+                // placeholders don't need the DATA section
+                let data = match length {
+                    0 => &[],
+                    _ => data?.get(offset..offset + length)?,
+                };
+                Some(MediaView {
+                    id,
+                    data: Cow::Borrowed(data),
+                })
+            })
+    }
+
+    pub fn media_by_id(&self, id: u32) -> Option<MediaView<'_>> {
+        self.media().find(|media| media.id == id)
+    }
+
+    /// Parse the viewed bank.
+    pub fn to_bnk(&self) -> Result<Bnk> {
+        let mut bytes = Vec::new();
+        for section in &self.sections {
+            bytes.extend_from_slice(&section.magic);
+            bytes.extend((section.data.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(&section.data);
+        }
+        Bnk::from_reader(&mut io::Cursor::new(bytes))
+    }
+}
+
+impl Bnk {
+    /// Iterate over the media of the bank without copying it.
+    pub fn media_views(&self) -> impl Iterator<Item = MediaView<'_>> {
+        self.wems().map(|(id, data)| MediaView {
+            id,
+            data: Cow::Borrowed(data),
+        })
+    }
+}
@@ -0,0 +1,40 @@
+/// 32-bit FNV-1 hash, as used by Wwise for ShortIDs.
+pub fn fnv1_32(data: &[u8]) -> u32 {
+    let mut hash: u32 = 2166136261;
+    for &byte in data {
+        hash = hash.wrapping_mul(16777619);
+        hash ^= byte as u32;
+    }
+    hash
+}
+
+/// 64-bit FNV-1a hash, used for content fingerprints that stay stable across
+/// builds.
+pub fn fnv1a_64(data: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for &byte in data {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// Compute the Wwise ShortID of an object name.
+///
+/// Names are case-insensitive in Wwise, so the name is lowercased before hashing.
+pub fn short_id(name: &str) -> u32 {
+    fnv1_32(name.to_lowercase().as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_id() {
+        assert_eq!(fnv1_32(b""), 2166136261);
+        assert_eq!(fnv1a_64(b"a"), 0xaf63dc4c8601ec8c);
+        assert_eq!(short_id("Play_Music"), short_id("play_music"));
+        assert_eq!(short_id("SFX"), 0x1770_5D3E);
+    }
+}
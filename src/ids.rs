@@ -0,0 +1,170 @@
+//! Allocation of new object ids.
+
+use std::{
+    collections::{HashSet, hash_map::RandomState},
+    hash::BuildHasher,
+};
+
+use crate::{
+    bnk::{Bnk, BnkError, SectionPayload},
+    hash,
+};
+
+/// Generates object ids that are not used by any of the added banks.
+#[derive(Debug, Clone)]
+pub struct IdAllocator {
+    used: HashSet<u32>,
+    next_sequential: u32,
+    rng_state: u64,
+}
+
+impl Default for IdAllocator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IdAllocator {
+    /// Sequential ids start from this value, clear of the small ids used by
+    /// Wwise for built-in objects.
+    pub const DEFAULT_SEQUENTIAL_BASE: u32 = 0x1000_0000;
+
+    pub fn new() -> Self {
+        IdAllocator {
+            used: HashSet::new(),
+            next_sequential: Self::DEFAULT_SEQUENTIAL_BASE,
+            rng_state: RandomState::new().hash_one(0u32) | 1,
+        }
+    }
+
+    /// Allocator avoiding the ids of every bank, e.g. all banks of a game.
+    pub fn from_banks<'a, I>(banks: I) -> Self
+    where
+        I: IntoIterator<Item = &'a Bnk>,
+    {
+        let mut allocator = Self::new();
+        for bnk in banks {
+            allocator.add_bank(bnk);
+        }
+        allocator
+    }
+
+    /// Mark the ids of a bank as used: the bank id, HIRC objects, media and
+    /// game syncs.
+    pub fn add_bank(&mut self, bnk: &Bnk) {
+        self.used.extend(bnk.bank_id());
+        for entry in bnk.hirc_entries() {
+            self.used.insert(entry.id);
+            self.used.extend(
+                entry
+                    .payload
+                    .sources()
+                    .iter()
+                    .map(|source| source.source_id()),
+            );
+        }
+        for section in &bnk.sections {
+            if let SectionPayload::Didx { entries } = &section.payload {
+                self.used.extend(entries.iter().map(|entry| entry.id));
+            }
+        }
+        for group in bnk.game_syncs().groups() {
+            self.used.insert(group.id);
+            self.used.extend(group.values.keys());
+        }
+    }
+
+    /// Use a fixed seed for [IdAllocator::random], for reproducible output.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng_state = seed | 1;
+        self
+    }
+
+    /// Start [IdAllocator::sequential] ids from `base`.
+    pub fn with_sequential_base(mut self, base: u32) -> Self {
+        self.next_sequential = base;
+        self
+    }
+
+    pub fn is_used(&self, id: u32) -> bool {
+        id == 0 || self.used.contains(&id)
+    }
+
+    /// Mark an id as used, returns false if it already was.
+    pub fn reserve(&mut self, id: u32) -> bool {
+        id != 0 && self.used.insert(id)
+    }
+
+    /// Allocate the ShortID of a name, as Wwise does for events, buses and
+    /// game syncs.
+    pub fn named(&mut self, name: &str) -> Result<u32, BnkError> {
+        let id = hash::short_id(name);
+        if !self.reserve(id) {
+            return Err(BnkError::IdCollision(id));
+        }
+        Ok(id)
+    }
+
+    /// Allocate the next free id after the previous sequential id.
+    pub fn sequential(&mut self) -> u32 {
+        loop {
+            let id = self.next_sequential;
+            self.next_sequential = self.next_sequential.wrapping_add(1);
+            if self.reserve(id) {
+                return id;
+            }
+        }
+    }
+
+    /// Allocate a free random id.
+    pub fn random(&mut self) -> u32 {
+        loop {
+            // xorshift64*
+            self.rng_state ^= self.rng_state >> 12;
+            self.rng_state ^= self.rng_state << 25;
+            self.rng_state ^= self.rng_state >> 27;
+            let id = (self.rng_state.wrapping_mul(0x2545_F491_4F6C_DD1D) >> 32) as u32;
+            if self.reserve(id) {
+                return id;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, io};
+
+    use super::*;
+
+    #[test]
+    fn test_id_allocator() {
+        let input = fs::read("test_files/Wp00_Cmn.sbnk.1.X64").unwrap();
+        let bnk = Bnk::from_reader(&mut io::Cursor::new(&input)).unwrap();
+        let mut allocator = IdAllocator::from_banks([&bnk]).with_seed(1);
+
+        let entry_id = bnk.hirc_entries().next().unwrap().id;
+        assert!(allocator.is_used(entry_id));
+        assert!(allocator.is_used(bnk.bank_id().unwrap()));
+        assert!(!allocator.reserve(entry_id));
+        assert!(matches!(
+            allocator.named("Wp00_Cmn"),
+            Err(BnkError::IdCollision(_))
+        ));
+        let id = allocator.named("Play_New_Event").unwrap();
+        assert_eq!(id, hash::short_id("Play_New_Event"));
+        assert!(allocator.named("play_new_event").is_err());
+
+        let mut allocator = allocator.with_sequential_base(entry_id);
+        let sequential = allocator.sequential();
+        assert_ne!(sequential, entry_id);
+        assert!(allocator.is_used(sequential));
+        let random: HashSet<u32> = (0..100).map(|_| allocator.random()).collect();
+        assert_eq!(random.len(), 100);
+        assert!(
+            random
+                .iter()
+                .all(|id| !bnk.hirc_entries().any(|e| e.id == *id))
+        );
+    }
+}
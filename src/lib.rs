@@ -1,5 +1,11 @@
 pub mod bnk;
+pub mod hash;
+pub mod ids;
+pub mod music;
+pub mod names;
 pub mod pck;
+pub mod wem;
+pub mod workspace;
 
 mod rwext;
 mod utils;
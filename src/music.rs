@@ -0,0 +1,314 @@
+//! Music editing across a bank and its package.
+
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    io,
+};
+
+use crate::{
+    bnk::{
+        Bnk, BnkError, SectionPayload,
+        hirc::{AkBankSourceStreamType, HircEntry, HircEntryPayload, HircEventActionType},
+    },
+    pck::{Pck, PckError},
+    wem::{self, PrefetchLength, WemInfo, WemSource},
+};
+
+/// What [replace_music] changed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MusicReplacement {
+    pub source_id: u32,
+    pub stream_type: AkBankSourceStreamType,
+    /// Duration of the new media in milliseconds.
+    pub duration: f64,
+    /// Tracks playing the source, sorted.
+    pub tracks: Vec<u32>,
+    /// Segments of these tracks whose duration changed, sorted.
+    pub segments: Vec<u32>,
+}
+
+/// Replace the media played by an event, a music container, segment or
+/// track, which must resolve to a single music source.
+///
+/// - The new audio is wrapped into a wem if needed, see [WemSource].
+/// - Embedded media is replaced in the bank and the in-memory size of the
+///   sources updated.
+/// - Streamed media is appended to `pck` in the language of the replaced
+///   wem, see [Pck::append_wems]. Prefetch data is regenerated with the size
+///   of the previous one.
+/// - Clips of every track playing the source get the new source duration,
+///   and segments of these tracks the duration of their longest track.
+///
+/// `pck` is only required for streamed sources, e.g.
+/// `None::<&mut Pck<std::fs::File>>` for banks with embedded media. The bank
+/// is left unchanged on error.
+pub fn replace_music<R>(
+    bank: &mut Bnk,
+    pck: Option<&mut Pck<R>>,
+    event_or_segment_id: u32,
+    new_audio: WemSource,
+) -> Result<MusicReplacement, PckError>
+where
+    R: io::Read + io::Write + io::Seek,
+{
+    let (source_id, stream_type, in_memory_size) = music_source(bank, event_or_segment_id)?;
+    let wem = new_audio.into_wem().map_err(BnkError::from)?;
+    let duration = WemInfo::from_bytes(&wem)
+        .map_err(BnkError::from)?
+        .duration_ms()
+        .ok_or_else(|| {
+            BnkError::ReplaceMusic("duration of the new audio is unknown".to_string())
+        })?;
+
+    // the package is written before the bank is changed, so that failures
+    // leave the bank untouched
+    let embedded = match stream_type {
+        AkBankSourceStreamType::DataBnk => Some(wem),
+        AkBankSourceStreamType::Streaming | AkBankSourceStreamType::PrefetchStreaming => {
+            let pck = pck.ok_or_else(|| {
+                BnkError::ReplaceMusic(format!(
+                    "source {source_id} is streamed, a package is required"
+                ))
+            })?;
+            let prefetch = if stream_type == AkBankSourceStreamType::PrefetchStreaming {
+                let length = PrefetchLength::Bytes(in_memory_size as usize);
+                Some(wem::prefetch_chunk(&wem, length).map_err(BnkError::from)?)
+            } else {
+                None
+            };
+            let language_id = pck
+                .header()
+                .wem_entries
+                .iter()
+                .find(|entry| entry.id == source_id)
+                .map_or(0, |entry| entry.language_id);
+            pck.append_wems(language_id, [(source_id, &wem)])?;
+            prefetch
+        }
+    };
+    if let Some(media) = embedded {
+        let size = media.len() as u32;
+        bank.insert_media(source_id, media);
+        for source in hirc_entries_mut(bank)
+            .flat_map(|entry| entry.payload.sources_mut())
+            .filter(|source| source.source_id() == source_id)
+        {
+            source.media_information.in_memory_media_size = size;
+        }
+    }
+
+    let mut tracks = Vec::new();
+    let mut clips_end = HashMap::new();
+    for entry in hirc_entries_mut(bank) {
+        if let HircEntryPayload::MusicTrack(track) = &mut entry.payload {
+            if track.set_source_duration(source_id, duration) > 0 {
+                tracks.push(entry.id);
+            }
+            clips_end.insert(entry.id, track.clips_end());
+        }
+    }
+    let mut segments = Vec::new();
+    for entry in hirc_entries_mut(bank) {
+        let HircEntryPayload::MusicSegment(segment) = &mut entry.payload else {
+            continue;
+        };
+        if !segment.children().iter().any(|id| tracks.contains(id)) {
+            continue;
+        }
+        let end = segment
+            .children()
+            .iter()
+            .filter_map(|id| clips_end.get(id))
+            .fold(0.0, |end: f64, clip_end| end.max(*clip_end));
+        if end != segment.duration() {
+            segment.set_duration(end);
+            segments.push(entry.id);
+        }
+    }
+    tracks.sort_unstable();
+    segments.sort_unstable();
+
+    Ok(MusicReplacement {
+        source_id,
+        stream_type,
+        duration,
+        tracks,
+        segments,
+    })
+}
+
+/// Source id, stream type and in-memory size of the single source played by
+/// the tracks under an object.
+fn music_source(bank: &Bnk, id: u32) -> Result<(u32, AkBankSourceStreamType, u32), BnkError> {
+    let entries: HashMap<u32, &HircEntry> = bank.hirc_entries().map(|e| (e.id, e)).collect();
+    if !entries.contains_key(&id) {
+        return Err(BnkError::ObjectNotFound(id));
+    }
+
+    let mut sources = BTreeSet::new();
+    let mut visited = HashSet::new();
+    let mut stack = vec![id];
+    while let Some(id) = stack.pop() {
+        if !visited.insert(id) {
+            continue;
+        }
+        let Some(entry) = entries.get(&id) else {
+            continue;
+        };
+        match &entry.payload {
+            HircEntryPayload::Event { action_ids } => stack.extend(action_ids),
+            HircEntryPayload::EventAction(action)
+                if action.action_type == HircEventActionType::Play =>
+            {
+                stack.push(action.game_object_id)
+            }
+            HircEntryPayload::MusicSwitchContainer(container) => stack.extend(container.children()),
+            HircEntryPayload::MusicRanSeqCntr(container) => stack.extend(container.children()),
+            HircEntryPayload::MusicSegment(segment) => stack.extend(segment.children()),
+            HircEntryPayload::MusicTrack(track) => {
+                // source plugins have no media
+                sources.extend(
+                    track
+                        .sources()
+                        .iter()
+                        .filter(|source| source.plugin_params.is_none())
+                        .map(|source| {
+                            (
+                                source.source_id(),
+                                source.stream_type,
+                                source.media_information.in_memory_media_size,
+                            )
+                        }),
+                );
+            }
+            _ => {}
+        }
+    }
+
+    let ids: BTreeSet<u32> = sources.iter().map(|(id, _, _)| *id).collect();
+    if ids.len() != 1 {
+        return Err(BnkError::ReplaceMusic(format!(
+            "object {id} plays {} music sources {ids:?}, expected one",
+            ids.len()
+        )));
+    }
+    let (source_id, stream_type, in_memory_size) = sources.into_iter().next().unwrap();
+    let stream_type = AkBankSourceStreamType::from_repr(stream_type).ok_or_else(|| {
+        BnkError::ReplaceMusic(format!(
+            "unknown stream type {stream_type} of source {source_id}"
+        ))
+    })?;
+    Ok((source_id, stream_type, in_memory_size))
+}
+
+fn hirc_entries_mut(bank: &mut Bnk) -> impl Iterator<Item = &mut HircEntry> {
+    bank.sections
+        .iter_mut()
+        .filter_map(|sec| match &mut sec.payload {
+            SectionPayload::Hirc { entries } => Some(entries),
+            _ => None,
+        })
+        .flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn test_replace_music() {
+        let input = fs::read("test_files/bgm_resident_ev.sbnk.1.X64").unwrap();
+        let mut bank = Bnk::from_reader(&mut io::Cursor::new(&input)).unwrap();
+        let input = fs::read("test_files/Cat_cmn_m.spck.1.X64").unwrap();
+        let mut pck = Pck::from_reader(io::Cursor::new(input)).unwrap();
+        let wem_id = pck.header().wem_entries[0].id;
+        let wem = pck.read_wem(wem_id).unwrap().unwrap();
+        let duration = WemInfo::from_bytes(&wem).unwrap().duration_ms().unwrap();
+
+        // the source of the segment is shared with a track of another segment
+        let segment_id = 55666304;
+        let source_id = 736119904;
+        assert!(matches!(
+            replace_music(
+                &mut bank,
+                None::<&mut Pck<fs::File>>,
+                segment_id,
+                WemSource::Wem(wem.clone())
+            ),
+            Err(PckError::Bnk(BnkError::ReplaceMusic(_)))
+        ));
+        let replacement = replace_music(
+            &mut bank,
+            Some(&mut pck),
+            segment_id,
+            WemSource::Wem(wem.clone()),
+        )
+        .unwrap();
+        assert_eq!(replacement.source_id, source_id);
+        assert_eq!(
+            replacement.stream_type,
+            AkBankSourceStreamType::PrefetchStreaming
+        );
+        for track_id in [539930255, 815429061] {
+            assert!(replacement.tracks.contains(&track_id));
+        }
+        assert!(replacement.segments.contains(&segment_id));
+
+        assert_eq!(pck.read_wem(source_id).unwrap().unwrap(), wem);
+        let prefetch = bank.media(source_id).unwrap();
+        assert!(wem.starts_with(prefetch));
+        assert_eq!(
+            bank.media_usage()[&source_id].in_memory_size as usize,
+            prefetch.len()
+        );
+        for entry in bank.hirc_entries() {
+            match &entry.payload {
+                HircEntryPayload::MusicTrack(track) if replacement.tracks.contains(&entry.id) => {
+                    for clip in track.playlist() {
+                        if clip.source_id == source_id {
+                            assert_eq!(clip.src_duration, duration);
+                            assert_eq!(clip.validate(Some(duration)), Ok(()));
+                        }
+                    }
+                }
+                HircEntryPayload::MusicSegment(segment) if entry.id == segment_id => {
+                    assert_eq!(segment.duration(), duration);
+                }
+                _ => {}
+            }
+        }
+        let mut output = io::Cursor::new(vec![]);
+        bank.write_to(&mut output).unwrap();
+
+        // a failed package write leaves the bank and the package unchanged
+        let input = fs::read("test_files/Cat_cmn_m.spck.1.X64").unwrap();
+        let mut buf = input.clone();
+        let other_id = pck.header().wem_entries[1].id;
+        let other = pck.read_wem(other_id).unwrap().unwrap();
+        assert_ne!(other, wem);
+        let mut full = Pck::from_reader(io::Cursor::new(&mut buf[..])).unwrap();
+        assert!(matches!(
+            replace_music(
+                &mut bank,
+                Some(&mut full),
+                segment_id,
+                WemSource::Wem(other)
+            ),
+            Err(PckError::IO(_))
+        ));
+        let mut unchanged = io::Cursor::new(vec![]);
+        bank.write_to(&mut unchanged).unwrap();
+        assert_eq!(unchanged.into_inner(), output.into_inner());
+        drop(full);
+        assert_eq!(buf, input);
+
+        // containers resolving to several sources are rejected
+        let container_id = 613488315;
+        assert!(matches!(
+            replace_music(&mut bank, Some(&mut pck), container_id, WemSource::Wem(wem)),
+            Err(PckError::Bnk(BnkError::ReplaceMusic(_)))
+        ));
+    }
+}
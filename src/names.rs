@@ -0,0 +1,560 @@
+mod xml;
+
+use std::{
+    collections::HashMap,
+    fmt,
+    io::{self, BufRead, Read, Write},
+    str::FromStr,
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bnk::{Bnk, BnkError},
+    hash,
+};
+
+use xml::{XmlEvent, XmlReader};
+
+/// Work unit object types whose ShortID is the hash of their name.
+const NAME_HASHED_TYPES: &[&str] = &[
+    "Event",
+    "DialogueEvent",
+    "StateGroup",
+    "State",
+    "SwitchGroup",
+    "Switch",
+    "GameParameter",
+    "Trigger",
+    "Argument",
+    "Bus",
+    "AuxBus",
+    "AudioDevice",
+    "SoundBank",
+    "AcousticTexture",
+];
+
+/// Database of known id to name mappings.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NameDb {
+    names: HashMap<u32, String>,
+    guids: GuidMap,
+}
+
+impl NameDb {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Collect names from the STID sections of every bank.
+    pub fn from_banks<'a, I>(banks: I) -> Result<Self, BnkError>
+    where
+        I: IntoIterator<Item = &'a Bnk>,
+    {
+        let mut db = Self::new();
+        for bnk in banks {
+            db.add_bank(bnk)?;
+        }
+        Ok(db)
+    }
+
+    /// Collect names from the STID sections of a bank.
+    pub fn add_bank(&mut self, bnk: &Bnk) -> Result<(), BnkError> {
+        for (id, name) in bnk.stid_names()? {
+            self.insert(id, name);
+        }
+        Ok(())
+    }
+
+    /// Insert a mapping. Existing names are kept.
+    pub fn insert(&mut self, id: u32, name: impl Into<String>) {
+        self.names.entry(id).or_insert_with(|| name.into());
+    }
+
+    /// Insert a name under its ShortID, returning the id.
+    pub fn insert_name(&mut self, name: &str) -> u32 {
+        let id = hash::short_id(name);
+        self.insert(id, name);
+        id
+    }
+
+    pub fn get(&self, id: u32) -> Option<&str> {
+        self.names.get(&id).map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (u32, &str)> {
+        self.names.iter().map(|(id, name)| (*id, name.as_str()))
+    }
+
+    /// Merge another database into this one. Existing names are kept.
+    pub fn merge(&mut self, other: NameDb) {
+        for (id, name) in other.names {
+            self.insert(id, name);
+        }
+        self.guids.merge(other.guids);
+    }
+
+    /// Get the ShortID of a Wwise project object.
+    pub fn short_id_of(&self, guid: &Guid) -> Option<u32> {
+        self.guids.short_id(guid)
+    }
+
+    /// Get the Wwise project object of a ShortID.
+    pub fn guid_of(&self, id: u32) -> Option<Guid> {
+        self.guids.guid(id)
+    }
+
+    pub fn guids(&self) -> &GuidMap {
+        &self.guids
+    }
+
+    pub fn guids_mut(&mut self) -> &mut GuidMap {
+        &mut self.guids
+    }
+
+    /// Harvest names and GUID to ShortID mappings from a `SoundbanksInfo`
+    /// file generated with the banks, in XML or JSON.
+    ///
+    /// Every object with an `Id` is recorded: banks, events, buses, media...
+    /// Returns the number of objects found.
+    pub fn add_soundbanks_info<R: Read>(&mut self, mut reader: R) -> io::Result<usize> {
+        let mut input = String::new();
+        reader.read_to_string(&mut input)?;
+        let objects = if input.trim_start().starts_with('<') {
+            soundbanks_info_xml(&input)
+        } else {
+            soundbanks_info_json(&input)?
+        };
+
+        let count = objects.len();
+        for object in objects {
+            if let Some(name) = object.name {
+                self.insert(object.id, name);
+            }
+            if let Some(guid) = object.guid {
+                self.guids.insert(guid, object.id);
+            }
+        }
+        Ok(count)
+    }
+
+    /// Harvest object names and GUID to ShortID mappings from a Wwise work unit (.wwu).
+    ///
+    /// Objects without a `ShortID` attribute are only recorded if their id is
+    /// derived from their name (events, states, switches, buses...).
+    /// Returns the number of objects found.
+    pub fn add_wwu<R: Read>(&mut self, mut reader: R) -> io::Result<usize> {
+        let mut input = String::new();
+        reader.read_to_string(&mut input)?;
+
+        let mut count = 0;
+        for event in XmlReader::new(&input) {
+            let XmlEvent::Start(tag) = event else {
+                continue;
+            };
+            let Some(name) = tag.attr("Name").filter(|name| !name.is_empty()) else {
+                continue;
+            };
+            let short_id = match tag.attr("ShortID") {
+                Some(short_id) => short_id.parse::<u32>().ok(),
+                None if NAME_HASHED_TYPES.contains(&tag.name.as_str()) => {
+                    Some(hash::short_id(name))
+                }
+                None => None,
+            };
+            let Some(short_id) = short_id else {
+                continue;
+            };
+
+            self.insert(short_id, name);
+            if let Some(guid) = tag.attr("ID").and_then(|id| id.parse::<Guid>().ok()) {
+                self.guids.insert(guid, short_id);
+            }
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Load a database written by [NameDb::write_to].
+    pub fn from_reader<R: BufRead>(reader: R) -> io::Result<Self> {
+        let mut db = Self::new();
+        for line in reader.lines() {
+            let line = line?;
+            if line.is_empty() {
+                continue;
+            }
+            let (id, name) = line
+                .split_once('\t')
+                .and_then(|(id, name)| Some((id.parse::<u32>().ok()?, name)))
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Invalid name entry: {line}"),
+                    )
+                })?;
+            db.insert(id, name);
+        }
+        Ok(db)
+    }
+
+    /// Write the database as `id\tname` lines, sorted by id.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let mut entries = self.iter().collect::<Vec<_>>();
+        entries.sort_unstable_by_key(|(id, _)| *id);
+        for (id, name) in entries {
+            writeln!(writer, "{id}\t{name}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Object of a `SoundbanksInfo` file.
+struct InfoObject {
+    id: u32,
+    guid: Option<Guid>,
+    name: Option<String>,
+}
+
+/// Objects of a `SoundbanksInfo.xml`, named by their `Name` attribute or
+/// their `ShortName` or `Name` child element.
+fn soundbanks_info_xml(input: &str) -> Vec<InfoObject> {
+    let mut objects = Vec::new();
+    // open objects with their tag name
+    let mut open: Vec<(String, InfoObject)> = Vec::new();
+    let mut text_tag = String::new();
+    for event in XmlReader::new(input) {
+        match event {
+            XmlEvent::Start(tag) => {
+                let id = tag.attr("Id").and_then(|id| id.parse::<u32>().ok());
+                if let Some(id) = id {
+                    let object = InfoObject {
+                        id,
+                        guid: tag.attr("GUID").and_then(|guid| guid.parse().ok()),
+                        name: tag.attr("Name").map(str::to_string),
+                    };
+                    if tag.is_empty {
+                        objects.push(object);
+                    } else {
+                        open.push((tag.name.clone(), object));
+                    }
+                }
+                text_tag = tag.name;
+            }
+            XmlEvent::Text(text) => {
+                if let Some((_, object)) = open.last_mut()
+                    && object.name.is_none()
+                    && (text_tag == "ShortName" || text_tag == "Name")
+                {
+                    object.name = Some(text);
+                }
+            }
+            XmlEvent::End(name) => {
+                if open.last().is_some_and(|(tag, _)| *tag == name) {
+                    objects.extend(open.pop().map(|(_, object)| object));
+                }
+                text_tag.clear();
+            }
+        }
+    }
+    objects
+}
+
+/// Objects of a `SoundbanksInfo.json`, where ids are strings.
+#[cfg(feature = "json")]
+fn soundbanks_info_json(input: &str) -> io::Result<Vec<InfoObject>> {
+    use serde_json::Value;
+
+    fn collect(value: &Value, objects: &mut Vec<InfoObject>) {
+        match value {
+            Value::Object(map) => {
+                let id = map.get("Id").and_then(|id| match id {
+                    Value::String(id) => id.parse().ok(),
+                    id => id.as_u64().and_then(|id| u32::try_from(id).ok()),
+                });
+                if let Some(id) = id {
+                    let text = |key: &str| map.get(key).and_then(Value::as_str);
+                    objects.push(InfoObject {
+                        id,
+                        guid: text("GUID").and_then(|guid| guid.parse().ok()),
+                        name: text("Name")
+                            .or_else(|| text("ShortName"))
+                            .map(str::to_string),
+                    });
+                }
+                map.values().for_each(|value| collect(value, objects));
+            }
+            Value::Array(values) => values.iter().for_each(|value| collect(value, objects)),
+            _ => {}
+        }
+    }
+
+    let value: Value =
+        serde_json::from_str(input).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let mut objects = Vec::new();
+    collect(&value, &mut objects);
+    Ok(objects)
+}
+
+#[cfg(not(feature = "json"))]
+fn soundbanks_info_json(_input: &str) -> io::Result<Vec<InfoObject>> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "JSON SoundbanksInfo requires the json feature",
+    ))
+}
+
+/// Bidirectional map of Wwise project object GUIDs and ShortIDs.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GuidMap {
+    by_guid: HashMap<Guid, u32>,
+    by_id: HashMap<u32, Guid>,
+}
+
+impl GuidMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Insert a mapping. Existing mappings of the GUID or the id are kept.
+    pub fn insert(&mut self, guid: Guid, id: u32) {
+        self.by_guid.entry(guid).or_insert(id);
+        self.by_id.entry(id).or_insert(guid);
+    }
+
+    pub fn short_id(&self, guid: &Guid) -> Option<u32> {
+        self.by_guid.get(guid).copied()
+    }
+
+    pub fn guid(&self, id: u32) -> Option<Guid> {
+        self.by_id.get(&id).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.by_guid.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_guid.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Guid, u32)> {
+        self.by_guid.iter().map(|(guid, id)| (*guid, *id))
+    }
+
+    /// Merge another map into this one. Existing mappings are kept.
+    pub fn merge(&mut self, other: GuidMap) {
+        for (guid, id) in other.by_guid {
+            self.insert(guid, id);
+        }
+    }
+
+    /// Load a map written by [GuidMap::write_to].
+    pub fn from_reader<R: BufRead>(reader: R) -> io::Result<Self> {
+        let mut map = Self::new();
+        for line in reader.lines() {
+            let line = line?;
+            if line.is_empty() {
+                continue;
+            }
+            let (guid, id) = line
+                .split_once('\t')
+                .and_then(|(guid, id)| Some((guid.parse().ok()?, id.parse::<u32>().ok()?)))
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Invalid GUID entry: {line}"),
+                    )
+                })?;
+            map.insert(guid, id);
+        }
+        Ok(map)
+    }
+
+    /// Write the map as `guid\tid` lines, sorted by GUID.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let mut entries = self.iter().collect::<Vec<_>>();
+        entries.sort_unstable();
+        for (guid, id) in entries {
+            writeln!(writer, "{guid}\t{id}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A Wwise project object GUID, written as `{XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX}`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Guid(pub u128);
+
+impl FromStr for Guid {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s
+            .trim()
+            .trim_start_matches('{')
+            .trim_end_matches('}')
+            .replace('-', "");
+        if hex.len() != 32 {
+            return Err(format!("Invalid GUID: {s}"));
+        }
+        u128::from_str_radix(&hex, 16)
+            .map(Guid)
+            .map_err(|_| format!("Invalid GUID: {s}"))
+    }
+}
+
+impl fmt::Display for Guid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let hex = format!("{:032X}", self.0);
+        write!(
+            f,
+            "{{{}-{}-{}-{}-{}}}",
+            &hex[0..8],
+            &hex[8..12],
+            &hex[12..16],
+            &hex[16..20],
+            &hex[20..32]
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for Guid {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Guid {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bnk::{Section, SectionPayload, StringTable};
+
+    use super::*;
+
+    #[test]
+    fn test_name_db_from_banks() {
+        let mut table = StringTable {
+            string_type: 1,
+            ..Default::default()
+        };
+        table.set_name(hash::short_id("Wp00_Cmn"), "Wp00_Cmn");
+        let bnk = Bnk::from_sections(vec![Section {
+            magic: *b"STID",
+            section_length: 0,
+            payload: SectionPayload::Stid { table },
+        }]);
+
+        let mut db = NameDb::from_banks([&bnk]).unwrap();
+        db.insert_name("Play_Wp00_Cmn");
+        assert_eq!(db.get(hash::short_id("Wp00_Cmn")), Some("Wp00_Cmn"));
+
+        let mut output = vec![];
+        db.write_to(&mut output).unwrap();
+        let loaded = NameDb::from_reader(io::Cursor::new(output)).unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(
+            loaded.get(hash::short_id("play_wp00_cmn")),
+            Some("Play_Wp00_Cmn")
+        );
+    }
+
+    #[test]
+    fn test_name_db_wwu() {
+        let wwu = r#"<?xml version="1.0" encoding="utf-8"?>
+<WwiseDocument Type="WorkUnit" ID="{5B2C1A4E-0C55-4C34-9DC2-8E9A0B0D4C11}" SchemaVersion="110">
+	<Events>
+		<WorkUnit Name="Default Work Unit" ID="{5B2C1A4E-0C55-4C34-9DC2-8E9A0B0D4C11}" PersistMode="Standalone">
+			<ChildrenList>
+				<Event Name="Play_Bgm_Title" ID="{0A1B2C3D-4E5F-6071-8293-A4B5C6D7E8F9}">
+					<ChildrenList>
+						<Action Name="" ID="{11111111-2222-3333-4444-555555555555}" ShortID="123456" Type="2"/>
+					</ChildrenList>
+				</Event>
+				<Sound Name="Bgm &amp; Title" ID="{AAAAAAAA-BBBB-CCCC-DDDD-EEEEEEEEEEEE}" ShortID="654321"/>
+			</ChildrenList>
+		</WorkUnit>
+	</Events>
+</WwiseDocument>"#;
+        let mut db = NameDb::new();
+        assert_eq!(db.add_wwu(wwu.as_bytes()).unwrap(), 2);
+        let event_id = hash::short_id("Play_Bgm_Title");
+        assert_eq!(db.get(event_id), Some("Play_Bgm_Title"));
+        assert_eq!(db.get(654321), Some("Bgm & Title"));
+
+        let guid: Guid = "{0A1B2C3D-4E5F-6071-8293-A4B5C6D7E8F9}".parse().unwrap();
+        assert_eq!(db.short_id_of(&guid), Some(event_id));
+        assert_eq!(guid.to_string(), "{0A1B2C3D-4E5F-6071-8293-A4B5C6D7E8F9}");
+    }
+
+    #[test]
+    fn test_name_db_soundbanks_info() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<SoundBanksInfo Platform="Windows" SchemaVersion="16">
+	<SoundBanks>
+		<SoundBank Id="1355168291" GUID="{701ECBBD-9C7B-4030-8CDC-01C43B6B3AD9}" Language="SFX">
+			<ShortName>Init</ShortName>
+			<Path>Init.bnk</Path>
+			<IncludedEvents>
+				<Event Id="3087285486" Name="Play_Bgm_Title" GUID="{0A1B2C3D-4E5F-6071-8293-A4B5C6D7E8F9}"/>
+			</IncludedEvents>
+		</SoundBank>
+	</SoundBanks>
+</SoundBanksInfo>"#;
+        let mut db = NameDb::new();
+        assert_eq!(db.add_soundbanks_info(xml.as_bytes()).unwrap(), 2);
+        assert_eq!(db.get(1355168291), Some("Init"));
+        assert_eq!(db.get(3087285486), Some("Play_Bgm_Title"));
+        let bank_guid: Guid = "{701ECBBD-9C7B-4030-8CDC-01C43B6B3AD9}".parse().unwrap();
+        assert_eq!(db.short_id_of(&bank_guid), Some(1355168291));
+        assert_eq!(db.guid_of(1355168291), Some(bank_guid));
+
+        #[cfg(feature = "json")]
+        {
+            let json = r#"{"SoundBanksInfo": {"SoundBanks": [{
+                "Id": "1355168291", "GUID": "{701ECBBD-9C7B-4030-8CDC-01C43B6B3AD9}",
+                "ShortName": "Init",
+                "Events": [{"Id": "1111", "Name": "Play_Other", "GUID": "{11111111-2222-3333-4444-555555555555}"}]
+            }]}}"#;
+            let mut json_db = NameDb::new();
+            assert_eq!(json_db.add_soundbanks_info(json.as_bytes()).unwrap(), 2);
+            assert_eq!(json_db.get(1355168291), Some("Init"));
+            assert_eq!(
+                json_db.guid_of(1111).map(|guid| guid.to_string()),
+                Some("{11111111-2222-3333-4444-555555555555}".to_string())
+            );
+
+            db.merge(json_db);
+            assert_eq!(db.guids().len(), 3);
+            let mut output = vec![];
+            db.guids().write_to(&mut output).unwrap();
+            let loaded = GuidMap::from_reader(io::Cursor::new(output)).unwrap();
+            assert_eq!(loaded.short_id(&bank_guid), Some(1355168291));
+            assert_eq!(loaded.len(), 3);
+        }
+    }
+}
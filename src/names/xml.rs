@@ -0,0 +1,205 @@
+//! Minimal XML start-tag reader, enough to harvest attributes from Wwise
+//! project files. Text content, namespaces and DTDs are ignored.
+
+/// A start (or empty) element tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XmlTag {
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    /// Whether the tag is a self-closing `<Tag/>`.
+    pub is_empty: bool,
+}
+
+impl XmlTag {
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// An item produced by [XmlReader].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum XmlEvent {
+    Start(XmlTag),
+    End(String),
+    /// Trimmed, unescaped text between tags. Whitespace-only text is skipped.
+    Text(String),
+}
+
+/// Iterator over the tags of an XML document.
+pub struct XmlReader<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> XmlReader<'a> {
+    pub fn new(input: &'a str) -> Self {
+        XmlReader { input, pos: 0 }
+    }
+
+    fn skip_past(&mut self, pattern: &str) {
+        self.pos = match self.input[self.pos..].find(pattern) {
+            Some(i) => self.pos + i + pattern.len(),
+            None => self.input.len(),
+        };
+    }
+}
+
+impl Iterator for XmlReader<'_> {
+    type Item = XmlEvent;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let rest = &self.input[self.pos..];
+            if rest.is_empty() {
+                return None;
+            }
+            if !rest.starts_with('<') {
+                let end = rest.find('<').unwrap_or(rest.len());
+                self.pos += end;
+                let text = rest[..end].trim();
+                if !text.is_empty() {
+                    return Some(XmlEvent::Text(unescape(text)));
+                }
+                continue;
+            }
+            if rest.starts_with("<?") {
+                self.skip_past("?>");
+            } else if rest.starts_with("<!--") {
+                self.skip_past("-->");
+            } else if let Some(cdata) = rest.strip_prefix("<![CDATA[") {
+                let end = cdata.find("]]>").unwrap_or(cdata.len());
+                let text = cdata[..end].to_string();
+                self.skip_past("]]>");
+                return Some(XmlEvent::Text(text));
+            } else if rest.starts_with("<!") {
+                self.skip_past(">");
+            } else if let Some(name) = rest.strip_prefix("</") {
+                let end = name.find('>').unwrap_or(name.len());
+                let name = name[..end].trim().to_string();
+                self.skip_past(">");
+                return Some(XmlEvent::End(name));
+            } else {
+                let (tag, len) = parse_tag(&rest[1..]);
+                self.pos += 1 + len;
+                return Some(XmlEvent::Start(tag));
+            }
+        }
+    }
+}
+
+/// Parse a tag body after `<`, returning the tag and the consumed length
+/// including the closing `>`.
+fn parse_tag(input: &str) -> (XmlTag, usize) {
+    let bytes = input.as_bytes();
+    let mut pos = 0;
+    let name_end = input
+        .find(|c: char| c.is_whitespace() || c == '/' || c == '>')
+        .unwrap_or(input.len());
+    let name = input[..name_end].to_string();
+    pos += name_end;
+
+    let mut attributes = Vec::new();
+    let mut is_empty = false;
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'>' => {
+                pos += 1;
+                break;
+            }
+            b'/' => {
+                is_empty = true;
+                pos += 1;
+            }
+            c if c.is_ascii_whitespace() => pos += 1,
+            _ => {
+                let key_end = input[pos..]
+                    .find(|c: char| c == '=' || c.is_whitespace() || c == '>' || c == '/')
+                    .map_or(input.len(), |i| pos + i);
+                let key = input[pos..key_end].to_string();
+                pos = key_end;
+                while pos < bytes.len() && bytes[pos].is_ascii_whitespace() {
+                    pos += 1;
+                }
+                if bytes.get(pos) != Some(&b'=') {
+                    attributes.push((key, String::new()));
+                    continue;
+                }
+                pos += 1;
+                while pos < bytes.len() && bytes[pos].is_ascii_whitespace() {
+                    pos += 1;
+                }
+                let value = match bytes.get(pos) {
+                    Some(&quote @ (b'"' | b'\'')) => {
+                        let start = pos + 1;
+                        let end = input[start..]
+                            .find(quote as char)
+                            .map_or(input.len(), |i| start + i);
+                        pos = (end + 1).min(input.len());
+                        &input[start..end]
+                    }
+                    _ => {
+                        let start = pos;
+                        let end = input[start..]
+                            .find(|c: char| c.is_whitespace() || c == '>')
+                            .map_or(input.len(), |i| start + i);
+                        pos = end;
+                        &input[start..end]
+                    }
+                };
+                attributes.push((key, unescape(value)));
+            }
+        }
+    }
+
+    (
+        XmlTag {
+            name,
+            attributes,
+            is_empty,
+        },
+        pos,
+    )
+}
+
+fn unescape(s: &str) -> String {
+    if !s.contains('&') {
+        return s.to_string();
+    }
+    let mut output = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(i) = rest.find('&') {
+        output.push_str(&rest[..i]);
+        rest = &rest[i..];
+        let Some(end) = rest.find(';') else {
+            break;
+        };
+        let entity = &rest[1..end];
+        let c = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity
+                .strip_prefix("#x")
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                .and_then(char::from_u32),
+        };
+        match c {
+            Some(c) => {
+                output.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                output.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
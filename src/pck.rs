@@ -1,12 +1,28 @@
+mod builder;
+#[cfg(any(unix, windows))]
+mod shared;
+
+pub use builder::*;
+#[cfg(any(unix, windows))]
+pub use shared::*;
+
 use std::{
-    fs::File,
-    io::{self, Read},
+    collections::HashMap,
+    fs::{self, File},
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    thread,
 };
 
 use byteorder::{LE, ReadBytesExt, WriteBytesExt};
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-use crate::utils;
+use crate::{
+    bnk::{Bnk, BnkError, MediaProvider, ValidationIssue},
+    utils, wem,
+};
 
 type Result<T> = std::result::Result<T, PckError>;
 
@@ -19,11 +35,39 @@ pub enum PckError {
     InvalidMagic([u8; 4]),
     #[error("Assertion failed: {0}")]
     Assertion(String),
+    #[error("Bnk error: {0}")]
+    Bnk(#[from] BnkError),
+
+    #[error("Bank not found in bank table: {0}")]
+    BnkNotFound(u32),
+    #[error("Wem not found in wem table: {0}")]
+    WemNotFound(u32),
+    #[error("Language not found: {0}")]
+    LanguageNotFound(u32),
+    #[error("Language already exists: {0}")]
+    LanguageExists(String),
+    #[error("Header length mismatch: stored {stored}, computed {computed}")]
+    HeaderLengthMismatch { stored: u32, computed: u32 },
+    #[error("PCK file truncated: expected at least {expected} bytes, got {actual}")]
+    Truncated { expected: u64, actual: u64 },
+    #[error("Duplicate entry {id} of language {language_id}")]
+    DuplicateEntry { id: u32, language_id: u32 },
+    #[error("Invalid manifest: {0}")]
+    Manifest(String),
+    #[error("{0} wems set by Pck::set_wem are not written yet")]
+    PendingReplacements(usize),
 }
 
 pub struct Pck<R> {
     reader: R,
+    /// Header of the package read, the wems set by [Pck::set_wem] are only
+    /// added to the header written.
     header: PckHeader,
+    /// Data of the wems set by [Pck::set_wem], by id and language.
+    replacements: HashMap<(u32, u32), PckSource>,
+    /// Index of the first wem entry of each id, built on the first lookup
+    /// and dropped when the entries may change.
+    wem_index: Option<HashMap<u32, usize>>,
 }
 
 impl Pck<io::BufReader<File>> {
@@ -44,7 +88,12 @@ where
     {
         let header = PckHeader::from_reader(&mut reader)?;
 
-        Ok(Pck { reader, header })
+        Ok(Pck {
+            reader,
+            header,
+            replacements: HashMap::new(),
+            wem_index: None,
+        })
     }
 
     pub fn header(&self) -> &PckHeader {
@@ -52,6 +101,7 @@ where
     }
 
     pub fn header_mut(&mut self) -> &mut PckHeader {
+        self.wem_index = None;
         &mut self.header
     }
 
@@ -75,16 +125,602 @@ where
 
         Some(PckWemReader::new(&mut self.reader, entry))
     }
+
+    /// Reader of a wem by id, see [Pck::wem_reader].
+    ///
+    /// Packages with several languages may hold several wems of an id, the
+    /// first one in the wem table is read.
+    pub fn wem_reader_by_id(&mut self, id: u32) -> Option<PckWemReader<'_, R>> {
+        let index = self.wem_position(id)?;
+        self.wem_reader(index)
+    }
+
+    /// Ids of the wems in table order, repeated for each language holding
+    /// them.
+    pub fn wem_ids(&self) -> impl Iterator<Item = u32> + '_ {
+        self.header.wem_entries.iter().map(|entry| entry.id)
+    }
+
+    /// Write every wem to the path returned by `naming` relative to `dir`,
+    /// e.g. [PckHeader::wem_path]. Returns the number of files written.
+    ///
+    /// Wems set by [Pck::set_wem] are written with their new data.
+    pub fn extract_all<F>(&mut self, dir: impl AsRef<Path>, naming: F) -> Result<usize>
+    where
+        F: Fn(&PckHeader, &PckWemEntry) -> PathBuf,
+    {
+        let dir = dir.as_ref();
+        let header = self.pending_header()?;
+        for entry in &header.wem_entries {
+            let path = dir.join(naming(&header, entry));
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let mut writer = io::BufWriter::new(File::create(path)?);
+            self.copy_wem_entry(entry, &mut writer)?;
+            writer.flush()?;
+        }
+        Ok(header.wem_entries.len())
+    }
+
+    /// Read a wem by id, `None` if it is not in the wem table.
+    pub fn read_wem(&mut self, id: u32) -> Result<Option<Vec<u8>>> {
+        let mut data = Vec::new();
+        Ok(self.copy_wem(id, &mut data)?.map(|_| data))
+    }
+
+    /// Stream a wem by id into `sink`, returns the number of bytes copied or
+    /// `None` if it is not in the wem table.
+    ///
+    /// Wems set by [Pck::set_wem] are read from their new data.
+    pub fn copy_wem<W>(&mut self, id: u32, sink: &mut W) -> Result<Option<u64>>
+    where
+        W: io::Write + ?Sized,
+    {
+        if let Some(index) = self.wem_position(id) {
+            let entry = self.header.wem_entries[index].clone();
+            return Ok(Some(self.copy_wem_entry(&entry, sink)?));
+        }
+        // wems added by set_wem
+        let added = self
+            .replacements
+            .iter()
+            .filter(|((wem_id, _), _)| *wem_id == id)
+            .min_by_key(|(key, _)| **key);
+        match added {
+            Some((_, source)) => Ok(Some(copy_source(source, sink)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn copy_wem_entry<W>(&mut self, entry: &PckWemEntry, sink: &mut W) -> Result<u64>
+    where
+        W: io::Write + ?Sized,
+    {
+        if let Some(source) = self.replacement(entry) {
+            return copy_source(source, sink);
+        }
+        let copied = PckWemReader::new(&mut self.reader, entry).write_to(sink)?;
+        Ok(copied)
+    }
+
+    fn wem_position(&mut self, id: u32) -> Option<usize> {
+        let entries = &self.header.wem_entries;
+        let index = self.wem_index.get_or_insert_with(|| {
+            let mut index = HashMap::with_capacity(entries.len());
+            for (position, entry) in entries.iter().enumerate().rev() {
+                index.insert(entry.id, position);
+            }
+            index
+        });
+        index.get(&id).copied()
+    }
+
+    /// Check that the stored header length matches the header and that the
+    /// file holds the data of every wem and bank, e.g. to catch truncated
+    /// downloads or bad repacks before reading entries.
+    pub fn verify_length(&mut self) -> Result<()> {
+        let computed = self.header.header_size() as u32;
+        if self.header.header_length != computed {
+            return Err(PckError::HeaderLengthMismatch {
+                stored: self.header.header_length,
+                computed,
+            });
+        }
+        let expected = self.header.data_end();
+        let actual = self.reader.seek(io::SeekFrom::End(0))?;
+        if actual < expected {
+            return Err(PckError::Truncated { expected, actual });
+        }
+        Ok(())
+    }
+
+    /// Check that every wem of the package is valid media, see
+    /// [wem::check_media].
+    pub fn validate(&mut self) -> Result<Vec<ValidationIssue>> {
+        let mut issues = Vec::new();
+        let mut data = Vec::new();
+        for index in 0..self.header.wem_entries.len() {
+            data.clear();
+            self.wem_reader(index)
+                .expect("index in range")
+                .read_to_end(&mut data)?;
+            if let Some(problem) = wem::check_media(&data, false) {
+                issues.push(ValidationIssue::InvalidMedia {
+                    media_id: self.header.wem_entries[index].id,
+                    problem,
+                });
+            }
+        }
+        Ok(issues)
+    }
+
+    /// Replace the data of a wem in every language, see [Pck::set_wem].
+    pub fn replace_wem(&mut self, id: u32, source: impl Into<PckSource>) -> Result<()> {
+        let source = source.into();
+        let mut language_ids: Vec<u32> = self
+            .header
+            .wem_entries
+            .iter()
+            .map(|entry| (entry.id, entry.language_id))
+            .chain(self.replacements.keys().copied())
+            .filter(|(wem_id, _)| *wem_id == id)
+            .map(|(_, language_id)| language_id)
+            .collect();
+        language_ids.sort_unstable();
+        language_ids.dedup();
+        if language_ids.is_empty() {
+            return Err(PckError::WemNotFound(id));
+        }
+        for language_id in language_ids {
+            self.set_wem(id, language_id, source.clone())?;
+        }
+        Ok(())
+    }
+
+    /// Add or replace a wem of a language, its data is read from `source`
+    /// when the package is written with [Pck::write_to] or
+    /// [Pck::rebuild_to].
+    ///
+    /// Unlike [Pck::append_wems], the reader and the header are left
+    /// untouched until the package is written: [Pck::wem_reader] still reads
+    /// the data of the package, [Pck::read_wem] reads the new data.
+    pub fn set_wem(
+        &mut self,
+        id: u32,
+        language_id: u32,
+        source: impl Into<PckSource>,
+    ) -> Result<()> {
+        let source = source.into();
+        to_offset(source.len()?)?;
+        self.replacements.insert((id, language_id), source);
+        Ok(())
+    }
+
+    /// Write the whole package: the header, then the banks and wems laid
+    /// out as [PckHeader::fix_values]. Returns the written header.
+    ///
+    /// The data is copied from the reader, or from the sources of the wems
+    /// set by [Pck::set_wem]. The writer is expected to be at the start of
+    /// the output, and can't be the package being read.
+    pub fn write_to<W>(&mut self, writer: &mut W) -> Result<PckHeader>
+    where
+        W: io::Write + io::Seek,
+    {
+        let pending = self.pending_header()?;
+        let mut header = pending.clone();
+        header.fix_values();
+        header.write_to(writer)?;
+
+        for (index, entry) in header.bnk_table.entries.iter().enumerate() {
+            self.write_data(writer, &pending, PckData::Bnk(index), entry.data_offset())?;
+        }
+        for (index, entry) in header.wem_entries.iter().enumerate() {
+            self.write_data(writer, &pending, PckData::Wem(index), entry.offset as u64)?;
+        }
+        Ok(header)
+    }
+
+    /// Rewrite the package keeping its layout, e.g. after replacing wems with
+    /// [Pck::replace_wem]. Returns the written header.
+    ///
+    /// Unlike [Pck::write_to], the data keeps its order and the alignment of
+    /// the wem offsets, and entries sharing data, e.g. of cloned languages,
+    /// still share it. Added wems are written last.
+    pub fn rebuild_to<W>(&mut self, writer: &mut W) -> Result<PckHeader>
+    where
+        W: io::Write + io::Seek,
+    {
+        let pending = self.pending_header()?;
+        let mut header = pending.clone();
+        header.header_length = header.header_size() as u32;
+        let wem_alignment = self.wem_alignment();
+
+        // replaced wems have no data in the package, they go last
+        let mut order: Vec<(u64, PckData)> = (0..header.bnk_table.entries.len())
+            .map(|index| {
+                (
+                    header.bnk_table.entries[index].data_offset(),
+                    PckData::Bnk(index),
+                )
+            })
+            .chain((0..header.wem_entries.len()).map(|index| {
+                let offset = match self.replacement(&header.wem_entries[index]) {
+                    Some(_) => u64::MAX,
+                    None => header.wem_entries[index].offset as u64,
+                };
+                (offset, PckData::Wem(index))
+            }))
+            .collect();
+        order.sort_by_key(|(offset, _)| *offset);
+
+        let mut end = header.get_wem_offset_start() as u64;
+        // new offsets of the data by original range or replacement source
+        let mut placed: HashMap<DataKey, u64> = HashMap::new();
+        let mut writes = Vec::new();
+        for (offset, data) in order {
+            let (length, align, key) = match data {
+                PckData::Bnk(index) => {
+                    let entry = &header.bnk_table.entries[index];
+                    let align = entry.block_size.max(1) as u64;
+                    (entry.length, align, DataKey::Range(offset, entry.length))
+                }
+                PckData::Wem(index) => {
+                    let length = header.wem_entries[index].length;
+                    let key = match self.replacement(&header.wem_entries[index]) {
+                        Some(source) => DataKey::Source(source),
+                        None => DataKey::Range(offset, length),
+                    };
+                    (length, wem_alignment, key)
+                }
+            };
+            let new_offset = match placed.get(&key) {
+                Some(&new_offset) => new_offset,
+                None => {
+                    let new_offset = end.next_multiple_of(align);
+                    end = new_offset + length as u64;
+                    placed.insert(key, new_offset);
+                    writes.push((data, new_offset));
+                    new_offset
+                }
+            };
+            match data {
+                PckData::Bnk(index) => {
+                    header.bnk_table.entries[index].offset = to_offset(new_offset / align)?
+                }
+                PckData::Wem(index) => header.wem_entries[index].offset = to_offset(new_offset)?,
+            }
+        }
+
+        header.write_to(writer)?;
+        for (data, offset) in writes {
+            self.write_data(writer, &pending, data, offset)?;
+        }
+        Ok(header)
+    }
+
+    /// Write the whole package to a file, see [Pck::write_to].
+    ///
+    /// The file is replaced once fully written, so a package may be saved
+    /// over the file it is read from.
+    pub fn save(&mut self, path: impl AsRef<Path>) -> Result<PckHeader> {
+        let mut header = None;
+        crate::utils::write_file_atomic(path.as_ref(), |writer| {
+            header = Some(self.write_to(writer)?);
+            Ok::<_, PckError>(())
+        })?;
+        Ok(header.expect("header written"))
+    }
+
+    /// Header written, with the entries of the wems set by [Pck::set_wem].
+    /// Offsets of their entries are left to the writer.
+    fn pending_header(&self) -> Result<PckHeader> {
+        let mut header = self.header.clone();
+        for (&(id, language_id), source) in &self.replacements {
+            header.set_wem_entry(PckWemEntry {
+                id,
+                one: 1,
+                length: to_offset(source.len()?)?,
+                offset: 0,
+                language_id,
+            });
+        }
+        Ok(header)
+    }
+
+    /// Source of a wem set by [Pck::set_wem].
+    fn replacement(&self, entry: &PckWemEntry) -> Option<&PckSource> {
+        self.replacements.get(&(entry.id, entry.language_id))
+    }
+
+    /// Largest power of two up to 2048 dividing the offsets of the wems in
+    /// the package data.
+    fn wem_alignment(&self) -> u64 {
+        self.header
+            .wem_entries
+            .iter()
+            .filter(|entry| self.replacement(entry).is_none())
+            .map(|entry| 1 << (entry.offset | 2048).trailing_zeros())
+            .min()
+            .unwrap_or(1)
+    }
+
+    /// Write the data of a bank or wem at `offset`, `layout` is the
+    /// [Pck::pending_header] locating the data in the package.
+    fn write_data<W>(
+        &mut self,
+        writer: &mut W,
+        layout: &PckHeader,
+        data: PckData,
+        offset: u64,
+    ) -> Result<()>
+    where
+        W: io::Write + io::Seek,
+    {
+        pad_to(writer, offset)?;
+        let (source, length) = match data {
+            PckData::Bnk(index) => {
+                let entry = &layout.bnk_table.entries[index];
+                (Err(entry.data_offset()), entry.length)
+            }
+            PckData::Wem(index) => {
+                let entry = &layout.wem_entries[index];
+                (
+                    self.replacement(entry).ok_or(entry.offset as u64),
+                    entry.length,
+                )
+            }
+        };
+        match source {
+            Ok(source) => source.write_to(writer)?,
+            Err(start) => {
+                self.reader.seek(io::SeekFrom::Start(start))?;
+                let length = length as u64;
+                if io::copy(&mut (&mut self.reader).take(length), writer)? != length {
+                    return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+                }
+            }
+        }
+        // files may change between the header and the data writes
+        if writer.stream_position()? != offset + length as u64 {
+            return Err(PckError::Assertion(format!(
+                "size of {data:?} changed while writing"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Ids of the embedded soundbanks in table order.
+    pub fn bnk_ids(&self) -> impl Iterator<Item = u32> + '_ {
+        self.header
+            .bnk_table
+            .entries
+            .iter()
+            .map(|entry| entry.id as u32)
+    }
+
+    /// Stream an embedded soundbank by id into `sink`, returns the number of
+    /// bytes copied.
+    pub fn copy_bnk<W>(&mut self, id: u32, sink: &mut W) -> Result<u64>
+    where
+        W: io::Write + ?Sized,
+    {
+        let (offset, length) = self.header.bnk_range(id).ok_or(PckError::BnkNotFound(id))?;
+        self.reader.seek(io::SeekFrom::Start(offset))?;
+        let copied = io::copy(&mut (&mut self.reader).take(length), sink)?;
+        if copied != length {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+        Ok(copied)
+    }
+
+    /// Read the data of an embedded soundbank by id, e.g. to save it as a
+    /// `.bnk` file.
+    pub fn read_bnk_data(&mut self, id: u32) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        self.copy_bnk(id, &mut data)?;
+        Ok(data)
+    }
+
+    /// Locate an embedded soundbank by id in the bank table and parse it.
+    pub fn read_bnk(&mut self, id: u32) -> Result<Bnk> {
+        let data = self.read_bnk_data(id)?;
+        Ok(Bnk::from_reader(&mut io::Cursor::new(data))?)
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Extract the wems of a package file to `<out_dir>/<id>.wem` with `threads`
+/// workers, in a folder per language for packages with several languages.
+///
+/// Returns the number of extracted wems.
+pub fn extract_file_parallel(
+    path: impl AsRef<Path>,
+    out_dir: impl AsRef<Path>,
+    threads: usize,
+) -> Result<usize> {
+    let path = path.as_ref();
+    let out_dir = out_dir.as_ref();
+    let pck = Pck::from_file(path)?;
+    let header = pck.header();
+    for entry in &header.wem_entries {
+        if let Some(parent) = out_dir.join(header.wem_path(entry)).parent() {
+            fs::create_dir_all(parent)?;
+        }
+    }
+
+    header.extract_parallel(
+        threads,
+        || File::open(path).map(io::BufReader::new),
+        |entry, data| fs::write(out_dir.join(header.wem_path(entry)), data),
+    )?;
+    Ok(header.wem_entries.len())
+}
+
+impl<R> Pck<R>
+where
+    R: io::Read + io::Write + io::Seek,
+{
+    /// Add or replace wems of a language without repacking the package, e.g.
+    /// a [File] opened for reading and writing.
+    ///
+    /// The wem data is written at the end of the package and only the header
+    /// is rewritten. If the larger header overlaps the first wems or banks,
+    /// they are moved to the end as well. Data of replaced wems is left
+    /// unreferenced in the file.
+    pub fn append_wems<I, D>(&mut self, language_id: u32, wems: I) -> Result<()>
+    where
+        I: IntoIterator<Item = (u32, D)>,
+        D: AsRef<[u8]>,
+    {
+        self.wem_index = None;
+        let mut end = self.reader.seek(io::SeekFrom::End(0))?;
+        for (id, data) in wems {
+            let data = data.as_ref();
+            self.reader.write_all(data)?;
+            self.header.set_wem_entry(PckWemEntry {
+                id,
+                one: 1,
+                length: data.len() as u32,
+                offset: to_offset(end)?,
+                language_id,
+            });
+            self.replacements.remove(&(id, language_id));
+            end += data.len() as u64;
+        }
+
+        self.relocate_under_header(end)?;
+        self.header.header_length = self.header.header_size() as u32;
+        self.reader.seek(io::SeekFrom::Start(0))?;
+        self.header.write_to(&mut self.reader)?;
+        self.reader.flush()?;
+        Ok(())
+    }
+
+    /// Move the data starting inside the header region to `end`.
+    fn relocate_under_header(&mut self, mut end: u64) -> Result<()> {
+        let header_end = self.header.get_wem_offset_start() as u64;
+        // entries cloned for another language share their data
+        let mut moved: HashMap<u64, u64> = HashMap::new();
+
+        for idx in 0..self.header.wem_entries.len() {
+            let PckWemEntry { offset, length, .. } = self.header.wem_entries[idx];
+            let offset = offset as u64;
+            if offset >= header_end {
+                continue;
+            }
+            let new_offset = match moved.get(&offset) {
+                Some(&new_offset) => new_offset,
+                None => {
+                    let new_offset = self.copy_to_end(offset, length, &mut end, 1)?;
+                    moved.insert(offset, new_offset);
+                    new_offset
+                }
+            };
+            self.header.wem_entries[idx].offset = to_offset(new_offset)?;
+        }
+
+        for idx in 0..self.header.bnk_table.entries.len() {
+            let entry = &self.header.bnk_table.entries[idx];
+            let (offset, length) = (entry.data_offset(), entry.length);
+            let block_size = entry.block_size.max(1) as u64;
+            if offset >= header_end {
+                continue;
+            }
+            let new_offset = match moved.get(&offset) {
+                Some(&new_offset) => new_offset,
+                None => {
+                    let new_offset = self.copy_to_end(offset, length, &mut end, block_size)?;
+                    moved.insert(offset, new_offset);
+                    new_offset
+                }
+            };
+            self.header.bnk_table.entries[idx].offset = to_offset(new_offset / block_size)?;
+        }
+        Ok(())
+    }
+
+    /// Copy `length` bytes at `offset` to `end` aligned to `align`, returns
+    /// the new offset.
+    fn copy_to_end(&mut self, offset: u64, length: u32, end: &mut u64, align: u64) -> Result<u64> {
+        let mut data = vec![0; length as usize];
+        self.reader.seek(io::SeekFrom::Start(offset))?;
+        self.reader.read_exact(&mut data)?;
+
+        let padding = end.next_multiple_of(align) - *end;
+        self.reader.seek(io::SeekFrom::Start(*end))?;
+        self.reader.write_all(&vec![0; padding as usize])?;
+        self.reader.write_all(&data)?;
+        let new_offset = *end + padding;
+        *end = new_offset + length as u64;
+        Ok(new_offset)
+    }
+}
+
+/// Write the data of `source` to `sink`, returns its length.
+fn copy_source<W>(source: &PckSource, sink: &mut W) -> Result<u64>
+where
+    W: io::Write + ?Sized,
+{
+    let mut data = Vec::new();
+    source.write_to(&mut data)?;
+    sink.write_all(&data)?;
+    Ok(data.len() as u64)
+}
+
+/// Write zeros up to `offset`.
+fn pad_to<W>(writer: &mut W, offset: u64) -> io::Result<()>
+where
+    W: io::Write + io::Seek,
+{
+    let position = writer.stream_position()?;
+    writer.write_all(&vec![0; (offset - position) as usize])
+}
+
+/// Bank or wem by index in its table.
+#[derive(Debug, Clone, Copy)]
+enum PckData {
+    Bnk(usize),
+    Wem(usize),
+}
+
+/// Data shared by entries when rebuilding a package.
+#[derive(PartialEq, Eq, Hash)]
+enum DataKey<'a> {
+    /// Offset and length in the package.
+    Range(u64, u32),
+    Source(&'a PckSource),
+}
+
+fn to_offset(offset: u64) -> Result<u32> {
+    u32::try_from(offset)
+        .map_err(|_| PckError::Assertion(format!("offset {offset} exceeds the PCK limit")))
+}
+
+impl<R> MediaProvider for Pck<R>
+where
+    R: io::Read + io::Seek,
+{
+    fn media(&mut self, id: u32) -> io::Result<Option<Vec<u8>>> {
+        self.read_wem(id).map_err(|e| match e {
+            PckError::IO(e) => e,
+            e => io::Error::other(e),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PckHeader {
     pub header_length: u32,
     pub unk2: u32,
     pub string_table: Vec<PckString>,
-    pub bnk_table_data: Vec<u32>,
+    pub bnk_table: PckFileTable,
     pub wem_entries: Vec<PckWemEntry>,
-    pub unk_struct_data: Vec<u32>,
+    /// Table of external sources, `None` for packages without it.
+    pub externals: Option<PckFileTable>,
+    /// Encoding of the language names, detected on read.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub string_encoding: PckStringEncoding,
 }
 
 impl PckHeader {
@@ -102,7 +738,7 @@ impl PckHeader {
         let language_length = reader.read_u32::<LE>()?;
         let bnk_table_length = reader.read_u32::<LE>()?;
         let _wem_table_length = reader.read_u32::<LE>()?;
-        let unk_struct_length = reader.read_u32::<LE>()?;
+        let externals_length = reader.read_u32::<LE>()?;
 
         // read strings
         #[derive(Debug)]
@@ -119,48 +755,59 @@ impl PckHeader {
                 index: reader.read_u32::<LE>()?,
             });
         }
+        let string_encoding = match entries.first() {
+            Some(entry) => {
+                reader.seek(io::SeekFrom::Start(string_start_pos + entry.offset as u64))?;
+                let mut first_char = [0; 2];
+                reader.read_exact(&mut first_char)?;
+                PckStringEncoding::detect(first_char)
+            }
+            None => PckStringEncoding::default(),
+        };
         let mut string_table = Vec::with_capacity(string_count as usize);
         for entry in entries {
             reader.seek(io::SeekFrom::Start(string_start_pos + entry.offset as u64))?;
-            let wstr = utils::string_from_utf16_reader(reader)?;
+            let value = match string_encoding {
+                PckStringEncoding::Utf16 => utils::string_from_utf16_reader(reader)?,
+                PckStringEncoding::Utf8 => utils::string_from_utf8_reader(reader)?,
+            };
             string_table.push(PckString {
                 index: entry.index,
-                value: wstr,
+                value,
             });
         }
         reader.seek(io::SeekFrom::Start(
             string_start_pos + language_length as u64,
         ))?;
 
-        let mut bnk_table_data = vec![0u32; bnk_table_length as usize / 4];
-        for i in 0..(bnk_table_length / 4) {
-            bnk_table_data[i as usize] = reader.read_u32::<LE>()?;
-        }
+        let bnk_table = match bnk_table_length {
+            0 => PckFileTable::default(),
+            length => PckFileTable::from_reader(reader, length)?,
+        };
 
         let wem_count = reader.read_u32::<LE>()?;
         let mut wem_entries = Vec::with_capacity(wem_count as usize);
         for _ in 0..wem_count {
-            let mut buf = [0u8; 20];
-            reader.read_exact(&mut buf)?;
-            let entry: PckWemEntry = unsafe { std::mem::transmute(buf) };
+            let entry = PckWemEntry::from_reader(reader)?;
             if entry.one != 1 {
                 return Err(PckError::Assertion("PckWemEntry.one != 1".to_string()));
             }
             wem_entries.push(entry);
         }
 
-        let mut unk_struct_data = vec![0u32; unk_struct_length as usize / 4];
-        for i in 0..(unk_struct_length / 4) {
-            unk_struct_data[i as usize] = reader.read_u32::<LE>()?;
-        }
+        let externals = match externals_length {
+            0 => None,
+            length => Some(PckFileTable::from_reader(reader, length)?),
+        };
 
         Ok(PckHeader {
             header_length,
             unk2,
             string_table,
-            bnk_table_data,
+            bnk_table,
             wem_entries,
-            unk_struct_data,
+            externals,
+            string_encoding,
         })
     }
 
@@ -174,52 +821,49 @@ impl PckHeader {
         writer.write_u32::<LE>(0)?; // language_length
         writer.write_u32::<LE>(0)?; // bnk_table_length
         writer.write_u32::<LE>(0)?; // wem_table_length
-        writer.write_u32::<LE>(0)?; // unk_struct_length
+        writer.write_u32::<LE>(0)?; // externals_length
 
         // write strings
         let language_size = utils::calc_write_size(writer, |writer| {
             writer.write_u32::<LE>(self.string_table.len() as u32)?; // string_count
-            let mut utf16_strings = vec![];
+            let mut string_bytes = vec![];
             for string in &self.string_table {
-                utf16_strings.push(utils::string_to_utf16_bytes(&string.value));
+                string_bytes.push(self.string_encoding.encode(&string.value));
             }
             // calculate offsets and write string entries
             let mut offset = size_of::<u32>() + size_of::<u32>() * 2 * self.string_table.len();
-            utf16_strings.iter().zip(&self.string_table).try_for_each(
-                |(utf16_bytes, pck_string)| -> io::Result<()> {
+            string_bytes.iter().zip(&self.string_table).try_for_each(
+                |(bytes, pck_string)| -> io::Result<()> {
                     writer.write_u32::<LE>(offset as u32)?;
                     writer.write_u32::<LE>(pck_string.index)?;
-                    offset += utf16_bytes.len();
+                    offset += bytes.len();
                     Ok(())
                 },
             )?;
             // write string data
-            for utf16_bytes in utf16_strings {
-                writer.write_all(&utf16_bytes)?;
+            for bytes in string_bytes {
+                writer.write_all(&bytes)?;
             }
             Ok(())
         })?;
 
-        for data in &self.bnk_table_data {
-            writer.write_u32::<LE>(*data)?;
-        }
+        self.bnk_table.write_to(writer)?;
         writer.write_u32::<LE>(self.wem_entries.len() as u32)?;
         for entry in &self.wem_entries {
-            let buf: [u8; 20] = unsafe { std::mem::transmute(entry.clone()) };
-            writer.write_all(&buf)?;
+            entry.write_to(writer)?;
         }
-        for data in &self.unk_struct_data {
-            writer.write_u32::<LE>(*data)?;
+        if let Some(externals) = &self.externals {
+            externals.write_to(writer)?;
         }
 
         let bnk_table_size = self.bnk_table_size();
         let wem_table_size = self.wem_table_size();
-        let unk_struct_size = self.unk_struct_size();
+        let externals_size = self.externals_size();
         let header_size = size_of::<u32>() * 5
             + language_size as usize
             + bnk_table_size
             + wem_table_size
-            + unk_struct_size;
+            + externals_size;
         let end_pos = writer.stream_position()?;
 
         writer.seek(io::SeekFrom::Start(4))?;
@@ -228,13 +872,218 @@ impl PckHeader {
         writer.write_u32::<LE>(language_size as u32)?;
         writer.write_u32::<LE>(bnk_table_size as u32)?;
         writer.write_u32::<LE>(wem_table_size as u32)?;
-        writer.write_u32::<LE>(unk_struct_size as u32)?;
+        writer.write_u32::<LE>(externals_size as u32)?;
 
         writer.seek(io::SeekFrom::Start(end_pos))?;
 
         Ok(())
     }
 
+    /// Read every wem with `threads` workers, each reading through its own
+    /// reader from `open_reader`, e.g. a new handle to the package file.
+    ///
+    /// `handle` is called from the workers with each entry and its data, the
+    /// first error stops the extraction.
+    pub fn extract_parallel<R, O, H>(&self, threads: usize, open_reader: O, handle: H) -> Result<()>
+    where
+        R: io::Read + io::Seek,
+        O: Fn() -> io::Result<R> + Sync,
+        H: Fn(&PckWemEntry, Vec<u8>) -> io::Result<()> + Sync,
+    {
+        let next = AtomicUsize::new(0);
+        let failed = AtomicBool::new(false);
+        let worker = || -> Result<()> {
+            let mut reader = open_reader()?;
+            while !failed.load(Ordering::Relaxed) {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(entry) = self.wem_entries.get(index) else {
+                    break;
+                };
+                let mut data = Vec::with_capacity(entry.length as usize);
+                let result = PckWemReader::new(&mut reader, entry)
+                    .read_to_end(&mut data)
+                    .and_then(|_| handle(entry, data));
+                if let Err(e) = result {
+                    failed.store(true, Ordering::Relaxed);
+                    return Err(e.into());
+                }
+            }
+            Ok(())
+        };
+
+        thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads.max(1)).map(|_| scope.spawn(worker)).collect();
+            workers
+                .into_iter()
+                .map(|worker| worker.join().expect("extraction worker panicked"))
+                .collect::<Result<Vec<()>>>()
+        })?;
+        Ok(())
+    }
+
+    /// Path of a wem relative to an output folder: `<id>.wem`, in a folder
+    /// per language for packages with several languages.
+    pub fn wem_path(&self, entry: &PckWemEntry) -> PathBuf {
+        let file_name = format!("{}.wem", entry.id);
+        if self.string_table.len() <= 1 {
+            return PathBuf::from(file_name);
+        }
+        let language = self
+            .language_name(entry.language_id)
+            .map(str::to_string)
+            .unwrap_or_else(|| entry.language_id.to_string());
+        Path::new(&language).join(file_name)
+    }
+
+    /// Id of a language in this package.
+    pub fn language_id(&self, name: &str) -> Option<u32> {
+        self.string_table
+            .iter()
+            .find(|string| string.value.eq_ignore_ascii_case(name))
+            .map(|string| string.index)
+    }
+
+    pub fn language_name(&self, id: u32) -> Option<&str> {
+        self.string_table
+            .iter()
+            .find(|string| string.index == id)
+            .map(|string| string.value.as_str())
+    }
+
+    /// Ids and names of the languages, sorted by name.
+    pub fn languages(&self) -> impl Iterator<Item = (u32, &str)> {
+        self.string_table
+            .iter()
+            .map(|string| (string.index, string.value.as_str()))
+    }
+
+    /// Wem entries of a language.
+    pub fn wems_of_language(&self, language_id: u32) -> impl Iterator<Item = &PckWemEntry> {
+        self.wem_entries
+            .iter()
+            .filter(move |entry| entry.language_id == language_id)
+    }
+
+    /// Add a language without entries, returning its id.
+    pub fn add_language(&mut self, name: &str) -> Result<u32> {
+        if self.language_id(name).is_some() {
+            return Err(PckError::LanguageExists(name.to_string()));
+        }
+        let language_id = self
+            .string_table
+            .iter()
+            .map(|string| string.index + 1)
+            .max()
+            .unwrap_or_default();
+        // the language map is sorted by name
+        let pos = self
+            .string_table
+            .partition_point(|string| string.value.as_str() < name);
+        self.string_table.insert(
+            pos,
+            PckString {
+                index: language_id,
+                value: name.to_string(),
+            },
+        );
+        Ok(language_id)
+    }
+
+    /// Add a language with the banks and wems of an existing one, returning
+    /// the new language id.
+    ///
+    /// The new entries point at the media of the source language, so the
+    /// package data is shared rather than copied.
+    pub fn clone_language(&mut self, from_language_id: u32, name: &str) -> Result<u32> {
+        if self.language_name(from_language_id).is_none() {
+            return Err(PckError::LanguageNotFound(from_language_id));
+        }
+        let language_id = self.add_language(name)?;
+
+        let cloned: Vec<PckWemEntry> = self
+            .wems_of_language(from_language_id)
+            .map(|entry| PckWemEntry {
+                language_id,
+                ..entry.clone()
+            })
+            .collect();
+        self.wem_entries.extend(cloned);
+        self.wem_entries
+            .sort_by_key(|entry| (entry.id, entry.language_id));
+
+        let entries = &mut self.bnk_table.entries;
+        let cloned: Vec<PckFileEntry> = entries
+            .iter()
+            .filter(|entry| entry.language_id == from_language_id)
+            .map(|entry| PckFileEntry {
+                language_id,
+                ..entry.clone()
+            })
+            .collect();
+        entries.extend(cloned);
+        entries.sort_by_key(|entry| (entry.id, entry.language_id));
+
+        Ok(language_id)
+    }
+
+    /// Add or replace the wem entry of the same id and language, wem entries
+    /// are sorted for lookups by the game.
+    fn set_wem_entry(&mut self, entry: PckWemEntry) {
+        let key = (entry.id, entry.language_id);
+        match self
+            .wem_entries
+            .binary_search_by_key(&key, |e| (e.id, e.language_id))
+        {
+            Ok(idx) => self.wem_entries[idx] = entry,
+            Err(idx) => self.wem_entries.insert(idx, entry),
+        }
+    }
+
+    /// Find the absolute byte range of an embedded bank.
+    fn bnk_range(&self, id: u32) -> Option<(u64, u64)> {
+        self.bnk_table
+            .entries
+            .iter()
+            .find(|entry| entry.id == id as u64)
+            .map(|entry| (entry.data_offset(), entry.length as u64))
+    }
+
+    /// Recompute the header length and the data offsets from the entry
+    /// lengths, for data written right after the header: banks then wems, in
+    /// table order. Banks are aligned to their block size, wems are not
+    /// aligned as their offsets are in bytes.
+    pub fn fix_values(&mut self) {
+        self.header_length = self.header_size() as u32;
+        let mut offset = self.get_wem_offset_start() as u64;
+
+        for entry in &mut self.bnk_table.entries {
+            let block_size = entry.block_size.max(1) as u64;
+            offset = offset.next_multiple_of(block_size);
+            entry.offset = (offset / block_size) as u32;
+            offset += entry.length as u64;
+        }
+        for entry in &mut self.wem_entries {
+            entry.offset = offset as u32;
+            offset += entry.length as u64;
+        }
+    }
+
+    /// End offset of the last wem or bank data, at least the header end.
+    fn data_end(&self) -> u64 {
+        let wem_end = self
+            .wem_entries
+            .iter()
+            .map(|entry| entry.offset as u64 + entry.length as u64);
+        let bnk_end = self
+            .bnk_table
+            .entries
+            .iter()
+            .map(|entry| entry.data_offset() + entry.length as u64);
+        wem_end
+            .chain(bnk_end)
+            .fold(self.get_wem_offset_start() as u64, u64::max)
+    }
+
     pub fn get_wem_offset_start(&self) -> u32 {
         // header_size + (magic + header_size(val))
         self.header_size() as u32 + 8
@@ -243,29 +1092,29 @@ impl PckHeader {
     fn header_size(&self) -> usize {
         self.bnk_table_size()
             + self.wem_table_size()
-            + self.unk_struct_size()
+            + self.externals_size()
             + self.language_size()
             + size_of::<u32>() * 5 // unk + size(val)*4
     }
 
     fn bnk_table_size(&self) -> usize {
-        self.bnk_table_data.len() * 4
+        self.bnk_table.size()
     }
 
     fn wem_table_size(&self) -> usize {
         // entries_count(val) + entries_size
-        4 + self.wem_entries.len() * size_of::<PckWemEntry>()
+        4 + self.wem_entries.len() * PckWemEntry::SIZE
     }
 
-    fn unk_struct_size(&self) -> usize {
-        self.unk_struct_data.len() * 4
+    fn externals_size(&self) -> usize {
+        self.externals.as_ref().map_or(0, PckFileTable::size)
     }
 
     fn language_size(&self) -> usize {
         let mut size = 0;
         // strings size
         for string in &self.string_table {
-            size += utils::string_to_utf16_bytes(&string.value).len();
+            size += self.string_encoding.encode(&string.value).len();
         }
         // entries size = count(val) + entry*count
         size += 4 + self.string_table.len() * 8;
@@ -273,26 +1122,224 @@ impl PckHeader {
     }
 }
 
-#[repr(C)]
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PckWemEntry {
     pub id: u32,
+    /// Block size of the entry, always 1 in packages read by [Pck].
     pub one: u32,
     pub length: u32,
+    /// Offset in bytes, not in blocks of `one` bytes like banks.
     pub offset: u32,
     pub language_id: u32,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl PckWemEntry {
+    /// Size of an entry in the wem table.
+    pub const SIZE: usize = 20;
+
+    fn from_reader<R>(reader: &mut R) -> io::Result<Self>
+    where
+        R: io::Read,
+    {
+        Ok(PckWemEntry {
+            id: reader.read_u32::<LE>()?,
+            one: reader.read_u32::<LE>()?,
+            length: reader.read_u32::<LE>()?,
+            offset: reader.read_u32::<LE>()?,
+            language_id: reader.read_u32::<LE>()?,
+        })
+    }
+
+    fn write_to<W>(&self, writer: &mut W) -> io::Result<()>
+    where
+        W: io::Write,
+    {
+        writer.write_u32::<LE>(self.id)?;
+        writer.write_u32::<LE>(self.one)?;
+        writer.write_u32::<LE>(self.length)?;
+        writer.write_u32::<LE>(self.offset)?;
+        writer.write_u32::<LE>(self.language_id)
+    }
+}
+
+/// Entry of the bank or externals table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PckFileEntry {
+    pub id: u64,
+    pub block_size: u32,
+    pub length: u32,
+    /// Offset in blocks of `block_size` bytes.
+    pub offset: u32,
+    pub language_id: u32,
+}
+
+impl PckFileEntry {
+    /// Absolute byte offset of the data.
+    pub fn data_offset(&self) -> u64 {
+        self.offset as u64 * self.block_size.max(1) as u64
+    }
+}
+
+/// Layout of the entries of a [PckFileTable], depends on the package version.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PckEntryLayout {
+    /// `id, length, offset, language_id`, without block size.
+    Compact,
+    /// `id, block_size, length, offset, language_id`.
+    #[default]
+    Standard,
+    /// Like [PckEntryLayout::Standard] with a 64-bit id, used by the
+    /// externals table of newer packages.
+    Id64,
+}
+
+impl PckEntryLayout {
+    pub fn entry_size(&self) -> usize {
+        match self {
+            PckEntryLayout::Compact => 16,
+            PckEntryLayout::Standard => 20,
+            PckEntryLayout::Id64 => 24,
+        }
+    }
+
+    fn from_entry_size(size: usize) -> Option<Self> {
+        match size {
+            16 => Some(PckEntryLayout::Compact),
+            20 => Some(PckEntryLayout::Standard),
+            24 => Some(PckEntryLayout::Id64),
+            _ => None,
+        }
+    }
+}
+
+/// Bank or externals table: count followed by the entries.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PckFileTable {
+    /// Detected from the declared table length, kept on write.
+    pub layout: PckEntryLayout,
+    pub entries: Vec<PckFileEntry>,
+}
+
+impl PckFileTable {
+    /// Read a table of `length` bytes, including the count.
+    fn from_reader<R>(reader: &mut R, length: u32) -> Result<Self>
+    where
+        R: io::Read,
+    {
+        let count = reader.read_u32::<LE>()? as usize;
+        let entries_length = (length as usize).checked_sub(4).ok_or_else(|| {
+            PckError::Assertion(format!("table length {length} is smaller than its count"))
+        })?;
+        if count == 0 {
+            io::copy(&mut reader.take(entries_length as u64), &mut io::sink())?;
+            return Ok(PckFileTable::default());
+        }
+        let layout = (entries_length % count == 0)
+            .then(|| PckEntryLayout::from_entry_size(entries_length / count))
+            .flatten()
+            .ok_or_else(|| {
+                PckError::Assertion(format!(
+                    "unknown entry size of a table of {count} entries and {length} bytes"
+                ))
+            })?;
+
+        let mut entries = Vec::with_capacity(count);
+        for _ in 0..count {
+            let id = match layout {
+                PckEntryLayout::Id64 => reader.read_u64::<LE>()?,
+                _ => reader.read_u32::<LE>()? as u64,
+            };
+            let block_size = match layout {
+                PckEntryLayout::Compact => 1,
+                _ => reader.read_u32::<LE>()?,
+            };
+            entries.push(PckFileEntry {
+                id,
+                block_size,
+                length: reader.read_u32::<LE>()?,
+                offset: reader.read_u32::<LE>()?,
+                language_id: reader.read_u32::<LE>()?,
+            });
+        }
+        Ok(PckFileTable { layout, entries })
+    }
+
+    fn write_to<W>(&self, writer: &mut W) -> io::Result<()>
+    where
+        W: io::Write,
+    {
+        writer.write_u32::<LE>(self.entries.len() as u32)?;
+        for entry in &self.entries {
+            match self.layout {
+                PckEntryLayout::Id64 => writer.write_u64::<LE>(entry.id)?,
+                _ => writer.write_u32::<LE>(entry.id as u32)?,
+            }
+            if self.layout != PckEntryLayout::Compact {
+                writer.write_u32::<LE>(entry.block_size)?;
+            }
+            writer.write_u32::<LE>(entry.length)?;
+            writer.write_u32::<LE>(entry.offset)?;
+            writer.write_u32::<LE>(entry.language_id)?;
+        }
+        Ok(())
+    }
+
+    /// Size in bytes, including the count.
+    fn size(&self) -> usize {
+        4 + self.entries.len() * self.layout.entry_size()
+    }
+}
+
+/// Encoding of the strings of the language table.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PckStringEncoding {
+    /// UTF-16 strings, used by most platforms.
+    #[default]
+    Utf16,
+    /// 8-bit strings, used by some platforms.
+    Utf8,
+}
+
+impl PckStringEncoding {
+    /// Guess the encoding from the first two bytes of a string, language
+    /// names are ASCII.
+    fn detect(first_char: [u8; 2]) -> Self {
+        match first_char {
+            [0, _] | [_, 0] => PckStringEncoding::Utf16,
+            _ => PckStringEncoding::Utf8,
+        }
+    }
+
+    /// Null terminated bytes of a string.
+    fn encode(&self, s: &str) -> Vec<u8> {
+        match self {
+            PckStringEncoding::Utf16 => utils::string_to_utf16_bytes(s),
+            PckStringEncoding::Utf8 => s.bytes().chain(Some(0)).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PckString {
     pub index: u32,
     pub value: String,
 }
 
+/// Reader of the data of a wem.
+///
+/// The underlying reader is seeked before each read, so it can be moved by
+/// other readers in between, e.g. file handles sharing their position.
 pub struct PckWemReader<'a, R> {
     reader: &'a mut R,
     entry: &'a PckWemEntry,
-    read_size: usize,
+    /// Bytes of the wem read so far.
+    position: u64,
 }
 
 impl<'a, R> PckWemReader<'a, R>
@@ -303,9 +1350,18 @@ where
         PckWemReader {
             reader,
             entry,
-            read_size: 0,
+            position: 0,
         }
     }
+
+    /// Copy the rest of the wem into `sink`, returns the number of bytes
+    /// copied.
+    pub fn write_to<W>(&mut self, sink: &mut W) -> io::Result<u64>
+    where
+        W: io::Write + ?Sized,
+    {
+        io::copy(self, sink)
+    }
 }
 
 impl<R> io::Read for PckWemReader<'_, R>
@@ -313,32 +1369,31 @@ where
     R: io::Read + io::Seek,
 {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        if self.read_size == 0 {
-            self.reader
-                .seek(io::SeekFrom::Start(self.entry.offset as u64))?;
-        }
-        let available = self.entry.length as usize - self.read_size;
-        if available == 0 {
+        let available = self.entry.length as u64 - self.position;
+        if available == 0 || buf.is_empty() {
             return Ok(0);
         }
 
-        let size = if buf.len() > available {
-            let mut read_buf = vec![0u8; available];
-            self.reader.read_exact(&mut read_buf)?;
-            buf[..available].copy_from_slice(&read_buf);
-            available
-        } else {
-            self.reader.read_exact(buf)?;
-            buf.len()
-        };
-        self.read_size += size;
-        Ok(size)
+        self.reader.seek(io::SeekFrom::Start(
+            self.entry.offset as u64 + self.position,
+        ))?;
+        let size = (buf.len() as u64).min(available) as usize;
+        let read = self.reader.read(&mut buf[..size])?;
+        // the package ends before the wem
+        if read == 0 {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+        }
+        self.position += read as u64;
+        Ok(read)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::{fs, io::Read};
+    use std::{
+        fs,
+        io::{Read, Write},
+    };
 
     use super::*;
 
@@ -352,7 +1407,7 @@ mod tests {
         assert_eq!(header.language_size(), 20);
         assert_eq!(header.bnk_table_size(), 4);
         assert_eq!(header.wem_table_size(), 6664);
-        assert_eq!(header.unk_struct_size(), 4);
+        assert_eq!(header.externals_size(), 4);
         assert_eq!(header.header_size(), 6712);
         assert_eq!(header.get_wem_offset_start(), 6720);
 
@@ -369,11 +1424,702 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_pck_verify_length() {
+        let mut pck = Pck::from_file("test_files/Cat_cmn_m.spck.1.X64").unwrap();
+        pck.verify_length().unwrap();
+        pck.header_mut().header_length += 4;
+        assert!(matches!(
+            pck.verify_length(),
+            Err(PckError::HeaderLengthMismatch { .. })
+        ));
+
+        let mut pck = Pck::from_file("test_files/Cat_cmn_m_headeronly.spck.1.X64").unwrap();
+        assert!(matches!(
+            pck.verify_length(),
+            Err(PckError::Truncated { actual: 6720, .. })
+        ));
+    }
+
+    #[test]
+    fn test_pck_fix_values() {
+        let pck = Pck::from_file("test_files/Cat_cmn_m_headeronly.spck.1.X64").unwrap();
+        let original = pck.header().clone();
+        let mut header = original.clone();
+        header.fix_values();
+        for (entry, original) in header.wem_entries.iter().zip(&original.wem_entries) {
+            assert_eq!(entry.offset, original.offset);
+        }
+
+        // grow the first wem and add a bank, wem offsets stay in bytes
+        header.wem_entries[0].length += 3;
+        header.wem_entries[1].one = 16;
+        header.bnk_table.entries = vec![PckFileEntry {
+            id: 0x8ECF17C3,
+            block_size: 16,
+            length: 100,
+            offset: 0,
+            language_id: 0,
+        }];
+        header.fix_values();
+        assert_eq!(header.header_length, original.header_length + 20);
+        let data_start = original.get_wem_offset_start() + 20;
+        assert_eq!(header.bnk_table.entries[0].offset, data_start.div_ceil(16));
+        assert_eq!(
+            header.wem_entries[0].offset,
+            data_start.div_ceil(16) * 16 + 100
+        );
+        assert_eq!(
+            header.wem_entries[1].offset,
+            header.wem_entries[0].offset + original.wem_entries[0].length + 3
+        );
+    }
+
+    #[test]
+    fn test_pck_read_bnk() {
+        let bnk_data = fs::read("test_files/Wp00_Cmn.sbnk.1.X64").unwrap();
+        let mut header = PckHeader {
+            header_length: 0,
+            unk2: 1,
+            string_table: vec![PckString {
+                index: 0,
+                value: "sfx".to_string(),
+            }],
+            bnk_table: PckFileTable {
+                layout: PckEntryLayout::Standard,
+                entries: vec![PckFileEntry {
+                    id: 0x8ECF17C3,
+                    block_size: 1,
+                    length: bnk_data.len() as u32,
+                    offset: 0,
+                    language_id: 0,
+                }],
+            },
+            wem_entries: vec![],
+            externals: Some(PckFileTable::default()),
+            string_encoding: PckStringEncoding::Utf16,
+        };
+        header.bnk_table.entries[0].offset = header.get_wem_offset_start();
+
+        let mut output = io::Cursor::new(Vec::new());
+        header.write_to(&mut output).unwrap();
+        output.write_all(&bnk_data).unwrap();
+        output.set_position(0);
+
+        let mut pck = Pck::from_reader(output).unwrap();
+        assert_eq!(pck.bnk_ids().collect::<Vec<_>>(), vec![0x8ECF17C3]);
+        assert_eq!(pck.read_bnk_data(0x8ECF17C3).unwrap(), bnk_data);
+        let bnk = pck.read_bnk(0x8ECF17C3).unwrap();
+        assert_eq!(&bnk.sections[0].magic, b"BKHD");
+        assert!(matches!(
+            pck.read_bnk(0).unwrap_err(),
+            PckError::BnkNotFound(0)
+        ));
+    }
+
+    #[test]
+    fn test_pck_file_table_layouts() {
+        let entry = PckFileEntry {
+            id: 0x1_0000_0002,
+            block_size: 16,
+            length: 100,
+            offset: 3,
+            language_id: 1,
+        };
+        for layout in [
+            PckEntryLayout::Compact,
+            PckEntryLayout::Standard,
+            PckEntryLayout::Id64,
+        ] {
+            let table = PckFileTable {
+                layout,
+                entries: vec![entry.clone(), entry.clone()],
+            };
+            let mut data = vec![];
+            table.write_to(&mut data).unwrap();
+            assert_eq!(data.len(), table.size());
+            let read = PckFileTable::from_reader(&mut data.as_slice(), data.len() as u32).unwrap();
+            assert_eq!(read.layout, layout);
+            let expected = match layout {
+                PckEntryLayout::Compact => (2, 1),
+                PckEntryLayout::Standard => (2, 16),
+                PckEntryLayout::Id64 => (0x1_0000_0002, 16),
+            };
+            assert_eq!((read.entries[1].id, read.entries[1].block_size), expected);
+            assert_eq!(read.entries[1].offset, 3);
+        }
+        assert!(matches!(
+            PckFileTable::from_reader(&mut [1, 0, 0, 0, 0, 0, 0, 0].as_slice(), 8),
+            Err(PckError::Assertion(_))
+        ));
+
+        let pck = Pck::from_file("test_files/Cat_cmn_m_headeronly.spck.1.X64").unwrap();
+        let mut header = pck.header().clone();
+        header.externals = Some(PckFileTable {
+            layout: PckEntryLayout::Id64,
+            entries: vec![entry],
+        });
+        header.fix_values();
+        let mut output = io::Cursor::new(vec![]);
+        header.write_to(&mut output).unwrap();
+        output.set_position(0);
+        let read = PckHeader::from_reader(&mut output).unwrap();
+        assert_eq!(read.externals, header.externals);
+        assert_eq!(read.header_length, header.header_length);
+    }
+
+    #[test]
+    fn test_pck_string_encoding() {
+        let pck = Pck::from_file("test_files/Cat_cmn_m_headeronly.spck.1.X64").unwrap();
+        let mut header = pck.header().clone();
+        assert_eq!(header.string_encoding, PckStringEncoding::Utf16);
+        header.clone_language(0, "english(us)").unwrap();
+
+        header.string_encoding = PckStringEncoding::Utf8;
+        header.fix_values();
+        let mut output = io::Cursor::new(vec![]);
+        header.write_to(&mut output).unwrap();
+        // "sfx" and "english(us)" with terminators
+        assert_eq!(header.language_size(), 4 + 2 * 8 + 4 + 12);
+        assert_eq!(output.get_ref().len() as u32, header.get_wem_offset_start());
+        output.set_position(0);
+        let read = PckHeader::from_reader(&mut output).unwrap();
+        assert_eq!(read.string_encoding, PckStringEncoding::Utf8);
+        assert_eq!(read.language_name(0), Some("sfx"));
+        assert_eq!(
+            read.language_id("english(us)"),
+            header.language_id("english(us)")
+        );
+        assert_eq!(read.wem_entries.len(), header.wem_entries.len());
+    }
+
+    #[test]
+    fn test_pck_clone_language() {
+        let mut pck = Pck::from_file("test_files/Cat_cmn_m_headeronly.spck.1.X64").unwrap();
+        let header = pck.header_mut();
+        let sfx = header.language_id("SFX").unwrap();
+        let wem_count = header.wem_entries.len();
+
+        let english = header.clone_language(sfx, "english(us)").unwrap();
+        assert_ne!(english, sfx);
+        assert_eq!(header.language_name(english), Some("english(us)"));
+        assert_eq!(header.wem_entries.len(), wem_count * 2);
+        let first = header.wem_entries[0].clone();
+        let cloned = header
+            .wem_entries
+            .iter()
+            .find(|entry| entry.id == first.id && entry.language_id == english)
+            .unwrap();
+        assert_eq!((cloned.offset, cloned.length), (first.offset, first.length));
+        assert!(matches!(
+            header.clone_language(sfx, "English(US)"),
+            Err(PckError::LanguageExists(_))
+        ));
+        assert!(matches!(
+            header.clone_language(99, "german"),
+            Err(PckError::LanguageNotFound(99))
+        ));
+
+        let mut output = io::Cursor::new(vec![]);
+        header.write_to(&mut output).unwrap();
+        output.set_position(0);
+        let header = PckHeader::from_reader(&mut output).unwrap();
+        assert_eq!(header.wem_entries.len(), wem_count * 2);
+        assert_eq!(header.language_id("english(us)"), Some(english));
+    }
+
+    #[test]
+    fn test_pck_languages() {
+        let pck = Pck::from_file("test_files/Cat_cmn_m_headeronly.spck.1.X64").unwrap();
+        let mut header = pck.header().clone();
+        let wem_count = header.wem_entries.len();
+        assert_eq!(header.languages().collect::<Vec<_>>(), vec![(0, "sfx")]);
+
+        let german = header.add_language("german").unwrap();
+        let english = header.add_language("english(us)").unwrap();
+        assert_eq!((german, english), (1, 2));
+        assert!(matches!(
+            header.add_language("German"),
+            Err(PckError::LanguageExists(_))
+        ));
+        assert_eq!(
+            header.languages().collect::<Vec<_>>(),
+            vec![(english, "english(us)"), (german, "german"), (0, "sfx")]
+        );
+        assert_eq!(header.wems_of_language(0).count(), wem_count);
+        assert_eq!(header.wems_of_language(german).count(), 0);
+
+        header.wem_entries[0].language_id = german;
+        let id = header.wem_entries[0].id;
+        let german_ids: Vec<u32> = header.wems_of_language(german).map(|e| e.id).collect();
+        assert_eq!(german_ids, vec![id]);
+        assert_eq!(header.wems_of_language(0).count(), wem_count - 1);
+    }
+
+    #[test]
+    fn test_pck_append_wems() {
+        let input = fs::read("test_files/Cat_cmn_m.spck.1.X64").unwrap();
+        let mut pck = Pck::from_reader(io::Cursor::new(input.clone())).unwrap();
+        let original = pck.header().clone();
+        let replaced_id = original.wem_entries[5].id;
+        let new_wem = b"RIFF new wem".to_vec();
+        let replaced_wem = b"RIFF replaced".to_vec();
+
+        pck.append_wems(0, [(1, &new_wem), (replaced_id, &replaced_wem)])
+            .unwrap();
+        let output = pck.reader.into_inner();
+        // data is untouched, apart from the relocated first wems
+        let header_end = pck.header.get_wem_offset_start() as usize;
+        assert_eq!(output[header_end..input.len()], input[header_end..]);
+
+        let mut pck = Pck::from_reader(io::Cursor::new(output)).unwrap();
+        pck.verify_length().unwrap();
+        assert_eq!(
+            pck.header().wem_entries.len(),
+            original.wem_entries.len() + 1
+        );
+        assert_eq!(pck.read_wem(1).unwrap().unwrap(), new_wem);
+        assert_eq!(pck.read_wem(replaced_id).unwrap().unwrap(), replaced_wem);
+        let issues = pck.validate().unwrap();
+        assert_eq!(issues.len(), 2);
+        assert!(issues.contains(&ValidationIssue::InvalidMedia {
+            media_id: 1,
+            problem: wem::MediaProblem::NotRiff
+        }));
+        for entry in &original.wem_entries {
+            if entry.id == replaced_id {
+                continue;
+            }
+            let start = entry.offset as usize;
+            let wem = pck.read_wem(entry.id).unwrap().unwrap();
+            assert_eq!(wem, input[start..start + entry.length as usize]);
+        }
+    }
+
+    #[test]
+    fn test_pck_extract_parallel() {
+        let path = "test_files/Cat_cmn_m.spck.1.X64";
+        let mut pck = Pck::from_file(path).unwrap();
+        let extracted = std::sync::Mutex::new(HashMap::new());
+        pck.header()
+            .extract_parallel(
+                4,
+                || File::open(path),
+                |entry, data| {
+                    extracted.lock().unwrap().insert(entry.id, data);
+                    Ok(())
+                },
+            )
+            .unwrap();
+        let extracted = extracted.into_inner().unwrap();
+        assert_eq!(extracted.len(), pck.header().wem_entries.len());
+        let id = pck.header().wem_entries[3].id;
+        assert_eq!(extracted[&id], pck.read_wem(id).unwrap().unwrap());
+
+        let result = pck.header().extract_parallel(
+            2,
+            || File::open(path),
+            |_, _| Err(io::Error::other("sink full")),
+        );
+        assert!(matches!(result, Err(PckError::IO(_))));
+
+        let out_dir = std::env::temp_dir().join(format!("re-sound-extract-{}", std::process::id()));
+        let count = extract_file_parallel(path, &out_dir, 3).unwrap();
+        assert_eq!(count, extracted.len());
+        assert_eq!(
+            fs::read(out_dir.join(format!("{id}.wem"))).unwrap(),
+            extracted[&id]
+        );
+        fs::remove_dir_all(out_dir).unwrap();
+    }
+
+    #[test]
+    fn test_pck_write_to() {
+        let input = fs::read("test_files/Cat_cmn_m.spck.1.X64").unwrap();
+        let mut pck = Pck::from_reader(io::Cursor::new(input.clone())).unwrap();
+        let mut output = io::Cursor::new(vec![]);
+        pck.write_to(&mut output).unwrap();
+        assert_eq!(output.into_inner(), input);
+
+        let original = pck.header().clone();
+        let replaced_id = original.wem_entries[5].id;
+        let replaced_wem = b"RIFF replaced".to_vec();
+        pck.set_wem(replaced_id, 0, replaced_wem.clone()).unwrap();
+        pck.set_wem(1, 0, b"RIFF new wem".to_vec()).unwrap();
+        assert_eq!(pck.read_wem(replaced_id).unwrap().unwrap(), replaced_wem);
+
+        let mut output = io::Cursor::new(vec![]);
+        let header = pck.write_to(&mut output).unwrap();
+        assert_eq!(header.wem_entries.len(), original.wem_entries.len() + 1);
+        output.set_position(0);
+        let mut written = Pck::from_reader(output).unwrap();
+        written.verify_length().unwrap();
+        assert_eq!(
+            written.read_wem(replaced_id).unwrap().unwrap(),
+            replaced_wem
+        );
+        assert_eq!(written.read_wem(1).unwrap().unwrap(), b"RIFF new wem");
+        for entry in &original.wem_entries {
+            if entry.id != replaced_id {
+                assert_eq!(
+                    written.read_wem(entry.id).unwrap(),
+                    pck.read_wem(entry.id).unwrap()
+                );
+            }
+        }
+
+        let path = std::env::temp_dir().join(format!("re-sound-save-{}.pck", std::process::id()));
+        pck.save(&path).unwrap();
+        let mut saved = Pck::from_file(&path).unwrap();
+        assert_eq!(saved.read_wem(1).unwrap().unwrap(), b"RIFF new wem");
+        // saved over the file it reads from
+        saved.set_wem(2, 0, b"RIFF second wem".to_vec()).unwrap();
+        saved.save(&path).unwrap();
+        let mut saved = Pck::from_file(&path).unwrap();
+        assert_eq!(saved.read_wem(1).unwrap().unwrap(), b"RIFF new wem");
+        assert_eq!(saved.read_wem(2).unwrap().unwrap(), b"RIFF second wem");
+        assert_eq!(saved.read_wem(replaced_id).unwrap().unwrap(), replaced_wem);
+        fs::remove_file(path).unwrap();
+
+        let mut pck = Pck::from_file("test_files/Cat_cmn_m_headeronly.spck.1.X64").unwrap();
+        assert!(pck.write_to(&mut io::Cursor::new(vec![])).is_err());
+    }
+
+    #[test]
+    fn test_pck_set_wem_before_write() {
+        let input = fs::read("test_files/Cat_cmn_m.spck.1.X64").unwrap();
+        let mut pck = Pck::from_reader(io::Cursor::new(input.clone())).unwrap();
+        let original = pck.header().clone();
+        let entry = original.wem_entries[3].clone();
+        let start = entry.offset as usize;
+        let data = &input[start..start + entry.length as usize];
+
+        pck.set_wem(entry.id, 0, b"RIFF replaced".to_vec()).unwrap();
+        pck.set_wem(1, 0, b"RIFF new wem".to_vec()).unwrap();
+        // the header and readers still describe the package
+        let layout = |header: &PckHeader| -> Vec<_> {
+            header
+                .wem_entries
+                .iter()
+                .map(|e| (e.id, e.language_id, e.offset, e.length))
+                .collect()
+        };
+        assert_eq!(layout(pck.header()), layout(&original));
+        let mut read = vec![];
+        pck.wem_reader(3).unwrap().read_to_end(&mut read).unwrap();
+        assert_eq!(read, data);
+        read.clear();
+        pck.wem_reader_by_id(entry.id)
+            .unwrap()
+            .read_to_end(&mut read)
+            .unwrap();
+        assert_eq!(read, data);
+        assert!(pck.has_data());
+        assert!(pck.validate().unwrap().is_empty());
+        // reads by id see the new data
+        assert_eq!(pck.read_wem(entry.id).unwrap().unwrap(), b"RIFF replaced");
+        assert_eq!(pck.read_wem(1).unwrap().unwrap(), b"RIFF new wem");
+    }
+
+    #[test]
+    fn test_pck_rebuild_to() {
+        let input = fs::read("test_files/Cat_cmn_m.spck.1.X64").unwrap();
+        let mut pck = Pck::from_reader(io::Cursor::new(input.clone())).unwrap();
+        let mut output = io::Cursor::new(vec![]);
+        pck.rebuild_to(&mut output).unwrap();
+        assert_eq!(output.into_inner(), input);
+
+        let original = pck.header().clone();
+        let english = pck.header_mut().clone_language(0, "english(us)").unwrap();
+        let id = original.wem_entries[2].id;
+        let wem = b"RIFF replaced".to_vec();
+        pck.replace_wem(id, wem.clone()).unwrap();
+        assert!(matches!(
+            pck.replace_wem(1, vec![]),
+            Err(PckError::WemNotFound(1))
+        ));
+
+        let mut output = io::Cursor::new(vec![]);
+        let header = pck.rebuild_to(&mut output).unwrap();
+        // cloned entries share their data, the replaced wem is written once
+        let data_length = input.len() as u64 - original.get_wem_offset_start() as u64;
+        let replaced_length = original.wem_entries[2].length as u64;
+        assert_eq!(
+            output.get_ref().len() as u64,
+            header.get_wem_offset_start() as u64 + data_length - replaced_length + wem.len() as u64
+        );
+        output.set_position(0);
+        let mut rebuilt = Pck::from_reader(output).unwrap();
+        rebuilt.verify_length().unwrap();
+        for index in 0..rebuilt.header().wem_entries.len() {
+            let entry = rebuilt.header().wem_entries[index].clone();
+            let mut data = vec![];
+            rebuilt
+                .wem_reader(index)
+                .unwrap()
+                .read_to_end(&mut data)
+                .unwrap();
+            if entry.id == id {
+                assert_eq!(data, wem);
+            } else {
+                let original = original.wem_entries.iter().find(|e| e.id == entry.id);
+                let start = original.unwrap().offset as usize;
+                assert_eq!(data, input[start..start + entry.length as usize]);
+            }
+        }
+        assert_eq!(rebuilt.header().language_id("english(us)"), Some(english));
+    }
+
+    #[test]
+    fn test_pck_wem_by_id() {
+        let mut pck = Pck::from_file("test_files/Cat_cmn_m.spck.1.X64").unwrap();
+        let ids: Vec<u32> = pck.wem_ids().collect();
+        assert_eq!(ids.len(), pck.header().wem_entries.len());
+        let mut data = vec![];
+        pck.wem_reader_by_id(ids[4])
+            .unwrap()
+            .read_to_end(&mut data)
+            .unwrap();
+        assert_eq!(data.len(), pck.header().wem_entries[4].length as usize);
+        assert!(pck.wem_reader_by_id(0).is_none());
+
+        // the index follows edits of the entries
+        pck.header_mut().wem_entries.swap(0, 4);
+        assert_eq!(pck.read_wem(ids[4]).unwrap().unwrap(), data);
+        let english = pck.header_mut().clone_language(0, "english(us)").unwrap();
+        assert_eq!(pck.wem_ids().count(), ids.len() * 2);
+
+        let out_dir =
+            std::env::temp_dir().join(format!("re-sound-extract-all-{}", std::process::id()));
+        let count = pck.extract_all(&out_dir, PckHeader::wem_path).unwrap();
+        assert_eq!(count, ids.len() * 2);
+        let language = pck.header().language_name(english).unwrap();
+        assert_eq!(
+            fs::read(out_dir.join(language).join(format!("{}.wem", ids[4]))).unwrap(),
+            data
+        );
+        let count = pck
+            .extract_all(&out_dir, |_, entry| format!("flat/{}.wem", entry.id).into())
+            .unwrap();
+        assert_eq!(count, ids.len() * 2);
+        assert_eq!(
+            fs::read_dir(out_dir.join("flat")).unwrap().count(),
+            ids.len()
+        );
+        fs::remove_dir_all(out_dir).unwrap();
+    }
+
+    #[cfg(feature = "manifest")]
+    #[test]
+    fn test_pck_builder() {
+        let mut pck = Pck::from_file("test_files/Cat_cmn_m.spck.1.X64").unwrap();
+        let out_dir =
+            std::env::temp_dir().join(format!("re-sound-manifest-{}", std::process::id()));
+        fs::create_dir_all(out_dir.join("wem")).unwrap();
+        let ids: Vec<u32> = pck.header().wem_entries[..3].iter().map(|e| e.id).collect();
+        for id in &ids {
+            let wem = pck.read_wem(*id).unwrap().unwrap();
+            fs::write(out_dir.join(format!("wem/{id}.wem")), wem).unwrap();
+        }
+        fs::copy(
+            "test_files/Wp00_Cmn.sbnk.1.X64",
+            out_dir.join("Wp00_Cmn.bnk"),
+        )
+        .unwrap();
+        let manifest = format!(
+            r#"{{
+                "languages": ["sfx", "english(us)"],
+                "banks": [{{ "path": "Wp00_Cmn.bnk" }}],
+                "wems": [
+                    {{ "id": {0}, "language": "English(US)", "path": "wem/{0}.wem" }},
+                    {{ "id": {1}, "path": "wem/{1}.wem" }},
+                    {{ "id": {2}, "path": "wem/{2}.wem" }}
+                ]
+            }}"#,
+            ids[2], ids[1], ids[0]
+        );
+        fs::write(out_dir.join("package.json"), manifest).unwrap();
+        let toml_manifest = format!(
+            "bnk_block_size = 16\n[[banks]]\npath = \"Wp00_Cmn.bnk\"\n[[wems]]\nid = {}\npath = \"wem/{}.wem\"\n",
+            ids[0], ids[0]
+        );
+        fs::write(out_dir.join("package.toml"), toml_manifest).unwrap();
+
+        let builder = PckBuilder::from_manifest(out_dir.join("package.json")).unwrap();
+        let mut output = io::Cursor::new(vec![]);
+        let header = builder.build(&mut output).unwrap();
+        // entries are sorted
+        assert_eq!(
+            header
+                .wem_entries
+                .iter()
+                .map(|e| (e.id, e.language_id))
+                .collect::<Vec<_>>(),
+            vec![(ids[0], 0), (ids[1], 0), (ids[2], 1)]
+        );
+        output.set_position(0);
+        let mut built = Pck::from_reader(output).unwrap();
+        built.verify_length().unwrap();
+        assert_eq!(built.header().language_name(1), Some("english(us)"));
+        for id in &ids {
+            assert_eq!(built.read_wem(*id).unwrap(), pck.read_wem(*id).unwrap());
+        }
+        let bnk = built.read_bnk(0x8ECF17C3).unwrap();
+        assert_eq!(bnk.bank_id(), Some(0x8ECF17C3));
+
+        let mut builder = PckBuilder::from_manifest(out_dir.join("package.json")).unwrap();
+        builder.set_bnk_block_size(2048);
+        let mut output = io::Cursor::new(vec![]);
+        let header = builder.build(&mut output).unwrap();
+        let bnk_entry = &header.bnk_table.entries[0];
+        assert_eq!(bnk_entry.block_size, 2048);
+        assert_eq!(bnk_entry.data_offset() % 2048, 0);
+        output.set_position(0);
+        let mut built = Pck::from_reader(output).unwrap();
+        built.verify_length().unwrap();
+        assert_eq!(
+            built.read_bnk(0x8ECF17C3).unwrap().bank_id(),
+            Some(0x8ECF17C3)
+        );
+        assert_eq!(
+            built.read_wem(ids[0]).unwrap(),
+            pck.read_wem(ids[0]).unwrap()
+        );
+
+        let builder = PckBuilder::from_manifest(out_dir.join("package.toml")).unwrap();
+        let header = builder.build(&mut io::Cursor::new(vec![])).unwrap();
+        assert_eq!(header.language_name(0), Some("sfx"));
+        assert_eq!(header.wem_entries.len(), 1);
+        assert_eq!(header.bnk_table.entries[0].block_size, 16);
+
+        let mut builder = PckBuilder::new();
+        let sfx = builder.add_language("sfx");
+        builder.add_wem(1, sfx, vec![1, 2]).unwrap();
+        let externals = PckFileTable {
+            layout: PckEntryLayout::Id64,
+            entries: vec![PckFileEntry {
+                id: 0x1_0000_0002,
+                block_size: 1,
+                length: 100,
+                offset: 0,
+                language_id: sfx,
+            }],
+        };
+        builder.set_externals(Some(externals.clone()));
+        let mut output = io::Cursor::new(vec![]);
+        builder.build(&mut output).unwrap();
+        output.set_position(0);
+        let mut built = Pck::from_reader(output).unwrap();
+        assert_eq!(built.header().externals, Some(externals));
+        assert_eq!(built.read_wem(1).unwrap().unwrap(), vec![1, 2]);
+        assert!(matches!(
+            builder.add_wem(1, sfx, vec![3]),
+            Err(PckError::DuplicateEntry { id: 1, .. })
+        ));
+        assert!(matches!(
+            builder.add_wem(2, 5, vec![3]),
+            Err(PckError::LanguageNotFound(5))
+        ));
+        fs::remove_dir_all(out_dir).unwrap();
+    }
+
+    #[test]
+    fn test_pck_wem_reader_short_reads() {
+        /// Reader returning at most 7 bytes per read.
+        struct ShortReader<R>(R);
+
+        impl<R: Read> Read for ShortReader<R> {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                let size = buf.len().min(7);
+                self.0.read(&mut buf[..size])
+            }
+        }
+
+        impl<R: io::Seek> io::Seek for ShortReader<R> {
+            fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+                self.0.seek(pos)
+            }
+        }
+
+        let path = "test_files/Cat_cmn_m.spck.1.X64";
+        let mut expected = Pck::from_file(path).unwrap();
+        let id = expected.header().wem_entries[2].id;
+        let expected = expected.read_wem(id).unwrap().unwrap();
+
+        let file = File::open(path).unwrap();
+        let mut pck = Pck::from_reader(ShortReader(file.try_clone().unwrap())).unwrap();
+        assert_eq!(pck.read_wem(id).unwrap().unwrap(), expected);
+
+        // another handle moves the shared position between reads
+        let mut other = file;
+        let mut wem_reader = pck.wem_reader(2).unwrap();
+        let mut data = vec![0; 100];
+        wem_reader.read_exact(&mut data).unwrap();
+        io::Seek::seek(&mut other, io::SeekFrom::Start(0)).unwrap();
+        other.read_exact(&mut [0; 16]).unwrap();
+        wem_reader.read_to_end(&mut data).unwrap();
+        assert_eq!(data, expected);
+    }
+
+    #[cfg(any(unix, windows))]
+    #[test]
+    fn test_pck_shared() {
+        let path = "test_files/Cat_cmn_m.spck.1.X64";
+        let mut pck = Pck::from_file(path).unwrap();
+        let ids: Vec<u32> = pck.wem_ids().collect();
+        let expected: Vec<Vec<u8>> = ids
+            .iter()
+            .map(|id| pck.read_wem(*id).unwrap().unwrap())
+            .collect();
+
+        pck.set_wem(ids[0], 0, b"RIFF replaced".to_vec()).unwrap();
+        let Err(PckError::PendingReplacements(1)) = pck.into_shared() else {
+            panic!("pending replacements are shared");
+        };
+        let shared = SharedPck::from_file(path).unwrap();
+        thread::scope(|scope| {
+            for offset in 0..4 {
+                let (shared, ids, expected) = (&shared, &ids, &expected);
+                scope.spawn(move || {
+                    // interleave reads of several wems
+                    let mut readers: Vec<_> = (offset..ids.len())
+                        .step_by(4)
+                        .map(|index| (index, shared.wem_reader(index).unwrap(), vec![]))
+                        .collect();
+                    let mut buf = [0; 1000];
+                    while !readers.is_empty() {
+                        readers.retain_mut(|(index, reader, data)| {
+                            let read = reader.read(&mut buf).unwrap();
+                            data.extend_from_slice(&buf[..read]);
+                            if read == 0 {
+                                assert_eq!(*data, expected[*index]);
+                            }
+                            read > 0
+                        });
+                    }
+                });
+            }
+        });
+        assert_eq!(shared.read_wem(ids[7]).unwrap().unwrap(), expected[7]);
+        assert_eq!(shared.wem_reader_by_id(ids[7]).unwrap().entry().id, ids[7]);
+        assert!(shared.read_wem(0).unwrap().is_none());
+    }
+
     #[test]
     fn test_pck_from_reader() {
         let mut pck = Pck::from_file("test_files/Cat_cmn_m.spck.1.X64").unwrap();
 
         assert!(pck.has_data());
+        assert!(pck.validate().unwrap().is_empty());
+        let id = pck.header().wem_entries[1].id;
+        let wem = pck.read_wem(id).unwrap().unwrap();
+        assert_eq!(wem.len(), pck.header().wem_entries[1].length as usize);
+        assert!(pck.read_wem(0).unwrap().is_none());
+        let mut sink = io::Cursor::new(vec![0; 4]);
+        sink.set_position(4);
+        assert_eq!(pck.copy_wem(id, &mut sink).unwrap(), Some(wem.len() as u64));
+        assert_eq!(sink.get_ref()[4..], wem);
+        assert_eq!(pck.copy_wem(0, &mut io::sink()).unwrap(), None);
         for i in 0..pck.header().wem_entries.len() {
             let mut wem_reader = pck.wem_reader(i).unwrap();
             let mut buf = vec![];
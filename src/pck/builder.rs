@@ -0,0 +1,377 @@
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use super::{
+    PckEntryLayout, PckError, PckFileEntry, PckFileTable, PckHeader, PckString, PckStringEncoding,
+    PckWemEntry, Result,
+};
+
+/// Data of a wem or bank added to a [PckBuilder].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PckSource {
+    Bytes(Vec<u8>),
+    /// File read when the package is built.
+    File(PathBuf),
+}
+
+impl PckSource {
+    pub(super) fn len(&self) -> io::Result<u64> {
+        match self {
+            PckSource::Bytes(data) => Ok(data.len() as u64),
+            PckSource::File(path) => Ok(fs::metadata(path)?.len()),
+        }
+    }
+
+    pub(super) fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        match self {
+            PckSource::Bytes(data) => writer.write_all(data),
+            PckSource::File(path) => io::copy(&mut File::open(path)?, writer).map(|_| ()),
+        }
+    }
+}
+
+impl From<Vec<u8>> for PckSource {
+    fn from(data: Vec<u8>) -> Self {
+        PckSource::Bytes(data)
+    }
+}
+
+impl From<PathBuf> for PckSource {
+    fn from(path: PathBuf) -> Self {
+        PckSource::File(path)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct BuilderEntry {
+    id: u32,
+    language_id: u32,
+    source: PckSource,
+}
+
+/// Assembles a package from wems and banks.
+///
+/// Entries are sorted by id and language like packages written by Wwise, so
+/// the output only depends on the added entries and not on their order.
+#[derive(Debug, Clone)]
+pub struct PckBuilder {
+    languages: Vec<String>,
+    banks: Vec<BuilderEntry>,
+    wems: Vec<BuilderEntry>,
+    string_encoding: PckStringEncoding,
+    bnk_block_size: u32,
+    externals: Option<PckFileTable>,
+}
+
+impl Default for PckBuilder {
+    fn default() -> Self {
+        PckBuilder {
+            languages: Vec::new(),
+            banks: Vec::new(),
+            wems: Vec::new(),
+            string_encoding: PckStringEncoding::default(),
+            bnk_block_size: 1,
+            externals: Some(PckFileTable::default()),
+        }
+    }
+}
+
+impl PckBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a language, returns its id. The id of an existing language with
+    /// the same name, ignoring case, is returned instead.
+    pub fn add_language(&mut self, name: &str) -> u32 {
+        if let Some(id) = self
+            .languages
+            .iter()
+            .position(|language| language.eq_ignore_ascii_case(name))
+        {
+            return id as u32;
+        }
+        self.languages.push(name.to_string());
+        self.languages.len() as u32 - 1
+    }
+
+    /// Encoding of the language names, UTF-16 by default.
+    pub fn set_string_encoding(&mut self, encoding: PckStringEncoding) {
+        self.string_encoding = encoding;
+    }
+
+    /// Alignment of the banks in bytes, 1 by default. Packages for optical
+    /// media align them to the sector size, e.g. 2048.
+    pub fn set_bnk_block_size(&mut self, block_size: u32) {
+        self.bnk_block_size = block_size.max(1);
+    }
+
+    /// Table of external sources written as is, empty by default. `None`
+    /// omits the table, as in older packages.
+    ///
+    /// The external sources are separate files, their entries aren't checked.
+    pub fn set_externals(&mut self, externals: Option<PckFileTable>) {
+        self.externals = externals;
+    }
+
+    pub fn add_wem(
+        &mut self,
+        id: u32,
+        language_id: u32,
+        source: impl Into<PckSource>,
+    ) -> Result<()> {
+        Self::add_entry(
+            &self.languages,
+            &mut self.wems,
+            id,
+            language_id,
+            source.into(),
+        )
+    }
+
+    /// Add an embedded bank, e.g. the bank of a streamed language.
+    pub fn add_bnk(
+        &mut self,
+        id: u32,
+        language_id: u32,
+        source: impl Into<PckSource>,
+    ) -> Result<()> {
+        Self::add_entry(
+            &self.languages,
+            &mut self.banks,
+            id,
+            language_id,
+            source.into(),
+        )
+    }
+
+    fn add_entry(
+        languages: &[String],
+        entries: &mut Vec<BuilderEntry>,
+        id: u32,
+        language_id: u32,
+        source: PckSource,
+    ) -> Result<()> {
+        if language_id as usize >= languages.len() {
+            return Err(PckError::LanguageNotFound(language_id));
+        }
+        let key = (id, language_id);
+        match entries.binary_search_by_key(&key, |e| (e.id, e.language_id)) {
+            Ok(_) => Err(PckError::DuplicateEntry { id, language_id }),
+            Err(idx) => {
+                entries.insert(
+                    idx,
+                    BuilderEntry {
+                        id,
+                        language_id,
+                        source,
+                    },
+                );
+                Ok(())
+            }
+        }
+    }
+
+    /// Write the package, returns its header.
+    ///
+    /// The writer is expected to be at the start of the output.
+    pub fn build<W>(&self, writer: &mut W) -> Result<PckHeader>
+    where
+        W: io::Write + io::Seek,
+    {
+        let bnk_entries = self
+            .banks
+            .iter()
+            .map(|entry| {
+                Ok(PckFileEntry {
+                    id: entry.id as u64,
+                    block_size: self.bnk_block_size,
+                    length: super::to_offset(entry.source.len()?)?,
+                    offset: 0,
+                    language_id: entry.language_id,
+                })
+            })
+            .collect::<Result<_>>()?;
+        let wem_entries = self
+            .wems
+            .iter()
+            .map(|entry| {
+                Ok(PckWemEntry {
+                    id: entry.id,
+                    one: 1,
+                    length: super::to_offset(entry.source.len()?)?,
+                    offset: 0,
+                    language_id: entry.language_id,
+                })
+            })
+            .collect::<Result<_>>()?;
+        let mut header = PckHeader {
+            header_length: 0,
+            unk2: 1,
+            string_table: self
+                .languages
+                .iter()
+                .enumerate()
+                .map(|(index, value)| PckString {
+                    index: index as u32,
+                    value: value.clone(),
+                })
+                .collect(),
+            bnk_table: PckFileTable {
+                layout: PckEntryLayout::Standard,
+                entries: bnk_entries,
+            },
+            wem_entries,
+            externals: self.externals.clone(),
+            string_encoding: self.string_encoding,
+        };
+        header.fix_values();
+        header.write_to(writer)?;
+
+        // same layout as fix_values: banks then wems
+        let bnk_offsets = header
+            .bnk_table
+            .entries
+            .iter()
+            .map(|entry| (entry.data_offset(), entry.length as u64));
+        let wem_offsets = header
+            .wem_entries
+            .iter()
+            .map(|entry| (entry.offset as u64, entry.length as u64));
+        let sources = self.banks.iter().chain(&self.wems);
+        for ((offset, length), entry) in bnk_offsets.chain(wem_offsets).zip(sources) {
+            super::pad_to(writer, offset)?;
+            entry.source.write_to(writer)?;
+            // files may change between the header and the data writes
+            if writer.stream_position()? != offset + length {
+                return Err(PckError::Assertion(format!(
+                    "size of {:?} changed while building",
+                    entry.source
+                )));
+            }
+        }
+        Ok(header)
+    }
+
+    /// Build a package to a file.
+    pub fn build_file(&self, path: impl AsRef<Path>) -> Result<PckHeader> {
+        let mut writer = io::BufWriter::new(File::create(path)?);
+        let header = self.build(&mut writer)?;
+        writer.flush()?;
+        Ok(header)
+    }
+
+    /// Create a builder from a manifest file, JSON or TOML with the `.toml`
+    /// extension, for packages built reproducibly from a description:
+    ///
+    /// ```json
+    /// {
+    ///     "languages": ["sfx", "english(us)"],
+    ///     "banks": [{ "language": "english(us)", "path": "banks/Vo_En.bnk" }],
+    ///     "wems": [
+    ///         { "id": 1001, "path": "wem/1001.wem" },
+    ///         { "id": 1002, "language": "english(us)", "path": "wem/en/1002.wem" }
+    ///     ]
+    /// }
+    /// ```
+    ///
+    /// Paths are relative to the manifest. Entries without a language use the
+    /// first language, `sfx` if none are listed. Banks without an id use the
+    /// id of their BKHD section. An optional `bnk_block_size` sets the
+    /// alignment of the banks, see [PckBuilder::set_bnk_block_size].
+    #[cfg(feature = "manifest")]
+    pub fn from_manifest(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let manifest: manifest::PckManifest = crate::utils::read_manifest(path)?;
+        let base = path.parent().unwrap_or(Path::new(""));
+
+        let mut builder = Self::new();
+        if let Some(block_size) = manifest.bnk_block_size {
+            builder.set_bnk_block_size(block_size);
+        }
+        for language in &manifest.languages {
+            builder.add_language(language);
+        }
+        if builder.languages.is_empty() {
+            builder.add_language("sfx");
+        }
+        let language_id = |name: &Option<String>| match name {
+            Some(name) => builder
+                .languages
+                .iter()
+                .position(|language| language.eq_ignore_ascii_case(name))
+                .map(|id| id as u32)
+                .ok_or_else(|| {
+                    PckError::Manifest(format!("language {name:?} is not in the languages"))
+                }),
+            None => Ok(0),
+        };
+        let banks = manifest
+            .banks
+            .iter()
+            .map(|bank| {
+                let source = base.join(&bank.path);
+                let id = match bank.id {
+                    Some(id) => id,
+                    None => crate::bnk::BnkView::from_bytes(&fs::read(&source)?)?
+                        .bank_id()
+                        .ok_or_else(|| {
+                            PckError::Manifest(format!("no bank id in {}", source.display()))
+                        })?,
+                };
+                Ok((id, language_id(&bank.language)?, source))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let wems = manifest
+            .wems
+            .iter()
+            .map(|wem| Ok((wem.id, language_id(&wem.language)?, base.join(&wem.path))))
+            .collect::<Result<Vec<_>>>()?;
+
+        for (id, language_id, source) in banks {
+            builder.add_bnk(id, language_id, source)?;
+        }
+        for (id, language_id, source) in wems {
+            builder.add_wem(id, language_id, source)?;
+        }
+        Ok(builder)
+    }
+}
+
+#[cfg(feature = "manifest")]
+mod manifest {
+    use std::path::PathBuf;
+
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    pub struct PckManifest {
+        #[serde(default)]
+        pub languages: Vec<String>,
+        #[serde(default)]
+        pub banks: Vec<BankEntry>,
+        #[serde(default)]
+        pub wems: Vec<WemEntry>,
+        pub bnk_block_size: Option<u32>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    pub struct BankEntry {
+        pub id: Option<u32>,
+        pub language: Option<String>,
+        pub path: PathBuf,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    pub struct WemEntry {
+        pub id: u32,
+        pub language: Option<String>,
+        pub path: PathBuf,
+    }
+}
@@ -0,0 +1,125 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, Read},
+    path::Path,
+};
+
+use super::{Pck, PckError, PckHeader, PckWemEntry, Result};
+
+/// Package whose wems are read concurrently, see [Pck::into_shared].
+///
+/// Readers use positional reads on the file, so any number of them can be
+/// used at the same time from several threads. Only available on Unix and
+/// Windows, which provide positional reads.
+pub struct SharedPck {
+    file: File,
+    header: PckHeader,
+    /// Index of the first wem entry of each id.
+    wem_index: HashMap<u32, usize>,
+}
+
+impl Pck<io::BufReader<File>> {
+    /// Share the package between threads, see [SharedPck].
+    ///
+    /// Only the data of the file is shared, fails with
+    /// [PckError::PendingReplacements] if wems were set by [Pck::set_wem]
+    /// without writing the package.
+    pub fn into_shared(self) -> Result<SharedPck> {
+        if !self.replacements.is_empty() {
+            return Err(PckError::PendingReplacements(self.replacements.len()));
+        }
+        Ok(SharedPck::new(self.reader.into_inner(), self.header))
+    }
+}
+
+impl SharedPck {
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        Pck::from_file(path)?.into_shared()
+    }
+
+    fn new(file: File, header: PckHeader) -> Self {
+        let mut wem_index = HashMap::with_capacity(header.wem_entries.len());
+        for (position, entry) in header.wem_entries.iter().enumerate().rev() {
+            wem_index.insert(entry.id, position);
+        }
+        SharedPck {
+            file,
+            header,
+            wem_index,
+        }
+    }
+
+    pub fn header(&self) -> &PckHeader {
+        &self.header
+    }
+
+    pub fn wem_reader(&self, index: usize) -> Option<SharedWemReader<'_>> {
+        let entry = self.header.wem_entries.get(index)?;
+        Some(SharedWemReader {
+            file: &self.file,
+            entry,
+            position: 0,
+        })
+    }
+
+    /// Reader of a wem by id, the first one of the wem table for ids held by
+    /// several languages.
+    pub fn wem_reader_by_id(&self, id: u32) -> Option<SharedWemReader<'_>> {
+        self.wem_reader(*self.wem_index.get(&id)?)
+    }
+
+    /// Read a wem by id, `None` if it is not in the wem table.
+    pub fn read_wem(&self, id: u32) -> Result<Option<Vec<u8>>> {
+        let Some(mut reader) = self.wem_reader_by_id(id) else {
+            return Ok(None);
+        };
+        let mut data = Vec::with_capacity(reader.entry.length as usize);
+        reader.read_to_end(&mut data)?;
+        Ok(Some(data))
+    }
+}
+
+/// Reader of the data of a wem of a [SharedPck].
+pub struct SharedWemReader<'a> {
+    file: &'a File,
+    entry: &'a PckWemEntry,
+    /// Bytes of the wem read so far.
+    position: u64,
+}
+
+impl SharedWemReader<'_> {
+    pub fn entry(&self) -> &PckWemEntry {
+        self.entry
+    }
+}
+
+impl io::Read for SharedWemReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.entry.length as u64 - self.position;
+        if available == 0 || buf.is_empty() {
+            return Ok(0);
+        }
+
+        let size = (buf.len() as u64).min(available) as usize;
+        let offset = self.entry.offset as u64 + self.position;
+        let read = read_at(self.file, &mut buf[..size], offset)?;
+        // the package ends before the wem
+        if read == 0 {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+        }
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+#[cfg(unix)]
+fn read_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    std::os::unix::fs::FileExt::read_at(file, buf, offset)
+}
+
+/// Reads move the file position, which other readers don't rely on.
+#[cfg(windows)]
+fn read_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    std::os::windows::fs::FileExt::seek_read(file, buf, offset)
+}
@@ -1,6 +1,9 @@
 use std::io::{self, Read, Write};
 
-use byteorder::{LE, ReadBytesExt, WriteBytesExt};
+use byteorder::{LE, WriteBytesExt};
+
+/// Most elements allocated up front by [ReadVecExt::read_vec_fn].
+const MAX_PREALLOC: usize = 4096;
 
 pub trait ReadVecExt: Read {
     fn read_vec_u8(&mut self, vec_size: usize) -> io::Result<Vec<u8>> {
@@ -23,50 +26,15 @@ pub trait ReadVecExt: Read {
         Ok(v)
     }
 
-    /// Read a vector of `T`s from the stream.
-    ///
-    /// # Safety
-    ///
-    /// Using [std::mem::transmute] to convert types.
-    unsafe fn read_vec_t_sized<T>(&mut self, vec_size: usize) -> io::Result<Vec<T>>
-    where
-        T: Sized,
-    {
-        let mut v = Vec::with_capacity(vec_size);
-        for _ in 0..vec_size {
-            unsafe {
-                let mut element = std::mem::MaybeUninit::<T>::uninit();
-                self.read_exact(std::slice::from_raw_parts_mut(
-                    element.as_mut_ptr() as *mut u8,
-                    std::mem::size_of::<T>(),
-                ))?;
-                v.push(element.assume_init());
-            }
-        }
-        Ok(v)
-    }
-
-    /// Read a vector of `T`s from the stream.
-    /// It will read the size of the vector first, then read the elements.
-    /// The size is a u32 in little-endian format.
+    /// Read `vec_size` elements with `f`.
     ///
-    /// # Safety
-    ///
-    /// Using [std::mem::transmute] to convert types.
-    #[allow(dead_code)]
-    unsafe fn read_vec_t<T>(&mut self) -> io::Result<Vec<T>>
-    where
-        T: Sized,
-    {
-        let size = self.read_u32::<LE>()?;
-        unsafe { self.read_vec_t_sized(size as usize) }
-    }
-
+    /// The size is often read from the input, the preallocation is capped so
+    /// corrupt sizes fail on the first missing element instead of allocating.
     fn read_vec_fn<F, T, E>(&mut self, vec_size: usize, mut f: F) -> std::result::Result<Vec<T>, E>
     where
         F: FnMut(&mut Self) -> std::result::Result<T, E>,
     {
-        let mut v = Vec::with_capacity(vec_size);
+        let mut v = Vec::with_capacity(vec_size.min(MAX_PREALLOC));
         for _ in 0..vec_size {
             v.push(f(self)?);
         }
@@ -88,25 +56,6 @@ pub trait WriteVecExt: Write {
         }
         Ok(size)
     }
-
-    unsafe fn write_vec_t_unsafe<T>(&mut self, elements: &[T]) -> io::Result<usize>
-    where
-        T: Sized,
-    {
-        let mut size = 0;
-        self.write_u32::<LE>(elements.len() as u32)?;
-        size += size_of::<u32>();
-        for element in elements {
-            unsafe {
-                self.write_all(std::slice::from_raw_parts(
-                    element as *const T as *const u8,
-                    std::mem::size_of::<T>(),
-                ))?;
-                size += std::mem::size_of::<T>();
-            }
-        }
-        Ok(size)
-    }
 }
 
 impl<T> WriteVecExt for T where T: Write {}
@@ -114,6 +63,7 @@ impl<T> WriteVecExt for T where T: Write {}
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct BinrwNullString(pub binrw::NullString);
 
+#[cfg(feature = "serde")]
 impl serde::Serialize for BinrwNullString {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -123,6 +73,7 @@ impl serde::Serialize for BinrwNullString {
     }
 }
 
+#[cfg(feature = "serde")]
 impl<'de> serde::de::Deserialize<'de> for BinrwNullString {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -15,6 +15,19 @@ pub fn string_from_utf16_reader<R: io::Read>(reader: &mut R) -> io::Result<Strin
     String::from_utf16(&utf16_buf).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
 }
 
+/// Create String from 8-bit string bytes with null terminator.
+pub fn string_from_utf8_reader<R: io::Read>(reader: &mut R) -> io::Result<String> {
+    let mut buf = vec![];
+    loop {
+        let char = reader.read_u8()?;
+        if char == 0 {
+            break;
+        }
+        buf.push(char);
+    }
+    String::from_utf8(buf).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
 pub fn string_to_utf16_bytes(s: &str) -> Vec<u8> {
     s.encode_utf16()
         .chain(Some(0))
@@ -32,3 +45,60 @@ where
     f(writer)?;
     Ok(writer.stream_position()? - pos)
 }
+
+/// Write a file through a temporary sibling file renamed over `path` once
+/// complete, so readers never see a partially written file.
+pub fn write_file_atomic<F, E>(path: &std::path::Path, f: F) -> Result<(), E>
+where
+    F: FnOnce(&mut io::BufWriter<std::fs::File>) -> Result<(), E>,
+    E: From<io::Error>,
+{
+    let name = path.file_name().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("not a file path: {}", path.display()),
+        )
+    })?;
+    let mut temp_name = std::ffi::OsString::from(".");
+    temp_name.push(name);
+    temp_name.push(format!(".{}.tmp", std::process::id()));
+    let temp_path = path.with_file_name(temp_name);
+
+    let result = (|| {
+        let mut writer = io::BufWriter::new(std::fs::File::create(&temp_path)?);
+        f(&mut writer)?;
+        let file = writer
+            .into_inner()
+            .map_err(io::IntoInnerError::into_error)?;
+        file.sync_all()?;
+        std::fs::rename(&temp_path, path)?;
+        Ok(())
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// Read a manifest file, TOML for `.toml` files and JSON otherwise.
+#[cfg(feature = "manifest")]
+pub fn read_manifest<T>(path: &std::path::Path) -> io::Result<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    let text = std::fs::read_to_string(path)?;
+    let invalid = |e: String| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid manifest {}: {e}", path.display()),
+        )
+    };
+    if path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("toml"))
+    {
+        toml::from_str(&text).map_err(|e| invalid(e.to_string()))
+    } else {
+        serde_json::from_str(&text).map_err(|e| invalid(e.to_string()))
+    }
+}
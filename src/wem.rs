@@ -0,0 +1,765 @@
+//! Wwise encoded media (.wem) headers.
+
+mod fmt;
+pub mod vorbis;
+
+pub use fmt::*;
+
+use std::{
+    fs,
+    io::{self, Read, Seek},
+    path::PathBuf,
+};
+
+use byteorder::{LE, ReadBytesExt, WriteBytesExt};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+type Result<T> = std::result::Result<T, WemError>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum WemError {
+    #[error("IO error: {0}")]
+    IO(#[from] io::Error),
+
+    #[error("Not a RIFF file.")]
+    NotRiff,
+    #[error("Big-endian RIFX file, convert it with rifx_to_riff.")]
+    Rifx,
+    #[error("Invalid Vorbis data: {0}")]
+    InvalidVorbis(String),
+    #[error("Invalid chunk size at {offset}: {size}")]
+    InvalidChunkSize { offset: usize, size: u32 },
+    #[error("Missing chunk: {0}")]
+    MissingChunk(String),
+    #[error("Unsupported WAV format: {0}")]
+    UnsupportedWav(String),
+}
+
+/// Format tag of Wwise Vorbis media.
+pub const FORMAT_VORBIS: u16 = 0xFFFF;
+pub const FORMAT_PCM: u16 = 0x0001;
+pub const FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+/// Basic information from the `fmt ` and `data` chunks of a wem file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct WemInfo {
+    pub format_tag: u16,
+    pub channels: u16,
+    pub sample_rate: u32,
+    pub avg_bytes_per_sec: u32,
+    pub block_align: u16,
+    pub bits_per_sample: u16,
+    /// Sample count stored in the extended fmt chunk of Vorbis media.
+    pub sample_count: Option<u32>,
+    /// Offset of the first audio packet from the start of the data of Vorbis
+    /// media, after the seek table and setup packet.
+    pub first_audio_packet_offset: Option<u32>,
+    /// Offset of the data chunk payload in the file.
+    pub data_offset: u32,
+    pub data_size: u32,
+}
+
+/// Format tags of the codecs Wwise encodes media with.
+pub const KNOWN_FORMATS: &[u16] = &[
+    FORMAT_PCM,
+    0x0002, // IMA ADPCM
+    0x0069, // Xbox IMA ADPCM
+    0x0161, // XWMA
+    0x0162, // XWMA
+    0x0165, // XMA2
+    0x0166, // XMA2
+    0x3039, // Opus (Switch)
+    0x3040, // Opus
+    0x3041, // Opus WEM
+    0x8311, // PTADPCM
+    0xAAC0, // AAC
+    0xFFF0, // DSP ADPCM
+    0xFFFB, // HEVAG
+    0xFFFC, // ATRAC9
+    FORMAT_EXTENSIBLE,
+    FORMAT_VORBIS,
+];
+
+/// Problem found in media data by [check_media].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MediaProblem {
+    /// Data doesn't start with a RIFF or RIFX header.
+    NotRiff,
+    /// The RIFF size doesn't match the length of the media.
+    SizeMismatch {
+        riff_size: u32,
+        length: u32,
+    },
+    MissingFmt,
+    UnknownFormat(u16),
+}
+
+/// Check that media data looks like a wem: a RIFF or RIFX header, a size
+/// matching `data.len()` and a known codec.
+///
+/// Prefetch data only holds the start of the wem, with `prefetch` set it may
+/// be shorter than the RIFF size.
+pub fn check_media(data: &[u8], prefetch: bool) -> Option<MediaProblem> {
+    if data.len() < 12 || !matches!(&data[..4], b"RIFF" | b"RIFX") || &data[8..12] != b"WAVE" {
+        return Some(MediaProblem::NotRiff);
+    }
+    let big_endian = is_rifx(data);
+    let u32_at = |pos: usize| {
+        let bytes = data[pos..pos + 4].try_into().unwrap();
+        if big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        }
+    };
+
+    let riff_size = u32_at(4);
+    let length = data.len() as u32;
+    let expected = riff_size as u64 + 8;
+    if expected < length as u64 || (!prefetch && expected != length as u64) {
+        return Some(MediaProblem::SizeMismatch { riff_size, length });
+    }
+
+    let mut pos = 12;
+    while pos + 10 <= data.len() {
+        let size = u32_at(pos + 4) as usize;
+        if &data[pos..pos + 4] == b"fmt " {
+            let bytes = [data[pos + 8], data[pos + 9]];
+            let format_tag = if big_endian {
+                u16::from_be_bytes(bytes)
+            } else {
+                u16::from_le_bytes(bytes)
+            };
+            if !KNOWN_FORMATS.contains(&format_tag) {
+                return Some(MediaProblem::UnknownFormat(format_tag));
+            }
+            return None;
+        }
+        // chunks are word aligned
+        pos += 8 + size + (size & 1);
+    }
+    Some(MediaProblem::MissingFmt)
+}
+
+/// Length of the prefetch data cut by [prefetch_chunk].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PrefetchLength {
+    /// At least this duration of audio.
+    Millis(f64),
+    /// At most this size, including the header.
+    Bytes(usize),
+}
+
+impl WemInfo {
+    pub fn from_bytes(data: &[u8]) -> Result<Self> {
+        Self::from_reader(&mut io::Cursor::new(data))
+    }
+
+    pub fn from_reader<R>(reader: &mut R) -> Result<Self>
+    where
+        R: Read + Seek,
+    {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic == b"RIFX" {
+            return Err(WemError::Rifx);
+        }
+        if &magic != b"RIFF" {
+            return Err(WemError::NotRiff);
+        }
+        let riff_size = reader.read_u32::<LE>()?;
+        let riff_end = 8 + riff_size as u64;
+        reader.read_exact(&mut magic)?;
+        if &magic != b"WAVE" {
+            return Err(WemError::NotRiff);
+        }
+
+        let mut fmt = None;
+        let mut data = None;
+        while reader.stream_position()? + 8 <= riff_end {
+            reader.read_exact(&mut magic)?;
+            let size = reader.read_u32::<LE>()?;
+            let start = reader.stream_position()?;
+            match &magic {
+                b"fmt " => {
+                    let format_tag = reader.read_u16::<LE>()?;
+                    let channels = reader.read_u16::<LE>()?;
+                    let sample_rate = reader.read_u32::<LE>()?;
+                    let avg_bytes_per_sec = reader.read_u32::<LE>()?;
+                    let block_align = reader.read_u16::<LE>()?;
+                    let bits_per_sample = reader.read_u16::<LE>()?;
+                    let sample_count = if format_tag == FORMAT_VORBIS && size >= 0x1C {
+                        reader.seek(io::SeekFrom::Start(start + 0x18))?;
+                        Some(reader.read_u32::<LE>()?)
+                    } else {
+                        None
+                    };
+                    let first_audio_packet_offset = if format_tag == FORMAT_VORBIS && size >= 0x30 {
+                        reader.seek(io::SeekFrom::Start(start + 0x2C))?;
+                        Some(reader.read_u32::<LE>()?)
+                    } else {
+                        None
+                    };
+                    fmt = Some((
+                        format_tag,
+                        channels,
+                        sample_rate,
+                        avg_bytes_per_sec,
+                        block_align,
+                        bits_per_sample,
+                        sample_count,
+                        first_audio_packet_offset,
+                    ));
+                }
+                b"data" => data = Some((start as u32, size)),
+                _ => {}
+            }
+            // chunks are word aligned
+            reader.seek(io::SeekFrom::Start(start + size as u64 + (size & 1) as u64))?;
+        }
+
+        let (
+            format_tag,
+            channels,
+            sample_rate,
+            avg_bytes_per_sec,
+            block_align,
+            bits_per_sample,
+            sample_count,
+            first_audio_packet_offset,
+        ) = fmt.ok_or_else(|| WemError::MissingChunk("fmt ".to_string()))?;
+        let (data_offset, data_size) =
+            data.ok_or_else(|| WemError::MissingChunk("data".to_string()))?;
+        Ok(WemInfo {
+            format_tag,
+            channels,
+            sample_rate,
+            avg_bytes_per_sec,
+            block_align,
+            bits_per_sample,
+            sample_count,
+            first_audio_packet_offset,
+            data_offset,
+            data_size,
+        })
+    }
+
+    /// Duration in milliseconds.
+    ///
+    /// Exact for Vorbis and PCM media, estimated from the average byte rate
+    /// for other codecs.
+    pub fn duration_ms(&self) -> Option<f64> {
+        if let Some(sample_count) = self.sample_count {
+            return (self.sample_rate != 0)
+                .then(|| sample_count as f64 * 1000.0 / self.sample_rate as f64);
+        }
+        if matches!(self.format_tag, FORMAT_PCM | FORMAT_EXTENSIBLE)
+            && self.block_align != 0
+            && self.sample_rate != 0
+        {
+            let samples = self.data_size / self.block_align as u32;
+            return Some(samples as f64 * 1000.0 / self.sample_rate as f64);
+        }
+        (self.avg_bytes_per_sec != 0)
+            .then(|| self.data_size as f64 * 1000.0 / self.avg_bytes_per_sec as f64)
+    }
+}
+
+/// Cut the start of a wem for use as the prefetch data of a streamed source.
+///
+/// Like Wwise, the header is kept unchanged so the stream continues from the
+/// end of the prefetch data. The data is cut at a packet boundary for
+/// Vorbis, a block boundary for PCM and the plain offset otherwise.
+pub fn prefetch_chunk(wem: &[u8], length: PrefetchLength) -> Result<Vec<u8>> {
+    let info = WemInfo::from_bytes(wem)?;
+    let data_start = info.data_offset as usize;
+    let data_end = (data_start + info.data_size as usize).min(wem.len());
+    // the header, seek table and setup packet are always kept
+    let audio_start = data_start + info.first_audio_packet_offset.unwrap_or(0) as usize;
+
+    let mut boundaries = Vec::new();
+    if let Some(first_audio_packet_offset) = info.first_audio_packet_offset {
+        let mut pos = data_start + first_audio_packet_offset as usize;
+        while pos + 2 <= data_end {
+            boundaries.push(pos);
+            pos += 2 + u16::from_le_bytes([wem[pos], wem[pos + 1]]) as usize;
+        }
+    } else {
+        let step = match info.format_tag {
+            FORMAT_PCM | FORMAT_EXTENSIBLE => info.block_align.max(1) as usize,
+            _ => 1,
+        };
+        boundaries.extend((audio_start..data_end).step_by(step));
+    }
+    boundaries.push(data_end);
+
+    let cut = match length {
+        PrefetchLength::Millis(ms) => {
+            let target =
+                audio_start + (ms * info.avg_bytes_per_sec as f64 / 1000.0).ceil() as usize;
+            boundaries
+                .iter()
+                .copied()
+                .find(|&b| b >= target)
+                .unwrap_or(data_end)
+        }
+        PrefetchLength::Bytes(size) => boundaries
+            .iter()
+            .copied()
+            .take_while(|&b| b <= size)
+            .last()
+            .unwrap_or(audio_start),
+    };
+    // only the first audio packet offset can point past the data
+    wem.get(..cut).map(<[u8]>::to_vec).ok_or_else(|| {
+        WemError::InvalidVorbis(format!(
+            "first audio packet at {audio_start} past the end of the file ({} bytes)",
+            wem.len()
+        ))
+    })
+}
+
+/// New audio for a source, see [crate::music::replace_music].
+#[derive(Debug, Clone)]
+pub enum WemSource {
+    /// Encoded wem, used as is.
+    Wem(Vec<u8>),
+    /// 16-bit PCM WAV file, wrapped with [wav_to_wem].
+    Wav(Vec<u8>),
+    /// WAV file with the `.wav` extension, wem otherwise.
+    File(PathBuf),
+}
+
+impl WemSource {
+    /// Data of the wem.
+    pub fn into_wem(self) -> Result<Vec<u8>> {
+        match self {
+            WemSource::Wem(wem) => Ok(wem),
+            WemSource::Wav(wav) => wav_to_wem(&wav),
+            WemSource::File(path) => {
+                let data = fs::read(&path)?;
+                let is_wav = path
+                    .extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("wav"));
+                if is_wav { wav_to_wem(&data) } else { Ok(data) }
+            }
+        }
+    }
+}
+
+/// Wrap the samples of a 16-bit PCM WAV file into a PCM wem.
+///
+/// The fmt chunk is rewritten in the extensible layout Wwise uses for PCM
+/// media, other chunks of the WAV file are dropped.
+pub fn wav_to_wem(wav: &[u8]) -> Result<Vec<u8>> {
+    let (fmt_start, fmt_size) =
+        find_chunk(wav, b"fmt ")?.ok_or_else(|| WemError::MissingChunk("fmt ".to_string()))?;
+    let (data_start, data_size) =
+        find_chunk(wav, b"data")?.ok_or_else(|| WemError::MissingChunk("data".to_string()))?;
+    if fmt_size < 16 {
+        return Err(WemError::InvalidChunkSize {
+            offset: fmt_start - 8,
+            size: fmt_size as u32,
+        });
+    }
+    let u16_at = |pos: usize| u16::from_le_bytes([wav[pos], wav[pos + 1]]);
+    let format_tag = u16_at(fmt_start);
+    let channels = u16_at(fmt_start + 2);
+    let sample_rate = u32::from_le_bytes(wav[fmt_start + 4..fmt_start + 8].try_into().unwrap());
+    let bits_per_sample = u16_at(fmt_start + 14);
+    if !matches!(format_tag, FORMAT_PCM | FORMAT_EXTENSIBLE) || bits_per_sample != 16 {
+        return Err(WemError::UnsupportedWav(format!(
+            "format 0x{format_tag:04X} with {bits_per_sample} bits per sample, expected 16-bit PCM"
+        )));
+    }
+    if channels == 0 {
+        return Err(WemError::UnsupportedWav("no channels".to_string()));
+    }
+    let channel_mask: u32 = match channels {
+        1 => 0x4,
+        2 => 0x3,
+        4 => 0x33,
+        6 => 0x3F,
+        8 => 0x63F,
+        _ => 0,
+    };
+    let block_align = channels * 2;
+    // whole sample frames only
+    let data = &wav[data_start..data_start + data_size - data_size % block_align as usize];
+
+    let mut wem = Vec::with_capacity(44 + data.len());
+    wem.extend(b"RIFF");
+    wem.write_u32::<LE>((4 + 8 + 0x18 + 8 + data.len()) as u32)?;
+    wem.extend(b"WAVEfmt ");
+    wem.write_u32::<LE>(0x18)?;
+    wem.write_u16::<LE>(FORMAT_EXTENSIBLE)?;
+    wem.write_u16::<LE>(channels)?;
+    wem.write_u32::<LE>(sample_rate)?;
+    wem.write_u32::<LE>(sample_rate * block_align as u32)?;
+    wem.write_u16::<LE>(block_align)?;
+    wem.write_u16::<LE>(bits_per_sample)?;
+    // extra size, valid bits and channel mask
+    wem.write_u16::<LE>(6)?;
+    wem.write_u16::<LE>(bits_per_sample)?;
+    wem.write_u32::<LE>(channel_mask)?;
+    wem.extend(b"data");
+    wem.write_u32::<LE>(data.len() as u32)?;
+    wem.extend(data);
+    Ok(wem)
+}
+
+/// Find a chunk of a RIFF wem, returns the start and size of its payload.
+fn find_chunk(wem: &[u8], id: &[u8; 4]) -> Result<Option<(usize, usize)>> {
+    if is_rifx(wem) {
+        return Err(WemError::Rifx);
+    }
+    if wem.len() < 12 || &wem[..4] != b"RIFF" || &wem[8..12] != b"WAVE" {
+        return Err(WemError::NotRiff);
+    }
+    let mut pos = 12;
+    while pos + 8 <= wem.len() {
+        let size = u32::from_le_bytes(wem[pos + 4..pos + 8].try_into().unwrap());
+        let start = pos + 8;
+        if start + size as usize > wem.len() {
+            return Err(WemError::InvalidChunkSize { offset: pos, size });
+        }
+        if &wem[pos..pos + 4] == id {
+            return Ok(Some((start, size as usize)));
+        }
+        // chunks are word aligned
+        pos = start + size as usize + (size & 1) as usize;
+    }
+    Ok(None)
+}
+
+/// Whether the media is a big-endian RIFX container, as used on consoles.
+pub fn is_rifx(wem: &[u8]) -> bool {
+    wem.starts_with(b"RIFX")
+}
+
+/// Convert a big-endian RIFX wem to RIFF.
+///
+/// Chunk sizes and the fmt chunk are byte swapped, as well as the samples of
+/// PCM media and the seek table and packet sizes of Vorbis media. Data of
+/// other codecs and the content of other chunks are copied unchanged.
+pub fn rifx_to_riff(wem: &[u8]) -> Result<Vec<u8>> {
+    swap_byte_order(wem, true)
+}
+
+/// Convert a RIFF wem to big-endian RIFX, the reverse of [rifx_to_riff].
+pub fn riff_to_rifx(wem: &[u8]) -> Result<Vec<u8>> {
+    swap_byte_order(wem, false)
+}
+
+fn swap_byte_order(wem: &[u8], from_big_endian: bool) -> Result<Vec<u8>> {
+    let (magic, new_magic) = if from_big_endian {
+        (b"RIFX", b"RIFF")
+    } else {
+        (b"RIFF", b"RIFX")
+    };
+    if wem.len() < 12 || &wem[..4] != magic || &wem[8..12] != b"WAVE" {
+        return Err(WemError::NotRiff);
+    }
+    // read values in the source byte order
+    let u16_at = |pos: usize| {
+        let bytes = [wem[pos], wem[pos + 1]];
+        if from_big_endian {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        }
+    };
+    let u32_at = |pos: usize| {
+        let bytes = wem[pos..pos + 4].try_into().unwrap();
+        if from_big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        }
+    };
+
+    let mut out = wem.to_vec();
+    out[..4].copy_from_slice(new_magic);
+    swap(&mut out, 4, 4);
+    let riff_end = (8 + u32_at(4) as usize).min(wem.len());
+
+    let mut format = None;
+    let mut pos = 12;
+    while pos + 8 <= riff_end {
+        let size = u32_at(pos + 4);
+        swap(&mut out, pos + 4, 4);
+        let start = pos + 8;
+        let end = start + size as usize;
+        if end > wem.len() {
+            return Err(WemError::InvalidChunkSize { offset: pos, size });
+        }
+        match &wem[pos..pos + 4] {
+            b"fmt " if size >= 16 => {
+                let format_tag = u16_at(start);
+                for (offset, width) in [(0, 2), (2, 2), (4, 4), (8, 4), (12, 2), (14, 2)] {
+                    swap(&mut out, start + offset, width);
+                }
+                if size >= 18 {
+                    swap(&mut out, start + 16, 2);
+                }
+                match format_tag {
+                    // valid bits, channel mask and the first GUID fields
+                    FORMAT_EXTENSIBLE if size >= 40 => {
+                        for (offset, width) in [(18, 2), (20, 4), (24, 4), (28, 2), (30, 2)] {
+                            swap(&mut out, start + offset, width);
+                        }
+                    }
+                    // samples per block, then dwords up to the two block size bytes
+                    FORMAT_VORBIS if size >= 0x30 => {
+                        swap(&mut out, start + 0x12, 2);
+                        let mut offset = 0x14;
+                        while offset + 4 <= size as usize - 2 {
+                            swap(&mut out, start + offset, 4);
+                            offset += 4;
+                        }
+                    }
+                    _ => {}
+                }
+                let bits_per_sample = u16_at(start + 14);
+                let setup_offset = (format_tag == FORMAT_VORBIS && size >= 0x30)
+                    .then(|| (u32_at(start + 0x28), u32_at(start + 0x2C)));
+                format = Some((format_tag, bits_per_sample, setup_offset));
+            }
+            b"data" => match format {
+                Some((FORMAT_PCM | FORMAT_EXTENSIBLE, bits_per_sample, _))
+                    if bits_per_sample > 8 =>
+                {
+                    let width = bits_per_sample as usize / 8;
+                    for sample in (start..end).step_by(width).take_while(|s| s + width <= end) {
+                        swap(&mut out, sample, width);
+                    }
+                }
+                Some((FORMAT_VORBIS, _, Some((setup_offset, _)))) => {
+                    // seek table entries are pairs of u16
+                    let setup_start = start + setup_offset as usize;
+                    for entry in (start..setup_start.min(end)).step_by(2) {
+                        swap(&mut out, entry, 2);
+                    }
+                    // the setup and audio packets are prefixed by their u16 size
+                    let mut packet = setup_start;
+                    while packet + 2 <= end {
+                        let packet_size = u16_at(packet) as usize;
+                        swap(&mut out, packet, 2);
+                        packet += 2 + packet_size;
+                    }
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+        // chunks are word aligned
+        pos = end + (size & 1) as usize;
+    }
+    Ok(out)
+}
+
+/// Reverse the bytes of a value in place.
+fn swap(data: &mut [u8], pos: usize, width: usize) {
+    data[pos..pos + width].reverse();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{bnk::hirc::AkChannelConfig, pck::Pck};
+
+    use super::*;
+
+    #[test]
+    fn test_rifx_conversion() {
+        let mut pck = Pck::from_file("test_files/Cat_cmn_m.spck.1.X64").unwrap();
+        let id = pck.header().wem_entries[0].id;
+        let wem = pck.read_wem(id).unwrap().unwrap();
+        assert!(!is_rifx(&wem));
+
+        let rifx = riff_to_rifx(&wem).unwrap();
+        assert!(is_rifx(&rifx));
+        assert_ne!(rifx[4..], wem[4..]);
+        assert!(matches!(WemInfo::from_bytes(&rifx), Err(WemError::Rifx)));
+        assert_eq!(rifx_to_riff(&rifx).unwrap(), wem);
+        assert!(matches!(rifx_to_riff(&wem), Err(WemError::NotRiff)));
+
+        let info = WemInfo::from_bytes(&rifx_to_riff(&rifx).unwrap()).unwrap();
+        assert_eq!(info, WemInfo::from_bytes(&wem).unwrap());
+    }
+
+    #[test]
+    fn test_wav_to_wem() {
+        // 100 ms of 48 kHz stereo, with a LIST chunk before the data
+        let samples = vec![0x12; 4800 * 4];
+        let mut wav = Vec::new();
+        wav.extend(b"RIFF");
+        wav.extend(((4 + 24 + 12 + 8 + samples.len()) as u32).to_le_bytes());
+        wav.extend(b"WAVEfmt ");
+        wav.extend(16u32.to_le_bytes());
+        for value in [FORMAT_PCM, 2] {
+            wav.extend(value.to_le_bytes());
+        }
+        for value in [48000u32, 48000 * 4] {
+            wav.extend(value.to_le_bytes());
+        }
+        for value in [4u16, 16] {
+            wav.extend(value.to_le_bytes());
+        }
+        wav.extend(b"LIST");
+        wav.extend(4u32.to_le_bytes());
+        wav.extend(b"INFO");
+        wav.extend(b"data");
+        wav.extend((samples.len() as u32).to_le_bytes());
+        wav.extend(&samples);
+
+        let wem = WemSource::Wav(wav.clone()).into_wem().unwrap();
+        assert_eq!(check_media(&wem, false), None);
+        let info = WemInfo::from_bytes(&wem).unwrap();
+        assert_eq!(info.format_tag, FORMAT_EXTENSIBLE);
+        assert_eq!((info.channels, info.sample_rate), (2, 48000));
+        assert_eq!(info.duration_ms(), Some(100.0));
+        assert!(wem.ends_with(&samples));
+
+        wav[34] = 8;
+        assert!(matches!(wav_to_wem(&wav), Err(WemError::UnsupportedWav(_))));
+    }
+
+    #[test]
+    fn test_wem_fmt() {
+        let mut pck = Pck::from_file("test_files/Cat_cmn_m.spck.1.X64").unwrap();
+        let id = pck.header().wem_entries[0].id;
+        let mut wem = pck.read_wem(id).unwrap().unwrap();
+        let info = WemInfo::from_bytes(&wem).unwrap();
+
+        let mut fmt = WemFmt::from_wem(&wem).unwrap();
+        assert_eq!(fmt.format_tag, FORMAT_VORBIS);
+        assert_eq!(fmt.channel_config, Some(AkChannelConfig::mono()));
+        let vorbis = fmt.vorbis.as_ref().unwrap();
+        assert_eq!(Some(vorbis.sample_count), info.sample_count);
+        assert_eq!(
+            Some(vorbis.first_audio_packet_offset),
+            info.first_audio_packet_offset
+        );
+        assert!(vorbis.has_seek_table());
+        assert_eq!((vorbis.blocksize_0_exp, vorbis.blocksize_1_exp), (8, 11));
+        assert!(fmt.extra.is_empty());
+        let (start, size) = find_chunk(&wem, b"fmt ").unwrap().unwrap();
+        assert_eq!(fmt.to_bytes(), wem[start..start + size]);
+
+        // write back with a changed size
+        let original = wem.clone();
+        fmt.vorbis.as_mut().unwrap().sample_count = 1000;
+        fmt.extra = vec![1, 2, 3];
+        fmt.write_to_wem(&mut wem).unwrap();
+        assert_eq!(wem.len(), original.len() + 4);
+        assert_eq!(WemFmt::from_wem(&wem).unwrap(), fmt);
+        let new_info = WemInfo::from_bytes(&wem).unwrap();
+        assert_eq!(new_info.sample_count, Some(1000));
+        assert_eq!(new_info.data_offset, info.data_offset + 4);
+        assert_eq!(
+            wem[new_info.data_offset as usize..],
+            original[info.data_offset as usize..]
+        );
+    }
+
+    #[test]
+    fn test_vorbis_seek_table() {
+        let mut pck = Pck::from_file("test_files/Cat_cmn_m.spck.1.X64").unwrap();
+        let ids: Vec<u32> = pck.header().wem_entries.iter().map(|e| e.id).collect();
+        for id in ids {
+            let wem = pck.read_wem(id).unwrap().unwrap();
+            let seek_table = vorbis::read_seek_table(&wem).unwrap();
+            assert_eq!(vorbis::build_seek_table(&wem).unwrap(), seek_table);
+            assert_eq!(vorbis::rebuild_seek_table(&wem).unwrap(), wem);
+        }
+
+        // wem with a stale seek table
+        let id = pck.header().wem_entries[0].id;
+        let wem = pck.read_wem(id).unwrap().unwrap();
+        let data = WemInfo::from_bytes(&wem).unwrap().data_offset as usize;
+        let mut fmt = WemFmt::from_wem(&wem).unwrap();
+        let vorbis_fmt = fmt.vorbis.as_mut().unwrap();
+        let table_size = vorbis_fmt.setup_packet_offset as usize;
+        vorbis_fmt.setup_packet_offset += 8;
+        vorbis_fmt.first_audio_packet_offset += 8;
+        let mut stale = wem.clone();
+        stale.splice(data..data, [0xFF; 8]);
+        let data_size = (WemInfo::from_bytes(&wem).unwrap().data_size + 8).to_le_bytes();
+        stale[data - 4..data].copy_from_slice(&data_size);
+        let riff_size = (stale.len() as u32 - 8).to_le_bytes();
+        stale[4..8].copy_from_slice(&riff_size);
+        fmt.write_to_wem(&mut stale).unwrap();
+        assert_eq!(
+            vorbis::read_seek_table(&stale).unwrap().len(),
+            table_size / 4 + 2
+        );
+        assert_eq!(vorbis::rebuild_seek_table(&stale).unwrap(), wem);
+    }
+
+    #[test]
+    fn test_vorbis_truncated_packets() {
+        let mut pck = Pck::from_file("test_files/Cat_cmn_m.spck.1.X64").unwrap();
+        let id = pck.header().wem_entries[0].id;
+        let mut wem = pck.read_wem(id).unwrap().unwrap();
+        let data = WemInfo::from_bytes(&wem).unwrap().data_offset as usize;
+        let mut fmt = WemFmt::from_wem(&wem).unwrap();
+        let vorbis_fmt = fmt.vorbis.as_mut().unwrap();
+        vorbis_fmt.first_audio_packet_offset = vorbis_fmt.setup_packet_offset;
+        let setup_offset = vorbis_fmt.setup_packet_offset;
+        fmt.write_to_wem(&mut wem).unwrap();
+        // no packets, then a single byte of packet size
+        for packets in [0, 1] {
+            let mut truncated = wem.clone();
+            truncated[data - 4..data].copy_from_slice(&(setup_offset + packets).to_le_bytes());
+            assert!(matches!(
+                vorbis::build_seek_table(&truncated),
+                Err(WemError::InvalidVorbis(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_vorbis_bad_fmt() {
+        let mut pck = Pck::from_file("test_files/Cat_cmn_m.spck.1.X64").unwrap();
+        let id = pck.header().wem_entries[0].id;
+        let wem = pck.read_wem(id).unwrap().unwrap();
+        let fmt = WemFmt::from_wem(&wem).unwrap();
+        let edits: [fn(&mut VorbisFmt); 3] = [
+            |vorbis| vorbis.blocksize_0_exp = 32,
+            |vorbis| vorbis.blocksize_1_exp = 14,
+            |vorbis| vorbis.first_audio_packet_offset = vorbis.setup_packet_offset - 1,
+        ];
+        for edit in edits {
+            let mut fmt = fmt.clone();
+            edit(fmt.vorbis.as_mut().unwrap());
+            let mut wem = wem.clone();
+            fmt.write_to_wem(&mut wem).unwrap();
+            assert!(matches!(
+                vorbis::build_seek_table(&wem),
+                Err(WemError::InvalidVorbis(_))
+            ));
+            assert!(matches!(
+                vorbis::rebuild_seek_table(&wem),
+                Err(WemError::InvalidVorbis(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_prefetch_chunk_bad_offset() {
+        let mut pck = Pck::from_file("test_files/Cat_cmn_m.spck.1.X64").unwrap();
+        let id = pck.header().wem_entries[0].id;
+        let mut wem = pck.read_wem(id).unwrap().unwrap();
+        let mut fmt = WemFmt::from_wem(&wem).unwrap();
+        fmt.vorbis.as_mut().unwrap().first_audio_packet_offset = wem.len() as u32;
+        fmt.write_to_wem(&mut wem).unwrap();
+        assert!(matches!(
+            prefetch_chunk(&wem, PrefetchLength::Bytes(16)),
+            Err(WemError::InvalidVorbis(_))
+        ));
+    }
+}
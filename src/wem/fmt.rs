@@ -0,0 +1,189 @@
+use std::io::{self, Read, Write};
+
+use byteorder::{LE, ReadBytesExt, WriteBytesExt};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::{FORMAT_VORBIS, Result, WemError};
+use crate::bnk::hirc::AkChannelConfig;
+
+/// Size of the Wwise fmt extension before the codec specific fields.
+const WWISE_EXTENSION_SIZE: usize = 0x18;
+/// Size of the Vorbis specific fields.
+const VORBIS_SIZE: usize = 0x2A;
+
+/// The `fmt ` chunk of a wem, with the Wwise extension decoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct WemFmt {
+    pub format_tag: u16,
+    pub channels: u16,
+    pub sample_rate: u32,
+    pub avg_bytes_per_sec: u32,
+    pub block_align: u16,
+    pub bits_per_sample: u16,
+    /// Valid bits per sample, samples per block for ADPCM media.
+    pub valid_bits_per_sample: Option<u16>,
+    pub channel_config: Option<AkChannelConfig>,
+    pub vorbis: Option<VorbisFmt>,
+    /// Bytes following the decoded fields.
+    pub extra: Vec<u8>,
+}
+
+/// Vorbis fields of the fmt extension.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct VorbisFmt {
+    pub sample_count: u32,
+    pub mod_signal: u32,
+    /// Size of the data following the seek table.
+    pub vorbis_data_size: u32,
+    pub unknown_24: u32,
+    /// Offset of the setup packet from the start of the data, the seek table
+    /// comes before it.
+    pub setup_packet_offset: u32,
+    pub first_audio_packet_offset: u32,
+    pub unknown_30: [u32; 3],
+    pub uid: u32,
+    pub blocksize_0_exp: u8,
+    pub blocksize_1_exp: u8,
+}
+
+impl WemFmt {
+    /// Parse the payload of a `fmt ` chunk.
+    pub fn from_bytes(data: &[u8]) -> Result<Self> {
+        let mut reader = io::Cursor::new(data);
+        let format_tag = reader.read_u16::<LE>()?;
+        let channels = reader.read_u16::<LE>()?;
+        let sample_rate = reader.read_u32::<LE>()?;
+        let avg_bytes_per_sec = reader.read_u32::<LE>()?;
+        let block_align = reader.read_u16::<LE>()?;
+        let bits_per_sample = reader.read_u16::<LE>()?;
+
+        let mut valid_bits_per_sample = None;
+        let mut channel_config = None;
+        let mut vorbis = None;
+        if data.len() >= WWISE_EXTENSION_SIZE {
+            // cbSize, recomputed on write
+            reader.read_u16::<LE>()?;
+            valid_bits_per_sample = Some(reader.read_u16::<LE>()?);
+            channel_config = Some(AkChannelConfig::from_u32(reader.read_u32::<LE>()?));
+            if format_tag == FORMAT_VORBIS && data.len() >= WWISE_EXTENSION_SIZE + VORBIS_SIZE {
+                vorbis = Some(VorbisFmt::read(&mut reader)?);
+            }
+        }
+        let mut extra = Vec::new();
+        reader.read_to_end(&mut extra)?;
+
+        Ok(WemFmt {
+            format_tag,
+            channels,
+            sample_rate,
+            avg_bytes_per_sec,
+            block_align,
+            bits_per_sample,
+            valid_bits_per_sample,
+            channel_config,
+            vorbis,
+            extra,
+        })
+    }
+
+    /// Serialize the chunk payload.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(WWISE_EXTENSION_SIZE + VORBIS_SIZE);
+        // writing to a Vec can't fail
+        self.write(&mut out).unwrap();
+        out
+    }
+
+    fn write(&self, writer: &mut Vec<u8>) -> io::Result<()> {
+        writer.write_u16::<LE>(self.format_tag)?;
+        writer.write_u16::<LE>(self.channels)?;
+        writer.write_u32::<LE>(self.sample_rate)?;
+        writer.write_u32::<LE>(self.avg_bytes_per_sec)?;
+        writer.write_u16::<LE>(self.block_align)?;
+        writer.write_u16::<LE>(self.bits_per_sample)?;
+        if let (Some(valid_bits_per_sample), Some(channel_config)) =
+            (self.valid_bits_per_sample, self.channel_config)
+        {
+            let vorbis_size = self.vorbis.as_ref().map_or(0, |_| VORBIS_SIZE);
+            let cb_size = WWISE_EXTENSION_SIZE - 0x12 + vorbis_size + self.extra.len();
+            writer.write_u16::<LE>(cb_size as u16)?;
+            writer.write_u16::<LE>(valid_bits_per_sample)?;
+            writer.write_u32::<LE>(channel_config.to_u32())?;
+            if let Some(vorbis) = &self.vorbis {
+                vorbis.write(writer)?;
+            }
+        }
+        writer.write_all(&self.extra)
+    }
+
+    /// Read the `fmt ` chunk of a wem.
+    pub fn from_wem(wem: &[u8]) -> Result<Self> {
+        let (start, size) = super::find_chunk(wem, b"fmt ")?
+            .ok_or_else(|| WemError::MissingChunk("fmt ".to_string()))?;
+        Self::from_bytes(&wem[start..start + size])
+    }
+
+    /// Replace the `fmt ` chunk of a wem, updating the RIFF size if the
+    /// chunk size changed.
+    pub fn write_to_wem(&self, wem: &mut Vec<u8>) -> Result<()> {
+        let (start, size) = super::find_chunk(wem, b"fmt ")?
+            .ok_or_else(|| WemError::MissingChunk("fmt ".to_string()))?;
+        let data = self.to_bytes();
+        // keep the word alignment of the following chunks
+        let padded = |size: usize| size + (size & 1);
+        let mut replacement = data.clone();
+        replacement.resize(padded(data.len()), 0);
+        wem.splice(
+            start..start + padded(size).min(wem.len() - start),
+            replacement,
+        );
+        wem[start - 4..start].copy_from_slice(&(data.len() as u32).to_le_bytes());
+        let riff_size = wem.len() as u32 - 8;
+        wem[4..8].copy_from_slice(&riff_size.to_le_bytes());
+        Ok(())
+    }
+}
+
+impl VorbisFmt {
+    pub fn has_seek_table(&self) -> bool {
+        self.setup_packet_offset != 0
+    }
+
+    fn read<R: Read>(reader: &mut R) -> io::Result<Self> {
+        Ok(VorbisFmt {
+            sample_count: reader.read_u32::<LE>()?,
+            mod_signal: reader.read_u32::<LE>()?,
+            vorbis_data_size: reader.read_u32::<LE>()?,
+            unknown_24: reader.read_u32::<LE>()?,
+            setup_packet_offset: reader.read_u32::<LE>()?,
+            first_audio_packet_offset: reader.read_u32::<LE>()?,
+            unknown_30: [
+                reader.read_u32::<LE>()?,
+                reader.read_u32::<LE>()?,
+                reader.read_u32::<LE>()?,
+            ],
+            uid: reader.read_u32::<LE>()?,
+            blocksize_0_exp: reader.read_u8()?,
+            blocksize_1_exp: reader.read_u8()?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_u32::<LE>(self.sample_count)?;
+        writer.write_u32::<LE>(self.mod_signal)?;
+        writer.write_u32::<LE>(self.vorbis_data_size)?;
+        writer.write_u32::<LE>(self.unknown_24)?;
+        writer.write_u32::<LE>(self.setup_packet_offset)?;
+        writer.write_u32::<LE>(self.first_audio_packet_offset)?;
+        for value in self.unknown_30 {
+            writer.write_u32::<LE>(value)?;
+        }
+        writer.write_u32::<LE>(self.uid)?;
+        writer.write_u8(self.blocksize_0_exp)?;
+        writer.write_u8(self.blocksize_1_exp)
+    }
+}
@@ -0,0 +1,315 @@
+//! Seek table of Wwise Vorbis media.
+//!
+//! The data of Vorbis wems starts with the seek table, followed by the setup
+//! packet and the audio packets, each packet prefixed by its u16 size.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::{Result, WemError, WemFmt};
+
+/// Samples between two seek points of tables written by Wwise.
+pub const SEEK_INTERVAL: u32 = 16384;
+/// Largest block size exponent allowed by Vorbis, blocks of 8192 samples.
+const MAX_BLOCKSIZE_EXP: u8 = 13;
+
+/// Entry of the seek table, relative to the previous entry.
+///
+/// The first entry is relative to the start of the setup packet and sample 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SeekPoint {
+    /// Samples decoded once the packet at this point is decoded.
+    pub sample_delta: u16,
+    /// Offset of the packet at this point.
+    pub byte_delta: u16,
+}
+
+/// Vorbis data of a wem, split in its parts.
+struct VorbisData<'a> {
+    fmt: WemFmt,
+    data_start: usize,
+    data_size: usize,
+    seek_table: &'a [u8],
+    /// Setup and audio packets.
+    packets: &'a [u8],
+}
+
+impl<'a> VorbisData<'a> {
+    fn from_wem(wem: &'a [u8]) -> Result<Self> {
+        let fmt = WemFmt::from_wem(wem)?;
+        let vorbis = fmt
+            .vorbis
+            .as_ref()
+            .ok_or_else(|| WemError::MissingChunk("vorbis fmt".to_string()))?;
+        let (data_start, data_size) = super::find_chunk(wem, b"data")?
+            .ok_or_else(|| WemError::MissingChunk("data".to_string()))?;
+        let data = &wem[data_start..data_start + data_size];
+        let setup_offset = vorbis.setup_packet_offset as usize;
+        if setup_offset > data.len() || vorbis.first_audio_packet_offset as usize > data.len() {
+            return Err(WemError::InvalidVorbis(
+                "packet offsets out of data".to_string(),
+            ));
+        }
+        if vorbis.first_audio_packet_offset < vorbis.setup_packet_offset {
+            return Err(WemError::InvalidVorbis(
+                "audio packets before the setup packet".to_string(),
+            ));
+        }
+        for exp in [vorbis.blocksize_0_exp, vorbis.blocksize_1_exp] {
+            if exp > MAX_BLOCKSIZE_EXP {
+                return Err(WemError::InvalidVorbis(format!(
+                    "block size exponent {exp} above {MAX_BLOCKSIZE_EXP}"
+                )));
+            }
+        }
+        Ok(VorbisData {
+            data_start,
+            data_size,
+            seek_table: &data[..setup_offset],
+            packets: &data[setup_offset..],
+            fmt,
+        })
+    }
+}
+
+/// Read the seek table of a Vorbis wem.
+pub fn read_seek_table(wem: &[u8]) -> Result<Vec<SeekPoint>> {
+    let data = VorbisData::from_wem(wem)?;
+    Ok(data
+        .seek_table
+        .chunks_exact(4)
+        .map(|entry| SeekPoint {
+            sample_delta: u16::from_le_bytes([entry[0], entry[1]]),
+            byte_delta: u16::from_le_bytes([entry[2], entry[3]]),
+        })
+        .collect())
+}
+
+/// Compute the seek table of a Vorbis wem from its packets, with a seek
+/// point every [SEEK_INTERVAL] samples like Wwise.
+pub fn build_seek_table(wem: &[u8]) -> Result<Vec<SeekPoint>> {
+    let data = VorbisData::from_wem(wem)?;
+    let vorbis = data.fmt.vorbis.as_ref().expect("checked in from_wem");
+    let packets = data.packets;
+    let setup_size = packet_size(packets, 0)?;
+    let blockflags = parse_mode_blockflags(&packets[2..2 + setup_size], data.fmt.channels)?;
+    let mode_bits = ilog(blockflags.len() as u32 - 1);
+    let blocksizes = [
+        1u32 << vorbis.blocksize_0_exp,
+        1u32 << vorbis.blocksize_1_exp,
+    ];
+
+    let mut seek_table = Vec::new();
+    let (mut last_granule, mut last_offset) = (0u64, 0usize);
+    let mut granule = 0u64;
+    let mut previous_blocksize = None;
+    let mut offset = 2 + setup_size;
+    while offset + 2 <= packets.len() {
+        let size = packet_size(packets, offset)?;
+        // audio packets start with the mode number
+        let mode = packets.get(offset + 2).copied().unwrap_or(0) as u32 & ((1 << mode_bits) - 1);
+        let blockflag = *blockflags
+            .get(mode as usize)
+            .ok_or_else(|| WemError::InvalidVorbis(format!("mode {mode} out of range")))?;
+        let blocksize = blocksizes[blockflag as usize];
+        if let Some(previous) = previous_blocksize {
+            granule += (previous / 4 + blocksize / 4) as u64;
+        }
+        previous_blocksize = Some(blocksize);
+
+        if granule >= last_granule + SEEK_INTERVAL as u64 {
+            let (Ok(sample_delta), Ok(byte_delta)) = (
+                u16::try_from(granule - last_granule),
+                u16::try_from(offset - last_offset),
+            ) else {
+                return Err(WemError::InvalidVorbis(format!(
+                    "seek point at {offset} too far from the previous one"
+                )));
+            };
+            seek_table.push(SeekPoint {
+                sample_delta,
+                byte_delta,
+            });
+            (last_granule, last_offset) = (granule, offset);
+        }
+        offset += 2 + size;
+    }
+    Ok(seek_table)
+}
+
+/// Replace the seek table of a Vorbis wem with one built from its packets,
+/// e.g. after replacing the audio packets.
+///
+/// The packet offsets of the fmt chunk and the data size are updated.
+pub fn rebuild_seek_table(wem: &[u8]) -> Result<Vec<u8>> {
+    let seek_table = build_seek_table(wem)?;
+    let data = VorbisData::from_wem(wem)?;
+    let table_bytes: Vec<u8> = seek_table
+        .iter()
+        .flat_map(|point| {
+            let mut bytes = point.sample_delta.to_le_bytes().to_vec();
+            bytes.extend(point.byte_delta.to_le_bytes());
+            bytes
+        })
+        .collect();
+
+    let data_end = data.data_start + data.data_size;
+    let mut out = wem[..data.data_start].to_vec();
+    out.extend(&table_bytes);
+    out.extend(data.packets);
+    out.extend(&wem[data_end..]);
+    let new_data_size = table_bytes.len() + data.packets.len();
+    out[data.data_start - 4..data.data_start]
+        .copy_from_slice(&(new_data_size as u32).to_le_bytes());
+    let riff_size = out.len() as u32 - 8;
+    out[4..8].copy_from_slice(&riff_size.to_le_bytes());
+
+    let mut fmt = data.fmt;
+    let vorbis = fmt.vorbis.as_mut().expect("checked in from_wem");
+    let old_table_size = data.seek_table.len() as u32;
+    vorbis.setup_packet_offset = table_bytes.len() as u32;
+    vorbis.first_audio_packet_offset =
+        vorbis.first_audio_packet_offset - old_table_size + table_bytes.len() as u32;
+    vorbis.vorbis_data_size = data.packets.len() as u32;
+    // the fmt chunk comes before the data, its size is unchanged
+    fmt.write_to_wem(&mut out)?;
+    Ok(out)
+}
+
+fn packet_size(packets: &[u8], offset: usize) -> Result<usize> {
+    packets
+        .get(offset..offset + 2)
+        .map(|size| u16::from_le_bytes([size[0], size[1]]) as usize)
+        .filter(|size| offset + 2 + size <= packets.len())
+        .ok_or_else(|| WemError::InvalidVorbis(format!("packet at {offset} exceeds the data")))
+}
+
+/// Bits needed to store `value`.
+fn ilog(value: u32) -> u32 {
+    32 - value.leading_zeros()
+}
+
+/// Vorbis bitstream reader, least significant bit first.
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl BitReader<'_> {
+    fn read(&mut self, bits: u32) -> Result<u32> {
+        let mut value = 0;
+        for i in 0..bits {
+            let byte = self.data.get(self.pos / 8).ok_or_else(|| {
+                WemError::InvalidVorbis("setup packet ended unexpectedly".to_string())
+            })?;
+            value |= ((byte >> (self.pos % 8)) as u32 & 1) << i;
+            self.pos += 1;
+        }
+        Ok(value)
+    }
+
+    fn skip(&mut self, bits: u32) -> Result<()> {
+        self.pos += bits as usize;
+        if self.pos > self.data.len() * 8 {
+            return Err(WemError::InvalidVorbis(
+                "setup packet ended unexpectedly".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Parse the block flag of each mode from a Wwise setup packet.
+///
+/// Wwise strips the setup header of fields with fixed values and references
+/// the codebooks by id, only the layout of the remaining fields is read.
+fn parse_mode_blockflags(setup: &[u8], channels: u16) -> Result<Vec<bool>> {
+    let mut reader = BitReader {
+        data: setup,
+        pos: 0,
+    };
+    let r = &mut reader;
+
+    // external codebook ids
+    let codebook_count = r.read(8)? + 1;
+    r.skip(codebook_count * 10)?;
+
+    // floors, all of type 1
+    let floor_count = r.read(6)? + 1;
+    for _ in 0..floor_count {
+        let partitions = r.read(5)?;
+        let partition_classes: Vec<u32> =
+            (0..partitions).map(|_| r.read(4)).collect::<Result<_>>()?;
+        let max_class = partition_classes.iter().copied().max().map_or(0, |c| c + 1);
+        let mut class_dimensions = Vec::with_capacity(max_class as usize);
+        for _ in 0..max_class {
+            class_dimensions.push(r.read(3)? + 1);
+            let subclasses = r.read(2)?;
+            if subclasses != 0 {
+                // masterbook
+                r.read(8)?;
+            }
+            r.skip((1 << subclasses) * 8)?;
+        }
+        // multiplier
+        r.read(2)?;
+        let range_bits = r.read(4)?;
+        for class in partition_classes {
+            r.skip(class_dimensions[class as usize] * range_bits)?;
+        }
+    }
+
+    let residue_count = r.read(6)? + 1;
+    for _ in 0..residue_count {
+        // type, begin, end, partition size
+        r.read(2)?;
+        r.skip(24 * 3)?;
+        let classifications = r.read(6)? + 1;
+        // classbook
+        r.read(8)?;
+        let mut cascades = Vec::with_capacity(classifications as usize);
+        for _ in 0..classifications {
+            let low_bits = r.read(3)?;
+            let high_bits = if r.read(1)? != 0 { r.read(5)? } else { 0 };
+            cascades.push(high_bits << 3 | low_bits);
+        }
+        for cascade in cascades {
+            r.skip(cascade.count_ones() * 8)?;
+        }
+    }
+
+    let mapping_count = r.read(6)? + 1;
+    let channel_bits = ilog(channels.max(1) as u32 - 1);
+    for _ in 0..mapping_count {
+        let submaps = if r.read(1)? != 0 { r.read(4)? + 1 } else { 1 };
+        if r.read(1)? != 0 {
+            let coupling_steps = r.read(8)? + 1;
+            r.skip(coupling_steps * channel_bits * 2)?;
+        }
+        if r.read(2)? != 0 {
+            return Err(WemError::InvalidVorbis(
+                "mapping reserved bits set".to_string(),
+            ));
+        }
+        if submaps > 1 {
+            r.skip(channels as u32 * 4)?;
+        }
+        // time config, floor and residue per submap
+        r.skip(submaps * 24)?;
+    }
+
+    let mode_count = r.read(6)? + 1;
+    let mut blockflags = Vec::with_capacity(mode_count as usize);
+    for _ in 0..mode_count {
+        blockflags.push(r.read(1)? != 0);
+        // mapping
+        let mapping = r.read(8)?;
+        if mapping >= mapping_count {
+            return Err(WemError::InvalidVorbis(format!(
+                "mode mapping {mapping} out of range"
+            )));
+        }
+    }
+    Ok(blockflags)
+}
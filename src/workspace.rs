@@ -0,0 +1,144 @@
+//! Analysis over the banks of a game.
+
+use std::collections::BTreeMap;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::bnk::{
+    Bnk, BnkError,
+    hirc::{HircEntry, HircEntryType},
+};
+
+/// A set of named banks analysed together.
+#[derive(Debug, Clone, Default)]
+pub struct Workspace {
+    banks: Vec<(String, Bnk)>,
+}
+
+/// An object defined in more than one bank.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DuplicateObject {
+    pub id: u32,
+    pub entry_type: HircEntryType,
+    /// Names of the banks defining the object, grouped by identical content.
+    pub variants: Vec<Vec<String>>,
+}
+
+impl DuplicateObject {
+    /// Whether the definitions differ between banks.
+    pub fn diverges(&self) -> bool {
+        self.variants.len() > 1
+    }
+}
+
+impl Workspace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, name: impl Into<String>, bnk: Bnk) {
+        self.banks.push((name.into(), bnk));
+    }
+
+    pub fn bank(&self, name: &str) -> Option<&Bnk> {
+        self.banks
+            .iter()
+            .find(|(bank_name, _)| bank_name == name)
+            .map(|(_, bnk)| bnk)
+    }
+
+    pub fn banks(&self) -> impl Iterator<Item = (&str, &Bnk)> {
+        self.banks.iter().map(|(name, bnk)| (name.as_str(), bnk))
+    }
+
+    pub fn len(&self) -> usize {
+        self.banks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.banks.is_empty()
+    }
+
+    /// Find HIRC objects defined in more than one bank, sorted by id.
+    ///
+    /// Definitions are compared by their serialized bytes.
+    pub fn duplicate_objects(&self) -> Result<Vec<DuplicateObject>, BnkError> {
+        let mut definitions: BTreeMap<u32, Vec<(&str, &HircEntry)>> = BTreeMap::new();
+        for (name, bnk) in self.banks() {
+            for entry in bnk.hirc_entries() {
+                definitions.entry(entry.id).or_default().push((name, entry));
+            }
+        }
+
+        let mut duplicates = Vec::new();
+        for (id, definitions) in definitions {
+            if definitions.len() < 2 {
+                continue;
+            }
+            let mut variants: Vec<(Vec<u8>, Vec<String>)> = Vec::new();
+            for (name, entry) in &definitions {
+                let data = entry.to_bytes()?;
+                match variants.iter_mut().find(|(variant, _)| *variant == data) {
+                    Some((_, names)) => names.push(name.to_string()),
+                    None => variants.push((data, vec![name.to_string()])),
+                }
+            }
+            duplicates.push(DuplicateObject {
+                id,
+                entry_type: definitions[0].1.entry_type,
+                variants: variants.into_iter().map(|(_, names)| names).collect(),
+            });
+        }
+        Ok(duplicates)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, io};
+
+    use crate::bnk::hirc::HircEntryPayload;
+
+    use super::*;
+
+    #[test]
+    fn test_duplicate_objects() {
+        let input = fs::read("test_files/bgm_resident_ev.sbnk.1.X64").unwrap();
+        let bnk = Bnk::from_reader(&mut io::Cursor::new(&input)).unwrap();
+        let mut modified = bnk.clone();
+        let mut workspace = Workspace::new();
+        workspace.add("a", bnk.clone());
+        workspace.add("b", bnk);
+        let duplicates = workspace.duplicate_objects().unwrap();
+        let count = duplicates.len();
+        assert!(count > 0);
+        assert!(duplicates.iter().all(|duplicate| !duplicate.diverges()));
+
+        let track_id = modified
+            .sections
+            .iter_mut()
+            .find_map(|section| match &mut section.payload {
+                crate::bnk::SectionPayload::Hirc { entries } => Some(entries),
+                _ => None,
+            })
+            .unwrap()
+            .iter_mut()
+            .find_map(|entry| match &mut entry.payload {
+                HircEntryPayload::MusicTrack(track) => {
+                    track.set_play_at(0, 1000.0).ok().map(|_| entry.id)
+                }
+                _ => None,
+            })
+            .unwrap();
+        workspace.add("c", modified);
+        let duplicates = workspace.duplicate_objects().unwrap();
+        assert_eq!(duplicates.len(), count);
+        let diverging: Vec<_> = duplicates.iter().filter(|d| d.diverges()).collect();
+        assert_eq!(diverging.len(), 1);
+        assert_eq!(diverging[0].id, track_id);
+        assert_eq!(diverging[0].entry_type, HircEntryType::MusicTrack);
+        assert_eq!(diverging[0].variants, [vec!["a", "b"], vec!["c"]]);
+    }
+}